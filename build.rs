@@ -0,0 +1,48 @@
+//! Records the version of the compiler, the commit and how the benchmark was
+//! built, which the benchmark reports.
+
+use std::{env, path::Path, process::Command};
+
+fn main() {
+    let rustc = env::var_os("RUSTC").unwrap_or_else(|| "rustc".into());
+    let version = run(Command::new(rustc).arg("--version"));
+    let version = version.as_deref().unwrap_or("unknown");
+    println!("cargo:rustc-env=MERGESORT_CMP_RUSTC={}", version);
+
+    // Outside of a checkout, there is no commit to record.
+    let commit = run(Command::new("git").args(["rev-parse", "HEAD"]));
+    println!(
+        "cargo:rustc-env=MERGESORT_CMP_COMMIT={}",
+        commit.as_deref().unwrap_or("")
+    );
+
+    let var = |name| env::var(name).unwrap_or_default();
+    println!("cargo:rustc-env=MERGESORT_CMP_PROFILE={}", var("PROFILE"));
+    println!("cargo:rustc-env=MERGESORT_CMP_OPT_LEVEL={}", var("OPT_LEVEL"));
+    println!("cargo:rustc-env=MERGESORT_CMP_TARGET={}", var("TARGET"));
+    // The flags are separated by the unit separator.
+    let flags = var("CARGO_ENCODED_RUSTFLAGS").replace('\x1f', " ");
+    println!("cargo:rustc-env=MERGESORT_CMP_RUSTFLAGS={}", flags);
+
+    println!("cargo:rerun-if-changed=build.rs");
+    // Committing moves a branch, checking out moves the head. Paths that do
+    // not exist would rerun this on every build, as outside of a checkout.
+    if Path::new(".git/HEAD").exists() {
+        println!("cargo:rerun-if-changed=.git/HEAD");
+        println!("cargo:rerun-if-changed=.git/refs");
+        // Branches only packed, as after `git gc`, move in here. Packing
+        // changes the refs, so the file is watched once it is made.
+        if Path::new(".git/packed-refs").exists() {
+            println!("cargo:rerun-if-changed=.git/packed-refs");
+        }
+    }
+}
+
+/// The trimmed output of the command, if it succeeded.
+fn run(command: &mut Command) -> Option<String> {
+    let output = command.output().ok()?;
+    match output.status.success() {
+        true => Some(String::from_utf8(output.stdout).ok()?.trim().to_owned()),
+        false => None,
+    }
+}
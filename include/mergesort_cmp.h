@@ -0,0 +1,51 @@
+/*
+ * The C interface of the parallel merge sort of mergesort-cmp, built into
+ * its cdylib with the `ffi` feature:
+ *
+ *     cargo rustc --release --lib --crate-type cdylib --features ffi
+ *
+ * Every function sorts the `len` elements at `ptr` in place, with `threads`
+ * threads, or one per logical CPU if it is zero, and returns MSCMP_OK, or
+ * the code of the error, leaving the array as it was. Floating point numbers
+ * are sorted by their total order: negative NaNs, negative infinity, the
+ * numbers, -0.0 before 0.0, positive infinity and positive NaNs.
+ */
+
+#ifndef MERGESORT_CMP_H
+#define MERGESORT_CMP_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* The array was sorted. */
+#define MSCMP_OK 0
+/* The pointer to the array was null, and its length was not zero. */
+#define MSCMP_INVALID_ARGUMENT 1
+/* The copy of the array the sort works on could not be allocated. */
+#define MSCMP_OUT_OF_MEMORY 2
+/* The sort failed unexpectedly, a bug of mergesort-cmp. */
+#define MSCMP_INTERNAL_ERROR 3
+
+/* Describes an error code. The string is static and must not be freed. */
+const char *mscmp_error_message(int code);
+
+int mscmp_sort_i8(int8_t *ptr, size_t len, size_t threads);
+int mscmp_sort_i16(int16_t *ptr, size_t len, size_t threads);
+int mscmp_sort_i32(int32_t *ptr, size_t len, size_t threads);
+int mscmp_sort_i64(int64_t *ptr, size_t len, size_t threads);
+int mscmp_sort_u8(uint8_t *ptr, size_t len, size_t threads);
+int mscmp_sort_u16(uint16_t *ptr, size_t len, size_t threads);
+int mscmp_sort_u32(uint32_t *ptr, size_t len, size_t threads);
+int mscmp_sort_u64(uint64_t *ptr, size_t len, size_t threads);
+int mscmp_sort_f32(float *ptr, size_t len, size_t threads);
+int mscmp_sort_f64(double *ptr, size_t len, size_t threads);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
@@ -0,0 +1,281 @@
+//! Counting of the memory sorts allocate, sort by sort rather than for the
+//! whole process: [`CountingAllocator`] wraps the global allocator, and
+//! [`measure`] counts what a closure allocates, on its thread and on the
+//! threads the parallel sorts it runs spawn. Nothing is counted, at no cost,
+//! unless the allocator is installed as the global one.
+//!
+//! # Examples
+//! ```rust
+//! use mergesort_cmp::{
+//!     alloc::{self, CountingAllocator},
+//!     sequential,
+//! };
+//!
+//! #[global_allocator]
+//! static ALLOCATOR: CountingAllocator = CountingAllocator::system();
+//!
+//! fn main() {
+//!     let array = (0 .. 1000).rev().collect::<Vec<i32>>();
+//!
+//!     let (sorted, stats) = alloc::measure(|| sequential::sort(&array));
+//!     let stats = stats.unwrap();
+//!
+//!     assert_eq!(sorted, (0 .. 1000).collect::<Vec<i32>>());
+//!     assert!(stats.allocations > 0);
+//!     // The sorted output alone is live at the end.
+//!     assert!(stats.peak_bytes >= 4000);
+//! }
+//! ```
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+    ptr,
+    sync::{
+        atomic::{AtomicBool, AtomicI64, AtomicU64, Ordering},
+        Arc,
+    },
+};
+
+/// Whether a [`CountingAllocator`] allocated anything, so it must be the
+/// global allocator.
+static INSTALLED: AtomicBool = AtomicBool::new(false);
+
+thread_local! {
+    // Neither allocates nor has a destructor, so the allocator can use it
+    // at any time.
+    /// The counter of the measurement going on in this thread, null if none.
+    static CURRENT: Cell<*const Counter> = const { Cell::new(ptr::null()) };
+}
+
+/// A global allocator counting what the measurements of [`measure`] allocate,
+/// wrapping another allocator, the system one by default. Allocations outside
+/// of measurements are only passed on.
+///
+/// # Examples
+/// ```rust
+/// use mergesort_cmp::alloc::CountingAllocator;
+/// use std::alloc::System;
+///
+/// #[global_allocator]
+/// static ALLOCATOR: CountingAllocator = CountingAllocator::new(System);
+///
+/// fn main() {}
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CountingAllocator<A = System> {
+    /// The allocator wrapped.
+    inner: A,
+}
+
+impl CountingAllocator {
+    /// Counts the allocations of the system allocator.
+    pub const fn system() -> Self {
+        Self { inner: System }
+    }
+}
+
+impl<A> CountingAllocator<A> {
+    /// Counts the allocations of the given allocator.
+    pub const fn new(inner: A) -> Self {
+        Self { inner }
+    }
+
+    /// Records an allocation of `size` bytes.
+    fn grow(&self, size: usize) {
+        if !INSTALLED.load(Ordering::Relaxed) {
+            INSTALLED.store(true, Ordering::Relaxed);
+        }
+        let counter = CURRENT.with(Cell::get);
+        // SAFETY: the counter is kept alive while it is current, see
+        // `Entered`.
+        if let Some(counter) = unsafe { counter.as_ref() } {
+            counter.grow(size);
+        }
+    }
+
+    /// Records that `size` bytes were freed.
+    fn shrink(&self, size: usize) {
+        let counter = CURRENT.with(Cell::get);
+        // SAFETY: as in `grow`.
+        if let Some(counter) = unsafe { counter.as_ref() } {
+            counter.shrink(size);
+        }
+    }
+}
+
+unsafe impl<A> GlobalAlloc for CountingAllocator<A>
+where
+    A: GlobalAlloc,
+{
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let pointer = self.inner.alloc(layout);
+        if !pointer.is_null() {
+            self.grow(layout.size());
+        }
+        pointer
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let pointer = self.inner.alloc_zeroed(layout);
+        if !pointer.is_null() {
+            self.grow(layout.size());
+        }
+        pointer
+    }
+
+    unsafe fn dealloc(&self, pointer: *mut u8, layout: Layout) {
+        self.inner.dealloc(pointer, layout);
+        self.shrink(layout.size());
+    }
+
+    unsafe fn realloc(
+        &self,
+        pointer: *mut u8,
+        layout: Layout,
+        new_size: usize,
+    ) -> *mut u8 {
+        let new_pointer = self.inner.realloc(pointer, layout, new_size);
+        if !new_pointer.is_null() {
+            self.grow(new_size);
+            self.shrink(layout.size());
+        }
+        new_pointer
+    }
+}
+
+/// What was allocated during a measurement.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AllocStats {
+    /// How many times memory was allocated, reallocations included.
+    pub allocations: u64,
+    /// How many bytes were allocated in all, a reallocation counting its
+    /// whole new size.
+    pub allocated_bytes: u64,
+    /// The most bytes allocated and not freed yet at once, counted from the
+    /// start of the measurement. Memory allocated before it and freed during
+    /// it does not lower the count below zero.
+    pub peak_bytes: u64,
+}
+
+/// Runs the closure, counting what it allocates on this thread and on the
+/// threads spawned by the parallel sorts it runs, as they inherit the
+/// measurement, as well as what the jobs of those sorts allocate on a
+/// [`SortPool`](crate::parallel::SortPool). Threads spawned otherwise are not
+/// counted, nor other jobs of the pool. In nested
+/// measurements, allocations count only to the innermost one. The stats are
+/// `None` if the global allocator is not a [`CountingAllocator`].
+///
+/// # Examples
+/// ```rust
+/// use mergesort_cmp::{alloc::{self, CountingAllocator}, parallel};
+/// use std::sync::Arc;
+///
+/// #[global_allocator]
+/// static ALLOCATOR: CountingAllocator = CountingAllocator::system();
+///
+/// fn main() {
+///     let array: Arc<[u64]> = (0 .. 10000).rev().collect();
+///
+///     let mut options = parallel::default_order();
+///     options.threads(4);
+///     let (_, stats) = alloc::measure(|| options.sort(&array));
+///     let stats = stats.unwrap();
+///
+///     // The halves and the merged output are all allocated.
+///     assert!(stats.allocated_bytes >= 2 * 8 * 10000);
+///     assert!(stats.peak_bytes <= stats.allocated_bytes);
+/// }
+/// ```
+pub fn measure<R, G>(task: G) -> (R, Option<AllocStats>)
+where
+    G: FnOnce() -> R,
+{
+    let counter = Arc::new(Counter::default());
+    let entered = Entered::new(Some(counter.clone()));
+    let result = task();
+    drop(entered);
+
+    let stats = match INSTALLED.load(Ordering::Relaxed) {
+        true => Some(counter.stats()),
+        false => None,
+    };
+    (result, stats)
+}
+
+/// The counts of a measurement.
+#[derive(Debug, Default)]
+pub(crate) struct Counter {
+    /// How many times memory was allocated.
+    allocations: AtomicU64,
+    /// How many bytes were allocated in all.
+    allocated_bytes: AtomicU64,
+    /// How many bytes are allocated now, more than at the start.
+    live_bytes: AtomicI64,
+    /// The most bytes allocated at once, more than at the start.
+    peak_bytes: AtomicI64,
+}
+
+impl Counter {
+    /// Counts an allocation of `size` bytes.
+    fn grow(&self, size: usize) {
+        let size = size as i64;
+        self.allocations.fetch_add(1, Ordering::Relaxed);
+        self.allocated_bytes.fetch_add(size as u64, Ordering::Relaxed);
+        let live = self.live_bytes.fetch_add(size, Ordering::Relaxed) + size;
+        self.peak_bytes.fetch_max(live, Ordering::Relaxed);
+    }
+
+    /// Counts that `size` bytes were freed.
+    fn shrink(&self, size: usize) {
+        self.live_bytes.fetch_sub(size as i64, Ordering::Relaxed);
+    }
+
+    /// The counts so far.
+    fn stats(&self) -> AllocStats {
+        AllocStats {
+            allocations: self.allocations.load(Ordering::Relaxed),
+            allocated_bytes: self.allocated_bytes.load(Ordering::Relaxed),
+            peak_bytes: self.peak_bytes.load(Ordering::Relaxed).max(0) as u64,
+        }
+    }
+}
+
+/// The counter of the measurement going on in this thread, if any, for the
+/// threads it spawns to inherit.
+pub(crate) fn current() -> Option<Arc<Counter>> {
+    let counter = CURRENT.with(Cell::get);
+    if counter.is_null() {
+        return None;
+    }
+    // SAFETY: the current counter comes from an `Arc` kept alive by the
+    // `Entered` that made it current, so it can have one more owner.
+    unsafe {
+        Arc::increment_strong_count(counter);
+        Some(Arc::from_raw(counter))
+    }
+}
+
+/// Makes a counter the current one of this thread, until dropped, when the
+/// previous one is current again.
+pub(crate) struct Entered {
+    /// The counter made current, kept alive while it is.
+    _counter: Option<Arc<Counter>>,
+    /// The counter current before.
+    previous: *const Counter,
+}
+
+impl Entered {
+    /// Makes the counter, or none, the current one of this thread.
+    pub(crate) fn new(counter: Option<Arc<Counter>>) -> Self {
+        let pointer = counter.as_ref().map_or(ptr::null(), Arc::as_ptr);
+        let previous = CURRENT.with(|current| current.replace(pointer));
+        Self { _counter: counter, previous }
+    }
+}
+
+impl Drop for Entered {
+    fn drop(&mut self) {
+        CURRENT.with(|current| current.set(self.previous));
+    }
+}
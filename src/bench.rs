@@ -0,0 +1,425 @@
+//! A harness timing sort functions over sets of cases the way the benchmark
+//! does, so other projects can compare their own sorts on their own data with
+//! the same method: every target sorts every case of a set, after untimed
+//! runs warming it up, and every case is timed on its own.
+//!
+//! # Examples
+//! ```rust
+//! use mergesort_cmp::bench::{CaseSet, Runner, Target};
+//!
+//! let sets = [CaseSet::<i64>::random("small", 64, 100 ..= 500, 42)];
+//! let mut targets = vec![
+//!     Target::sequential(),
+//!     Target::new("reversed_insertion", |array| {
+//!         let mut sorted = Vec::with_capacity(array.len());
+//!         for element in array.iter().rev() {
+//!             let index = sorted.partition_point(|other| other <= element);
+//!             sorted.insert(index, *element);
+//!         }
+//!         sorted
+//!     }),
+//! ];
+//!
+//! let measured = Runner::new()
+//!     .repeat(3)
+//!     .verify(true)
+//!     .run(&sets, &mut targets)
+//!     .unwrap();
+//!
+//! assert_eq!(measured.len(), 2);
+//! assert_eq!(measured[1].target, "reversed_insertion");
+//! assert_eq!(measured[1].runs.len(), 3);
+//! assert!(measured[1].speedup_over(&measured[0]).is_some());
+//! ```
+
+use crate::{gen::Pattern, parallel, sequential, verify};
+use rand::{
+    distributions::{Distribution, Standard, Uniform},
+    rngs::StdRng,
+    Rng,
+    SeedableRng,
+};
+use std::{
+    convert::TryFrom,
+    error::Error,
+    fmt,
+    ops::RangeInclusive,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+/// A sort function that can be benchmarked.
+pub type Sorter<T> = Box<dyn FnMut(&Arc<[T]>) -> Vec<T>>;
+
+/// A named set of cases, the arrays every target sorts.
+#[derive(Debug, Clone)]
+pub struct CaseSet<T> {
+    /// The name the results of the set are reported under.
+    pub name: String,
+    /// The arrays to be sorted.
+    pub cases: Vec<Arc<[T]>>,
+}
+
+impl<T> CaseSet<T> {
+    /// A set of the given cases.
+    pub fn new<S>(name: S, cases: Vec<Vec<T>>) -> Self
+    where
+        S: Into<String>,
+    {
+        let cases = cases.into_iter().map(Arc::from).collect();
+        Self { name: name.into(), cases }
+    }
+
+    /// A set of `count` arrays of uniformly random elements, with sizes
+    /// uniformly distributed over `sizes`. The same seed always gives the
+    /// same cases.
+    ///
+    /// # Panics
+    /// Panics if `sizes` is empty.
+    pub fn random<S>(
+        name: S,
+        count: usize,
+        sizes: RangeInclusive<usize>,
+        seed: u64,
+    ) -> Self
+    where
+        S: Into<String>,
+        T: Ord + Clone,
+        Standard: Distribution<T>,
+    {
+        Self::generate(name, Pattern::Random, count, sizes, seed)
+    }
+
+    /// A set of `count` arrays of the given pattern, with sizes uniformly
+    /// distributed over `sizes`. The same seed always gives the same cases.
+    ///
+    /// # Panics
+    /// Panics if `sizes` is empty.
+    pub fn generate<S>(
+        name: S,
+        pattern: Pattern,
+        count: usize,
+        sizes: RangeInclusive<usize>,
+        seed: u64,
+    ) -> Self
+    where
+        S: Into<String>,
+        T: Ord + Clone,
+        Standard: Distribution<T>,
+    {
+        if sizes.is_empty() {
+            panic!("the range of sizes {:?} is empty", sizes);
+        }
+
+        // Sizes have their own generator, so they are the same whatever the
+        // pattern and the type of the elements are.
+        let mut size_rng = StdRng::seed_from_u64(seed);
+        let mut rng = StdRng::seed_from_u64(!seed);
+        let sizes = Uniform::new_inclusive(sizes.start(), sizes.end());
+        let cases = (0 .. count).map(|_| {
+            pattern.generate(size_rng.sample(sizes), &mut rng)
+        });
+        Self::new(name, cases.collect())
+    }
+
+    /// How many elements the cases have together.
+    pub fn elements(&self) -> usize {
+        self.cases.iter().map(|case| case.len()).sum()
+    }
+}
+
+/// A named sort function to be benchmarked.
+pub struct Target<T> {
+    /// The name the results of the target are reported under.
+    pub name: String,
+    /// What sorts for the target.
+    pub sorter: Sorter<T>,
+}
+
+impl<T> fmt::Debug for Target<T> {
+    fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
+        fmtr.debug_struct("Target").field("name", &self.name).finish()
+    }
+}
+
+impl<T> Target<T> {
+    /// A target sorting with the given function, which must return the case
+    /// it is given, sorted.
+    pub fn new<S, F>(name: S, sort: F) -> Self
+    where
+        S: Into<String>,
+        F: FnMut(&Arc<[T]>) -> Vec<T> + 'static,
+    {
+        Self { name: name.into(), sorter: Box::new(sort) }
+    }
+}
+
+impl<T> Target<T>
+where
+    T: Ord + Clone + Send + Sync + 'static,
+{
+    /// The sequential merge sort, as `sequential`.
+    pub fn sequential() -> Self {
+        Self::new("sequential", |array| sequential::sort(array))
+    }
+
+    /// The parallel merge sort, 1 thread per logical CPU, as
+    /// `parallel_logical`.
+    pub fn parallel() -> Self {
+        Self::new("parallel_logical", parallel::sort)
+    }
+
+    /// The standard library's stable sort, as `std_sort`.
+    pub fn std_sort() -> Self {
+        Self::new("std_sort", |array| {
+            let mut sorted = array.to_vec();
+            sorted.sort();
+            sorted
+        })
+    }
+}
+
+/// Runs targets over case sets, timing them.
+#[derive(Debug, Clone)]
+pub struct Runner {
+    /// Untimed runs of each set before a target is timed on it.
+    warmup: usize,
+    /// Timed runs of each set by each target.
+    repeat: usize,
+    /// Whether every output is checked.
+    verify: bool,
+}
+
+impl Default for Runner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Runner {
+    /// A runner with a run of warmup, a timed run, and no checking, like the
+    /// benchmark by default.
+    pub fn new() -> Self {
+        Self { warmup: 1, repeat: 1, verify: false }
+    }
+
+    /// Sets the number of untimed runs of each set before a target is timed
+    /// on it, which fault the pages in and warm the caches up.
+    pub fn warmup(&mut self, runs: usize) -> &mut Self {
+        self.warmup = runs;
+        self
+    }
+
+    /// Sets the number of timed runs of each set by each target. Must not be
+    /// zero.
+    pub fn repeat(&mut self, runs: usize) -> &mut Self {
+        assert!(runs > 0, "at least a run must be timed");
+        self.repeat = runs;
+        self
+    }
+
+    /// Sets whether every output is checked to be its case sorted, the
+    /// warmup included. The checks are not timed.
+    pub fn verify(&mut self, verify: bool) -> &mut Self {
+        self.verify = verify;
+        self
+    }
+
+    /// Times every target on every set, the targets of a set one after the
+    /// other, in order. Stops at the first case sorted wrong, if outputs are
+    /// checked.
+    pub fn run<T>(
+        &self,
+        sets: &[CaseSet<T>],
+        targets: &mut [Target<T>],
+    ) -> Result<Vec<Measurement>, VerifyError>
+    where
+        T: Ord + Clone,
+    {
+        let mut measured = Vec::with_capacity(sets.len() * targets.len());
+        for set in sets {
+            for target in targets.iter_mut() {
+                measured.push(self.measure(set, target)?);
+            }
+        }
+        Ok(measured)
+    }
+
+    /// Times the target on the set.
+    pub fn measure<T>(
+        &self,
+        set: &CaseSet<T>,
+        target: &mut Target<T>,
+    ) -> Result<Measurement, VerifyError>
+    where
+        T: Ord + Clone,
+    {
+        for _ in 0 .. self.warmup {
+            self.run_once(set, target)?;
+        }
+        let mut runs = Vec::with_capacity(self.repeat);
+        for _ in 0 .. self.repeat {
+            runs.push(self.run_once(set, target)?);
+        }
+        Ok(Measurement {
+            set: set.name.clone(),
+            target: target.name.clone(),
+            elements: set.elements(),
+            runs,
+        })
+    }
+
+    /// Sorts every case of the set once, timing each.
+    fn run_once<T>(
+        &self,
+        set: &CaseSet<T>,
+        target: &mut Target<T>,
+    ) -> Result<Vec<Duration>, VerifyError>
+    where
+        T: Ord + Clone,
+    {
+        let mut times = Vec::with_capacity(set.cases.len());
+        for (index, case) in set.cases.iter().enumerate() {
+            let then = Instant::now();
+            let sorted = (target.sorter)(case);
+            times.push(then.elapsed());
+
+            if self.verify {
+                check(case, &sorted).map_err(|failure| VerifyError {
+                    set: set.name.clone(),
+                    target: target.name.clone(),
+                    case: index,
+                    failure,
+                })?;
+            }
+        }
+        Ok(times)
+    }
+}
+
+/// What was measured of a target on a set.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Measurement {
+    /// The name of the set.
+    pub set: String,
+    /// The name of the target.
+    pub target: String,
+    /// How many elements the cases of the set have together.
+    pub elements: usize,
+    /// How long it took to sort each case, once per timed run.
+    pub runs: Vec<Vec<Duration>>,
+}
+
+impl Measurement {
+    /// How long each timed run over the whole set took.
+    pub fn totals(&self) -> Vec<Duration> {
+        self.runs.iter().map(|run| run.iter().sum()).collect()
+    }
+
+    /// The mean time of a run over the whole set. `None` without runs.
+    pub fn mean(&self) -> Option<Duration> {
+        let totals = self.totals();
+        let count = u32::try_from(totals.len()).ok().filter(|&count| count > 0);
+        count.map(|count| totals.iter().sum::<Duration>() / count)
+    }
+
+    /// How many elements were sorted per second, over the mean run. `None`
+    /// without runs, or if they took no time.
+    pub fn throughput(&self) -> Option<f64> {
+        let seconds = self.mean()?.as_secs_f64();
+        Some(self.elements as f64 / seconds).filter(|_| seconds > 0.0)
+    }
+
+    /// How many times faster the mean run is than the one of the baseline.
+    /// `None` without runs of both, or if this one took no time.
+    pub fn speedup_over(&self, baseline: &Self) -> Option<f64> {
+        let seconds = self.mean()?.as_secs_f64();
+        let baseline = baseline.mean()?.as_secs_f64();
+        Some(baseline / seconds).filter(|_| seconds > 0.0)
+    }
+}
+
+/// What is wrong with a sorted case.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Failure {
+    /// The output has a different length than the input.
+    Length {
+        /// The length of the input.
+        expected: usize,
+        /// The length of the output.
+        found: usize,
+    },
+    /// The element at this index is less than the one before it.
+    Unsorted {
+        /// The index of the out of order element.
+        index: usize,
+    },
+    /// The output is sorted, but its elements are not the input's.
+    NotPermutation,
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Failure::Length { expected, found } => write!(
+                fmtr,
+                "expected {} elements, found {}",
+                expected, found
+            ),
+            Failure::Unsorted { index } => write!(
+                fmtr,
+                "element {} is less than the one before it",
+                index
+            ),
+            Failure::NotPermutation => {
+                fmtr.write_str("elements are not a permutation of the input")
+            },
+        }
+    }
+}
+
+/// Error returned when a target sorted a case wrong.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerifyError {
+    /// The name of the set of the case.
+    pub set: String,
+    /// The name of the target that sorted it.
+    pub target: String,
+    /// The index of the case in its set.
+    pub case: usize,
+    /// What is wrong with the output.
+    pub failure: Failure,
+}
+
+impl fmt::Display for VerifyError {
+    fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            fmtr,
+            "target {} failed case {} of set {}: {}",
+            self.target, self.case, self.set, self.failure
+        )
+    }
+}
+
+impl Error for VerifyError {}
+
+/// Checks that the output is the input, sorted.
+fn check<T>(input: &[T], output: &[T]) -> Result<(), Failure>
+where
+    T: Ord + Clone,
+{
+    if input.len() != output.len() {
+        return Err(Failure::Length {
+            expected: input.len(),
+            found: output.len(),
+        });
+    }
+
+    if let Some(index) = verify::find_first_unsorted_pair(output, T::cmp) {
+        return Err(Failure::Unsorted { index: index + 1 });
+    }
+    match verify::is_permutation_of(output, input, T::cmp) {
+        true => Ok(()),
+        false => Err(Failure::NotPermutation),
+    }
+}
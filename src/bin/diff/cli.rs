@@ -0,0 +1,138 @@
+//! Parsing of the command line arguments.
+
+use std::{error::Error, fmt, path::PathBuf};
+
+/// What the binary was asked to do.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    /// Compares two reports.
+    Diff(Config),
+    /// Prints the usage.
+    Help,
+}
+
+/// Which reports are compared, and how the difference is written.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Config {
+    /// The report compared to.
+    pub old: PathBuf,
+    /// The report compared.
+    pub new: PathBuf,
+    /// How the difference is written.
+    pub format: Format,
+}
+
+/// How the difference is written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// Aligned text, for people.
+    Text,
+    /// A JSON document, for tools.
+    Json,
+}
+
+/// An invalid command line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CliError {
+    /// What is wrong about it.
+    message: String,
+}
+
+impl CliError {
+    /// Creates an error with the given message.
+    fn new<S>(message: S) -> Self
+    where
+        S: Into<String>,
+    {
+        Self { message: message.into() }
+    }
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.write_str(&self.message)
+    }
+}
+
+impl Error for CliError {}
+
+/// The usage of the binary, as printed by `--help`.
+pub fn usage() -> &'static str {
+    "Compares two reports of the benchmark saved with --format json, such as
+archived results of old runs: the change of every target on every case set
+both have, and the case sets and targets only one of them has.
+
+Usage: mergesort-cmp-diff [OPTIONS] OLD NEW
+
+Changes are marked where they are significant by Welch's t-test over the
+repetitions of the target.
+
+Options:
+    --format <FORMAT>      Format of the difference, text or json
+                           [default: text]
+    -h, --help             Prints this message
+"
+}
+
+/// Parses the arguments, the binary left out.
+pub fn parse<I>(args: I) -> Result<Command, CliError>
+where
+    I: IntoIterator<Item = String>,
+{
+    let mut args = args.into_iter();
+    let mut paths = Vec::new();
+    let mut format = Format::Text;
+
+    while let Some(arg) = args.next() {
+        let (option, inline) = split_option(arg);
+
+        let mut value = || {
+            inline.clone().or_else(|| args.next()).ok_or_else(|| {
+                CliError::new(format!("missing value for {}", option))
+            })
+        };
+
+        match option.as_str() {
+            "-h" | "--help" => return Ok(Command::Help),
+            "--format" => format = parse_format(&value()?)?,
+            path if !path.starts_with('-') => paths.push(PathBuf::from(path)),
+            _ => {
+                return Err(CliError::new(format!(
+                    "unexpected argument {:?}",
+                    option
+                )))
+            },
+        }
+    }
+
+    let mut paths = paths.into_iter();
+    match (paths.next(), paths.next(), paths.next()) {
+        (Some(old), Some(new), None) => {
+            Ok(Command::Diff(Config { old, new, format }))
+        },
+        _ => Err(CliError::new("expected two report files")),
+    }
+}
+
+/// Splits an argument into the option and its value, if given inline. Both
+/// `--option value` and `--option=value` are accepted.
+fn split_option(arg: String) -> (String, Option<String>) {
+    match arg.find('=') {
+        Some(index) if arg.starts_with("--") => {
+            (arg[.. index].to_owned(), Some(arg[index + 1 ..].to_owned()))
+        },
+        _ => (arg, None),
+    }
+}
+
+/// Parses the format of the difference.
+fn parse_format(value: &str) -> Result<Format, CliError> {
+    match value {
+        "text" => Ok(Format::Text),
+        "json" => Ok(Format::Json),
+        _ => Err(CliError::new(format!(
+            "unknown format {:?}, expected text or json",
+            value
+        ))),
+    }
+}
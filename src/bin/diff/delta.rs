@@ -0,0 +1,348 @@
+//! The difference between two reports: how the time of every target changed
+//! on every case set both reports have, and what only one of them has.
+
+use crate::{json::Json, stats};
+use std::{fs, io, path::Path};
+
+/// What a report has of a run, enough to compare it.
+#[derive(Debug, Clone)]
+pub struct Report {
+    /// The name of the report, its path.
+    pub name: String,
+    /// The type of the elements of the run.
+    pub element: String,
+    /// The results of each case set.
+    pub sets: Vec<SetReport>,
+}
+
+/// What a report has of a case set.
+#[derive(Debug, Clone)]
+pub struct SetReport {
+    /// The name of the case set.
+    pub name: String,
+    /// The pattern of its arrays.
+    pub pattern: String,
+    /// The results of each target.
+    pub targets: Vec<TargetReport>,
+}
+
+/// What a report has of a target on a case set.
+#[derive(Debug, Clone)]
+pub struct TargetReport {
+    /// The label of the target, with its thread count if it ran in a sweep.
+    pub label: String,
+    /// The mean time over the set, in seconds, `None` if it timed out.
+    pub mean: Option<f64>,
+    /// The time over the set of each repetition, in seconds.
+    pub seconds: Vec<f64>,
+}
+
+impl Report {
+    /// Reads a JSON report from a file, naming it after its path.
+    pub fn read(path: &Path) -> io::Result<Self> {
+        let name = path.display().to_string();
+        let invalid = |message: String| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{}: {}", name, message),
+            )
+        };
+        let text = fs::read_to_string(path).map_err(|error| {
+            io::Error::new(error.kind(), format!("{}: {}", name, error))
+        })?;
+        let json = Json::parse(&text).map_err(|error| {
+            invalid(error.to_string())
+        })?;
+        Self::from_json(&name, &json)
+            .ok_or_else(|| invalid("not a report of the benchmark".to_owned()))
+    }
+
+    /// Reads the results out of a report.
+    fn from_json(name: &str, json: &Json) -> Option<Self> {
+        let element = json.get("element_type")?.as_str()?.to_owned();
+        let sets = json.get("case_sets")?.as_array()?;
+        let sets = sets.iter().map(SetReport::from_json);
+        let sets = sets.collect::<Option<_>>()?;
+        Some(Self { name: name.to_owned(), element, sets })
+    }
+
+    /// The case set of the given name and pattern.
+    fn set(&self, name: &str, pattern: &str) -> Option<&SetReport> {
+        self.sets.iter().find(|set| set.name == name && set.pattern == pattern)
+    }
+}
+
+impl SetReport {
+    /// The results of the target of the given label.
+    fn target(&self, label: &str) -> Option<&TargetReport> {
+        self.targets.iter().find(|target| target.label == label)
+    }
+
+    /// Reads the results of a case set out of a report.
+    fn from_json(json: &Json) -> Option<Self> {
+        let name = json.get("name")?.as_str()?.to_owned();
+        let pattern = json.get("pattern")?.as_str()?.to_owned();
+        let targets = json.get("targets")?.as_array()?;
+        let targets = targets.iter().map(TargetReport::from_json);
+        let targets = targets.collect::<Option<_>>()?;
+        Some(Self { name, pattern, targets })
+    }
+}
+
+impl TargetReport {
+    /// Reads the results of a target out of a report.
+    fn from_json(json: &Json) -> Option<Self> {
+        let name = json.get("name")?.as_str()?;
+        // Reports from before sweeps have no thread counts.
+        let label = match json.get("threads").and_then(Json::as_u64) {
+            Some(threads) => format!("{}@{}", name, threads),
+            None => name.to_owned(),
+        };
+        let mean = json.get("total_seconds").and_then(|stats| {
+            stats.get("mean").and_then(Json::as_f64)
+        });
+        let seconds = match json.get("seconds") {
+            Some(seconds) => {
+                let seconds = seconds.as_array()?.iter().map(Json::as_f64);
+                seconds.collect::<Option<_>>()?
+            },
+            None => Vec::new(),
+        };
+        Some(Self { label, mean, seconds })
+    }
+}
+
+/// Whether a case set or a target is in both reports or in one of them only.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    /// In both reports.
+    Common,
+    /// In the new report only.
+    Added,
+    /// In the old report only.
+    Removed,
+}
+
+impl Status {
+    /// The name of the status, as the JSON difference has it.
+    pub fn name(self) -> &'static str {
+        match self {
+            Status::Common => "common",
+            Status::Added => "added",
+            Status::Removed => "removed",
+        }
+    }
+}
+
+/// The difference between two reports.
+#[derive(Debug, Clone)]
+pub struct Delta {
+    /// The name of the report compared to.
+    pub old: String,
+    /// The name of the report compared.
+    pub new: String,
+    /// The type of the elements of the old report.
+    pub old_element: String,
+    /// The type of the elements of the new report.
+    pub new_element: String,
+    /// The case sets of the new report, in its order, then those of the old
+    /// report only.
+    pub sets: Vec<SetDelta>,
+}
+
+/// The difference of a case set.
+#[derive(Debug, Clone)]
+pub struct SetDelta {
+    /// The name of the case set.
+    pub name: String,
+    /// The pattern of its arrays.
+    pub pattern: String,
+    /// Which reports have it.
+    pub status: Status,
+    /// The targets of the new report, in its order, then those of the old
+    /// report only.
+    pub targets: Vec<TargetDelta>,
+}
+
+/// The difference of a target on a case set.
+#[derive(Debug, Clone)]
+pub struct TargetDelta {
+    /// The label of the target.
+    pub label: String,
+    /// Which reports have it.
+    pub status: Status,
+    /// The mean time in the old report, if it has the target and it did not
+    /// time out.
+    pub old_mean: Option<f64>,
+    /// The mean time in the new report, if it has the target and it did not
+    /// time out.
+    pub new_mean: Option<f64>,
+    /// How much the mean time changed, as a percentage, if both reports have
+    /// one.
+    pub percent: Option<f64>,
+    /// The p-value of the change by Welch's t-test, if both reports have at
+    /// least 2 repetitions.
+    pub p_value: Option<f64>,
+}
+
+impl Delta {
+    /// The difference from the old report to the new one.
+    pub fn between(old: &Report, new: &Report) -> Self {
+        let mut sets = Vec::new();
+        for set in &new.sets {
+            let before = old.set(&set.name, &set.pattern);
+            let status = match before {
+                Some(_) => Status::Common,
+                None => Status::Added,
+            };
+            sets.push(SetDelta {
+                name: set.name.clone(),
+                pattern: set.pattern.clone(),
+                status,
+                targets: target_deltas(before, Some(set)),
+            });
+        }
+        for set in &old.sets {
+            if new.set(&set.name, &set.pattern).is_none() {
+                sets.push(SetDelta {
+                    name: set.name.clone(),
+                    pattern: set.pattern.clone(),
+                    status: Status::Removed,
+                    targets: target_deltas(Some(set), None),
+                });
+            }
+        }
+
+        Self {
+            old: old.name.clone(),
+            new: new.name.clone(),
+            old_element: old.element.clone(),
+            new_element: new.element.clone(),
+            sets,
+        }
+    }
+
+    /// How many case sets have the given status.
+    pub fn count_sets(&self, status: Status) -> usize {
+        self.sets.iter().filter(|set| set.status == status).count()
+    }
+
+    /// How many targets of the case sets both reports have have the given
+    /// status.
+    pub fn count_targets(&self, status: Status) -> usize {
+        let common = self.sets.iter().filter(|set| {
+            set.status == Status::Common
+        });
+        let targets = common.flat_map(|set| &set.targets);
+        targets.filter(|target| target.status == status).count()
+    }
+
+    /// The difference as a JSON document.
+    pub fn to_json(&self) -> Json {
+        let count = |status| {
+            Json::object(vec![
+                ("case_sets", Json::from(self.count_sets(status))),
+                ("targets", Json::from(self.count_targets(status))),
+            ])
+        };
+        let sets = self.sets.iter().map(SetDelta::to_json);
+        Json::object(vec![
+            ("old", Json::from(&*self.old)),
+            ("new", Json::from(&*self.new)),
+            ("old_element_type", Json::from(&*self.old_element)),
+            ("new_element_type", Json::from(&*self.new_element)),
+            ("added", count(Status::Added)),
+            ("removed", count(Status::Removed)),
+            ("case_sets", Json::Array(sets.collect())),
+        ])
+    }
+}
+
+impl SetDelta {
+    /// The difference of the case set as JSON.
+    fn to_json(&self) -> Json {
+        let targets = self.targets.iter().map(TargetDelta::to_json);
+        Json::object(vec![
+            ("name", Json::from(&*self.name)),
+            ("pattern", Json::from(&*self.pattern)),
+            ("status", Json::from(self.status.name())),
+            ("targets", Json::Array(targets.collect())),
+        ])
+    }
+}
+
+impl TargetDelta {
+    /// The difference of the target as JSON.
+    fn to_json(&self) -> Json {
+        let optional = |value: Option<f64>| {
+            value.map_or(Json::Null, Json::from)
+        };
+        Json::object(vec![
+            ("label", Json::from(&*self.label)),
+            ("status", Json::from(self.status.name())),
+            ("old_mean_seconds", optional(self.old_mean)),
+            ("new_mean_seconds", optional(self.new_mean)),
+            ("change_percent", optional(self.percent)),
+            ("p_value", optional(self.p_value)),
+        ])
+    }
+}
+
+/// The differences of the targets of a case set, which either report may
+/// lack.
+fn target_deltas(
+    old: Option<&SetReport>,
+    new: Option<&SetReport>,
+) -> Vec<TargetDelta> {
+    let mut deltas = Vec::new();
+    if let Some(new) = new {
+        for target in &new.targets {
+            let before = old.and_then(|old| old.target(&target.label));
+            deltas.push(target_delta(before, Some(target)));
+        }
+    }
+    if let Some(old) = old {
+        for target in &old.targets {
+            let after = new.and_then(|new| new.target(&target.label));
+            if after.is_none() {
+                deltas.push(target_delta(Some(target), None));
+            }
+        }
+    }
+    deltas
+}
+
+/// The difference of a target, which either report may lack.
+fn target_delta(
+    old: Option<&TargetReport>,
+    new: Option<&TargetReport>,
+) -> TargetDelta {
+    let (label, status) = match (old, new) {
+        (Some(_), Some(new)) => (&new.label, Status::Common),
+        (None, Some(new)) => (&new.label, Status::Added),
+        (Some(old), None) => (&old.label, Status::Removed),
+        (None, None) => unreachable!("a target is in either report"),
+    };
+    let old_mean = old.and_then(|old| old.mean);
+    let new_mean = new.and_then(|new| new.mean);
+    let percent = match (old_mean, new_mean) {
+        (Some(before), Some(now)) if before > 0.0 => {
+            Some((now - before) / before * 100.0)
+        },
+        _ => None,
+    };
+    let p_value = match (old, new) {
+        (Some(old), Some(new)) => {
+            stats::welch_p_value(&old.seconds, &new.seconds)
+        },
+        _ => None,
+    };
+    TargetDelta {
+        label: label.clone(),
+        status,
+        old_mean,
+        new_mean,
+        percent,
+        p_value,
+    }
+}
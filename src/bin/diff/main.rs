@@ -0,0 +1,178 @@
+//! Compares two reports of the benchmark saved as JSON, such as archived
+//! results, without running anything: the change of the mean time of every
+//! target, and the case sets and targets either report lacks. The difference
+//! is written as text, or as JSON for tools.
+
+mod cli;
+mod delta;
+// Shared with the benchmark, which uses more of them.
+#[allow(dead_code)]
+#[path = "../main/json.rs"]
+mod json;
+#[allow(dead_code)]
+#[path = "../main/stats.rs"]
+mod stats;
+
+use cli::{Command, Config, Format};
+use delta::{Delta, Report, SetDelta, Status, TargetDelta};
+use std::{
+    env,
+    io::{self, Write},
+    process::exit,
+};
+
+/// The p-values under which a change is marked, with their markers.
+const MARKERS: [(f64, &str); 2] = [(0.01, "**"), (0.05, "*")];
+
+fn main() {
+    let config = match cli::parse(env::args().skip(1)) {
+        Ok(Command::Diff(config)) => config,
+        Ok(Command::Help) => {
+            print!("{}", cli::usage());
+            return;
+        },
+        Err(error) => {
+            eprintln!("error: {}", error);
+            eprintln!("Try `--help` for more information.");
+            exit(2);
+        },
+    };
+    if let Err(error) = run(&config) {
+        eprintln!("error: {}", error);
+        exit(1);
+    }
+}
+
+/// Reads the reports and writes their difference.
+fn run(config: &Config) -> io::Result<()> {
+    let old = Report::read(&config.old)?;
+    let new = Report::read(&config.new)?;
+    let delta = Delta::between(&old, &new);
+
+    let stdout = io::stdout();
+    let mut output = stdout.lock();
+    match config.format {
+        Format::Text => write_text(&delta, &mut output),
+        Format::Json => writeln!(output, "{}", delta.to_json()),
+    }
+}
+
+/// Writes the difference as aligned text.
+fn write_text<W>(delta: &Delta, output: &mut W) -> io::Result<()>
+where
+    W: Write,
+{
+    writeln!(output, "Comparing {} to {}:", delta.new, delta.old)?;
+    if delta.old_element != delta.new_element {
+        writeln!(
+            output,
+            "  {} sorted elements of type {}, and {} of type {}",
+            delta.old, delta.old_element, delta.new, delta.new_element
+        )?;
+    }
+
+    for set in &delta.sets {
+        let status = match set.status {
+            Status::Common => String::new(),
+            Status::Added => format!(" not in {}", delta.old),
+            Status::Removed => format!(" not in {}", delta.new),
+        };
+        writeln!(
+            output,
+            "  {}, pattern = {}:{}",
+            set.name, set.pattern, status
+        )?;
+        for target in &set.targets {
+            write_target(output, delta, set, target)?;
+        }
+    }
+
+    writeln!(output)?;
+    writeln!(
+        output,
+        "Case sets: {} added, {} removed. Targets of common case sets: {} \
+         added, {} removed.",
+        delta.count_sets(Status::Added),
+        delta.count_sets(Status::Removed),
+        delta.count_targets(Status::Added),
+        delta.count_targets(Status::Removed)
+    )?;
+    let legend = MARKERS.iter().map(|(limit, marker)| {
+        format!("{} p < {}", marker, limit)
+    });
+    writeln!(
+        output,
+        "{}, by Welch's t-test over the repetitions of each target",
+        legend.collect::<Vec<_>>().join(", "),
+    )?;
+    writeln!(output, "? where a side has fewer than 2 repetitions")
+}
+
+/// Writes how a target changed, marking the change if it is significant.
+fn write_target<W>(
+    output: &mut W,
+    delta: &Delta,
+    set: &SetDelta,
+    target: &TargetDelta,
+) -> io::Result<()>
+where
+    W: Write,
+{
+    let time = |mean: Option<f64>| mean.map_or("timed out".to_owned(), human);
+    let line = match target.status {
+        Status::Common => {
+            let change = match target.percent {
+                Some(percent) => format!("{:>+8.1}%", percent),
+                None => format!("{:>9}", "-"),
+            };
+            let marker = match target.p_value {
+                Some(p_value) => {
+                    let found =
+                        MARKERS.iter().find(|&&(limit, _)| p_value < limit);
+                    found.map_or("", |&(_, marker)| marker)
+                },
+                None => "?",
+            };
+            format!(
+                "    {:<24} {:>12} -> {:>12} {} {}",
+                target.label,
+                time(target.old_mean),
+                time(target.new_mean),
+                change,
+                marker
+            )
+        },
+        // The whole set is marked already.
+        _ if target.status == set.status => format!(
+            "    {:<24} {:>12}",
+            target.label,
+            time(target.old_mean.or(target.new_mean))
+        ),
+        Status::Added => format!(
+            "    {:<24} {:>12}   not in {}",
+            target.label,
+            time(target.new_mean),
+            delta.old
+        ),
+        Status::Removed => format!(
+            "    {:<24} {:>12}   not in {}",
+            target.label,
+            time(target.old_mean),
+            delta.new
+        ),
+    };
+    writeln!(output, "{}", line.trim_end())
+}
+
+/// Formats seconds in the most readable unit.
+fn human(seconds: f64) -> String {
+    if seconds >= 1.0 {
+        format!("{:.3}s", seconds)
+    } else if seconds >= 1e-3 {
+        format!("{:.3}ms", seconds * 1e3)
+    } else if seconds >= 1e-6 {
+        format!("{:.3}µs", seconds * 1e6)
+    } else {
+        format!("{:.0}ns", seconds * 1e9)
+    }
+}
@@ -0,0 +1,185 @@
+//! Random cases: the elements sorted, how they are compared, and the threads
+//! of the parallel sort.
+
+use mergesort_cmp::gen::Pattern;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::{cmp::Ordering, fmt};
+
+/// An element of a case. Only the key is compared, the index is where the
+/// element started, telling apart elements that compare equal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Element {
+    /// What the element is compared by.
+    pub key: i64,
+    /// The position of the element in the unsorted array.
+    pub index: usize,
+}
+
+/// How the elements of a case are compared. Every order is consistent, but
+/// most of them have elements with different keys comparing equal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Order {
+    /// Ascending keys.
+    Ascending,
+    /// Descending keys.
+    Descending,
+    /// Ascending remainders of the keys by the modulus.
+    Modulo {
+        /// What the keys are divided by.
+        modulus: i64,
+    },
+    /// Ascending quotients of the keys by the divisor, equal in buckets.
+    Buckets {
+        /// What the keys are divided by.
+        divisor: i64,
+    },
+    /// Ascending absolute values of the keys.
+    Absolute,
+    /// The keys as decimal strings, in lexicographic order.
+    Decimal,
+    /// Ascending keys multiplied by the odd factor, wrapping around, which
+    /// shuffles them into an arbitrary total order.
+    Scrambled {
+        /// What the keys are multiplied by.
+        factor: u64,
+    },
+    /// Every element is equal.
+    Constant,
+}
+
+impl Order {
+    /// Compares the elements.
+    pub fn compare(self, left: &Element, right: &Element) -> Ordering {
+        let (left, right) = (left.key, right.key);
+        match self {
+            Order::Ascending => left.cmp(&right),
+            Order::Descending => right.cmp(&left),
+            Order::Modulo { modulus } => {
+                left.rem_euclid(modulus).cmp(&right.rem_euclid(modulus))
+            },
+            Order::Buckets { divisor } => {
+                left.div_euclid(divisor).cmp(&right.div_euclid(divisor))
+            },
+            Order::Absolute => left.unsigned_abs().cmp(&right.unsigned_abs()),
+            Order::Decimal => left.to_string().cmp(&right.to_string()),
+            Order::Scrambled { factor } => {
+                let scramble = |key: i64| (key as u64).wrapping_mul(factor);
+                scramble(left).cmp(&scramble(right))
+            },
+            Order::Constant => Ordering::Equal,
+        }
+    }
+
+    /// Picks an order at random.
+    fn random(rng: &mut StdRng) -> Self {
+        match rng.gen_range(0, 8) {
+            0 => Order::Ascending,
+            1 => Order::Descending,
+            2 => Order::Modulo { modulus: rng.gen_range(1, 10) },
+            3 => Order::Buckets { divisor: rng.gen_range(1, 1000) },
+            4 => Order::Absolute,
+            5 => Order::Decimal,
+            6 => Order::Scrambled { factor: rng.gen::<u64>() | 1 },
+            _ => Order::Constant,
+        }
+    }
+}
+
+impl fmt::Display for Order {
+    fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Order::Ascending => write!(fmtr, "ascending"),
+            Order::Descending => write!(fmtr, "descending"),
+            Order::Modulo { modulus } => write!(fmtr, "modulo({})", modulus),
+            Order::Buckets { divisor } => write!(fmtr, "buckets({})", divisor),
+            Order::Absolute => write!(fmtr, "absolute"),
+            Order::Decimal => write!(fmtr, "decimal"),
+            Order::Scrambled { factor } => {
+                write!(fmtr, "scrambled({:#x})", factor)
+            },
+            Order::Constant => write!(fmtr, "constant"),
+        }
+    }
+}
+
+/// A case, everything about it following from its seed.
+#[derive(Debug, Clone)]
+pub struct Case {
+    /// The seed the case was generated from.
+    pub seed: u64,
+    /// How the elements were laid out.
+    pub pattern: Pattern,
+    /// The keys are drawn from `-range .. range`.
+    pub range: i64,
+    /// How the elements are compared.
+    pub order: Order,
+    /// The elements, unsorted.
+    pub elements: Vec<Element>,
+    /// The threads the parallel sort splits the array with.
+    pub split_threads: usize,
+    /// The threads the parallel sort merges the halves with.
+    pub merge_threads: usize,
+}
+
+impl Case {
+    /// Generates the case of the given seed. Small arrays are as likely as
+    /// arrays of any size up to the given maximum, since they have most of
+    /// the edge cases.
+    pub fn generate(seed: u64, max_size: usize, max_threads: usize) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let size = match rng.gen::<bool>() {
+            true => rng.gen_range(0, max_size.min(32) + 1),
+            false => rng.gen_range(0, max_size + 1),
+        };
+        // Narrow ranges make keys repeat, telling unstable sorts apart.
+        let range = match rng.gen_range(0, 4) {
+            0 => 1,
+            1 => 10,
+            2 => 1000,
+            _ => i64::MAX,
+        };
+        let pattern = random_pattern(&mut rng, size);
+        let order = Order::random(&mut rng);
+
+        let keys = pattern.generate_by(
+            size,
+            &mut rng,
+            |rng: &mut StdRng| rng.gen_range(-range, range),
+            |left: &i64, right: &i64| {
+                let left = Element { key: *left, index: 0 };
+                let right = Element { key: *right, index: 0 };
+                order.compare(&left, &right)
+            },
+        );
+        let elements = keys
+            .into_iter()
+            .enumerate()
+            .map(|(index, key)| Element { key, index })
+            .collect();
+
+        Self {
+            seed,
+            pattern,
+            range,
+            order,
+            elements,
+            split_threads: rng.gen_range(1, max_threads + 1),
+            merge_threads: rng.gen_range(1, max_threads + 1),
+        }
+    }
+}
+
+/// Picks a pattern at random, with parameters fitting the size.
+fn random_pattern(rng: &mut StdRng, size: usize) -> Pattern {
+    match rng.gen_range(0, 9) {
+        0 => Pattern::Random,
+        1 => Pattern::Sorted,
+        2 => Pattern::Reverse,
+        3 => Pattern::NearlySorted { basis_points: rng.gen_range(0, 10001) },
+        4 => Pattern::FewUnique { values: rng.gen_range(1, 11) },
+        5 => Pattern::Sawtooth { period: rng.gen_range(1, size + 2) },
+        6 => Pattern::OrganPipe,
+        7 => Pattern::AllEqual,
+        _ => Pattern::MergeAdversarial,
+    }
+}
@@ -0,0 +1,145 @@
+//! Parsing of the command line arguments.
+
+use std::{error::Error, fmt, str::FromStr};
+
+/// What the binary was asked to do.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    /// Runs random cases.
+    Fuzz(Config),
+    /// Prints the usage.
+    Help,
+}
+
+/// Which cases are run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Config {
+    /// How many cases are run. `None` means until one fails.
+    pub cases: Option<u64>,
+    /// The seed of the first case, each next one having the next seed.
+    pub seed: u64,
+    /// The most elements a case sorts.
+    pub max_size: usize,
+    /// The most threads the parallel sort of a case runs on.
+    pub max_threads: usize,
+}
+
+/// An invalid command line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CliError {
+    /// What is wrong about it.
+    message: String,
+}
+
+impl CliError {
+    /// Creates an error with the given message.
+    fn new<S>(message: S) -> Self
+    where
+        S: Into<String>,
+    {
+        Self { message: message.into() }
+    }
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.write_str(&self.message)
+    }
+}
+
+impl Error for CliError {}
+
+/// The usage of the binary, as printed by `--help`.
+pub fn usage() -> &'static str {
+    "Sorts random arrays with random comparison functions with the sequential
+and the parallel sorts and with slice::sort_by, checking that they all give
+the same elements in the same order.
+
+Usage: mergesort-fuzz [OPTIONS]
+
+Stops at the first case that fails, printing it and how to run it again.
+
+Options:
+    --cases <N>            Number of cases run [default: until one fails]
+    --seed <SEED>          Seed of the first case, the next cases having the
+                           next seeds [default: random]
+    --max-size <N>         Most elements sorted by a case [default: 5000]
+    --max-threads <N>      Most threads of the parallel sort [default: 8]
+    -h, --help             Prints this message
+"
+}
+
+/// Parses the arguments, the binary left out.
+pub fn parse<I>(args: I) -> Result<Command, CliError>
+where
+    I: IntoIterator<Item = String>,
+{
+    let mut args = args.into_iter();
+    let mut cases = None;
+    let mut seed = None;
+    let mut max_size = 5000;
+    let mut max_threads = 8;
+
+    while let Some(arg) = args.next() {
+        let (option, inline) = split_option(arg);
+
+        let mut value = || {
+            inline.clone().or_else(|| args.next()).ok_or_else(|| {
+                CliError::new(format!("missing value for {}", option))
+            })
+        };
+
+        match option.as_str() {
+            "-h" | "--help" => return Ok(Command::Help),
+            "--cases" => cases = Some(parse_number(&option, &value()?)?),
+            "--seed" => seed = Some(parse_number(&option, &value()?)?),
+            "--max-size" => max_size = parse_number(&option, &value()?)?,
+            "--max-threads" => {
+                max_threads = parse_positive(&option, &value()?)?
+            },
+            _ => {
+                return Err(CliError::new(format!(
+                    "unexpected argument {:?}",
+                    option
+                )))
+            },
+        }
+    }
+
+    Ok(Command::Fuzz(Config {
+        cases,
+        seed: seed.unwrap_or_else(rand::random),
+        max_size,
+        max_threads,
+    }))
+}
+
+/// Splits an argument into the option and its value, if given inline. Both
+/// `--option value` and `--option=value` are accepted.
+fn split_option(arg: String) -> (String, Option<String>) {
+    match arg.find('=') {
+        Some(index) if arg.starts_with("--") => {
+            (arg[.. index].to_owned(), Some(arg[index + 1 ..].to_owned()))
+        },
+        _ => (arg, None),
+    }
+}
+
+/// Parses the number given to an option.
+fn parse_number<T>(option: &str, value: &str) -> Result<T, CliError>
+where
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    value.parse().map_err(|error| {
+        CliError::new(format!("invalid {} {:?}: {}", option, value, error))
+    })
+}
+
+/// Parses the number given to an option, which must not be zero.
+fn parse_positive(option: &str, value: &str) -> Result<usize, CliError> {
+    match parse_number(option, value)? {
+        0 => Err(CliError::new(format!("{} must be at least 1", option))),
+        number => Ok(number),
+    }
+}
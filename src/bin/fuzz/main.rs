@@ -0,0 +1,138 @@
+//! Checks the sorts against each other and against `slice::sort_by` on random
+//! cases, the elements, comparison functions and threads all drawn from the
+//! seed of the case. Every sort is stable, so they must all give the very
+//! same elements in the very same order.
+
+mod case;
+mod cli;
+
+use case::{Case, Element};
+use cli::{Command, Config};
+use mergesort_cmp::{parallel, sequential};
+use std::{
+    env,
+    panic::{self, AssertUnwindSafe},
+    process::exit,
+    sync::Arc,
+    time::Instant,
+};
+
+/// How many cases pass between reports of progress.
+const REPORT_EVERY: u64 = 1000;
+
+fn main() {
+    let config = match cli::parse(env::args().skip(1)) {
+        Ok(Command::Fuzz(config)) => config,
+        Ok(Command::Help) => {
+            print!("{}", cli::usage());
+            return;
+        },
+        Err(error) => {
+            eprintln!("error: {}", error);
+            eprintln!("Try `--help` for more information.");
+            exit(2);
+        },
+    };
+    if let Err(failure) = fuzz(&config) {
+        eprintln!("{}", failure);
+        exit(1);
+    }
+}
+
+/// Runs the cases, stopping at the first one failing.
+fn fuzz(config: &Config) -> Result<(), String> {
+    eprintln!("fuzzing from seed {}", config.seed);
+    let start = Instant::now();
+    let mut passed = 0;
+
+    while config.cases.is_none_or(|cases| passed < cases) {
+        let seed = config.seed.wrapping_add(passed);
+        let case = Case::generate(seed, config.max_size, config.max_threads);
+        check(config, &case)?;
+        passed += 1;
+        if passed % REPORT_EVERY == 0 {
+            let elapsed = start.elapsed().as_secs_f64();
+            eprintln!("{} cases passed in {:.1} s", passed, elapsed);
+        }
+    }
+
+    eprintln!("all {} cases passed", passed);
+    Ok(())
+}
+
+/// Sorts the case with every sort, failing with a description of the case if
+/// any of them does not give what `slice::sort_by` gives.
+fn check(config: &Config, case: &Case) -> Result<(), String> {
+    let order = case.order;
+    let compare = move |left: &Element, right: &Element| {
+        order.compare(left, right)
+    };
+    let mut expected = case.elements.clone();
+    expected.sort_by(compare);
+
+    let array: Arc<[Element]> = Arc::from(&case.elements[..]);
+    let mut options = parallel::custom_order(compare);
+    options
+        .split_threads(case.split_threads)
+        .merge_threads(case.merge_threads);
+    let sorts: [(&str, &dyn Fn() -> Vec<Element>); 3] = [
+        ("sequential::sort_by", &|| sequential::sort_by(&array, compare)),
+        ("sequential::sort_galloping_by", &|| {
+            sequential::sort_galloping_by(&array, compare)
+        }),
+        ("parallel::custom_order", &|| options.sort(&array)),
+    ];
+
+    for (name, sort) in sorts.iter() {
+        let sorted = panic::catch_unwind(AssertUnwindSafe(sort))
+            .map_err(|_| describe(config, case, name, "panicked"))?;
+        if let Some(problem) = difference(&expected, &sorted) {
+            return Err(describe(config, case, name, &problem));
+        }
+    }
+    Ok(())
+}
+
+/// Where the sorted elements first differ from the expected ones, if they do.
+fn difference(expected: &[Element], sorted: &[Element]) -> Option<String> {
+    if sorted.len() != expected.len() {
+        return Some(format!(
+            "gave {} elements instead of {}",
+            sorted.len(),
+            expected.len()
+        ));
+    }
+    let position = (0 .. sorted.len())
+        .find(|&position| sorted[position] != expected[position])?;
+    Some(format!(
+        "gave {:?} at position {} instead of {:?}",
+        sorted[position], position, expected[position]
+    ))
+}
+
+/// Describes the failure of a sort on the case, with how to run it again.
+fn describe(
+    config: &Config,
+    case: &Case,
+    sort: &str,
+    problem: &str,
+) -> String {
+    let mut text = format!("{} {}\n", sort, problem);
+    text += &format!("seed: {}\n", case.seed);
+    text += &format!("size: {}\n", case.elements.len());
+    text += &format!("pattern: {}\n", case.pattern);
+    text += &format!("range: {}\n", case.range);
+    text += &format!("order: {}\n", case.order);
+    text += &format!("split threads: {}\n", case.split_threads);
+    text += &format!("merge threads: {}\n", case.merge_threads);
+    if case.elements.len() <= 64 {
+        let keys = case.elements.iter().map(|element| element.key);
+        text += &format!("keys: {:?}\n", keys.collect::<Vec<_>>());
+    }
+    text += &format!(
+        "run it again with: mergesort-fuzz --seed {} --cases 1 \
+         --max-size {} --max-threads {}",
+        case.seed, config.max_size, config.max_threads
+    );
+    text
+}
@@ -0,0 +1,55 @@
+//! Pinning of the benchmark to some of the CPUs, so machines with cores of
+//! different kinds and servers shared with other work give the same results
+//! from run to run. Threads start on the CPUs of the thread starting them, so
+//! pinning the main thread before anything runs pins the workers too.
+
+use std::io;
+
+/// Pins the calling thread, and every thread it starts from now on, to the
+/// given CPUs. Only supported on Linux, with the `affinity` feature.
+#[cfg(all(target_os = "linux", feature = "affinity"))]
+pub fn pin(cpus: &[usize]) -> io::Result<()> {
+    let mut set = unsafe { std::mem::zeroed::<libc::cpu_set_t>() };
+    for &cpu in cpus {
+        if cpu >= libc::CPU_SETSIZE as usize {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("CPU {} is past the last one that can be pinned", cpu),
+            ));
+        }
+        unsafe { libc::CPU_SET(cpu, &mut set) };
+    }
+    let size = std::mem::size_of::<libc::cpu_set_t>();
+    match unsafe { libc::sched_setaffinity(0, size, &set) } {
+        0 => Ok(()),
+        _ => Err(io::Error::last_os_error()),
+    }
+}
+
+/// Pins the calling thread to the given CPUs. Only supported on Linux, with
+/// the `affinity` feature.
+#[cfg(not(all(target_os = "linux", feature = "affinity")))]
+pub fn pin(_cpus: &[usize]) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "pinning to CPUs is only supported on Linux, with the affinity \
+         feature",
+    ))
+}
+
+/// Writes sorted CPUs as they are given to `--cpus`, with ranges for the
+/// consecutive ones, such as `0-3,6`.
+pub fn describe(cpus: &[usize]) -> String {
+    let mut ranges = Vec::<(usize, usize)>::new();
+    for &cpu in cpus {
+        match ranges.last_mut() {
+            Some((_, last)) if *last + 1 == cpu => *last = cpu,
+            _ => ranges.push((cpu, cpu)),
+        }
+    }
+    let ranges = ranges.iter().map(|&(first, last)| match first == last {
+        true => first.to_string(),
+        false => format!("{}-{}", first, last),
+    });
+    ranges.collect::<Vec<_>>().join(",")
+}
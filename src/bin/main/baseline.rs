@@ -0,0 +1,216 @@
+//! Saved results a run is compared to, so regressions of the sorts are caught.
+//!
+//! A baseline is the JSON report of a run, saved under its name in the
+//! `baselines` directory.
+
+use crate::{
+    json::Json,
+    output::{
+        human,
+        json::{report_json, report_schema},
+    },
+    report::BenchReport,
+};
+use std::{
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+/// The directory the baselines are saved in.
+const DIRECTORY: &str = "baselines";
+
+/// Where the baseline of the given name is saved.
+pub fn path(name: &str) -> PathBuf {
+    PathBuf::from(DIRECTORY).join(format!("{}.json", name))
+}
+
+/// Saves the results of a run as the baseline of the given name, replacing
+/// any baseline it had.
+pub fn save(name: &str, report: &BenchReport) -> io::Result<()> {
+    fs::create_dir_all(DIRECTORY)?;
+    let json = report_json(&report_schema(report));
+    fs::write(path(name), format!("{}\n", json))
+}
+
+/// The results of a run that was saved.
+#[derive(Debug, Clone)]
+pub struct Baseline {
+    /// The name it was saved under.
+    pub name: String,
+    /// The type of the elements of the run.
+    pub element: String,
+    /// The results of each case set.
+    pub sets: Vec<SetBaseline>,
+}
+
+/// The saved results of a case set.
+#[derive(Debug, Clone)]
+pub struct SetBaseline {
+    /// The name of the case set.
+    pub name: String,
+    /// The pattern of its arrays.
+    pub pattern: String,
+    /// The results of each target that has a complete run.
+    pub targets: Vec<SavedTarget>,
+}
+
+/// The saved results of a target on a case set.
+#[derive(Debug, Clone)]
+pub struct SavedTarget {
+    /// The label of the target, with its thread count if it ran in a sweep.
+    pub label: String,
+    /// The mean time over the set, in seconds.
+    pub mean: f64,
+    /// The time over the set of each repetition, in seconds.
+    pub seconds: Vec<f64>,
+}
+
+/// A target that got slower.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Regression {
+    /// The case set it got slower on.
+    pub set: String,
+    /// The label of the target.
+    pub target: String,
+    /// How much slower it got, as a percentage.
+    pub percent: f64,
+}
+
+impl Baseline {
+    /// Loads the baseline of the given name.
+    pub fn load(name: &str) -> io::Result<Self> {
+        Self::read(&path(name), name)
+    }
+
+    /// Reads a JSON report from a file, naming it as given.
+    pub fn read(path: &Path, name: &str) -> io::Result<Self> {
+        let text = fs::read_to_string(path)?;
+        let json = Json::parse(&text).map_err(|error| {
+            io::Error::new(io::ErrorKind::InvalidData, error)
+        })?;
+        Self::from_json(name, &json).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidData, "not a saved report")
+        })
+    }
+
+    /// Reads the results out of a report.
+    fn from_json(name: &str, json: &Json) -> Option<Self> {
+        let element = json.get("element_type")?.as_str()?.to_owned();
+        let sets = json.get("case_sets")?.as_array()?;
+        let sets = sets.iter().map(SetBaseline::from_json);
+        let sets = sets.collect::<Option<_>>()?;
+        Some(Self { name: name.to_owned(), element, sets })
+    }
+
+    /// Writes how the mean time of each target changed since the baseline,
+    /// returning the targets slower by more than `threshold` percent.
+    pub fn compare<W>(
+        &self,
+        report: &BenchReport,
+        threshold: f64,
+        output: &mut W,
+    ) -> io::Result<Vec<Regression>>
+    where
+        W: Write,
+    {
+        let mut regressions = Vec::new();
+        writeln!(
+            output,
+            "Compared to baseline {} (regression threshold {}%):",
+            self.name, threshold
+        )?;
+        if self.element != report.element.name() {
+            writeln!(
+                output,
+                "  the baseline sorted elements of type {}, nothing compared",
+                self.element
+            )?;
+            return Ok(regressions);
+        }
+
+        for set in &report.sets {
+            let pattern = set.pattern.to_string();
+            writeln!(output, "  {}, pattern = {}:", set.spec.name, pattern)?;
+            let saved = self.sets.iter().find(|saved| {
+                saved.name == set.spec.name && saved.pattern == pattern
+            });
+
+            for target in &set.targets {
+                let label = target.label();
+                if target.runs.is_empty() {
+                    writeln!(output, "    {:<24} {:>12}", label, "timed out")?;
+                    continue;
+                }
+                let now = target.mean_seconds();
+                let before = saved.and_then(|saved| saved.target(&label));
+                let before = before.map(|target| target.mean);
+                let before = match before {
+                    Some(before) if before > 0.0 => before,
+                    _ => {
+                        writeln!(
+                            output,
+                            "    {:<24} {:>12}   not in the baseline",
+                            label,
+                            human(now)
+                        )?;
+                        continue;
+                    },
+                };
+
+                let percent = (now - before) / before * 100.0;
+                let regressed = percent > threshold;
+                writeln!(
+                    output,
+                    "    {:<24} {:>12} -> {:>12} {:>+8.1}%{}",
+                    label,
+                    human(before),
+                    human(now),
+                    percent,
+                    if regressed { "  regressed" } else { "" }
+                )?;
+                if regressed {
+                    regressions.push(Regression {
+                        set: set.spec.name.to_owned(),
+                        target: label.into_owned(),
+                        percent,
+                    });
+                }
+            }
+        }
+
+        Ok(regressions)
+    }
+}
+
+impl SetBaseline {
+    /// The saved results of the target of the given label.
+    pub fn target(&self, label: &str) -> Option<&SavedTarget> {
+        self.targets.iter().find(|target| target.label == label)
+    }
+
+    /// Reads the results of a case set out of a report.
+    fn from_json(json: &Json) -> Option<Self> {
+        let name = json.get("name")?.as_str()?.to_owned();
+        let pattern = json.get("pattern")?.as_str()?.to_owned();
+        let targets = json.get("targets")?.as_array()?;
+        let mut saved = Vec::new();
+        for target in targets {
+            let name = target.get("name")?.as_str()?;
+            let label = match target.get("threads")?.as_u64() {
+                Some(threads) => format!("{}@{}", name, threads),
+                None => name.to_owned(),
+            };
+            // Targets that timed out have no mean to compare to.
+            let mean = target.get("total_seconds")?.get("mean");
+            let seconds = target.get("seconds")?.as_array()?;
+            let seconds = seconds.iter().map(Json::as_f64);
+            let seconds = seconds.collect::<Option<_>>()?;
+            if let Some(mean) = mean.and_then(Json::as_f64) {
+                saved.push(SavedTarget { label, mean, seconds });
+            }
+        }
+        let targets = saved;
+        Some(Self { name, pattern, targets })
+    }
+}
@@ -0,0 +1,67 @@
+//! Eviction of the CPU caches, so the targets read the cases from memory and
+//! not from the caches the runs before left them in.
+
+use std::{fs, hint};
+
+/// The files with the sizes of the caches of the first CPU, on Linux.
+const CACHE_DIR: &str = "/sys/devices/system/cpu/cpu0/cache";
+
+/// The size of the biggest cache assumed where it cannot be found.
+const DEFAULT_CACHE_BYTES: usize = 32 << 20;
+
+/// The size of a cache line, as far as eviction goes. Touching a byte of
+/// every line is enough.
+const LINE_BYTES: usize = 64;
+
+/// A buffer twice as big as the biggest cache, which the caches only hold
+/// once everything else in them has been evicted.
+#[derive(Debug)]
+pub struct Evictor {
+    /// The buffer written over to evict the caches.
+    buffer: Vec<u8>,
+    /// How many times the buffer was written over, so every write changes
+    /// it.
+    passes: u8,
+}
+
+impl Evictor {
+    /// Allocates the buffer, faulting its pages in.
+    pub fn new() -> Self {
+        Self { buffer: vec![1; eviction_bytes()], passes: 1 }
+    }
+
+    /// Evicts the caches by writing to every line of the buffer.
+    pub fn evict(&mut self) {
+        self.passes = self.passes.wrapping_add(1);
+        for line in self.buffer.chunks_mut(LINE_BYTES) {
+            line[0] = self.passes;
+        }
+        hint::black_box(&mut self.buffer);
+    }
+}
+
+/// How big the buffer evicting the caches is: twice the biggest cache.
+pub fn eviction_bytes() -> usize {
+    2 * biggest_cache().unwrap_or(DEFAULT_CACHE_BYTES)
+}
+
+/// The size of the biggest cache of the first CPU, in bytes. Only found on
+/// Linux.
+fn biggest_cache() -> Option<usize> {
+    let sizes = fs::read_dir(CACHE_DIR).ok()?.filter_map(|entry| {
+        let size = fs::read_to_string(entry.ok()?.path().join("size")).ok()?;
+        parse_size(size.trim())
+    });
+    sizes.max()
+}
+
+/// Parses a size as written in sysfs, such as `48K` or `32M`.
+fn parse_size(size: &str) -> Option<usize> {
+    let (number, scale) = match size.as_bytes().last()? {
+        b'K' => (&size[.. size.len() - 1], 1 << 10),
+        b'M' => (&size[.. size.len() - 1], 1 << 20),
+        b'G' => (&size[.. size.len() - 1], 1 << 30),
+        _ => (size, 1),
+    };
+    number.parse::<usize>().ok().map(|number| number * scale)
+}
@@ -0,0 +1,270 @@
+//! Files of generated cases, so a run can be replayed with the exact same
+//! cases on another machine or version.
+//!
+//! The file starts with a header: the magic bytes, the version, the seed,
+//! the type and shape of the elements, and the number of sets. Each set
+//! follows with its parameters, its pattern and its cases. Numbers are
+//! little-endian, strings are prefixed by their length in bytes.
+
+use crate::{
+    cases::{CaseSet, SetSpec},
+    elements::{Element, ElementType, StringShape, PAYLOAD_BYTES},
+    patterns::Pattern,
+};
+use std::{
+    convert::TryFrom,
+    fs::File,
+    io::{self, BufReader, BufWriter, Read, Write},
+    path::Path,
+    sync::Arc,
+};
+
+/// The bytes every case file starts with.
+const MAGIC: &[u8] = b"mergesort-cmp cases\n";
+
+/// The version of the format written.
+const VERSION: u64 = 1;
+
+/// What is known about the cases before reading any of them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Header {
+    /// The seed the cases were generated from.
+    pub seed: u64,
+    /// The type of the elements.
+    pub element: ElementType,
+    /// What the strings look like, used if the elements are strings.
+    pub strings: StringShape,
+    /// The size of the payloads, used if the elements have payloads.
+    pub payload_bytes: usize,
+    /// How many sets the file has.
+    pub sets: usize,
+}
+
+/// Writes case sets to a file as they are generated.
+pub struct CaseWriter {
+    /// The file being written.
+    output: BufWriter<File>,
+}
+
+impl CaseWriter {
+    /// Creates the file, writing the header.
+    pub fn create(path: &Path, header: &Header) -> io::Result<Self> {
+        let mut output = BufWriter::new(File::create(path)?);
+        output.write_all(MAGIC)?;
+        write_u64(&mut output, VERSION)?;
+        write_u64(&mut output, header.seed)?;
+        write_str(&mut output, header.element.name())?;
+        write_usize(&mut output, header.strings.min_length)?;
+        write_usize(&mut output, header.strings.max_length)?;
+        let alphabet = header.strings.alphabet.iter().collect::<String>();
+        write_str(&mut output, &alphabet)?;
+        write_usize(&mut output, header.payload_bytes)?;
+        write_usize(&mut output, header.sets)?;
+        Ok(Self { output })
+    }
+
+    /// Writes a set with the parameters and pattern it was generated with.
+    pub fn write_set<T>(
+        &mut self,
+        spec: &SetSpec,
+        pattern: Pattern,
+        set: &CaseSet<T>,
+    ) -> io::Result<()>
+    where
+        T: Element,
+    {
+        let output = &mut self.output;
+        write_str(output, spec.name)?;
+        write_usize(output, spec.min_size)?;
+        write_usize(output, spec.max_size)?;
+        write_str(output, &pattern.to_string())?;
+        write_usize(output, set.cases.len())?;
+
+        for case in &set.cases {
+            write_usize(output, case.len())?;
+            for element in case.iter() {
+                element.write_to(output)?;
+            }
+        }
+
+        output.flush()
+    }
+}
+
+/// Reads the case sets of a file, one at a time.
+pub struct CaseReader {
+    /// The file being read.
+    input: BufReader<File>,
+    /// How many sets are left.
+    remaining: usize,
+}
+
+impl CaseReader {
+    /// Opens the file, reading the header.
+    pub fn open(path: &Path) -> io::Result<(Self, Header)> {
+        let mut input = BufReader::new(File::open(path)?);
+
+        let mut magic = [0; MAGIC.len()];
+        input.read_exact(&mut magic).map_err(|_| invalid("not a case file"))?;
+        if magic != MAGIC {
+            return Err(invalid("not a case file"));
+        }
+        let version = read_u64(&mut input)?;
+        if version != VERSION {
+            return Err(invalid(format!("unsupported version {}", version)));
+        }
+
+        let seed = read_u64(&mut input)?;
+        let element = read_str(&mut input)?;
+        let element = ElementType::from_name(&element).ok_or_else(|| {
+            invalid(format!("unknown element type {:?}", element))
+        })?;
+        let min_length = read_usize(&mut input)?;
+        let max_length = read_usize(&mut input)?;
+        let alphabet = read_str(&mut input)?.chars().collect();
+        let strings = StringShape { min_length, max_length, alphabet };
+        let payload_bytes = read_usize(&mut input)?;
+        if element == ElementType::Payload
+            && !PAYLOAD_BYTES.contains(&payload_bytes)
+        {
+            return Err(invalid(format!(
+                "unsupported payload of {} bytes",
+                payload_bytes
+            )));
+        }
+        let sets = read_usize(&mut input)?;
+
+        let header = Header { seed, element, strings, payload_bytes, sets };
+        Ok((Self { input, remaining: sets }, header))
+    }
+
+    /// How many sets are left to read.
+    pub fn remaining(&self) -> usize {
+        self.remaining
+    }
+
+    /// Reads the next set, with its parameters and pattern. `None` once every
+    /// set was read.
+    pub fn next_set<T>(
+        &mut self,
+    ) -> io::Result<Option<(&'static SetSpec, Pattern, CaseSet<T>)>>
+    where
+        T: Element,
+    {
+        if self.remaining == 0 {
+            return Ok(None);
+        }
+        self.remaining -= 1;
+
+        let input = &mut self.input;
+        let name = read_str(input)?;
+        let min_size = read_usize(input)?;
+        let max_size = read_usize(input)?;
+        let pattern = read_str(input)?;
+        let pattern = pattern.parse().map_err(|_| {
+            invalid(format!("unknown pattern {:?}", pattern))
+        })?;
+        let count = read_usize(input)?;
+
+        let mut cases = Vec::new();
+        for _ in 0 .. count {
+            let length = read_usize(input)?;
+            let case = (0 .. length)
+                .map(|_| T::read_from(input))
+                .collect::<io::Result<Vec<_>>>()?;
+            cases.push(Arc::from(case));
+        }
+
+        let spec = match SetSpec::find(&name) {
+            Some(known)
+                if (known.count, known.min_size, known.max_size)
+                    == (count, min_size, max_size) =>
+            {
+                known
+            },
+            // Other sets need to live as long as the built-in ones, and there
+            // are only a few of them per run.
+            _ => Box::leak(Box::new(SetSpec {
+                name: Box::leak(name.into_boxed_str()),
+                count,
+                min_size,
+                max_size,
+            })),
+        };
+        Ok(Some((spec, pattern, CaseSet { cases })))
+    }
+}
+
+/// An error about the contents of a case file.
+fn invalid<S>(message: S) -> io::Error
+where
+    S: Into<String>,
+{
+    io::Error::new(io::ErrorKind::InvalidData, message.into())
+}
+
+/// Explains that a file ended too soon, keeping other errors as they are.
+fn truncated(error: io::Error) -> io::Error {
+    match error.kind() {
+        io::ErrorKind::UnexpectedEof => invalid("the case file is truncated"),
+        _ => error,
+    }
+}
+
+/// Writes a number.
+pub fn write_u64<W>(output: &mut W, value: u64) -> io::Result<()>
+where
+    W: Write,
+{
+    output.write_all(&value.to_le_bytes())
+}
+
+/// Reads a number.
+pub fn read_u64<R>(input: &mut R) -> io::Result<u64>
+where
+    R: Read,
+{
+    let mut bytes = [0; 8];
+    input.read_exact(&mut bytes).map_err(truncated)?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+/// Writes a size or a count.
+fn write_usize<W>(output: &mut W, value: usize) -> io::Result<()>
+where
+    W: Write,
+{
+    write_u64(output, value as u64)
+}
+
+/// Reads a size or a count.
+fn read_usize<R>(input: &mut R) -> io::Result<usize>
+where
+    R: Read,
+{
+    let value = read_u64(input)?;
+    usize::try_from(value).map_err(|_| invalid("size too big"))
+}
+
+/// Writes a string, prefixed by its length.
+pub fn write_str<W>(output: &mut W, value: &str) -> io::Result<()>
+where
+    W: Write,
+{
+    write_usize(output, value.len())?;
+    output.write_all(value.as_bytes())
+}
+
+/// Reads a string prefixed by its length.
+pub fn read_str<R>(input: &mut R) -> io::Result<String>
+where
+    R: Read,
+{
+    let length = read_usize(input)?;
+    let mut bytes = Vec::new();
+    input.take(length as u64).read_to_end(&mut bytes)?;
+    if bytes.len() != length {
+        return Err(truncated(io::ErrorKind::UnexpectedEof.into()));
+    }
+    String::from_utf8(bytes).map_err(|_| invalid("string is not UTF-8"))
+}
@@ -0,0 +1,103 @@
+//! Generation of the test cases.
+
+use crate::{
+    elements::Element,
+    patterns::{self, Pattern},
+};
+use rand::{distributions::Uniform, rngs::StdRng, Rng, SeedableRng};
+use std::sync::Arc;
+
+/// The parameters of a set of cases.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SetSpec {
+    /// The name the set is selected by.
+    pub name: &'static str,
+    /// How many cases the set has.
+    pub count: usize,
+    /// The minimum size of an array.
+    pub min_size: usize,
+    /// The maximum size of an array, inclusive.
+    pub max_size: usize,
+}
+
+/// The built-in case sets, from the smallest arrays to the biggest ones.
+pub const SETS: &[SetSpec] = &[
+    SetSpec { name: "tiny", count: 5120, min_size: 1, max_size: 50 },
+    SetSpec { name: "small", count: 1280, min_size: 100, max_size: 500 },
+    SetSpec { name: "medium", count: 320, min_size: 1000, max_size: 5000 },
+    SetSpec { name: "big", count: 80, min_size: 10000, max_size: 50000 },
+    SetSpec { name: "large", count: 20, min_size: 100000, max_size: 500000 },
+    SetSpec { name: "huge", count: 5, min_size: 1000000, max_size: 5000000 },
+];
+
+impl SetSpec {
+    /// Finds the built-in set of the given name.
+    pub fn find(name: &str) -> Option<&'static Self> {
+        SETS.iter().find(|spec| spec.name == name)
+    }
+
+    /// The seed of this set's generator. It only depends on the run's seed
+    /// and the set's name, so selecting some sets does not change the others.
+    pub fn seed(&self, seed: u64) -> u64 {
+        // FNV-1a, starting from the run's seed.
+        self.name.bytes().fold(seed, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x100000001b3)
+        })
+    }
+
+    /// The sizes of the cases generated from the run's seed, in order.
+    pub fn sizes(&self, seed: u64) -> Vec<usize> {
+        let mut rng = StdRng::seed_from_u64(self.seed(seed));
+        let sizes = Uniform::new_inclusive(self.min_size, self.max_size);
+        (0 .. self.count).map(|_| rng.sample(sizes)).collect()
+    }
+}
+
+/// A set of test cases generated randomly.
+#[derive(Debug, Clone)]
+pub struct CaseSet<T> {
+    /// The arrays to be sorted.
+    pub cases: Vec<Arc<[T]>>,
+}
+
+impl<T> CaseSet<T>
+where
+    T: Element,
+{
+    /// Generates the cases of the given set with the given pattern and shape
+    /// of elements, with array sizes uniformly distributed between the set's
+    /// bounds.
+    pub fn generate(
+        spec: &SetSpec,
+        pattern: Pattern,
+        shape: &T::Shape,
+        seed: u64,
+    ) -> Self {
+        // Sizes have their own generator, so they are the same whatever the
+        // pattern is.
+        let mut rng = StdRng::seed_from_u64(!spec.seed(seed));
+        let mut cases = Vec::with_capacity(spec.count);
+
+        for size in spec.sizes(seed) {
+            let case = patterns::generate(pattern, shape, size, &mut rng);
+            cases.push(Arc::from(case));
+        }
+
+        Self { cases }
+    }
+
+    /// Generates the cases of the given set with each of the seeds, those of
+    /// a seed after the ones of the seed before.
+    pub fn generate_seeds(
+        spec: &SetSpec,
+        pattern: Pattern,
+        shape: &T::Shape,
+        seeds: &[u64],
+    ) -> Self {
+        let mut cases = Vec::with_capacity(spec.count * seeds.len());
+        for &seed in seeds {
+            cases.extend(Self::generate(spec, pattern, shape, seed).cases);
+        }
+        Self { cases }
+    }
+}
@@ -0,0 +1,313 @@
+//! An SVG chart of the time each target took by the size of each case.
+
+use crate::report::BenchReport;
+use std::{
+    borrow::Cow,
+    fmt::{self, Write as _},
+    fs,
+    io,
+    path::Path,
+};
+
+/// The size of the whole chart, in pixels.
+const WIDTH: f64 = 960.0;
+const HEIGHT: f64 = 600.0;
+/// The space around the plot: left, right, top and bottom. The right side
+/// has the legend.
+const MARGINS: (f64, f64, f64, f64) = (80.0, 200.0, 50.0, 60.0);
+
+/// The colors of the targets, in order, repeating if there are more targets.
+const COLORS: &[&str] = &[
+    "#1f77b4", "#ff7f0e", "#2ca02c", "#d62728", "#9467bd", "#8c564b",
+    "#e377c2", "#7f7f7f", "#bcbd22", "#17becf",
+];
+
+/// How many buckets of sizes the lines go through, by their medians.
+const BUCKETS: usize = 24;
+
+/// The points of a target: the size of each case and the seconds it took.
+struct Series<'report> {
+    /// The label of the target.
+    label: Cow<'report, str>,
+    /// Every case of every set the target sorted.
+    points: Vec<(f64, f64)>,
+}
+
+/// Renders the chart of the report: a point per case of every target, with a
+/// line through the medians, on log-log axes.
+pub fn render(report: &BenchReport) -> String {
+    let mut series = Vec::<Series>::new();
+    for set in &report.sets {
+        for target in &set.targets {
+            let label = target.label();
+            let index = match series.iter().position(|s| s.label == label) {
+                Some(index) => index,
+                None => {
+                    series.push(Series { label, points: Vec::new() });
+                    series.len() - 1
+                },
+            };
+            let sizes = set.sizes.iter().map(|&size| size as f64);
+            let points = sizes.zip(target.case_means());
+            // Logarithmic axes cannot show zeros.
+            let points = points.filter(|&(x, y)| x > 0.0 && y > 0.0);
+            series[index].points.extend(points);
+        }
+    }
+
+    let all = series.iter().flat_map(|series| series.points.iter());
+    let x_range = Range::of(all.clone().map(|&(size, _)| size));
+    let y_range = Range::of(all.map(|&(_, time)| time));
+    let (left, right, top, bottom) = MARGINS;
+    let plot = Plot {
+        x: x_range,
+        y: y_range,
+        left,
+        right: WIDTH - right,
+        top,
+        bottom: HEIGHT - bottom,
+    };
+
+    let mut svg = String::new();
+    // Writing to a string does not fail.
+    let _ = write_chart(&mut svg, report, &plot, &series);
+    svg
+}
+
+/// Renders the chart to a file.
+pub fn write(path: &Path, report: &BenchReport) -> io::Result<()> {
+    fs::write(path, render(report))
+}
+
+/// Every part of the chart.
+fn write_chart(
+    svg: &mut String,
+    report: &BenchReport,
+    plot: &Plot,
+    series: &[Series],
+) -> fmt::Result {
+    writeln!(
+        svg,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" \
+         height=\"{h}\" viewBox=\"0 0 {w} {h}\" font-family=\"sans-serif\" \
+         font-size=\"12\">",
+        w = WIDTH,
+        h = HEIGHT
+    )?;
+    writeln!(svg, "<rect width=\"100%\" height=\"100%\" fill=\"white\"/>")?;
+    writeln!(
+        svg,
+        "<text x=\"{:.1}\" y=\"30\" text-anchor=\"middle\" font-size=\"16\">\
+         Time by case size, {}, elements of type {}</text>",
+        (plot.left + plot.right) / 2.0,
+        report.seeds_description(),
+        escape(&report.element_description())
+    )?;
+
+    write_axes(svg, plot)?;
+
+    for (index, series) in series.iter().enumerate() {
+        let color = COLORS[index % COLORS.len()];
+        writeln!(svg, "<g fill=\"{}\" fill-opacity=\"0.35\">", color)?;
+        for &(size, time) in &series.points {
+            writeln!(
+                svg,
+                "<circle cx=\"{:.1}\" cy=\"{:.1}\" r=\"1.5\"/>",
+                plot.x_of(size),
+                plot.y_of(time)
+            )?;
+        }
+        writeln!(svg, "</g>")?;
+
+        let line = medians(&series.points, &plot.x)
+            .into_iter()
+            .map(|(size, time)| {
+                format!("{:.1},{:.1}", plot.x_of(size), plot.y_of(time))
+            })
+            .collect::<Vec<_>>();
+        writeln!(
+            svg,
+            "<polyline fill=\"none\" stroke=\"{}\" stroke-width=\"2\" \
+             points=\"{}\"/>",
+            color,
+            line.join(" ")
+        )?;
+
+        let y = plot.top + 10.0 + index as f64 * 20.0;
+        writeln!(
+            svg,
+            "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"12\" height=\"12\" \
+             fill=\"{}\"/>",
+            plot.right + 20.0,
+            y,
+            color
+        )?;
+        writeln!(
+            svg,
+            "<text x=\"{:.1}\" y=\"{:.1}\">{}</text>",
+            plot.right + 38.0,
+            y + 10.0,
+            escape(&series.label)
+        )?;
+    }
+
+    writeln!(svg, "</svg>")
+}
+
+/// The frame, the ticks at powers of ten and the names of the axes.
+fn write_axes(svg: &mut String, plot: &Plot) -> fmt::Result {
+    for exponent in plot.x.decades() {
+        let x = plot.x_of(10f64.powi(exponent));
+        writeln!(
+            svg,
+            "<line x1=\"{x:.1}\" y1=\"{:.1}\" x2=\"{x:.1}\" y2=\"{:.1}\" \
+             stroke=\"#ddd\"/>",
+            plot.top,
+            plot.bottom,
+            x = x
+        )?;
+        writeln!(
+            svg,
+            "<text x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"middle\">\
+             1e{}</text>",
+            x,
+            plot.bottom + 18.0,
+            exponent
+        )?;
+    }
+    for exponent in plot.y.decades() {
+        let y = plot.y_of(10f64.powi(exponent));
+        writeln!(
+            svg,
+            "<line x1=\"{:.1}\" y1=\"{y:.1}\" x2=\"{:.1}\" y2=\"{y:.1}\" \
+             stroke=\"#ddd\"/>",
+            plot.left,
+            plot.right,
+            y = y
+        )?;
+        writeln!(
+            svg,
+            "<text x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"end\">1e{}s</text>",
+            plot.left - 6.0,
+            y + 4.0,
+            exponent
+        )?;
+    }
+
+    // The frame goes over the grid.
+    writeln!(
+        svg,
+        "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" \
+         fill=\"none\" stroke=\"black\"/>",
+        plot.left,
+        plot.top,
+        plot.right - plot.left,
+        plot.bottom - plot.top
+    )?;
+
+    writeln!(
+        svg,
+        "<text x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"middle\">elements</text>",
+        (plot.left + plot.right) / 2.0,
+        HEIGHT - 15.0
+    )?;
+    writeln!(
+        svg,
+        "<text transform=\"translate(20 {:.1}) rotate(-90)\" \
+         text-anchor=\"middle\">seconds per case</text>",
+        (plot.top + plot.bottom) / 2.0
+    )
+}
+
+/// The median point of each bucket of sizes with points in it, in order of
+/// size.
+fn medians(points: &[(f64, f64)], x: &Range) -> Vec<(f64, f64)> {
+    let mut buckets = vec![Vec::new(); BUCKETS];
+    for &(size, time) in points {
+        let bucket = (x.fraction(size) * BUCKETS as f64) as usize;
+        buckets[bucket.min(BUCKETS - 1)].push((size, time));
+    }
+
+    let median = |values: &mut Vec<f64>| {
+        values.sort_by(f64::total_cmp);
+        values[values.len() / 2]
+    };
+    buckets
+        .into_iter()
+        .filter(|bucket| !bucket.is_empty())
+        .map(|bucket| {
+            let (mut sizes, mut times) =
+                bucket.into_iter().unzip::<_, _, Vec<_>, Vec<_>>();
+            (median(&mut sizes), median(&mut times))
+        })
+        .collect()
+}
+
+/// The span of an axis, in `log10` of the values.
+#[derive(Debug, Clone, Copy)]
+struct Range {
+    /// The smallest logarithm shown.
+    low: f64,
+    /// The biggest logarithm shown.
+    high: f64,
+}
+
+impl Range {
+    /// The range covering the values, which must be positive, extended to
+    /// the powers of ten around them.
+    fn of<I>(values: I) -> Self
+    where
+        I: Iterator<Item = f64>,
+    {
+        let (low, high) = values.fold((f64::MAX, f64::MIN), |(low, high), v| {
+            (low.min(v.log10()), high.max(v.log10()))
+        });
+        if low > high {
+            // Nothing to show, any range does.
+            return Self { low: 0.0, high: 1.0 };
+        }
+        let (low, high) = (low.floor(), high.ceil());
+        Self { low, high: if high > low { high } else { low + 1.0 } }
+    }
+
+    /// Where the value falls in the range, from 0 to 1.
+    fn fraction(&self, value: f64) -> f64 {
+        (value.log10() - self.low) / (self.high - self.low)
+    }
+
+    /// The exponents of the powers of ten in the range.
+    fn decades(&self) -> impl Iterator<Item = i32> {
+        self.low as i32 ..= self.high as i32
+    }
+}
+
+/// Where the data goes in the chart.
+#[derive(Debug, Clone, Copy)]
+struct Plot {
+    /// The sizes of the cases.
+    x: Range,
+    /// The seconds taken.
+    y: Range,
+    /// The edges of the plot, in pixels.
+    left: f64,
+    right: f64,
+    top: f64,
+    bottom: f64,
+}
+
+impl Plot {
+    /// The horizontal position of a size.
+    fn x_of(&self, size: f64) -> f64 {
+        self.left + self.x.fraction(size) * (self.right - self.left)
+    }
+
+    /// The vertical position of a time.
+    fn y_of(&self, time: f64) -> f64 {
+        self.bottom - self.y.fraction(time) * (self.bottom - self.top)
+    }
+}
+
+/// Escapes text for the SVG.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
@@ -0,0 +1,180 @@
+//! Checkpoints of long runs, so a run interrupted after hours goes on from
+//! where it stopped instead of starting over.
+//!
+//! A checkpoint is a file of JSON lines. The first one has how to run the
+//! benchmark again: the arguments it was given and its seeds. Each one after
+//! has the results of a target on a set, written as soon as the target was
+//! measured on the set. A target interrupted in the middle of a set runs the
+//! whole set again, its warmup included.
+
+use crate::{
+    cli::{self, Command, Config},
+    isolate,
+    json::Json,
+    report::TargetResult,
+    targets::Target,
+};
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+/// The version of the format written.
+const VERSION: u64 = 1;
+
+/// The results of a target on a set, as a checkpoint has them.
+#[derive(Debug, Clone)]
+pub struct Completed {
+    /// The index of the set, in the order the sets run.
+    pub set: usize,
+    /// The name of the set, so the results are not taken for those of
+    /// another set.
+    pub set_name: String,
+    /// The target measured.
+    pub target: Target,
+    /// The logical CPUs the target assumed, if it ran as part of a sweep.
+    pub threads: Option<usize>,
+    /// What was measured, before rejecting outliers.
+    pub result: TargetResult,
+}
+
+/// Writes the checkpoint of a run as it goes.
+#[derive(Debug)]
+pub struct Writer {
+    /// The file of the checkpoint, unbuffered so every line is there once
+    /// it is written.
+    file: File,
+}
+
+impl Writer {
+    /// Creates the checkpoint of a run, starting it with how to run it
+    /// again.
+    pub fn create(path: &Path, config: &Config) -> io::Result<Self> {
+        let mut writer = Self { file: File::create(path)? };
+        let header = Json::object(vec![
+            ("checkpoint", Json::from(VERSION)),
+            ("args", Json::from(config.args.clone())),
+            ("seeds", Json::from(config.seeds.clone())),
+        ]);
+        writer.line(&header)?;
+        Ok(writer)
+    }
+
+    /// Opens the checkpoint of a resumed run, to add what it measures. A last
+    /// line cut short by the interruption is dropped first.
+    pub fn append(path: &Path) -> io::Result<Self> {
+        let text = fs::read(path)?;
+        let complete = text.iter().rposition(|&byte| byte == b'\n');
+        let file = OpenOptions::new().append(true).open(path)?;
+        file.set_len(complete.map_or(0, |newline| newline as u64 + 1))?;
+        Ok(Self { file })
+    }
+
+    /// Records what the target measured on the set of the given index.
+    pub fn write(
+        &mut self,
+        set: usize,
+        set_name: &str,
+        result: &TargetResult,
+    ) -> io::Result<()> {
+        let line = Json::object(vec![
+            ("set", Json::from(set)),
+            ("set_name", Json::from(set_name)),
+            ("target", Json::from(result.target.name())),
+            ("threads", result.threads.map_or(Json::Null, Json::from)),
+            ("result", isolate::encode(result)),
+        ]);
+        self.line(&line)
+    }
+
+    /// Writes a line at once, so an interruption leaves at most the last
+    /// line incomplete.
+    fn line(&mut self, json: &Json) -> io::Result<()> {
+        self.file.write_all(format!("{}\n", json.compact()).as_bytes())
+    }
+}
+
+/// Reads a checkpoint back, parsing the arguments of its run again. The
+/// configuration has the seeds of the run, writes to the checkpoint and
+/// has what the run already measured.
+pub fn resume(path: &Path) -> io::Result<Box<Config>> {
+    let invalid = |message: String| {
+        let message = format!("{}: {}", path.display(), message);
+        io::Error::new(io::ErrorKind::InvalidData, message)
+    };
+    let text = fs::read_to_string(path)?;
+    let mut lines = text.split_inclusive('\n');
+
+    let header = lines.next().map(|line| Json::parse(line.trim_end()));
+    let header = match header {
+        Some(Ok(header)) => header,
+        _ => return Err(invalid("not a checkpoint".to_owned())),
+    };
+    match header.get("checkpoint").and_then(Json::as_u64) {
+        Some(VERSION) => (),
+        Some(version) => {
+            return Err(invalid(format!("unknown version {}", version)))
+        },
+        None => return Err(invalid("not a checkpoint".to_owned())),
+    }
+    let strings = |key: &str| {
+        let values = header.get(key)?.as_array()?.iter();
+        values.map(|value| value.as_str().map(str::to_owned)).collect()
+    };
+    let args: Vec<String> = match strings("args") {
+        Some(args) => args,
+        None => return Err(invalid("invalid arguments".to_owned())),
+    };
+    let seeds = header.get("seeds").and_then(Json::as_array).and_then(|s| {
+        s.iter().map(Json::as_u64).collect::<Option<Vec<_>>>()
+    });
+    let seeds = match seeds {
+        Some(seeds) if !seeds.is_empty() => seeds,
+        _ => return Err(invalid("invalid seeds".to_owned())),
+    };
+
+    let mut config = match cli::parse(args) {
+        Ok(Command::Run(config)) => config,
+        Ok(_) => return Err(invalid("not the arguments of a run".to_owned())),
+        Err(error) => return Err(invalid(format!("{}", error))),
+    };
+
+    let mut completed = Vec::new();
+    for (number, line) in lines.enumerate() {
+        // The last line can be cut short by the interruption.
+        if !line.ends_with('\n') {
+            break;
+        }
+        let entry = Json::parse(line.trim_end()).ok();
+        match entry.as_ref().and_then(read_completed) {
+            Some(entry) => completed.push(entry),
+            None => {
+                return Err(invalid(format!("invalid line {}", number + 2)))
+            },
+        }
+    }
+
+    config.seeds = seeds;
+    config.checkpoint = Some(PathBuf::from(path));
+    config.resumed = Some(completed);
+    Ok(config)
+}
+
+/// Reads a line of results.
+fn read_completed(json: &Json) -> Option<Completed> {
+    let target = Target::from_name(json.get("target")?.as_str()?)?;
+    let threads = match json.get("threads")? {
+        Json::Null => None,
+        threads => Some(threads.as_u64()? as usize),
+    };
+    let mut result = isolate::decode(json.get("result")?, target)?;
+    result.threads = threads;
+    Some(Completed {
+        set: json.get("set")?.as_u64()? as usize,
+        set_name: json.get("set_name")?.as_str()?.to_owned(),
+        target,
+        threads,
+        result,
+    })
+}
@@ -0,0 +1,1342 @@
+//! Parsing of the command line arguments.
+
+use crate::{
+    cases::{SetSpec, SETS},
+    elements::{ElementType, StringShape, PAYLOAD_BYTES},
+    gate::SpeedupAssertion,
+    checkpoint::Completed,
+    isolate::{self, ChildTarget},
+    order::Order,
+    phase::Phase,
+    output::{Format, Verbosity},
+    patterns::{ParsePatternError, Pattern},
+    targets::Target,
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::{error::Error, fmt, path::PathBuf, str::FromStr, time::Duration};
+
+/// What the binary was asked to do.
+#[derive(Debug)]
+pub enum Command {
+    /// Runs the benchmark.
+    Run(Box<Config>),
+    /// Sorts the lines of a file with one of the targets.
+    Sort(SortConfig),
+    /// Compares the results of two runs.
+    Compare(CompareConfig),
+    /// Resumes the run of the checkpoint at the path.
+    Resume(PathBuf),
+    /// Prints the usage.
+    Help,
+}
+
+/// The configuration of a benchmark run.
+#[derive(Debug, Clone)]
+pub struct Config {
+    /// The seeds the cases are generated from, never empty. Each one
+    /// generates every set, and the cases of all of them run together.
+    pub seeds: Vec<u64>,
+    /// The case sets to run, in order.
+    pub sets: Vec<&'static SetSpec>,
+    /// The pattern of the sets without one of their own.
+    pub pattern: Pattern,
+    /// The patterns of specific sets.
+    pub set_patterns: Vec<(&'static SetSpec, Pattern)>,
+    /// The type of the elements of the cases.
+    pub element: ElementType,
+    /// What the strings look like when the elements are strings.
+    pub strings: StringShape,
+    /// The size of the payloads when the elements have payloads.
+    pub payload_bytes: usize,
+    /// The numbers of logical CPUs the parallel targets assume. With more
+    /// than one, the targets that depend on it run at each of them.
+    pub threads: Vec<usize>,
+    /// The CPUs the benchmark is pinned to, sorted, if it is.
+    pub cpus: Option<Vec<usize>>,
+    /// How many times each target runs each case set.
+    pub repeat: usize,
+    /// How many times each target runs each case set before the timed runs.
+    pub warmup: usize,
+    /// Where the results are written to. `None` means stdout.
+    pub output: Option<PathBuf>,
+    /// The format the results are written in.
+    pub format: Format,
+    /// Whether the output of every target is checked.
+    pub verify: bool,
+    /// Whether the comparisons of every target are counted.
+    pub count_comparisons: bool,
+    /// Whether the energy every target uses is measured.
+    pub energy: bool,
+    /// Whether the caches are evicted before every timed run.
+    pub cold_cache: bool,
+    /// Whether how busy the threads of the parallel merge sort are is
+    /// measured.
+    pub utilization: bool,
+    /// Where a chart of the results is written to.
+    pub chart: Option<PathBuf>,
+    /// Where an HTML page with the results is written to.
+    pub report: Option<PathBuf>,
+    /// The directory the data for plotting is written to.
+    pub plot_data: Option<PathBuf>,
+    /// How much detail the results and progress are written with.
+    pub verbosity: Verbosity,
+    /// The name the results are saved under as a baseline.
+    pub save_baseline: Option<String>,
+    /// The name of the baseline the results are compared to.
+    pub compare_baseline: Option<String>,
+    /// The speedups over the sequential sort the targets must reach.
+    pub assert_speedups: Vec<SpeedupAssertion>,
+    /// How much slower than the baseline a target can get, as a percentage,
+    /// before it counts as a regression.
+    pub regression_threshold: f64,
+    /// How long a target can take over a case set before its remaining
+    /// cases are skipped.
+    pub timeout: Option<Duration>,
+    /// Where the generated cases are saved to.
+    pub dump_cases: Option<PathBuf>,
+    /// Where the cases are read from instead of being generated. The file
+    /// sets the seed, the sets, their patterns and the elements.
+    pub cases: Option<PathBuf>,
+    /// The targets to run, in order.
+    pub targets: Vec<Target>,
+    /// The order the targets run in.
+    pub order: Order,
+    /// What of the targets is timed.
+    pub phase: Phase,
+    /// How many iterations of dummy work every comparison of the targets
+    /// does before comparing, modeling expensive comparisons.
+    pub cmp_cost: u64,
+    /// How many median absolute deviations away from the median of its
+    /// repetitions the time of a case is an outlier, if they are rejected.
+    pub reject_outliers: Option<f64>,
+    /// The confidence level, as a fraction, at which a difference from the
+    /// baseline is significant, and of the intervals runs converge to.
+    pub confidence: f64,
+    /// How narrow, as a fraction of the mean, the confidence interval of the
+    /// mean run must get before a target stops running, if runs go on until
+    /// it does.
+    pub converge: Option<f64>,
+    /// How long the timed runs of a target converging can take before it
+    /// stops anyway.
+    pub converge_budget: Duration,
+    /// Whether what would run is written instead of running it.
+    pub dry_run: bool,
+    /// Whether each target is measured in a process of its own.
+    pub isolate: bool,
+    /// What this process measures, if it is the child of an isolated run.
+    pub child: Option<ChildTarget>,
+    /// Where the results are checkpointed as the run goes, if they are.
+    pub checkpoint: Option<PathBuf>,
+    /// What the run measured before it was interrupted, if it is resumed
+    /// from its checkpoint.
+    pub resumed: Option<Vec<Completed>>,
+    /// The arguments the configuration was parsed from, which children and
+    /// resumed runs parse again.
+    pub args: Vec<String>,
+}
+
+/// The configuration of the `sort` subcommand.
+#[derive(Debug, Clone)]
+pub struct SortConfig {
+    /// The target that sorts.
+    pub target: Target,
+    /// The number of logical CPUs the target assumes.
+    pub threads: usize,
+    /// Whether the lines are sorted as strings instead of integers.
+    pub lines: bool,
+    /// Where the lines are read from. `None` means stdin.
+    pub input: Option<PathBuf>,
+    /// Where the sorted lines are written to. `None` means stdout.
+    pub output: Option<PathBuf>,
+}
+
+/// The configuration of the `compare` subcommand.
+#[derive(Debug, Clone)]
+pub struct CompareConfig {
+    /// The JSON results compared to.
+    pub old: PathBuf,
+    /// The JSON results compared.
+    pub new: PathBuf,
+}
+
+impl Config {
+    /// The pattern the given set is generated with.
+    pub fn pattern_of(&self, spec: &SetSpec) -> Pattern {
+        self.set_patterns
+            .iter()
+            .rev()
+            .find(|(other, _)| *other == spec)
+            .map_or(self.pattern, |&(_, pattern)| pattern)
+    }
+
+    /// The thread counts the target runs at: each count of a sweep, or
+    /// `None` for a single run at the first count.
+    pub fn thread_counts(&self, target: Target) -> Vec<Option<usize>> {
+        // A sweep runs the targets that care at every thread count.
+        match target.uses_threads() && self.threads.len() > 1 {
+            true => self.threads.iter().copied().map(Some).collect(),
+            false => vec![None],
+        }
+    }
+}
+
+/// An invalid command line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CliError {
+    /// What is wrong about it.
+    message: String,
+}
+
+impl CliError {
+    /// Creates an error with the given message.
+    fn new<S>(message: S) -> Self
+    where
+        S: Into<String>,
+    {
+        Self { message: message.into() }
+    }
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.write_str(&self.message)
+    }
+}
+
+impl Error for CliError {}
+
+/// The usage of the binary, as printed by `--help`.
+pub fn usage() -> String {
+    let sets = SETS.iter().map(|spec| spec.name).collect::<Vec<_>>();
+    let patterns = Pattern::NAMES;
+    let targets = Target::ALL.iter().map(|target| target.name());
+    let targets = targets.collect::<Vec<_>>();
+    let formats = Format::ALL.iter().map(|format| format.name());
+    let formats = formats.collect::<Vec<_>>();
+    let orders = Order::ALL.iter().map(|order| order.name());
+    let orders = orders.collect::<Vec<_>>();
+    let phases = Phase::ALL.iter().map(|phase| phase.name());
+    let phases = phases.collect::<Vec<_>>();
+    let elements = ElementType::ALL.iter().map(|element| element.name());
+    let elements = elements.collect::<Vec<_>>();
+    let payload_sizes = PAYLOAD_BYTES.iter().map(usize::to_string);
+    let payload_sizes = payload_sizes.collect::<Vec<_>>();
+    let payload_sizes =
+        payload_sizes.iter().map(String::as_str).collect::<Vec<_>>();
+
+    format!(
+        "Compares the sequential and the parallel merge sorts.
+
+Usage: main [OPTIONS]
+       main sort [SORT OPTIONS] [FILE]
+       main compare OLD NEW
+       main --resume CHECKPOINT
+
+Options:
+    --seed <SEED>          Seed of the random cases [default: random]
+    --seeds <N|SEEDS>      Generates the cases with N seeds, the first one from
+                           --seed and the others drawn from it, or with the
+                           comma-separated SEEDS, running the cases of all of
+                           them together [default: 1]
+    --sets <SETS>          Comma-separated case sets to run [default: all]
+                           Available: {}
+    --case <NAME:COUNT:MIN:MAX>
+                           Adds a case set of COUNT arrays of MIN to MAX
+                           elements, run after the sets of --sets, or alone
+                           without it. It can be given many times
+    --pattern <PATTERNS>   Comma-separated patterns of the arrays, either
+                           PATTERN for every set or SET=PATTERN for a single
+                           one [default: random]
+                           Available: {}
+    --type <TYPE>          Type of the elements of the arrays [default: i64]
+                           Available: {}
+    --string-length <LEN>  Length of the strings, either N or MIN..MAX for
+                           uniformly random lengths [default: 8..32]
+    --alphabet <CHARS>     Characters of the strings, with ranges such as
+                           a-z [default: a-z]
+    --payload-bytes <N>    Size of the payloads, implies --type payload
+                           [default: 64]
+                           Available: {}
+    --threads <COUNTS>     Comma-separated logical CPUs assumed by the
+                           parallel targets, with more than one running them
+                           at each count, e.g. 1,2,4,8 [default: {}, or
+                           as many as --cpus]
+    --cpus <CPUS>          Pins the benchmark and the threads of the targets
+                           to the comma-separated CPUs and ranges of CPUs,
+                           e.g. 0-7 or 0,2,4-7. Only supported on Linux,
+                           with the affinity feature
+    --repeat <N>           How many times each target runs each case set
+                           [default: 1]
+    --warmup <N>           Untimed runs of each case set before a target's
+                           timed runs [default: 1]
+    --output <PATH>        Writes the results to a file instead of stdout
+    --format <FORMAT>      Format of the results [default: text]
+                           Available: {}
+    --targets <TARGETS>    Comma-separated targets to run, in the order
+                           given [default: all]
+                           Available: {}
+    --order <ORDER>        Order the targets run in, as given, shuffled for
+                           every set, or taking turns on every case in a
+                           shuffled order [default: fixed]
+                           Available: {}
+    --phase <PHASE>        What of the targets is timed: the whole sort, or
+                           only the merge of the two halves of each case,
+                           sorted before, for the targets that merge
+                           [default: sort]
+                           Available: {}
+    --cmp-cost <N>         Iterations of dummy work every comparison of the
+                           targets does before comparing, to model expensive
+                           comparisons such as collations [default: 0]
+    --verify               Checks the output of every case, failing if a
+                           target does not sort it
+    --dump-cases <PATH>    Saves the generated cases to a file
+    --cases <PATH>         Runs the cases saved to a file by --dump-cases,
+                           with their seed, sets, patterns and elements
+    --checkpoint <PATH>    Writes the results of every target on every case
+                           set to a file as soon as they are measured, for
+                           --resume to go on from if the run stops
+    --resume <PATH>        Goes on with the run of a checkpoint, with its
+                           options and seeds, skipping what it measured. It
+                           takes no other options
+    --count-comparisons    Counts the comparisons of each target over a run
+                           of each case set, in an extra untimed run
+    --energy               Measures the joules the CPU packages use in the
+                           timed runs of each target, through RAPL on Linux,
+                           which usually takes root
+    --cold-cache           Evicts the caches before every timed run of each
+                           target, writing over a buffer twice as big as the
+                           biggest one, so the cases come from memory
+    --utilization          Measures how busy the threads of the parallel merge
+                           sort keep over each case set, in an extra untimed
+                           run tracing every case
+    --chart <PATH>         Draws the time of every case by its size, for
+                           every target, to an SVG file
+    --report <PATH>        Writes a single HTML file with the results of every
+                           set, the chart, the machine and the configuration
+    --plot-data <DIR>      Writes the time of every case by its size, a CSV
+                           file per target, and a gnuplot script to plot them
+    --save-baseline <NAME> Saves the results as a baseline, in
+                           baselines/NAME.json
+    --compare-baseline <NAME>
+                           Compares the results to a saved baseline, failing
+                           if a target regressed
+    --regression-threshold <PERCENT>
+                           How much slower than the baseline a target can
+                           get before it regressed [default: 5]
+    --confidence <PERCENT> Confidence level at which, with --repeat 2 or
+                           more, the difference of a target from the
+                           baseline is significant, by Welch's t-test over
+                           the repetitions [default: 95]
+    --converge <PERCENT>   Runs each target over each case set, after the
+                           runs of --repeat, until the confidence interval of
+                           its mean run is within PERCENT of the mean, e.g. 1
+    --converge-budget <DURATION>
+                           How long the timed runs of a target can take
+                           before it stops converging anyway [default: 60s]
+    --reject-outliers <K>  Replaces the time of a case further than K median
+                           absolute deviations from the median of its
+                           repetitions with that median, e.g. 3.5. It needs
+                           --repeat 3 or more
+    --timeout <DURATION>   How long a target can take over a case set, its
+                           warmup included, e.g. 90s, 500ms or 2m. Once past
+                           it, the parallel merge sorts are cancelled, the
+                           other targets finish the sort running, and the
+                           remaining cases are skipped
+    --assert-speedup <TARGET:FACTOR>
+                           Fails unless the target is FACTOR times faster than
+                           sequential on the large and huge sets, or on every
+                           set if neither runs. It can be given many times
+    --isolate              Measures each target on each case set in a fresh
+                           child process, so what earlier targets left in
+                           the allocator and their threads does not count
+    --dry-run              Writes the case sets, with their sizes and the
+                           seeds of their generators, the targets and about
+                           how much memory the run takes, without running
+    -q, --quiet            Writes only the summary at the end, without the
+                           progress of each case set, which is otherwise
+                           shown when stdout is a terminal
+    -v, --verbose          Writes the time of each repetition too, and with
+                           -vv the time of each case and how the parallel
+                           sorts split the biggest case of each set, logging
+                           their decisions on stderr with the log feature.
+                           Only the text format has the extra detail
+    -h, --help             Prints this help
+
+Sort options, for sorting the integers on the lines of FILE or stdin:
+    --target <TARGET>      Target that sorts [default: parallel_logical]
+    --threads <N>          Logical CPUs assumed by the target [default: {}]
+    --lines                Sorts the lines as strings instead of integers
+    --output <PATH>        Writes the sorted lines to a file instead of stdout
+
+Compare, for the change of every target between two results saved with
+--format json, with markers where it is significant.
+",
+        wrap_names(&sets),
+        wrap_names(patterns),
+        wrap_names(&elements),
+        wrap_names(&payload_sizes),
+        num_cpus::get(),
+        wrap_names(&formats),
+        wrap_names(&targets),
+        wrap_names(&orders),
+        wrap_names(&phases),
+        num_cpus::get(),
+    )
+}
+
+/// Joins the names with commas, breaking lines so the usage fits 80 columns.
+fn wrap_names(names: &[&str]) -> String {
+    // The descriptions of the options start at column 27.
+    let indent = " ".repeat(27);
+    let mut wrapped = String::new();
+    let mut column = indent.len() + "Available: ".len();
+
+    for (index, name) in names.iter().enumerate() {
+        if index > 0 {
+            wrapped.push(',');
+            column += 1;
+            if column + 1 + name.len() + 1 > 80 {
+                wrapped.push('\n');
+                wrapped.push_str(&indent);
+                column = indent.len();
+            } else {
+                wrapped.push(' ');
+                column += 1;
+            }
+        }
+        wrapped.push_str(name);
+        column += name.len();
+    }
+
+    wrapped
+}
+
+/// Parses the given arguments, not including the program name.
+pub fn parse<I>(args: I) -> Result<Command, CliError>
+where
+    I: IntoIterator<Item = String>,
+{
+    let mut seed = None;
+    let mut seeds = None;
+    let mut sets = None;
+    let mut pattern = Pattern::Random;
+    let mut set_patterns = Vec::new();
+    let mut pattern_values = Vec::new();
+    let mut custom = Vec::<&'static SetSpec>::new();
+    let mut element = None;
+    let mut strings = StringShape::default();
+    let mut payload_bytes = None;
+    let mut threads = None;
+    let mut cpus = None;
+    let mut repeat = None;
+    let mut warmup = None;
+    let mut output = None;
+    let mut format = None;
+    let mut targets = None;
+    let mut order = None;
+    let mut phase = None;
+    let mut cmp_cost = None;
+    let mut reject_outliers = None;
+    let mut confidence = None;
+    let mut converge = None;
+    let mut converge_budget = None;
+    let mut verify = false;
+    let mut count_comparisons = false;
+    let mut energy = false;
+    let mut cold_cache = false;
+    let mut utilization = false;
+    let mut dry_run = false;
+    let mut isolate = false;
+    let mut child = None;
+    let mut verbosity = Verbosity::Normal;
+    let mut chart = None;
+    let mut report = None;
+    let mut plot_data = None;
+    let mut save_baseline = None;
+    let mut compare_baseline = None;
+    let mut regression_threshold = None;
+    let mut timeout = None;
+    let mut assert_speedups = Vec::new();
+    let mut dump_cases = None;
+    let mut cases = None;
+    // The first option given that changes how cases are generated.
+    let mut generation = None;
+
+    let given = args.into_iter().collect::<Vec<_>>();
+    let mut checkpoint = None;
+    let mut args = given.clone().into_iter().peekable();
+    match args.peek().map(String::as_str) {
+        Some("sort") => {
+            args.next();
+            return parse_sort(args);
+        },
+        Some("compare") => {
+            args.next();
+            return parse_compare(args);
+        },
+        Some(first) if split_option(first.to_owned()).0 == "--resume" => {
+            return parse_resume(args);
+        },
+        _ => (),
+    }
+
+    while let Some(arg) = args.next() {
+        let (option, inline) = split_option(arg);
+
+        let mut value = || {
+            inline.clone().or_else(|| args.next()).ok_or_else(|| {
+                CliError::new(format!("missing value for {}", option))
+            })
+        };
+
+        if GENERATION_OPTIONS.contains(&option.as_str()) {
+            generation.get_or_insert_with(|| option.clone());
+        }
+
+        match option.as_str() {
+            "-h" | "--help" => return Ok(Command::Help),
+            "--seed" => seed = Some(parse_number(&option, &value()?)?),
+            "--seeds" => seeds = Some(parse_seeds(&option, &value()?)?),
+            "--sets" => sets = Some(parse_sets(&value()?)?),
+            // Patterns can name the custom sets, wherever they are given.
+            "--pattern" => pattern_values.push(value()?),
+            "--case" => {
+                let spec = parse_case(&option, &value()?)?;
+                let taken = SetSpec::find(spec.name).is_some()
+                    || custom.iter().any(|other| other.name == spec.name);
+                if taken {
+                    return Err(CliError::new(format!(
+                        "case set {:?} already exists",
+                        spec.name
+                    )));
+                }
+                // The sets live as long as the built-in ones, and there are
+                // only a few of them.
+                custom.push(Box::leak(Box::new(spec)));
+            },
+            "--type" => element = Some(parse_element(&value()?)?),
+            "--string-length" => {
+                let (min, max) = parse_lengths(&option, &value()?)?;
+                strings.min_length = min;
+                strings.max_length = max;
+            },
+            "--alphabet" => strings.alphabet = parse_alphabet(&value()?)?,
+            "--payload-bytes" => {
+                payload_bytes = Some(parse_payload(&option, &value()?)?)
+            },
+            "--threads" => threads = Some(parse_counts(&option, &value()?)?),
+            "--cpus" => cpus = Some(parse_cpus(&value()?)?),
+            "--repeat" => repeat = Some(parse_positive(&option, &value()?)?),
+            "--warmup" => warmup = Some(parse_number(&option, &value()?)?),
+            "--output" => output = Some(PathBuf::from(value()?)),
+            "--format" => format = Some(parse_format(&value()?)?),
+            "--targets" => targets = Some(parse_targets(&value()?)?),
+            "--order" => order = Some(parse_order(&value()?)?),
+            "--phase" => phase = Some(parse_phase(&value()?)?),
+            "--cmp-cost" => {
+                cmp_cost = Some(parse_number(&option, &value()?)?)
+            },
+            "--confidence" => {
+                confidence = Some(parse_confidence(&option, &value()?)?)
+            },
+            "--converge" => {
+                converge = Some(parse_width(&option, &value()?)?)
+            },
+            "--converge-budget" => {
+                converge_budget = Some(parse_duration(&option, &value()?)?)
+            },
+            "--reject-outliers" => {
+                reject_outliers = Some(parse_mads(&option, &value()?)?)
+            },
+            "--dump-cases" => dump_cases = Some(PathBuf::from(value()?)),
+            "--checkpoint" => checkpoint = Some(PathBuf::from(value()?)),
+            "--resume" => {
+                return Err(CliError::new(
+                    "--resume takes no other options, those of the run it \
+                     resumes are in the checkpoint",
+                ))
+            },
+            "--cases" => cases = Some(PathBuf::from(value()?)),
+            "--chart" => chart = Some(PathBuf::from(value()?)),
+            "--report" => report = Some(PathBuf::from(value()?)),
+            "--plot-data" => plot_data = Some(PathBuf::from(value()?)),
+            "--save-baseline" => {
+                save_baseline = Some(parse_baseline(&option, value()?)?)
+            },
+            "--compare-baseline" => {
+                compare_baseline = Some(parse_baseline(&option, value()?)?)
+            },
+            "--assert-speedup" => {
+                assert_speedups.push(parse_speedup(&option, &value()?)?)
+            },
+            "--regression-threshold" => {
+                regression_threshold = Some(parse_percent(&option, &value()?)?)
+            },
+            "--timeout" => timeout = Some(parse_duration(&option, &value()?)?),
+            "--verify" if inline.is_none() => verify = true,
+            "--count-comparisons" if inline.is_none() => {
+                count_comparisons = true
+            },
+            "--energy" if inline.is_none() => energy = true,
+            "--cold-cache" if inline.is_none() => cold_cache = true,
+            "--utilization" if inline.is_none() => utilization = true,
+            "--dry-run" if inline.is_none() => dry_run = true,
+            "--isolate" if inline.is_none() => isolate = true,
+            isolate::CHILD_OPTION => {
+                let value = value()?;
+                let parsed = ChildTarget::from_arg(&value).ok_or_else(|| {
+                    CliError::new(format!("invalid {} {:?}", option, value))
+                })?;
+                child = Some(parsed);
+            },
+            "-q" | "--quiet" if inline.is_none() => {
+                verbosity = Verbosity::Quiet
+            },
+            "-v" | "--verbose" if inline.is_none() => {
+                verbosity = verbosity.max(Verbosity::Normal).more()
+            },
+            "-vv" => verbosity = Verbosity::Debug,
+            "--verify" | "--count-comparisons" | "--energy" | "--cold-cache"
+            | "--utilization" | "--dry-run" | "--isolate" | "--quiet"
+            | "--verbose" => {
+                return Err(CliError::new(format!(
+                    "{} does not take a value",
+                    option
+                )))
+            },
+            _ => {
+                return Err(CliError::new(format!(
+                    "unexpected argument {:?}",
+                    option
+                )))
+            },
+        }
+    }
+
+    if let (Some(_), Some(option)) = (&cases, generation) {
+        return Err(CliError::new(format!(
+            "--cases cannot be used with {}, the file sets it",
+            option
+        )));
+    }
+
+    // The seeds after the first one are drawn from it, unless given.
+    let seeds = match seeds {
+        Some(Seeds::List(_)) if seed.is_some() => {
+            return Err(CliError::new(
+                "--seed cannot be used with a list of --seeds",
+            ))
+        },
+        Some(Seeds::List(seeds)) => seeds,
+        count => {
+            let first = seed.unwrap_or_else(|| rand::thread_rng().gen());
+            let count = match count {
+                Some(Seeds::Count(count)) => count,
+                _ => 1,
+            };
+            let mut rng = StdRng::seed_from_u64(first);
+            let rest = (1 .. count).map(|_| rng.gen::<u64>());
+            Some(first).into_iter().chain(rest).collect()
+        },
+    };
+    // A child draws nothing, it uses the seeds of its parent.
+    let seeds = match &child {
+        Some(child) => child.seeds.clone(),
+        None => seeds,
+    };
+    if dump_cases.is_some() && seeds.len() > 1 {
+        return Err(CliError::new("--dump-cases saves the cases of one seed"));
+    }
+
+    let find = |name: &str| {
+        let found = custom.iter().copied().find(|spec| spec.name == name);
+        found.or_else(|| SetSpec::find(name))
+    };
+    for value in &pattern_values {
+        for (spec, parsed) in parse_patterns(value, find)? {
+            match spec {
+                Some(spec) => set_patterns.push((spec, parsed)),
+                None => pattern = parsed,
+            }
+        }
+    }
+
+    let sets = match (sets, custom.is_empty()) {
+        (None, true) => SETS.iter().collect(),
+        (sets, _) => {
+            let mut sets = sets.unwrap_or_default();
+            sets.extend(custom);
+            sets
+        },
+    };
+
+    // Only the merge sorts have a merge to time alone.
+    let phase = phase.unwrap_or(Phase::Sort);
+    let targets = match (targets, phase) {
+        (Some(targets), _) => targets,
+        (None, Phase::Sort) => Target::ALL.to_vec(),
+        (None, Phase::Merge) => {
+            let merging = Target::ALL.iter().filter(|target| target.merges());
+            merging.copied().collect()
+        },
+    };
+    if phase == Phase::Merge {
+        if let Some(target) = targets.iter().find(|target| !target.merges()) {
+            return Err(CliError::new(format!(
+                "--phase merge cannot time {}, which does not merge",
+                target.name()
+            )));
+        }
+        // The traces are of whole sorts.
+        if utilization {
+            return Err(CliError::new(
+                "--utilization cannot be used with --phase merge",
+            ));
+        }
+    }
+    // Speedups are over the sequential sort, of targets that run.
+    let needed = assert_speedups.first().map(|_| Target::Sequential);
+    let asserted = assert_speedups.iter().map(|assertion| assertion.target);
+    if let Some(missing) = needed.into_iter().chain(asserted).find(|target| {
+        !targets.contains(target)
+    }) {
+        return Err(CliError::new(format!(
+            "--assert-speedup needs the {} target to run",
+            missing.name()
+        )));
+    }
+
+    // The cost is added to the comparison function the targets are given.
+    let cmp_cost = cmp_cost.unwrap_or(0);
+    if cmp_cost > 0 {
+        let compares = |target: &&Target| target.uses_compare();
+        if let Some(target) = targets.iter().find(|t| !compares(t)) {
+            return Err(CliError::new(format!(
+                "--cmp-cost cannot slow down {}, which compares on its own",
+                target.name()
+            )));
+        }
+    }
+
+    // The energy would count the writes evicting the caches.
+    if cold_cache && energy {
+        return Err(CliError::new("--cold-cache cannot be used with --energy"));
+    }
+
+    // With fewer repetitions, no time is further from the median than the
+    // others.
+    let repeat = repeat.unwrap_or(1);
+    if reject_outliers.is_some() && repeat < 3 {
+        return Err(CliError::new("--reject-outliers needs --repeat 3 or more"));
+    }
+
+    // Interleaved targets run in a single process, the energy of one cannot
+    // be told from the others', all but the first to sort a case find it in
+    // the caches, and they all take the same number of turns.
+    let order = order.unwrap_or(Order::Fixed);
+    if order == Order::Interleaved {
+        let option = match (isolate, energy, cold_cache) {
+            (true, _, _) => Some("--isolate"),
+            (false, true, _) => Some("--energy"),
+            (false, false, true) => Some("--cold-cache"),
+            (false, false, false) => converge.map(|_| "--converge"),
+        };
+        if let Some(option) = option {
+            return Err(CliError::new(format!(
+                "--order interleaved cannot be used with {}",
+                option
+            )));
+        }
+    }
+
+    // A payload size alone is enough to ask for payloads.
+    let element = match (element, payload_bytes) {
+        (None, Some(_)) => ElementType::Payload,
+        (Some(ElementType::Payload), _) | (_, None) => {
+            element.unwrap_or(ElementType::I64)
+        },
+        (Some(element), Some(_)) => {
+            return Err(CliError::new(format!(
+                "--payload-bytes does not apply to elements of type {}",
+                element.name()
+            )))
+        },
+    };
+
+    Ok(Command::Run(Box::new(Config {
+        seeds,
+        sets,
+        pattern,
+        set_patterns,
+        element,
+        strings,
+        payload_bytes: payload_bytes.unwrap_or(64),
+        // Pinned, the parallel targets assume the CPUs they get.
+        threads: threads.unwrap_or_else(|| {
+            vec![cpus.as_ref().map_or_else(num_cpus::get, Vec::len)]
+        }),
+        cpus,
+        repeat,
+        warmup: warmup.unwrap_or(1),
+        output,
+        format: format.unwrap_or(Format::Text),
+        verify,
+        count_comparisons,
+        energy,
+        cold_cache,
+        utilization,
+        chart,
+        report,
+        plot_data,
+        verbosity,
+        save_baseline,
+        compare_baseline,
+        assert_speedups,
+        regression_threshold: regression_threshold.unwrap_or(5.0),
+        timeout,
+        dump_cases,
+        cases,
+        targets,
+        order,
+        phase,
+        cmp_cost,
+        reject_outliers,
+        confidence: confidence.unwrap_or(0.95),
+        converge,
+        converge_budget: converge_budget.unwrap_or(Duration::from_secs(60)),
+        dry_run,
+        isolate,
+        child,
+        checkpoint,
+        resumed: None,
+        args: given,
+    })))
+}
+
+/// Parses the arguments of the `sort` subcommand, after its name.
+fn parse_sort<I>(mut args: I) -> Result<Command, CliError>
+where
+    I: Iterator<Item = String>,
+{
+    let mut target = Target::ParallelLogical;
+    let mut threads = None;
+    let mut lines = false;
+    let mut input = None;
+    let mut output = None;
+
+    while let Some(arg) = args.next() {
+        let (option, inline) = split_option(arg);
+
+        let mut value = || {
+            inline.clone().or_else(|| args.next()).ok_or_else(|| {
+                CliError::new(format!("missing value for {}", option))
+            })
+        };
+
+        match option.as_str() {
+            "-h" | "--help" => return Ok(Command::Help),
+            "--target" => {
+                let name = value()?;
+                target = Target::from_name(name.trim()).ok_or_else(|| {
+                    CliError::new(format!("unknown target {:?}", name))
+                })?;
+            },
+            "--threads" => {
+                threads = Some(parse_positive(&option, &value()?)?)
+            },
+            "--output" => output = Some(PathBuf::from(value()?)),
+            "--lines" if inline.is_none() => lines = true,
+            "--lines" => {
+                return Err(CliError::new("--lines does not take a value"))
+            },
+            // A lone dash is stdin, like no file at all.
+            "-" if input.is_none() => input = Some(None),
+            path if !path.starts_with('-') && input.is_none() => {
+                input = Some(Some(PathBuf::from(path)))
+            },
+            _ => {
+                return Err(CliError::new(format!(
+                    "unexpected argument {:?}",
+                    option
+                )))
+            },
+        }
+    }
+
+    Ok(Command::Sort(SortConfig {
+        target,
+        threads: threads.unwrap_or_else(num_cpus::get),
+        lines,
+        input: input.flatten(),
+        output,
+    }))
+}
+
+/// Parses `--resume` and its value, the only arguments it can be given.
+fn parse_resume<I>(mut args: I) -> Result<Command, CliError>
+where
+    I: Iterator<Item = String>,
+{
+    let (option, inline) = split_option(args.next().expect("--resume"));
+    let path = inline.or_else(|| args.next()).ok_or_else(|| {
+        CliError::new(format!("missing value for {}", option))
+    })?;
+    match args.next() {
+        None => Ok(Command::Resume(PathBuf::from(path))),
+        Some(_) => Err(CliError::new(
+            "--resume takes no other options, those of the run it resumes \
+             are in the checkpoint",
+        )),
+    }
+}
+
+/// Parses the arguments of the `compare` subcommand, after its name.
+fn parse_compare<I>(args: I) -> Result<Command, CliError>
+where
+    I: Iterator<Item = String>,
+{
+    let mut paths = Vec::new();
+    for arg in args {
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(Command::Help),
+            path if !path.starts_with('-') => paths.push(PathBuf::from(path)),
+            _ => {
+                return Err(CliError::new(format!(
+                    "unexpected argument {:?}",
+                    arg
+                )))
+            },
+        }
+    }
+
+    let mut paths = paths.into_iter();
+    match (paths.next(), paths.next(), paths.next()) {
+        (Some(old), Some(new), None) => {
+            Ok(Command::Compare(CompareConfig { old, new }))
+        },
+        _ => Err(CliError::new("compare takes two result files")),
+    }
+}
+
+/// Splits an argument into the option and its value, if given inline. Both
+/// `--option value` and `--option=value` are accepted.
+fn split_option(arg: String) -> (String, Option<String>) {
+    match arg.find('=') {
+        Some(index) if arg.starts_with("--") => {
+            (arg[.. index].to_owned(), Some(arg[index + 1 ..].to_owned()))
+        },
+        _ => (arg, None),
+    }
+}
+
+/// The options that change how the cases are generated, which replayed cases
+/// already have.
+const GENERATION_OPTIONS: &[&str] = &[
+    "--seed",
+    "--seeds",
+    "--sets",
+    "--case",
+    "--pattern",
+    "--type",
+    "--string-length",
+    "--alphabet",
+    "--payload-bytes",
+];
+
+/// Parses the number given to an option.
+fn parse_number<T>(option: &str, value: &str) -> Result<T, CliError>
+where
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    value.parse().map_err(|error| {
+        CliError::new(format!("invalid {} {:?}: {}", option, value, error))
+    })
+}
+
+/// Parses the number given to an option, which must not be zero.
+fn parse_positive(option: &str, value: &str) -> Result<usize, CliError> {
+    match parse_number(option, value)? {
+        0 => Err(CliError::new(format!("{} must be at least 1", option))),
+        number => Ok(number),
+    }
+}
+
+/// Parses a speedup given to `--assert-speedup`, as `TARGET:FACTOR`.
+fn parse_speedup(
+    option: &str,
+    value: &str,
+) -> Result<SpeedupAssertion, CliError> {
+    let (name, factor) = value.split_once(':').ok_or_else(|| {
+        CliError::new(format!(
+            "invalid {} {:?}, expected TARGET:FACTOR",
+            option, value
+        ))
+    })?;
+    let target = Target::from_name(name.trim()).ok_or_else(|| {
+        CliError::new(format!("unknown target {:?}", name))
+    })?;
+    let factor = parse_number::<f64>(option, factor.trim())?;
+    if !(factor > 0.0 && factor.is_finite()) {
+        return Err(CliError::new(format!("invalid {} {:?}", option, value)));
+    }
+    Ok(SpeedupAssertion { target, factor })
+}
+
+/// Parses a percentage, which must not be negative.
+fn parse_percent(option: &str, value: &str) -> Result<f64, CliError> {
+    let percent = parse_number::<f64>(option, value.trim_end_matches('%'))?;
+    if !(percent >= 0.0 && percent.is_finite()) {
+        return Err(CliError::new(format!("invalid {} {:?}", option, value)));
+    }
+    Ok(percent)
+}
+
+/// Parses a confidence level, as a percentage strictly between 0 and 100,
+/// into a fraction.
+fn parse_confidence(option: &str, value: &str) -> Result<f64, CliError> {
+    let percent = parse_percent(option, value)?;
+    if !(percent > 0.0 && percent < 100.0) {
+        return Err(CliError::new(format!("invalid {} {:?}", option, value)));
+    }
+    Ok(percent / 100.0)
+}
+
+/// Parses the relative width intervals converge to, as a positive
+/// percentage, into a fraction.
+fn parse_width(option: &str, value: &str) -> Result<f64, CliError> {
+    let percent = parse_percent(option, value)?;
+    if percent == 0.0 {
+        return Err(CliError::new(format!("invalid {} {:?}", option, value)));
+    }
+    Ok(percent / 100.0)
+}
+
+/// Parses how many median absolute deviations make an outlier, which must be
+/// positive.
+fn parse_mads(option: &str, value: &str) -> Result<f64, CliError> {
+    let mads = parse_number::<f64>(option, value)?;
+    if !(mads > 0.0 && mads.is_finite()) {
+        return Err(CliError::new(format!("invalid {} {:?}", option, value)));
+    }
+    Ok(mads)
+}
+
+/// Parses a duration, as seconds, or a number followed by `ms`, `s`, `m` or
+/// `h`. It must not be zero.
+fn parse_duration(option: &str, value: &str) -> Result<Duration, CliError> {
+    let units = [("ms", 1e-3), ("s", 1.0), ("m", 60.0), ("h", 3600.0)];
+    let (number, scale) = units
+        .iter()
+        .find_map(|&(unit, scale)| {
+            value.strip_suffix(unit).map(|number| (number, scale))
+        })
+        .unwrap_or((value, 1.0));
+    let seconds = parse_number::<f64>(option, number.trim())? * scale;
+    if !(seconds > 0.0 && seconds.is_finite()) {
+        return Err(CliError::new(format!("invalid {} {:?}", option, value)));
+    }
+    Ok(Duration::from_secs_f64(seconds))
+}
+
+/// Parses the name of a baseline, which names a file in the baselines
+/// directory.
+fn parse_baseline(option: &str, value: String) -> Result<String, CliError> {
+    let valid = |ch: char| ch.is_alphanumeric() || "-_.".contains(ch);
+    if value.is_empty() || value.starts_with('.') || !value.chars().all(valid)
+    {
+        return Err(CliError::new(format!(
+            "invalid {} {:?}, names are letters, digits, -, _ and .",
+            option, value
+        )));
+    }
+    Ok(value)
+}
+
+/// The seeds given to `--seeds`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Seeds {
+    /// How many seeds, drawn from the first one.
+    Count(usize),
+    /// The seeds themselves.
+    List(Vec<u64>),
+}
+
+/// Parses the seeds given to `--seeds`: a list if there is a comma, a count
+/// otherwise.
+fn parse_seeds(option: &str, value: &str) -> Result<Seeds, CliError> {
+    if !value.contains(',') {
+        return Ok(Seeds::Count(parse_positive(option, value)?));
+    }
+    let seeds = value
+        .split(',')
+        .map(str::trim)
+        .filter(|seed| !seed.is_empty())
+        .map(|seed| parse_number(option, seed))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if seeds.is_empty() {
+        return Err(CliError::new(format!("missing value for {}", option)));
+    }
+    Ok(Seeds::List(seeds))
+}
+
+/// Parses a comma-separated list of counts, none of them zero.
+fn parse_counts(option: &str, value: &str) -> Result<Vec<usize>, CliError> {
+    let counts = value
+        .split(',')
+        .map(str::trim)
+        .filter(|count| !count.is_empty())
+        .map(|count| parse_positive(option, count))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if counts.is_empty() {
+        return Err(CliError::new(format!("missing value for {}", option)));
+    }
+    Ok(counts)
+}
+
+/// Parses the CPUs given to `--cpus`, comma-separated CPUs and ranges of
+/// CPUs such as `4-7`, sorted and without repeats.
+fn parse_cpus(value: &str) -> Result<Vec<usize>, CliError> {
+    let invalid = || CliError::new(format!("invalid CPUs {:?}", value));
+    let mut cpus = Vec::new();
+    for part in value.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+        let (first, last) = match part.split_once('-') {
+            Some((first, last)) => (first.trim(), last.trim()),
+            None => (part, part),
+        };
+        let first = first.parse::<usize>().map_err(|_| invalid())?;
+        let last = last.parse::<usize>().map_err(|_| invalid())?;
+        if first > last {
+            return Err(invalid());
+        }
+        cpus.extend(first ..= last);
+    }
+    if cpus.is_empty() {
+        return Err(CliError::new("--cpus needs at least one CPU"));
+    }
+    cpus.sort_unstable();
+    cpus.dedup();
+    Ok(cpus)
+}
+
+/// Parses a comma-separated list of names.
+fn parse_list<T, F>(
+    value: &str,
+    kind: &str,
+    mut find: F,
+) -> Result<Vec<T>, CliError>
+where
+    F: FnMut(&str) -> Option<T>,
+{
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(|name| {
+            find(name).ok_or_else(|| {
+                CliError::new(format!("unknown {} {:?}", kind, name))
+            })
+        })
+        .collect()
+}
+
+/// Parses the case sets given to `--sets`. A set named twice runs once,
+/// where it was first named.
+fn parse_sets(value: &str) -> Result<Vec<&'static SetSpec>, CliError> {
+    let mut sets = Vec::new();
+    for spec in parse_list(value, "case set", SetSpec::find)? {
+        if !sets.contains(&spec) {
+            sets.push(spec);
+        }
+    }
+    if sets.is_empty() {
+        return Err(CliError::new("--sets needs at least one case set"));
+    }
+    Ok(sets)
+}
+
+/// Parses the targets given to `--targets`. A target named twice runs once,
+/// where it was first named.
+fn parse_targets(value: &str) -> Result<Vec<Target>, CliError> {
+    let mut targets = Vec::new();
+    for target in parse_list(value, "target", Target::from_name)? {
+        if !targets.contains(&target) {
+            targets.push(target);
+        }
+    }
+    if targets.is_empty() {
+        return Err(CliError::new("--targets needs at least one target"));
+    }
+    Ok(targets)
+}
+
+/// Parses the patterns given to `--pattern`, each with the set it is
+/// restricted to, if any.
+fn parse_patterns<F>(
+    value: &str,
+    find: F,
+) -> Result<Vec<(Option<&'static SetSpec>, Pattern)>, CliError>
+where
+    F: Fn(&str) -> Option<&'static SetSpec>,
+{
+    let parse = |item: &str| {
+        let (spec, pattern) = match item.split_once('=') {
+            Some((name, pattern)) => {
+                let spec = find(name.trim()).ok_or_else(|| {
+                    CliError::new(format!("unknown case set {:?}", name))
+                })?;
+                (Some(spec), pattern)
+            },
+            None => (None, item),
+        };
+        let pattern = pattern.parse().map_err(|error: ParsePatternError| {
+            CliError::new(error.to_string())
+        })?;
+        Ok((spec, pattern))
+    };
+
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(parse)
+        .collect()
+}
+
+/// Parses a case set given to `--case`, as `NAME:COUNT:MIN:MAX`.
+fn parse_case(option: &str, value: &str) -> Result<SetSpec, CliError> {
+    let fields = value.split(':').map(str::trim).collect::<Vec<_>>();
+    let (name, count, min, max) = match fields[..] {
+        [name, count, min, max] => (name, count, min, max),
+        _ => {
+            return Err(CliError::new(format!(
+                "invalid {} {:?}, expected NAME:COUNT:MIN:MAX",
+                option, value
+            )))
+        },
+    };
+
+    // Lists of sets are separated by commas, and patterns name them before
+    // an equals sign.
+    if name.is_empty() || name.contains([',', '=']) {
+        return Err(CliError::new(format!("invalid case set name {:?}", name)));
+    }
+    let count = parse_positive(option, count)?;
+    let min_size = parse_number(option, min)?;
+    let max_size = parse_number(option, max)?;
+    if min_size > max_size {
+        return Err(CliError::new(format!(
+            "{} has a minimum size bigger than its maximum in {:?}",
+            option, value
+        )));
+    }
+
+    // Names of custom sets live as long as the built-in ones.
+    let name = Box::leak(name.to_owned().into_boxed_str());
+    Ok(SetSpec { name, count, min_size, max_size })
+}
+
+/// Parses the format given to `--format`.
+fn parse_format(value: &str) -> Result<Format, CliError> {
+    Format::from_name(value.trim()).ok_or_else(|| {
+        CliError::new(format!("unknown format {:?}", value))
+    })
+}
+
+/// Parses the order given to `--order`.
+fn parse_order(value: &str) -> Result<Order, CliError> {
+    Order::from_name(value.trim()).ok_or_else(|| {
+        CliError::new(format!("unknown order {:?}", value))
+    })
+}
+
+/// Parses the phase given to `--phase`.
+fn parse_phase(value: &str) -> Result<Phase, CliError> {
+    Phase::from_name(value.trim()).ok_or_else(|| {
+        CliError::new(format!("unknown phase {:?}", value))
+    })
+}
+
+/// Parses the element type given to `--type`.
+fn parse_element(value: &str) -> Result<ElementType, CliError> {
+    ElementType::from_name(value.trim()).ok_or_else(|| {
+        CliError::new(format!("unknown element type {:?}", value))
+    })
+}
+
+/// Parses the lengths given to `--string-length`, either a single one or an
+/// inclusive range `MIN..MAX`.
+fn parse_lengths(
+    option: &str,
+    value: &str,
+) -> Result<(usize, usize), CliError> {
+    let (min, max) = match value.split_once("..") {
+        Some((min, max)) => {
+            let min = parse_number(option, min.trim())?;
+            (min, parse_number(option, max.trim())?)
+        },
+        None => {
+            let length = parse_number(option, value.trim())?;
+            (length, length)
+        },
+    };
+
+    if min > max {
+        return Err(CliError::new(format!(
+            "invalid {} {:?}: minimum is bigger than maximum",
+            option, value
+        )));
+    }
+    Ok((min, max))
+}
+
+/// Parses the characters given to `--alphabet`. A `-` between two characters
+/// stands for every character between them.
+fn parse_alphabet(value: &str) -> Result<Vec<char>, CliError> {
+    let chars = value.chars().collect::<Vec<_>>();
+    let mut alphabet = Vec::new();
+    let mut index = 0;
+
+    while index < chars.len() {
+        match chars.get(index + 1 ..= index + 2) {
+            Some(&['-', last]) => {
+                let first = chars[index];
+                if first > last {
+                    return Err(CliError::new(format!(
+                        "invalid range {}-{} in --alphabet",
+                        first, last
+                    )));
+                }
+                alphabet.extend(first ..= last);
+                index += 3;
+            },
+            _ => {
+                alphabet.push(chars[index]);
+                index += 1;
+            },
+        }
+    }
+
+    // Repeated characters would just make some of them more likely.
+    alphabet.sort_unstable();
+    alphabet.dedup();
+    if alphabet.is_empty() {
+        return Err(CliError::new("--alphabet must not be empty"));
+    }
+    Ok(alphabet)
+}
+
+/// Parses the size given to `--payload-bytes`, which must be one of the
+/// supported ones.
+fn parse_payload(option: &str, value: &str) -> Result<usize, CliError> {
+    let bytes = parse_number(option, value.trim())?;
+    if !PAYLOAD_BYTES.contains(&bytes) {
+        return Err(CliError::new(format!(
+            "unsupported {} {}, the sizes are {:?}",
+            option, bytes, PAYLOAD_BYTES
+        )));
+    }
+    Ok(bytes)
+}
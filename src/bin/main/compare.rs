@@ -0,0 +1,126 @@
+//! The `compare` subcommand, which compares the results of two runs saved as
+//! JSON, such as before and after a change.
+
+use crate::{
+    baseline::{Baseline, SavedTarget},
+    cli::CompareConfig,
+    output::human,
+    stats,
+};
+use std::{
+    io::{self, Write},
+    path::PathBuf,
+};
+
+/// The p-values under which a difference is marked, with their markers.
+const MARKERS: [(f64, &str); 2] = [(0.01, "**"), (0.05, "*")];
+
+/// Compares the new results to the old ones, writing the change of every
+/// target on every case set they both have.
+pub fn run(config: &CompareConfig) -> io::Result<()> {
+    let read = |path: &PathBuf| {
+        let name = path.display().to_string();
+        Baseline::read(path, &name).map_err(|error| {
+            io::Error::new(error.kind(), format!("{}: {}", name, error))
+        })
+    };
+    let old = read(&config.old)?;
+    let new = read(&config.new)?;
+
+    let stdout = io::stdout();
+    let mut output = stdout.lock();
+    write(&old, &new, &mut output)
+}
+
+/// Writes how the new results changed from the old ones.
+fn write<W>(old: &Baseline, new: &Baseline, output: &mut W) -> io::Result<()>
+where
+    W: Write,
+{
+    writeln!(output, "Comparing {} to {}:", new.name, old.name)?;
+    if old.element != new.element {
+        writeln!(
+            output,
+            "  {} sorted elements of type {}, and {} of type {}",
+            old.name, old.element, new.name, new.element
+        )?;
+    }
+
+    for set in &new.sets {
+        writeln!(output, "  {}, pattern = {}:", set.name, set.pattern)?;
+        let before = old.sets.iter().find(|before| {
+            before.name == set.name && before.pattern == set.pattern
+        });
+        let before = match before {
+            Some(before) => before,
+            None => {
+                writeln!(output, "    not in {}", old.name)?;
+                continue;
+            },
+        };
+
+        for target in &set.targets {
+            match before.target(&target.label) {
+                Some(previous) => write_change(output, previous, target)?,
+                None => writeln!(
+                    output,
+                    "    {:<24} {:>12}   not in {}",
+                    target.label,
+                    human(target.mean),
+                    old.name
+                )?,
+            }
+        }
+        for target in &before.targets {
+            if set.target(&target.label).is_none() {
+                writeln!(
+                    output,
+                    "    {:<24} {:>12}   not in {}",
+                    target.label,
+                    human(target.mean),
+                    new.name
+                )?;
+            }
+        }
+    }
+
+    let legend = MARKERS.iter().map(|(limit, marker)| {
+        format!("{} p < {}", marker, limit)
+    });
+    writeln!(output)?;
+    writeln!(
+        output,
+        "{}, by Welch's t-test over the repetitions of each target",
+        legend.collect::<Vec<_>>().join(", "),
+    )?;
+    writeln!(output, "? where a side has fewer than 2 repetitions")
+}
+
+/// Writes how a target changed, marking the change if it is significant.
+fn write_change<W>(
+    output: &mut W,
+    old: &SavedTarget,
+    new: &SavedTarget,
+) -> io::Result<()>
+where
+    W: Write,
+{
+    let percent = (new.mean - old.mean) / old.mean * 100.0;
+    let p_value = stats::welch_p_value(&old.seconds, &new.seconds);
+    let marker = match p_value {
+        Some(p_value) => {
+            let found = MARKERS.iter().find(|&&(limit, _)| p_value < limit);
+            found.map_or("", |&(_, marker)| marker)
+        },
+        None => "?",
+    };
+    let line = format!(
+        "    {:<24} {:>12} -> {:>12} {:>+8.1}% {}",
+        old.label,
+        human(old.mean),
+        human(new.mean),
+        percent,
+        marker
+    );
+    writeln!(output, "{}", line.trim_end())
+}
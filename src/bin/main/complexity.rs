@@ -0,0 +1,141 @@
+//! Fitting the time each target took to the size of the cases, so targets are
+//! compared by how they grow and not only by how long they took.
+
+use crate::report::BenchReport;
+use std::borrow::Cow;
+
+/// How far the empirical exponent of a target can be from the exponent of
+/// `n log n` over the same sizes before it is flagged.
+const EXPONENT_TOLERANCE: f64 = 0.15;
+
+/// The smallest cases fitted: below it the time is mostly the fixed cost of a
+/// sort, and the tiny set would outweigh every other.
+const MIN_SIZE: f64 = 100.0;
+
+/// How well a model `time = constant * f(n)` fits the times of a target.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ModelFit {
+    /// The constant of the model, in seconds.
+    pub constant: f64,
+    /// The coefficient of determination: 1 is a perfect fit, 0 is no better
+    /// than the mean time, and it can go below.
+    pub r_squared: f64,
+}
+
+/// How the time of a target grows with the size of the cases.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fit<'report> {
+    /// The label of the target.
+    pub label: Cow<'report, str>,
+    /// How many cases were fitted.
+    pub points: usize,
+    /// The fit of `time = c * n * log2(n)`.
+    pub n_log_n: ModelFit,
+    /// The fit of `time = c * n`.
+    pub linear: ModelFit,
+    /// The slope of `log(time)` by `log(n)`: the `k` of `time ~ n^k`.
+    pub exponent: f64,
+    /// The exponent an exact `n log n` would have over the same sizes.
+    pub expected_exponent: f64,
+}
+
+impl<'report> Fit<'report> {
+    /// Whether the exponent is too far from the one of `n log n`.
+    pub fn deviates(&self) -> bool {
+        (self.exponent - self.expected_exponent).abs() > EXPONENT_TOLERANCE
+    }
+}
+
+/// Fits every target of the report, over the cases of every set big enough.
+/// Targets with cases of less than 3 distinct sizes are left out.
+pub fn fit(report: &BenchReport) -> Vec<Fit<'_>> {
+    let mut series = Vec::<(Cow<str>, Vec<(f64, f64)>)>::new();
+    for set in &report.sets {
+        for target in &set.targets {
+            let label = target.label();
+            let index = match series.iter().position(|(l, _)| *l == label) {
+                Some(index) => index,
+                None => {
+                    series.push((label, Vec::new()));
+                    series.len() - 1
+                },
+            };
+            let sizes = set.sizes.iter().map(|&size| size as f64);
+            let points = sizes.zip(target.case_means());
+            // Logarithms need positive times.
+            let points = points.filter(|&(n, time)| {
+                n >= MIN_SIZE && time > 0.0
+            });
+            series[index].1.extend(points);
+        }
+    }
+
+    series
+        .into_iter()
+        .filter_map(|(label, points)| fit_points(label, &points))
+        .collect()
+}
+
+/// Fits the points of a target.
+fn fit_points<'report>(
+    label: Cow<'report, str>,
+    points: &[(f64, f64)],
+) -> Option<Fit<'report>> {
+    let mut sizes = points.iter().map(|&(n, _)| n).collect::<Vec<_>>();
+    sizes.sort_by(f64::total_cmp);
+    sizes.dedup();
+    if sizes.len() < 3 {
+        return None;
+    }
+
+    let log_n = points.iter().map(|&(n, _)| n.ln()).collect::<Vec<_>>();
+    let log_time = points.iter().map(|&(_, time)| time.ln());
+    let log_n_log_n = points.iter().map(|&(n, _)| (n * n.log2()).ln());
+
+    Some(Fit {
+        label,
+        points: points.len(),
+        n_log_n: fit_model(points, |n| n * n.log2()),
+        linear: fit_model(points, |n| n),
+        exponent: slope(&log_n, &log_time.collect::<Vec<_>>()),
+        expected_exponent: slope(&log_n, &log_n_log_n.collect::<Vec<_>>()),
+    })
+}
+
+/// Fits `time = constant * model(n)` by least squares.
+fn fit_model<F>(points: &[(f64, f64)], model: F) -> ModelFit
+where
+    F: Fn(f64) -> f64,
+{
+    let (mut cross, mut squares) = (0.0, 0.0);
+    for &(n, time) in points {
+        let x = model(n);
+        cross += x * time;
+        squares += x * x;
+    }
+    let constant = cross / squares;
+
+    let mean = points.iter().map(|&(_, time)| time).sum::<f64>()
+        / points.len() as f64;
+    let (mut residual, mut total) = (0.0, 0.0);
+    for &(n, time) in points {
+        residual += (time - constant * model(n)).powi(2);
+        total += (time - mean).powi(2);
+    }
+    let r_squared = if total > 0.0 { 1.0 - residual / total } else { 1.0 };
+
+    ModelFit { constant, r_squared }
+}
+
+/// The slope of the least squares line of `y` by `x`.
+fn slope(x: &[f64], y: &[f64]) -> f64 {
+    let count = x.len() as f64;
+    let mean_x = x.iter().sum::<f64>() / count;
+    let mean_y = y.iter().sum::<f64>() / count;
+    let (mut cross, mut squares) = (0.0, 0.0);
+    for (&x, &y) in x.iter().zip(y) {
+        cross += (x - mean_x) * (y - mean_y);
+        squares += (x - mean_x).powi(2);
+    }
+    cross / squares
+}
@@ -0,0 +1,27 @@
+//! The CPU time of the process, which sums every thread, so parallel targets
+//! can be told apart by the work they do and not only by how long they take.
+//! Only measured with the `cpu-time` feature.
+
+use std::time::Duration;
+
+/// The CPU time the process used so far, in user and kernel mode, over every
+/// thread, those that finished included. `None` where it cannot be read.
+#[cfg(all(unix, feature = "cpu-time"))]
+pub fn now() -> Option<Duration> {
+    let mut usage = std::mem::MaybeUninit::<libc::rusage>::uninit();
+    if unsafe { libc::getrusage(libc::RUSAGE_SELF, usage.as_mut_ptr()) } < 0 {
+        return None;
+    }
+    let usage = unsafe { usage.assume_init() };
+    let time = |time: libc::timeval| {
+        let micros = time.tv_sec as u64 * 1_000_000 + time.tv_usec as u64;
+        Duration::from_micros(micros)
+    };
+    Some(time(usage.ru_utime) + time(usage.ru_stime))
+}
+
+/// The CPU time the process used so far. `None` where it cannot be read.
+#[cfg(not(all(unix, feature = "cpu-time")))]
+pub fn now() -> Option<Duration> {
+    None
+}
@@ -0,0 +1,160 @@
+//! What a run would do, written without sorting anything, so a long run can
+//! be checked before it starts.
+
+use crate::{
+    cache,
+    casefile::CaseReader,
+    cli::Config,
+    elements::ElementType,
+    output::human_bytes,
+};
+use std::io::{self, Write};
+
+/// How many copies of its biggest case a set needs besides its cases while a
+/// target sorts: the output and the buffers of the merges.
+const SORT_COPIES: usize = 3;
+
+/// Writes the case sets and the targets the configuration would run, and
+/// about how much memory it would take.
+pub fn write<W>(config: &Config, output: &mut W) -> io::Result<()>
+where
+    W: Write,
+{
+    writeln!(output, "Dry run, nothing is sorted.")?;
+
+    if let Some(path) = &config.cases {
+        let (_, header) = CaseReader::open(path).map_err(|error| {
+            let message = format!("{}: {}", path.display(), error);
+            io::Error::new(error.kind(), message)
+        })?;
+        writeln!(
+            output,
+            "Cases read from {}: {} sets, seed {}, elements of type {}",
+            path.display(),
+            header.sets,
+            header.seed,
+            header.element.name()
+        )?;
+    } else {
+        let seeds = config.seeds.iter().map(u64::to_string);
+        let seeds = seeds.collect::<Vec<_>>().join(", ");
+        let plural = if config.seeds.len() == 1 { "" } else { "s" };
+        writeln!(
+            output,
+            "Seed{} {}, elements of type {}",
+            plural,
+            seeds,
+            config.element.name()
+        )?;
+        write_sets(config, output)?;
+    }
+
+    writeln!(output)?;
+    writeln!(
+        output,
+        "Targets, each running every set {} times after {} warmup runs:",
+        config.repeat, config.warmup
+    )?;
+    for &target in &config.targets {
+        let counts = config.thread_counts(target);
+        match counts.as_slice() {
+            [None] => writeln!(output, "  {}", target.name())?,
+            counts => {
+                let counts =
+                    counts.iter().flatten().map(ToString::to_string);
+                let counts = counts.collect::<Vec<_>>().join(", ");
+                writeln!(output, "  {} at {} threads", target.name(), counts)?
+            },
+        }
+    }
+    Ok(())
+}
+
+/// Writes the sets that would be generated, with the memory they take.
+fn write_sets<W>(config: &Config, output: &mut W) -> io::Result<()>
+where
+    W: Write,
+{
+    let element = element_bytes(config);
+    let runs = config
+        .targets
+        .iter()
+        .map(|&target| config.thread_counts(target).len())
+        .sum::<usize>()
+        * (config.warmup + config.repeat);
+    // Sets are generated one at a time, the biggest one decides.
+    let mut peak = 0.0f64;
+
+    for spec in &config.sets {
+        let seeds = config.seeds.iter();
+        let sizes = seeds.flat_map(|&seed| spec.sizes(seed));
+        let sizes = sizes.collect::<Vec<_>>();
+        let elements = sizes.iter().sum::<usize>();
+        let biggest = sizes.iter().copied().max().unwrap_or(0);
+        let bytes = (elements + biggest * SORT_COPIES) as f64 * element;
+        peak = peak.max(bytes);
+
+        writeln!(output)?;
+        writeln!(
+            output,
+            "Case set {}, pattern = {}, min size = {}, max size = {}, \
+             cases = {}",
+            spec.name,
+            config.pattern_of(spec),
+            spec.min_size,
+            spec.max_size,
+            spec.count
+        )?;
+        let generators = config.seeds.iter();
+        let generators =
+            generators.map(|&seed| format!("{:#018x}", spec.seed(seed)));
+        let generators = generators.collect::<Vec<_>>();
+        let plural = if generators.len() == 1 { "" } else { "s" };
+        writeln!(
+            output,
+            "  generator seed{} {}, {} elements, the biggest case {}",
+            plural,
+            generators.join(", "),
+            elements,
+            biggest
+        )?;
+        writeln!(
+            output,
+            "  {} sorts, about {} of memory",
+            runs * sizes.len(),
+            human_bytes(bytes as usize)
+        )?;
+    }
+
+    // The buffer evicting the caches is there the whole run.
+    if config.cold_cache {
+        peak += cache::eviction_bytes() as f64;
+    }
+    writeln!(output)?;
+    writeln!(
+        output,
+        "About {} of memory at most, for elements of {:.0} bytes",
+        human_bytes(peak as usize),
+        element
+    )
+}
+
+/// About how many bytes an element takes, counting what strings point to.
+fn element_bytes(config: &Config) -> f64 {
+    match config.element {
+        ElementType::I64 | ElementType::F64 => 8.0,
+        ElementType::KeyValue => 16.0,
+        // The key, with the payload padded to its alignment.
+        ElementType::Payload => {
+            (8 + config.payload_bytes.div_ceil(8) * 8) as f64
+        },
+        // The string itself, and the characters on the heap.
+        ElementType::String => {
+            let shape = &config.strings;
+            let length = (shape.min_length + shape.max_length) as f64 / 2.0;
+            let alphabet = &shape.alphabet;
+            let bytes = alphabet.iter().map(|ch| ch.len_utf8()).sum::<usize>();
+            24.0 + length * bytes as f64 / alphabet.len() as f64
+        },
+    }
+}
@@ -0,0 +1,383 @@
+//! The types of the sorted elements.
+
+use crate::casefile::{read_str, read_u64, write_str, write_u64};
+#[cfg(feature = "gpu")]
+use mergesort_cmp::{gpu::GpuSorter, parallel};
+use rand::Rng;
+use std::{
+    cmp::Ordering,
+    fmt,
+    io::{self, Read, Write},
+};
+#[cfg(feature = "gpu")]
+use std::sync::Arc;
+
+/// A type of element the cases can be made of.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ElementType {
+    /// 64-bit signed integers.
+    I64,
+    /// 64-bit floating point numbers, in their total order.
+    F64,
+    /// Strings of random characters.
+    String,
+    /// Structures of a 64-bit integer key and a payload of some bytes.
+    Payload,
+    /// Pairs of a 64-bit unsigned key and value, sorted by the key.
+    KeyValue,
+}
+
+impl ElementType {
+    /// Every element type.
+    pub const ALL: &'static [Self] = &[
+        ElementType::I64,
+        ElementType::F64,
+        ElementType::String,
+        ElementType::Payload,
+        ElementType::KeyValue,
+    ];
+
+    /// The name the element type is selected by and reported as.
+    pub fn name(self) -> &'static str {
+        match self {
+            ElementType::I64 => "i64",
+            ElementType::F64 => "f64",
+            ElementType::String => "string",
+            ElementType::Payload => "payload",
+            ElementType::KeyValue => "kv",
+        }
+    }
+
+    /// Finds the element type of the given name.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|element| element.name() == name)
+    }
+}
+
+/// An element the targets can sort.
+pub trait Element: Clone + Send + Sync + 'static {
+    /// What the random elements look like, besides being of this type.
+    type Shape;
+
+    /// A uniformly random element of the given shape.
+    fn random<R>(shape: &Self::Shape, rng: &mut R) -> Self
+    where
+        R: Rng;
+
+    /// The order the targets sort in. It must be a total order.
+    fn compare(&self, other: &Self) -> Ordering;
+
+    /// A total order that only finds an element equal to itself, for elements
+    /// with parts that are not compared. Checking that the output of a target
+    /// is a permutation of its input needs it.
+    fn identity(&self, other: &Self) -> Ordering {
+        self.compare(other)
+    }
+
+    /// Whether the element is still consistent, for elements with parts that
+    /// are not compared.
+    fn intact(&self) -> bool {
+        true
+    }
+
+    /// Writes the element to a case file, exactly.
+    fn write_to<W>(&self, output: &mut W) -> io::Result<()>
+    where
+        W: Write;
+
+    /// Reads an element written by [`Element::write_to`].
+    fn read_from<R>(input: &mut R) -> io::Result<Self>
+    where
+        R: Read;
+
+    /// Random elements of the given shape in descending order.
+    fn descending<R>(shape: &Self::Shape, size: usize, rng: &mut R) -> Vec<Self>
+    where
+        R: Rng,
+    {
+        let mut array = (0 .. size)
+            .map(|_| Self::random(shape, rng))
+            .collect::<Vec<_>>();
+        array.sort_unstable_by(|left, right| right.compare(left));
+        array
+    }
+
+    /// Sorts the array with the GPU backend. Types it cannot sort go to the
+    /// parallel sort, like the backend does with them.
+    #[cfg(feature = "gpu")]
+    fn gpu_sort(_sorter: &GpuSorter, array: &Arc<[Self]>) -> Vec<Self> {
+        parallel::custom_order(Self::compare).sort(array)
+    }
+}
+
+/// The biggest gap between consecutive integers of descending arrays.
+const MAX_GAP: i64 = 1 << 40;
+
+impl Element for i64 {
+    type Shape = ();
+
+    fn random<R>(_shape: &(), rng: &mut R) -> Self
+    where
+        R: Rng,
+    {
+        rng.gen()
+    }
+
+    fn compare(&self, other: &Self) -> Ordering {
+        self.cmp(other)
+    }
+
+    fn write_to<W>(&self, output: &mut W) -> io::Result<()>
+    where
+        W: Write,
+    {
+        write_u64(output, *self as u64)
+    }
+
+    fn read_from<R>(input: &mut R) -> io::Result<Self>
+    where
+        R: Read,
+    {
+        Ok(read_u64(input)? as i64)
+    }
+
+    fn descending<R>(_shape: &(), size: usize, rng: &mut R) -> Vec<Self>
+    where
+        R: Rng,
+    {
+        // Random gaps of at least 1 make it strictly descending, and are
+        // small enough for the biggest arrays not to overflow.
+        let mut current = i64::MAX - rng.gen_range(0, MAX_GAP);
+        let mut array = Vec::with_capacity(size);
+        for _ in 0 .. size {
+            array.push(current);
+            current -= rng.gen_range(1, MAX_GAP + 1);
+        }
+        array
+    }
+
+    #[cfg(feature = "gpu")]
+    fn gpu_sort(sorter: &GpuSorter, array: &Arc<[Self]>) -> Vec<Self> {
+        sorter.sort(array)
+    }
+}
+
+/// The magnitude of the random floating point numbers.
+const FLOAT_RANGE: f64 = 1e9;
+
+impl Element for f64 {
+    type Shape = ();
+
+    fn random<R>(_shape: &(), rng: &mut R) -> Self
+    where
+        R: Rng,
+    {
+        rng.gen_range(-FLOAT_RANGE, FLOAT_RANGE)
+    }
+
+    fn compare(&self, other: &Self) -> Ordering {
+        self.total_cmp(other)
+    }
+
+    fn write_to<W>(&self, output: &mut W) -> io::Result<()>
+    where
+        W: Write,
+    {
+        write_u64(output, self.to_bits())
+    }
+
+    fn read_from<R>(input: &mut R) -> io::Result<Self>
+    where
+        R: Read,
+    {
+        Ok(f64::from_bits(read_u64(input)?))
+    }
+}
+
+/// The lengths and characters of random strings.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StringShape {
+    /// The minimum length of a string, in characters.
+    pub min_length: usize,
+    /// The maximum length of a string, in characters, inclusive.
+    pub max_length: usize,
+    /// The characters strings are made of, never empty.
+    pub alphabet: Vec<char>,
+}
+
+impl Default for StringShape {
+    fn default() -> Self {
+        let alphabet = ('a' ..= 'z').collect();
+        Self { min_length: 8, max_length: 32, alphabet }
+    }
+}
+
+impl fmt::Display for StringShape {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            fmt,
+            "{} to {} characters from ",
+            self.min_length, self.max_length
+        )?;
+
+        // Runs of consecutive characters are written as ranges, `a-z`.
+        let mut index = 0;
+        while index < self.alphabet.len() {
+            let first = self.alphabet[index];
+            let mut last = first;
+            while let Some(&next) = self.alphabet.get(index + 1) {
+                if u32::from(next) != u32::from(last) + 1 {
+                    break;
+                }
+                last = next;
+                index += 1;
+            }
+            match u32::from(last) - u32::from(first) {
+                0 => write!(fmt, "{}", first)?,
+                1 => write!(fmt, "{}{}", first, last)?,
+                _ => write!(fmt, "{}-{}", first, last)?,
+            }
+            index += 1;
+        }
+
+        Ok(())
+    }
+}
+
+impl Element for String {
+    type Shape = StringShape;
+
+    fn random<R>(shape: &StringShape, rng: &mut R) -> Self
+    where
+        R: Rng,
+    {
+        let length = rng.gen_range(shape.min_length, shape.max_length + 1);
+        let alphabet = &shape.alphabet;
+        (0 .. length)
+            .map(|_| alphabet[rng.gen_range(0, alphabet.len())])
+            .collect()
+    }
+
+    fn compare(&self, other: &Self) -> Ordering {
+        self.cmp(other)
+    }
+
+    fn write_to<W>(&self, output: &mut W) -> io::Result<()>
+    where
+        W: Write,
+    {
+        write_str(output, self)
+    }
+
+    fn read_from<R>(input: &mut R) -> io::Result<Self>
+    where
+        R: Read,
+    {
+        read_str(input)
+    }
+}
+
+/// The sizes a payload can have, in bytes.
+pub const PAYLOAD_BYTES: &[usize] = &[8, 16, 32, 64, 128, 256, 512, 1024];
+
+/// A key with a payload of `N` bytes carried along, which makes the element
+/// as expensive to move and to clone as its size says.
+#[derive(Debug, Clone, Copy)]
+pub struct Payload<const N: usize> {
+    /// What the elements are sorted by.
+    pub key: i64,
+    /// The bytes carried along.
+    pub bytes: [u8; N],
+}
+
+impl<const N: usize> Payload<N> {
+    /// An element with the given key. The payload is made of the key's bytes,
+    /// so elements with equal keys are equal altogether and checking the keys
+    /// checks the payloads too.
+    fn with_key(key: i64) -> Self {
+        let key_bytes = key.to_le_bytes();
+        let mut bytes = [0; N];
+        for (byte, key_byte) in bytes.iter_mut().zip(key_bytes.iter().cycle())
+        {
+            *byte = *key_byte;
+        }
+        Self { key, bytes }
+    }
+}
+
+impl<const N: usize> Element for Payload<N> {
+    type Shape = ();
+
+    fn random<R>(shape: &(), rng: &mut R) -> Self
+    where
+        R: Rng,
+    {
+        Self::with_key(i64::random(shape, rng))
+    }
+
+    fn compare(&self, other: &Self) -> Ordering {
+        self.key.cmp(&other.key)
+    }
+
+    /// Only the key is written, the payload is made from it.
+    fn write_to<W>(&self, output: &mut W) -> io::Result<()>
+    where
+        W: Write,
+    {
+        self.key.write_to(output)
+    }
+
+    fn read_from<R>(input: &mut R) -> io::Result<Self>
+    where
+        R: Read,
+    {
+        Ok(Self::with_key(i64::read_from(input)?))
+    }
+
+    fn intact(&self) -> bool {
+        self.bytes == Self::with_key(self.key).bytes
+    }
+
+    fn descending<R>(shape: &(), size: usize, rng: &mut R) -> Vec<Self>
+    where
+        R: Rng,
+    {
+        let keys = i64::descending(shape, size, rng);
+        keys.into_iter().map(Self::with_key).collect()
+    }
+}
+
+impl Element for (u64, u64) {
+    type Shape = ();
+
+    fn random<R>(_shape: &(), rng: &mut R) -> Self
+    where
+        R: Rng,
+    {
+        rng.gen()
+    }
+
+    /// Only the keys are compared, like a sort by key does.
+    fn compare(&self, other: &Self) -> Ordering {
+        self.0.cmp(&other.0)
+    }
+
+    fn identity(&self, other: &Self) -> Ordering {
+        self.cmp(other)
+    }
+
+    fn write_to<W>(&self, output: &mut W) -> io::Result<()>
+    where
+        W: Write,
+    {
+        write_u64(output, self.0)?;
+        write_u64(output, self.1)
+    }
+
+    fn read_from<R>(input: &mut R) -> io::Result<Self>
+    where
+        R: Read,
+    {
+        Ok((read_u64(input)?, read_u64(input)?))
+    }
+}
@@ -0,0 +1,117 @@
+//! The energy the CPUs use, read from the RAPL counters Linux exposes through
+//! powercap, on Intel and on AMD.
+
+use std::{
+    fs,
+    io,
+    path::{Path, PathBuf},
+};
+
+/// Where powercap has its zones.
+const POWERCAP: &str = "/sys/class/powercap";
+
+/// The energy counters of every CPU package.
+#[derive(Debug, Clone)]
+pub struct Meter {
+    /// The package domains found.
+    domains: Vec<Domain>,
+}
+
+/// The counter of a package.
+#[derive(Debug, Clone)]
+struct Domain {
+    /// The file with the energy used so far, in microjoules.
+    energy: PathBuf,
+    /// The value past which the counter wraps around to zero.
+    range: u64,
+}
+
+/// The counters of every package at some point.
+#[derive(Debug, Clone)]
+pub struct Reading {
+    /// The microjoules of each domain, in the order of the meter.
+    microjoules: Vec<u64>,
+}
+
+impl Meter {
+    /// Finds the counters of the packages. Fails if there are none, or if
+    /// they cannot be read, which usually takes root.
+    pub fn open() -> io::Result<Self> {
+        let mut domains = Vec::new();
+        let entries = fs::read_dir(POWERCAP).map_err(with_path(POWERCAP))?;
+        for entry in entries {
+            let path = entry.map_err(with_path(POWERCAP))?.path();
+            let zone = path.file_name().and_then(|name| name.to_str());
+            // Packages are the top zones, `intel-rapl:0` and so on, while
+            // their parts are `intel-rapl:0:0`.
+            let top = zone.is_some_and(|zone| {
+                let parts = zone.matches(':').count();
+                zone.starts_with("intel-rapl:") && parts == 1
+            });
+            if !top || !read_text(&path.join("name"))?.starts_with("package") {
+                continue;
+            }
+            let range = read_number(&path.join("max_energy_range_uj"))?;
+            let energy = path.join("energy_uj");
+            read_number(&energy)?;
+            domains.push(Domain { energy, range });
+        }
+
+        if domains.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("no RAPL package in {}", POWERCAP),
+            ));
+        }
+        Ok(Self { domains })
+    }
+
+    /// Reads the counters now.
+    pub fn read(&self) -> io::Result<Reading> {
+        let microjoules = self
+            .domains
+            .iter()
+            .map(|domain| read_number(&domain.energy))
+            .collect::<io::Result<_>>()?;
+        Ok(Reading { microjoules })
+    }
+
+    /// The joules used by every package since the reading. A counter can
+    /// wrap around once in between, which takes minutes at full power.
+    pub fn joules_since(&self, start: &Reading) -> io::Result<f64> {
+        let now = self.read()?;
+        let mut total = 0;
+        let counters = now.microjoules.iter().zip(&start.microjoules);
+        for ((&now, &then), domain) in counters.zip(&self.domains) {
+            total += match now >= then {
+                true => now - then,
+                false => domain.range - then + now,
+            };
+        }
+        Ok(total as f64 / 1e6)
+    }
+}
+
+/// Reads a file of powercap, without the line break.
+fn read_text(path: &Path) -> io::Result<String> {
+    let text = fs::read_to_string(path).map_err(with_path(path))?;
+    Ok(text.trim_end().to_owned())
+}
+
+/// Reads a file of powercap with a number in it.
+fn read_number(path: &Path) -> io::Result<u64> {
+    read_text(path)?.parse().map_err(|error| {
+        with_path(path)(io::Error::new(io::ErrorKind::InvalidData, error))
+    })
+}
+
+/// Converts errors about the given path, so they tell which it was.
+fn with_path<P>(path: P) -> impl Fn(io::Error) -> io::Error
+where
+    P: AsRef<Path>,
+{
+    move |error| {
+        let message = format!("{}: {}", path.as_ref().display(), error);
+        io::Error::new(error.kind(), message)
+    }
+}
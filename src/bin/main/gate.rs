@@ -0,0 +1,89 @@
+//! Speedups a run must reach, so the benchmark can accept or reject a change
+//! to the sorts.
+
+use crate::{
+    report::{BenchReport, CaseSetResult},
+    targets::Target,
+};
+use std::io::{self, Write};
+
+/// The sets speedups are checked on when they ran: the small ones are
+/// dominated by the cost of starting threads.
+const CHECKED_SETS: &[&str] = &["large", "huge"];
+
+/// A speedup over the sequential sort a target must reach.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SpeedupAssertion {
+    /// The target that must be faster.
+    pub target: Target,
+    /// How many times faster than the sequential sort it must be.
+    pub factor: f64,
+}
+
+/// The sets the speedups are checked on: the large and huge sets, or every
+/// set if neither ran.
+fn checked_sets(report: &BenchReport) -> Vec<&CaseSetResult> {
+    let big = report
+        .sets
+        .iter()
+        .filter(|set| CHECKED_SETS.contains(&set.spec.name))
+        .collect::<Vec<_>>();
+    match big.is_empty() {
+        true => report.sets.iter().collect(),
+        false => big,
+    }
+}
+
+/// Checks the speedups of the run, writing how each went. Returns how many
+/// were not reached.
+pub fn check<W>(
+    report: &BenchReport,
+    assertions: &[SpeedupAssertion],
+    output: &mut W,
+) -> io::Result<usize>
+where
+    W: Write,
+{
+    let mut failed = 0;
+
+    for set in checked_sets(report) {
+        let sequential = set
+            .targets
+            .iter()
+            .find(|result| result.target == Target::Sequential);
+        let sequential = match sequential {
+            Some(sequential) => sequential.mean_seconds(),
+            None => continue,
+        };
+
+        for assertion in assertions {
+            // In a sweep, the target is as fast as its best thread count.
+            let best = set
+                .targets
+                .iter()
+                .filter(|result| result.target == assertion.target)
+                .map(|result| result.mean_seconds())
+                .min_by(f64::total_cmp);
+            let speedup = match best {
+                Some(best) => sequential / best,
+                None => continue,
+            };
+
+            let reached = speedup >= assertion.factor;
+            if !reached {
+                failed += 1;
+            }
+            writeln!(
+                output,
+                "{} on {}: {:.2}x over sequential, {} {:.2}x",
+                assertion.target.name(),
+                set.spec.name,
+                speedup,
+                if reached { "reached" } else { "FAILED" },
+                assertion.factor
+            )?;
+        }
+    }
+
+    Ok(failed)
+}
@@ -0,0 +1,266 @@
+//! A single HTML file with the results, the chart, the machine and the
+//! configuration of the run, to share with people who will not read the
+//! console.
+
+use crate::{
+    affinity,
+    chart,
+    cli::Config,
+    manifest::Manifest,
+    output::{human, human_bytes, human_p_value, human_throughput},
+    report::{BenchReport, CaseSetResult},
+};
+use std::{
+    fmt::{self, Write as _},
+    fs,
+    io,
+    path::Path,
+};
+
+/// The style of the page, inlined so the file needs nothing else.
+const STYLE: &str = "\
+body { font-family: sans-serif; max-width: 1000px; margin: 2em auto; }
+table { border-collapse: collapse; margin: 0.5em 0 1.5em; }
+th, td { border: 1px solid #ccc; padding: 0.3em 0.7em; }
+th { background: #f3f3f3; text-align: left; }
+td.number { text-align: right; font-variant-numeric: tabular-nums; }
+code { background: #f3f3f3; padding: 0.1em 0.3em; }";
+
+/// Renders the page of the report of a run with the given configuration.
+pub fn render(config: &Config, report: &BenchReport) -> String {
+    let mut html = String::new();
+    write_page(&mut html, config, report).expect("writing to a string");
+    html
+}
+
+/// Renders the page to a file.
+pub fn write(
+    path: &Path,
+    config: &Config,
+    report: &BenchReport,
+) -> io::Result<()> {
+    fs::write(path, render(config, report))
+}
+
+/// Every part of the page.
+fn write_page(
+    html: &mut String,
+    config: &Config,
+    report: &BenchReport,
+) -> fmt::Result {
+    writeln!(html, "<!DOCTYPE html>")?;
+    writeln!(html, "<html lang=\"en\">")?;
+    writeln!(html, "<head>")?;
+    writeln!(html, "<meta charset=\"utf-8\">")?;
+    writeln!(html, "<title>mergesort-cmp results</title>")?;
+    writeln!(html, "<style>\n{}\n</style>", STYLE)?;
+    writeln!(html, "</head>")?;
+    writeln!(html, "<body>")?;
+    writeln!(html, "<h1>Sequential and parallel merge sorts</h1>")?;
+    writeln!(
+        html,
+        "<p>Using {}, elements of type <code>{}</code>.</p>",
+        escape(&report.run_description()),
+        escape(&report.element_description())
+    )?;
+
+    writeln!(html, "<h2>Machine</h2>")?;
+    writeln!(html, "<p>{}.</p>", escape(&report.machine.description()))?;
+
+    writeln!(html, "<h2>Configuration</h2>")?;
+    write_configuration(html, config)?;
+
+    if let Some(manifest) = &report.manifest {
+        writeln!(html, "<h2>Reproducibility</h2>")?;
+        write_manifest(html, manifest)?;
+    }
+
+    writeln!(html, "<h2>Case sets</h2>")?;
+    for set in &report.sets {
+        write_set(html, set)?;
+    }
+
+    writeln!(html, "<h2>Time by case size</h2>")?;
+    writeln!(html, "{}", chart::render(report))?;
+    writeln!(html, "</body>")?;
+    writeln!(html, "</html>")
+}
+
+/// The table of how the run was configured, starting with its command line.
+fn write_configuration(html: &mut String, config: &Config) -> fmt::Result {
+    let sets = config.sets.iter().map(|spec| spec.name);
+    let targets = config.targets.iter().map(|target| target.name());
+    let threads = config.threads.iter().map(usize::to_string);
+    let mut rows = vec![
+        ("case sets", sets.collect::<Vec<_>>().join(", ")),
+        ("targets", targets.collect::<Vec<_>>().join(", ")),
+        ("threads", threads.collect::<Vec<_>>().join(", ")),
+        ("repetitions", config.repeat.to_string()),
+        ("warmup runs", config.warmup.to_string()),
+        ("order", config.order.name().to_owned()),
+        ("phase", config.phase.name().to_owned()),
+        ("confidence", format!("{}%", config.confidence * 100.0)),
+    ];
+    if let Some(timeout) = config.timeout {
+        rows.push(("timeout", human(timeout.as_secs_f64())));
+    }
+    if let Some(width) = config.converge {
+        rows.push(("converge to", format!("±{}%", width * 100.0)));
+    }
+    if let Some(mads) = config.reject_outliers {
+        rows.push(("outliers beyond", format!("{} MADs", mads)));
+    }
+    if config.cmp_cost > 0 {
+        let cost = format!("{} iterations", config.cmp_cost);
+        rows.push(("work per comparison", cost));
+    }
+    if config.cold_cache {
+        rows.push(("caches", "cold".to_owned()));
+    }
+    if let Some(cpus) = &config.cpus {
+        rows.push(("pinned to CPUs", affinity::describe(cpus)));
+    }
+
+    write_rows(html, rows)
+}
+
+/// The table of how the run was produced: its command line, the build and
+/// the environment.
+fn write_manifest(html: &mut String, manifest: &Manifest) -> fmt::Result {
+    let mut rows = vec![("command line", manifest.command_line())];
+    if let Some(hash) = &manifest.cases_hash {
+        rows.push(("case file hash", hash.clone()));
+    }
+    rows.push(("version", manifest.version.to_owned()));
+    rows.push(("commit", manifest.commit.unwrap_or("unknown").to_owned()));
+    rows.push(("rustc", manifest.rustc.to_owned()));
+    rows.push(("profile", manifest.profile.to_owned()));
+    rows.push(("opt-level", manifest.opt_level.to_owned()));
+    rows.push(("target", manifest.target.to_owned()));
+    if !manifest.rustflags.is_empty() {
+        rows.push(("RUSTFLAGS", manifest.rustflags.to_owned()));
+    }
+    let assertions = match manifest.debug_assertions {
+        true => "on",
+        false => "off",
+    };
+    rows.push(("debug assertions", assertions.to_owned()));
+    rows.push(("features", match manifest.features.is_empty() {
+        true => "none".to_owned(),
+        false => manifest.features.join(", "),
+    }));
+    write_rows(html, rows)?;
+
+    if !manifest.env.is_empty() {
+        writeln!(html, "<h3>Environment</h3>")?;
+        writeln!(html, "<table>")?;
+        for (name, value) in &manifest.env {
+            writeln!(
+                html,
+                "<tr><th><code>{}</code></th><td><code>{}</code></td></tr>",
+                escape(name),
+                escape(value)
+            )?;
+        }
+        writeln!(html, "</table>")?;
+    }
+    Ok(())
+}
+
+/// A table of named values.
+fn write_rows(html: &mut String, rows: Vec<(&str, String)>) -> fmt::Result {
+    writeln!(html, "<table>")?;
+    for (name, value) in rows {
+        writeln!(
+            html,
+            "<tr><th>{}</th><td><code>{}</code></td></tr>",
+            name,
+            escape(&value)
+        )?;
+    }
+    writeln!(html, "</table>")
+}
+
+/// The heading and the table of a case set, a row per target.
+fn write_set(html: &mut String, set: &CaseSetResult) -> fmt::Result {
+    writeln!(
+        html,
+        "<h3>{} ({} cases, {}, sizes {} to {})</h3>",
+        escape(set.spec.name),
+        set.cases,
+        set.pattern,
+        set.spec.min_size,
+        set.spec.max_size
+    )?;
+    let baseline = match set.baseline() {
+        Some(baseline) => baseline,
+        None => return writeln!(html, "<p>No targets ran.</p>"),
+    };
+
+    writeln!(html, "<table>")?;
+    let headers = [
+        "target",
+        "mean",
+        "±",
+        "cpu time",
+        "median case",
+        "throughput",
+        "peak memory",
+    ];
+    write!(html, "<tr>")?;
+    for header in &headers {
+        write!(html, "<th>{}</th>", header)?;
+    }
+    writeln!(
+        html,
+        "<th>speedup over {}</th><th>p-value</th></tr>",
+        escape(&baseline.label())
+    )?;
+
+    for target in &set.targets {
+        let stats = target.total_stats();
+        let dash = || "-".to_owned();
+        let peak = target.memory.and_then(|memory| memory.peak_bytes);
+        let throughput = target.throughput(set.elements());
+        let p_value = set.p_value(target).map(|p_value| {
+            let text = escape(&human_p_value(p_value));
+            match set.significant(target) {
+                Some(true) => format!("<b>{}</b>", text),
+                _ => text,
+            }
+        });
+        let cells = [
+            match stats {
+                Some(stats) => human(stats.mean),
+                None if target.timed_out => "timed out".to_owned(),
+                None => dash(),
+            },
+            stats.map_or_else(dash, |stats| {
+                format!("{:.1}%", stats.relative_stddev())
+            }),
+            target.cpu.map_or_else(dash, human),
+            target.case_stats().map_or_else(dash, |s| human(s.median)),
+            throughput.map_or_else(dash, human_throughput),
+            peak.map_or_else(dash, human_bytes),
+            target.speedup_over(baseline).map_or_else(dash, |speedup| {
+                format!("{:.2}x", speedup)
+            }),
+        ];
+        write!(html, "<tr><td>{}</td>", escape(&target.label()))?;
+        for cell in &cells {
+            write!(html, "<td class=\"number\">{}</td>", escape(cell))?;
+        }
+        // The p-value has its own markup, and is escaped already.
+        writeln!(
+            html,
+            "<td class=\"number\">{}</td></tr>",
+            p_value.unwrap_or_else(dash)
+        )?;
+    }
+    writeln!(html, "</table>")
+}
+
+/// Escapes text for the page.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
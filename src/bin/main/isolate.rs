@@ -0,0 +1,195 @@
+//! Measurement of each target in a process of its own, so what earlier
+//! targets left behind, their heap and the warmth of their threads, does not
+//! change the measurements of later ones.
+//!
+//! The parent runs the same binary, with the arguments of the run and one more,
+//! naming the set and the target. The child measures them alone and sends
+//! the results back as a line of JSON on its stdout.
+
+use crate::{
+    json::Json,
+    report::{CounterStats, MemoryStats, TargetResult},
+    targets::Target,
+};
+use std::{
+    env,
+    io,
+    process::{Command, Stdio},
+    time::Duration,
+};
+
+/// The hidden option making the process a child, followed by
+/// `SET:TARGET:THREADS:SEEDS`.
+pub const CHILD_OPTION: &str = "--isolated-child";
+
+/// What a child measures.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChildTarget {
+    /// The index of the set, in the order the sets run.
+    pub set: usize,
+    /// The target measured.
+    pub target: Target,
+    /// The logical CPUs the target assumes.
+    pub threads: usize,
+    /// The seeds of the whole run, which the child would otherwise draw on
+    /// its own.
+    pub seeds: Vec<u64>,
+}
+
+impl ChildTarget {
+    /// The value of [`CHILD_OPTION`] asking for this.
+    pub fn to_arg(&self) -> String {
+        let seeds = self.seeds.iter().map(u64::to_string);
+        format!(
+            "{}:{}:{}:{}",
+            self.set,
+            self.target.name(),
+            self.threads,
+            seeds.collect::<Vec<_>>().join(",")
+        )
+    }
+
+    /// Parses the value of [`CHILD_OPTION`].
+    pub fn from_arg(value: &str) -> Option<Self> {
+        let mut parts = value.split(':');
+        let set = parts.next()?.parse().ok()?;
+        let target = Target::from_name(parts.next()?)?;
+        let threads = parts.next()?.parse().ok()?;
+        let seeds = parts.next()?.split(',').map(str::parse);
+        let seeds = seeds.collect::<Result<Vec<_>, _>>().ok()?;
+        match parts.next() {
+            None if !seeds.is_empty() => {
+                Some(Self { set, target, threads, seeds })
+            },
+            _ => None,
+        }
+    }
+}
+
+/// Measures a target in a child process given the arguments of the run,
+/// waiting for it. The target and the thread count of the result are left
+/// for the caller to fill.
+pub fn measure(
+    child: &ChildTarget,
+    args: &[String],
+) -> io::Result<TargetResult> {
+    let mut args = args.to_vec();
+    args.push(CHILD_OPTION.to_owned());
+    args.push(child.to_arg());
+
+    // Errors of the child, such as a case sorted wrong, go straight to
+    // stderr.
+    let output = Command::new(env::current_exe()?)
+        .args(args)
+        .stdin(Stdio::null())
+        .stderr(Stdio::inherit())
+        .output()?;
+    if !output.status.success() {
+        let message = format!("it exited with {}", output.status);
+        return Err(io::Error::other(message));
+    }
+
+    let text = String::from_utf8_lossy(&output.stdout);
+    let json = Json::parse(text.trim_end())
+        .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+    decode(&json, child.target).ok_or_else(|| {
+        io::Error::new(io::ErrorKind::InvalidData, "it sent invalid results")
+    })
+}
+
+/// Converts what a child measured to send it to the parent. Times are in
+/// nanoseconds, to come back exactly as they were.
+pub fn encode(target: &TargetResult) -> Json {
+    let nanos = |time: &Duration| Json::from(time.as_nanos() as u64);
+    let runs = target.runs.iter().map(|run| run.iter().map(nanos).collect());
+    let memory = |memory: MemoryStats| {
+        Json::object(vec![
+            ("allocations", Json::from(memory.allocations)),
+            ("peak_bytes", memory.peak_bytes.map_or(Json::Null, Json::from)),
+        ])
+    };
+    let counters = |counters: CounterStats| {
+        let named = counters.named();
+        Json::object(named.iter().map(|&(name, value)| (name, value.into())))
+    };
+    Json::object(vec![
+        ("runs", Json::Array(runs.map(Json::Array).collect())),
+        ("memory", target.memory.map_or(Json::Null, memory)),
+        ("comparisons", target.comparisons.map_or(Json::Null, Json::from)),
+        ("counters", target.counters.map_or(Json::Null, counters)),
+        ("cpu_seconds", target.cpu.map_or(Json::Null, Json::from)),
+        ("energy_joules", target.energy.map_or(Json::Null, Json::from)),
+        ("timed_out", Json::from(target.timed_out)),
+        ("converged", target.converged.map_or(Json::Null, Json::from)),
+        ("utilization", target.utilization.map_or(Json::Null, Json::from)),
+    ])
+}
+
+/// Reads back what a child measured of the given target.
+pub fn decode(json: &Json, target: Target) -> Option<TargetResult> {
+    // A member that is `null` is `Some(None)`, a missing one `None`.
+    let optional = |key: &str| {
+        json.get(key).map(|value| match value {
+            Json::Null => None,
+            value => Some(value),
+        })
+    };
+    let number = |key: &str| match optional(key)? {
+        Some(value) => value.as_u64().map(Some),
+        None => Some(None),
+    };
+    let float = |key: &str| match optional(key)? {
+        Some(value) => value.as_f64().map(Some),
+        None => Some(None),
+    };
+
+    let runs = json.get("runs")?.as_array()?.iter().map(|run| {
+        let times = run.as_array()?.iter().map(|time| time.as_u64());
+        times.map(|nanos| nanos.map(Duration::from_nanos)).collect()
+    });
+    let runs = runs.collect::<Option<Vec<Vec<_>>>>()?;
+
+    let memory = match optional("memory")? {
+        Some(memory) => Some(MemoryStats {
+            allocations: memory.get("allocations")?.as_u64()?,
+            peak_bytes: match memory.get("peak_bytes")? {
+                Json::Null => None,
+                peak => Some(peak.as_u64()? as usize),
+            },
+        }),
+        None => None,
+    };
+    let counters = match optional("counters")? {
+        Some(counters) => Some(CounterStats {
+            instructions: counters.get("instructions")?.as_u64()?,
+            cache_misses: counters.get("cache_misses")?.as_u64()?,
+            branch_misses: counters.get("branch_misses")?.as_u64()?,
+        }),
+        None => None,
+    };
+    let timed_out = match json.get("timed_out")? {
+        Json::Bool(timed_out) => *timed_out,
+        _ => return None,
+    };
+    let converged = match optional("converged")? {
+        Some(Json::Bool(converged)) => Some(*converged),
+        Some(_) => return None,
+        None => None,
+    };
+
+    Some(TargetResult {
+        target,
+        threads: None,
+        runs,
+        memory,
+        comparisons: number("comparisons")?,
+        counters,
+        cpu: float("cpu_seconds")?,
+        energy: float("energy_joules")?,
+        trace: None,
+        timed_out,
+        outliers: None,
+        converged,
+        utilization: float("utilization")?,
+    })
+}
@@ -0,0 +1,491 @@
+//! A minimal JSON document model, enough for the reports and reading them
+//! back.
+
+use std::{
+    convert::TryFrom,
+    error::Error,
+    fmt::{self, Write},
+    iter::Peekable,
+    str::CharIndices,
+};
+
+/// A JSON value.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Json {
+    /// `null`.
+    Null,
+    /// `true` or `false`.
+    Bool(bool),
+    /// A number without fractional part, kept exact.
+    Integer(i128),
+    /// Any other number. Must be finite.
+    Float(f64),
+    /// A string.
+    String(String),
+    /// An array.
+    Array(Vec<Json>),
+    /// An object, keeping the order of its members.
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    /// Builds an object out of its members.
+    pub fn object<I, K>(members: I) -> Self
+    where
+        I: IntoIterator<Item = (K, Json)>,
+        K: Into<String>,
+    {
+        let members = members.into_iter();
+        Json::Object(members.map(|(key, value)| (key.into(), value)).collect())
+    }
+
+    /// Parses a whole document.
+    pub fn parse(text: &str) -> Result<Self, ParseJsonError> {
+        let mut parser = Parser { text, chars: text.char_indices().peekable() };
+        let value = parser.value()?;
+        parser.skip_whitespace();
+        match parser.chars.peek() {
+            Some(&(offset, _)) => Err(parser.error(offset, "trailing data")),
+            None => Ok(value),
+        }
+    }
+
+    /// The member of the given key, if this is an object that has it.
+    pub fn get(&self, key: &str) -> Option<&Json> {
+        match self {
+            Json::Object(members) => {
+                members.iter().find(|(other, _)| other == key).map(|(_, v)| v)
+            },
+            _ => None,
+        }
+    }
+
+    /// The string, if this is one.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Json::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// The number, if this is one.
+    pub fn as_f64(&self) -> Option<f64> {
+        match *self {
+            Json::Integer(value) => Some(value as f64),
+            Json::Float(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// The number, if this is an integer that fits.
+    pub fn as_u64(&self) -> Option<u64> {
+        match *self {
+            Json::Integer(value) => u64::try_from(value).ok(),
+            _ => None,
+        }
+    }
+
+    /// The elements, if this is an array.
+    pub fn as_array(&self) -> Option<&[Json]> {
+        match self {
+            Json::Array(elements) => Some(elements),
+            _ => None,
+        }
+    }
+
+    /// The value on a single line, without spaces.
+    pub fn compact(&self) -> String {
+        let mut text = String::new();
+        // Writing to a string does not fail.
+        let _ = self.write_compact(&mut text);
+        text
+    }
+
+    /// Writes the value on a single line.
+    fn write_compact<W>(&self, output: &mut W) -> fmt::Result
+    where
+        W: Write,
+    {
+        match self {
+            Json::Array(elements) => {
+                output.write_char('[')?;
+                for (index, element) in elements.iter().enumerate() {
+                    if index > 0 {
+                        output.write_char(',')?;
+                    }
+                    element.write_compact(output)?;
+                }
+                output.write_char(']')
+            },
+            Json::Object(members) => {
+                output.write_char('{')?;
+                for (index, (key, value)) in members.iter().enumerate() {
+                    if index > 0 {
+                        output.write_char(',')?;
+                    }
+                    write_string(output, key)?;
+                    output.write_char(':')?;
+                    value.write_compact(output)?;
+                }
+                output.write_char('}')
+            },
+            // The others are the same either way.
+            _ => self.write_pretty(output, 0),
+        }
+    }
+
+    /// Writes the value, indented by `level` steps after the first line.
+    fn write_pretty<W>(&self, output: &mut W, level: usize) -> fmt::Result
+    where
+        W: Write,
+    {
+        match self {
+            Json::Null => output.write_str("null"),
+            Json::Bool(value) => write!(output, "{}", value),
+            Json::Integer(value) => write!(output, "{}", value),
+            Json::Float(value) => write!(output, "{:?}", value),
+            Json::String(value) => write_string(output, value),
+
+            Json::Array(elements) if elements.is_empty() => {
+                output.write_str("[]")
+            },
+            Json::Array(elements) => {
+                output.write_str("[\n")?;
+                for (index, element) in elements.iter().enumerate() {
+                    indent(output, level + 1)?;
+                    element.write_pretty(output, level + 1)?;
+                    if index + 1 < elements.len() {
+                        output.write_char(',')?;
+                    }
+                    output.write_char('\n')?;
+                }
+                indent(output, level)?;
+                output.write_char(']')
+            },
+
+            Json::Object(members) if members.is_empty() => {
+                output.write_str("{}")
+            },
+            Json::Object(members) => {
+                output.write_str("{\n")?;
+                for (index, (key, value)) in members.iter().enumerate() {
+                    indent(output, level + 1)?;
+                    write_string(output, key)?;
+                    output.write_str(": ")?;
+                    value.write_pretty(output, level + 1)?;
+                    if index + 1 < members.len() {
+                        output.write_char(',')?;
+                    }
+                    output.write_char('\n')?;
+                }
+                indent(output, level)?;
+                output.write_char('}')
+            },
+        }
+    }
+}
+
+/// Pretty prints the value, indenting with 2 spaces.
+impl fmt::Display for Json {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        self.write_pretty(fmt, 0)
+    }
+}
+
+impl From<bool> for Json {
+    fn from(value: bool) -> Self {
+        Json::Bool(value)
+    }
+}
+
+impl From<u64> for Json {
+    fn from(value: u64) -> Self {
+        Json::Integer(i128::from(value))
+    }
+}
+
+impl From<usize> for Json {
+    fn from(value: usize) -> Self {
+        Json::Integer(value as i128)
+    }
+}
+
+impl From<f64> for Json {
+    fn from(value: f64) -> Self {
+        // JSON has no infinities nor NaNs.
+        if value.is_finite() {
+            Json::Float(value)
+        } else {
+            Json::Null
+        }
+    }
+}
+
+impl From<&str> for Json {
+    fn from(value: &str) -> Self {
+        Json::String(value.to_owned())
+    }
+}
+
+impl From<String> for Json {
+    fn from(value: String) -> Self {
+        Json::String(value)
+    }
+}
+
+impl<T> From<Vec<T>> for Json
+where
+    T: Into<Json>,
+{
+    fn from(values: Vec<T>) -> Self {
+        Json::Array(values.into_iter().map(Into::into).collect())
+    }
+}
+
+/// Writes `level` steps of indentation.
+fn indent<W>(output: &mut W, level: usize) -> fmt::Result
+where
+    W: Write,
+{
+    for _ in 0 .. level {
+        output.write_str("  ")?;
+    }
+    Ok(())
+}
+
+/// Writes a quoted, escaped string.
+fn write_string<W>(output: &mut W, value: &str) -> fmt::Result
+where
+    W: Write,
+{
+    output.write_char('"')?;
+    for ch in value.chars() {
+        match ch {
+            '"' => output.write_str("\\\"")?,
+            '\\' => output.write_str("\\\\")?,
+            '\n' => output.write_str("\\n")?,
+            '\r' => output.write_str("\\r")?,
+            '\t' => output.write_str("\\t")?,
+            ch if ch < ' ' => write!(output, "\\u{:04x}", ch as u32)?,
+            ch => output.write_char(ch)?,
+        }
+    }
+    output.write_char('"')
+}
+
+/// A document that is not valid JSON.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseJsonError {
+    /// The line the error is at, starting at 1.
+    line: usize,
+    /// The column the error is at, in characters, starting at 1.
+    column: usize,
+    /// What is wrong.
+    message: &'static str,
+}
+
+impl fmt::Display for ParseJsonError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            fmt,
+            "{} at line {}, column {}",
+            self.message, self.line, self.column
+        )
+    }
+}
+
+impl Error for ParseJsonError {}
+
+/// Parses a document, one value at a time.
+struct Parser<'text> {
+    /// The whole document.
+    text: &'text str,
+    /// What is left of it.
+    chars: Peekable<CharIndices<'text>>,
+}
+
+impl<'text> Parser<'text> {
+    /// An error at the given byte offset.
+    fn error(&self, offset: usize, message: &'static str) -> ParseJsonError {
+        let before = &self.text[.. offset];
+        let line = before.matches('\n').count() + 1;
+        let start = before.rfind('\n').map_or(0, |index| index + 1);
+        let column = before[start ..].chars().count() + 1;
+        ParseJsonError { line, column, message }
+    }
+
+    /// An error at the next character, or at the end.
+    fn unexpected(&mut self, message: &'static str) -> ParseJsonError {
+        let offset = self.chars.peek().map_or(self.text.len(), |&(at, _)| at);
+        self.error(offset, message)
+    }
+
+    /// Skips spaces, tabs and line breaks.
+    fn skip_whitespace(&mut self) {
+        while let Some(&(_, ' ' | '\t' | '\n' | '\r')) = self.chars.peek() {
+            self.chars.next();
+        }
+    }
+
+    /// Consumes the given character, after any whitespace.
+    fn expect(&mut self, expected: char) -> Result<(), ParseJsonError> {
+        self.skip_whitespace();
+        match self.chars.peek() {
+            Some(&(_, ch)) if ch == expected => {
+                self.chars.next();
+                Ok(())
+            },
+            _ => Err(self.unexpected("unexpected character")),
+        }
+    }
+
+    /// Consumes the given word, such as `true`.
+    fn word(&mut self, word: &str) -> Result<(), ParseJsonError> {
+        for expected in word.chars() {
+            match self.chars.next() {
+                Some((_, ch)) if ch == expected => (),
+                _ => return Err(self.unexpected("invalid literal")),
+            }
+        }
+        Ok(())
+    }
+
+    /// Parses any value, after any whitespace.
+    fn value(&mut self) -> Result<Json, ParseJsonError> {
+        self.skip_whitespace();
+        match self.chars.peek() {
+            Some((_, 'n')) => self.word("null").map(|_| Json::Null),
+            Some((_, 't')) => self.word("true").map(|_| Json::Bool(true)),
+            Some((_, 'f')) => self.word("false").map(|_| Json::Bool(false)),
+            Some((_, '"')) => self.string().map(Json::String),
+            Some((_, '[')) => self.array(),
+            Some((_, '{')) => self.object(),
+            Some((_, '-' | '0' ..= '9')) => self.number(),
+            _ => Err(self.unexpected("expected a value")),
+        }
+    }
+
+    /// Parses a number.
+    fn number(&mut self) -> Result<Json, ParseJsonError> {
+        let start = self.chars.peek().map_or(self.text.len(), |&(at, _)| at);
+        let mut integer = true;
+        while let Some(&(_, ch)) = self.chars.peek() {
+            match ch {
+                '0' ..= '9' | '-' | '+' => (),
+                '.' | 'e' | 'E' => integer = false,
+                _ => break,
+            }
+            self.chars.next();
+        }
+        let end = self.chars.peek().map_or(self.text.len(), |&(at, _)| at);
+
+        let digits = &self.text[start .. end];
+        let parsed = match integer {
+            true => digits.parse().map(Json::Integer).ok(),
+            false => digits.parse().map(Json::Float).ok(),
+        };
+        parsed.ok_or_else(|| self.error(start, "invalid number"))
+    }
+
+    /// Parses a string, unescaping it.
+    fn string(&mut self) -> Result<String, ParseJsonError> {
+        self.expect('"')?;
+        let mut string = String::new();
+        loop {
+            match self.chars.next() {
+                Some((_, '"')) => return Ok(string),
+                Some((_, '\\')) => string.push(self.escape()?),
+                Some((_, ch)) if ch >= ' ' => string.push(ch),
+                _ => return Err(self.unexpected("unterminated string")),
+            }
+        }
+    }
+
+    /// Parses what follows a backslash in a string.
+    fn escape(&mut self) -> Result<char, ParseJsonError> {
+        let escaped = match self.chars.next() {
+            Some((_, '"')) => '"',
+            Some((_, '\\')) => '\\',
+            Some((_, '/')) => '/',
+            Some((_, 'b')) => '\u{8}',
+            Some((_, 'f')) => '\u{c}',
+            Some((_, 'n')) => '\n',
+            Some((_, 'r')) => '\r',
+            Some((_, 't')) => '\t',
+            Some((_, 'u')) => {
+                let high = self.code_unit()?;
+                let code = match high {
+                    // Characters outside the basic plane are escaped as a
+                    // pair of surrogates.
+                    0xd800 ..= 0xdbff => {
+                        self.word("\\u")?;
+                        let low = self.code_unit()?;
+                        if !(0xdc00 ..= 0xdfff).contains(&low) {
+                            return Err(self.unexpected("invalid escape"));
+                        }
+                        0x10000 + ((high - 0xd800) << 10) + (low - 0xdc00)
+                    },
+                    _ => high,
+                };
+                return char::from_u32(code)
+                    .ok_or_else(|| self.unexpected("invalid escape"));
+            },
+            _ => return Err(self.unexpected("invalid escape")),
+        };
+        Ok(escaped)
+    }
+
+    /// Parses the 4 hexadecimal digits of a `\u` escape.
+    fn code_unit(&mut self) -> Result<u32, ParseJsonError> {
+        let mut code = 0;
+        for _ in 0 .. 4 {
+            let digit = self.chars.next().and_then(|(_, ch)| ch.to_digit(16));
+            let digit = digit.ok_or_else(|| self.unexpected("invalid escape"))?;
+            code = code * 16 + digit;
+        }
+        Ok(code)
+    }
+
+    /// Parses an array.
+    fn array(&mut self) -> Result<Json, ParseJsonError> {
+        self.expect('[')?;
+        let mut elements = Vec::new();
+        self.skip_whitespace();
+        if let Some((_, ']')) = self.chars.peek() {
+            self.chars.next();
+            return Ok(Json::Array(elements));
+        }
+        loop {
+            elements.push(self.value()?);
+            self.skip_whitespace();
+            match self.chars.next() {
+                Some((_, ',')) => (),
+                Some((_, ']')) => return Ok(Json::Array(elements)),
+                _ => return Err(self.unexpected("expected , or ]")),
+            }
+        }
+    }
+
+    /// Parses an object.
+    fn object(&mut self) -> Result<Json, ParseJsonError> {
+        self.expect('{')?;
+        let mut members = Vec::new();
+        self.skip_whitespace();
+        if let Some((_, '}')) = self.chars.peek() {
+            self.chars.next();
+            return Ok(Json::Object(members));
+        }
+        loop {
+            self.skip_whitespace();
+            let key = self.string()?;
+            self.expect(':')?;
+            members.push((key, self.value()?));
+            self.skip_whitespace();
+            match self.chars.next() {
+                Some((_, ',')) => (),
+                Some((_, '}')) => return Ok(Json::Object(members)),
+                _ => return Err(self.unexpected("expected , or }")),
+            }
+        }
+    }
+}
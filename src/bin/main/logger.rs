@@ -0,0 +1,49 @@
+//! The log of how the parallel merge sort splits and merges, written on
+//! stderr with `-vv`. Only with the `log` feature. Nothing is logged but the
+//! extra sort of the biggest case of each set, so the timed runs do not pay
+//! for it.
+
+use log::{LevelFilter, Log, Metadata, Record};
+use std::{
+    io::{self, Write},
+    sync::Once,
+};
+
+/// Writes the records of the library on stderr.
+struct StderrLogger;
+
+impl Log for StderrLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.target().starts_with("mergesort_cmp")
+    }
+
+    fn log(&self, record: &Record) {
+        if self.enabled(record.metadata()) {
+            let stderr = io::stderr();
+            let _ = writeln!(stderr.lock(), "    {}", record.args());
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+/// The logger, installed on first use.
+static LOGGER: StderrLogger = StderrLogger;
+
+/// Runs the sort of the given target, logging every decision it makes.
+pub fn during<R, G>(label: &str, task: G) -> R
+where
+    G: FnOnce() -> R,
+{
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        // Only fails if there is a logger already, which logs instead.
+        let _ = log::set_logger(&LOGGER);
+    });
+
+    eprintln!("  {}:", label);
+    log::set_max_level(LevelFilter::Trace);
+    let result = task();
+    log::set_max_level(LevelFilter::Off);
+    result
+}
@@ -0,0 +1,1208 @@
+//! Compares the sequential and the parallel merge sorts.
+
+mod affinity;
+mod baseline;
+mod cache;
+mod casefile;
+mod chart;
+mod checkpoint;
+mod cases;
+mod cli;
+mod compare;
+mod complexity;
+mod cputime;
+mod dryrun;
+mod elements;
+mod energy;
+mod isolate;
+mod gate;
+mod html;
+mod json;
+#[cfg(feature = "log")]
+mod logger;
+mod manifest;
+mod order;
+mod output;
+mod patterns;
+mod perf;
+mod phase;
+mod plot;
+mod progress;
+mod report;
+mod sort;
+mod stats;
+mod targets;
+mod verify;
+mod watchdog;
+
+use baseline::Baseline;
+use cache::Evictor;
+use casefile::{CaseReader, CaseWriter, Header};
+use cases::{CaseSet, SetSpec};
+use cli::{Command, Config};
+use elements::{Element, ElementType, Payload};
+use energy::Meter;
+use isolate::ChildTarget;
+use manifest::Manifest;
+use mergesort_cmp::{
+    alloc::{self, CountingAllocator},
+    comparator::CountingComparator,
+    parallel::Cancellation,
+};
+use order::Order;
+use output::Verbosity;
+use patterns::Pattern;
+use perf::Counters;
+use phase::Phase;
+use progress::Progress;
+use rand::{rngs::StdRng, seq::SliceRandom};
+use report::{
+    BenchReport,
+    CaseSetResult,
+    CounterStats,
+    Machine,
+    MemoryStats,
+    TargetResult,
+};
+use std::{
+    borrow::Cow,
+    cmp,
+    env,
+    fmt,
+    fs::File,
+    hint,
+    io::{self, BufWriter, Write},
+    mem,
+    path::{Path, PathBuf},
+    process::exit,
+    slice,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use targets::{Sorter, Target};
+use verify::{Failure, VerifyError};
+use watchdog::Watchdog;
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator::system();
+
+fn main() {
+    let config = match cli::parse(env::args().skip(1)) {
+        Ok(Command::Run(config)) => config,
+        Ok(Command::Sort(config)) => {
+            sort::run(&config).unwrap_or_else(|error| fail(error));
+            return;
+        },
+        Ok(Command::Compare(config)) => {
+            compare::run(&config).unwrap_or_else(|error| fail(error));
+            return;
+        },
+        Ok(Command::Resume(path)) => {
+            checkpoint::resume(&path).unwrap_or_else(|error| fail(error))
+        },
+        Ok(Command::Help) => {
+            print!("{}", cli::usage());
+            return;
+        },
+        Err(error) => {
+            eprintln!("error: {}", error);
+            eprintln!("Try `--help` for more information.");
+            exit(2);
+        },
+    };
+
+    // Before any thread starts, so they are all pinned.
+    if let Some(cpus) = &config.cpus {
+        if let Err(error) = affinity::pin(cpus) {
+            let cpus = affinity::describe(cpus);
+            fail(format!("cannot pin to CPUs {}: {}", cpus, error));
+        }
+    }
+
+    if config.dry_run {
+        let result = dryrun::write(&config, &mut io::stdout().lock());
+        result.unwrap_or_else(|error| fail(error));
+        return;
+    }
+
+    if config.child.is_some() {
+        // Only the parent writes the results where they were asked to go.
+        let result = run(&config, &mut io::stdout().lock());
+        result.unwrap_or_else(|error| fail(error));
+        return;
+    }
+
+    // A missing baseline is found before running, not after.
+    let baseline = config.compare_baseline.as_ref().map(|name| {
+        Baseline::load(name).unwrap_or_else(|error| {
+            fail(format!(
+                "cannot load baseline {} from {}: {}",
+                name,
+                baseline::path(name).display(),
+                error
+            ))
+        })
+    });
+
+    let mut output: Box<dyn Write> = match &config.output {
+        Some(path) => match File::create(path) {
+            Ok(file) => Box::new(BufWriter::new(file)),
+            Err(error) => {
+                fail(format!("cannot create {}: {}", path.display(), error))
+            },
+        },
+        None => Box::new(io::stdout()),
+    };
+
+    let report = run(&config, &mut output).unwrap_or_else(|error| fail(error));
+    // Flushes and closes the output before anything else is written.
+    drop(output);
+
+    if let Some(path) = &config.chart {
+        if let Err(error) = chart::write(path, &report) {
+            fail(format!("cannot write {}: {}", path.display(), error));
+        }
+    }
+
+    if let Some(path) = &config.report {
+        if let Err(error) = html::write(path, &config, &report) {
+            fail(format!("cannot write {}: {}", path.display(), error));
+        }
+    }
+
+    if let Some(directory) = &config.plot_data {
+        if let Err(error) = plot::write(directory, &report) {
+            fail(format!("cannot write to {}: {}", directory.display(), error));
+        }
+    }
+
+    if let Some(name) = &config.save_baseline {
+        if let Err(error) = baseline::save(name, &report) {
+            let path = baseline::path(name);
+            fail(format!("cannot save {}: {}", path.display(), error));
+        }
+    }
+
+    if let Some(baseline) = baseline {
+        let threshold = config.regression_threshold;
+        let compared = baseline.compare(&report, threshold, &mut io::stderr());
+        let regressions = compared.unwrap_or_else(|error| fail(error));
+        let worst = regressions
+            .iter()
+            .max_by(|left, right| left.percent.total_cmp(&right.percent));
+        if let Some(worst) = worst {
+            fail(format!(
+                "{} of the targets regressed by more than {}%, the worst \
+                 being {} on {} by {:.1}%",
+                regressions.len(),
+                threshold,
+                worst.target,
+                worst.set,
+                worst.percent
+            ));
+        }
+    }
+
+    if !config.assert_speedups.is_empty() {
+        let assertions = &config.assert_speedups;
+        let checked = gate::check(&report, assertions, &mut io::stderr());
+        match checked.unwrap_or_else(|error| fail(error)) {
+            0 => (),
+            failed => fail(format!("{} of the speedup checks failed", failed)),
+        }
+    }
+}
+
+/// Reports the error and exits with a failure.
+fn fail<E>(error: E) -> !
+where
+    E: fmt::Display,
+{
+    eprintln!("error: {}", error);
+    exit(1);
+}
+
+/// Why a run stopped before finishing.
+#[derive(Debug)]
+enum RunError {
+    /// The results could not be written.
+    Io(io::Error),
+    /// A target did not sort a case.
+    Verify(VerifyError),
+    /// A case file could not be read or written.
+    Cases(PathBuf, io::Error),
+    /// The checkpoint could not be written.
+    Checkpoint(PathBuf, io::Error),
+    /// The hardware counters could not be opened or read.
+    Counters(io::Error),
+    /// The energy counters could not be read.
+    Energy(io::Error),
+    /// The child process measuring a target, at a thread count if it ran as
+    /// part of a sweep, failed.
+    Child(Target, Option<usize>, io::Error),
+}
+
+impl RunError {
+    /// Converts errors about the given case file.
+    fn cases(path: &Path) -> impl Fn(io::Error) -> Self + '_ {
+        move |error| RunError::Cases(path.to_owned(), error)
+    }
+
+    /// Converts errors about the checkpoint.
+    fn checkpoint(path: &Path) -> impl Fn(io::Error) -> Self + '_ {
+        move |error| RunError::Checkpoint(path.to_owned(), error)
+    }
+}
+
+impl From<io::Error> for RunError {
+    fn from(error: io::Error) -> Self {
+        RunError::Io(error)
+    }
+}
+
+impl From<VerifyError> for RunError {
+    fn from(error: VerifyError) -> Self {
+        RunError::Verify(error)
+    }
+}
+
+impl fmt::Display for RunError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RunError::Io(error) => write!(fmt, "{}", error),
+            RunError::Verify(error) => write!(fmt, "{}", error),
+            RunError::Cases(path, error) => {
+                write!(fmt, "{}: {}", path.display(), error)
+            },
+            RunError::Checkpoint(path, error) => {
+                let path = path.display();
+                write!(fmt, "cannot checkpoint to {}: {}", path, error)
+            },
+            RunError::Counters(error) => {
+                write!(fmt, "the hardware counters cannot be used: {}", error)
+            },
+            RunError::Energy(error) => {
+                write!(fmt, "the energy cannot be measured: {}", error)
+            },
+            RunError::Child(target, threads, error) => {
+                write!(fmt, "the process measuring {}", target.name())?;
+                if let Some(threads) = threads {
+                    write!(fmt, " at {} threads", threads)?;
+                }
+                write!(fmt, " failed: {}", error)
+            },
+        }
+    }
+}
+
+/// Runs every selected case set for every selected target, writing the
+/// results to the output as they come.
+fn run<W>(config: &Config, output: &mut W) -> Result<BenchReport, RunError>
+where
+    W: Write,
+{
+    // Saved cases bring the parameters they were generated with.
+    let (config, cases) = match &config.cases {
+        Some(path) => {
+            let (cases, header) =
+                CaseReader::open(path).map_err(RunError::cases(path))?;
+            let config = Config {
+                seeds: vec![header.seed],
+                element: header.element,
+                strings: header.strings,
+                payload_bytes: header.payload_bytes,
+                ..config.clone()
+            };
+            (Cow::Owned(config), Some(cases))
+        },
+        None => (Cow::Borrowed(config), None),
+    };
+    let config = &*config;
+
+    match config.element {
+        ElementType::I64 => run_with::<i64, W>(config, &(), cases, output),
+        ElementType::F64 => run_with::<f64, W>(config, &(), cases, output),
+        ElementType::String => {
+            run_with::<String, W>(config, &config.strings, cases, output)
+        },
+        ElementType::Payload => run_payload(config, cases, output),
+        ElementType::KeyValue => {
+            run_with::<(u64, u64), W>(config, &(), cases, output)
+        },
+    }
+}
+
+/// Runs the benchmark with payloads of the configured size. The sizes must
+/// be the ones in [`elements::PAYLOAD_BYTES`].
+fn run_payload<W>(
+    config: &Config,
+    cases: Option<CaseReader>,
+    output: &mut W,
+) -> Result<BenchReport, RunError>
+where
+    W: Write,
+{
+    macro_rules! sizes {
+        ($($bytes:literal),*) => {
+            match config.payload_bytes {
+                $($bytes => {
+                    run_with::<Payload<$bytes>, W>(config, &(), cases, output)
+                },)*
+                bytes => unreachable!("payload of {} bytes", bytes),
+            }
+        };
+    }
+
+    sizes!(8, 16, 32, 64, 128, 256, 512, 1024)
+}
+
+/// Runs the benchmark with cases made of elements of type `T` and the given
+/// shape, read from the case file if there is one.
+fn run_with<T, W>(
+    config: &Config,
+    shape: &T::Shape,
+    mut cases: Option<CaseReader>,
+    output: &mut W,
+) -> Result<BenchReport, RunError>
+where
+    T: Element,
+    W: Write,
+{
+    let mut instruments = Instruments::open(config)?;
+    // Children write no report, only their results.
+    let manifest = match (&config.child, &config.cases) {
+        (Some(_), _) => None,
+        (None, Some(path)) => {
+            Some(Manifest::capture(config).map_err(RunError::cases(path))?)
+        },
+        (None, None) => Some(Manifest::capture(config)?),
+    };
+    let color = output::color_wanted(config.output.is_none());
+    let mut reporter = config.format.reporter(config.verbosity, color);
+    let mut report = BenchReport {
+        seeds: config.seeds.clone(),
+        element: config.element,
+        strings: match config.element {
+            ElementType::String => Some(config.strings.clone()),
+            _ => None,
+        },
+        payload_bytes: match config.element {
+            ElementType::Payload => Some(config.payload_bytes),
+            _ => None,
+        },
+        machine: Machine::detect(),
+        order: config.order,
+        cold_cache: config.cold_cache,
+        phase: config.phase,
+        cpus: config.cpus.clone(),
+        cmp_cost: config.cmp_cost,
+        manifest,
+        sets: Vec::new(),
+    };
+    if let Some(child) = &config.child {
+        let result =
+            run_child::<T>(config, shape, cases, child, &mut instruments)?;
+        writeln!(output, "{}", isolate::encode(&result.targets[0]).compact())?;
+        report.sets.push(result);
+        return Ok(report);
+    }
+    reporter.start(output, &report)?;
+
+    let mut dump = match &config.dump_cases {
+        Some(path) => {
+            let header = Header {
+                seed: config.seeds[0],
+                element: config.element,
+                strings: config.strings.clone(),
+                payload_bytes: config.payload_bytes,
+                sets: cases
+                    .as_ref()
+                    .map_or(config.sets.len(), CaseReader::remaining),
+            };
+            let writer = CaseWriter::create(path, &header);
+            Some(writer.map_err(RunError::cases(path))?)
+        },
+        None => None,
+    };
+    // Children leave the checkpoint to their parent.
+    let mut checkpoint = match (&config.checkpoint, &config.child) {
+        (Some(path), None) => {
+            let writer = match config.resumed {
+                Some(_) => checkpoint::Writer::append(path),
+                None => checkpoint::Writer::create(path, config),
+            };
+            Some(writer.map_err(RunError::checkpoint(path))?)
+        },
+        _ => None,
+    };
+    let mut specs = config.sets.iter();
+    let show_progress = Progress::wanted(config.verbosity);
+
+    for index in 0 .. {
+        // Sets are generated or read one at a time, the big ones take a lot
+        // of memory.
+        let (spec, pattern, set) =
+            match next_set::<T>(config, shape, &mut cases, &mut specs)? {
+                Some(set) => set,
+                None => break,
+            };
+
+        if let Some(dump) = &mut dump {
+            let path = config.dump_cases.as_deref().expect("dump file");
+            dump.write_set(spec, pattern, &set)
+                .map_err(RunError::cases(path))?;
+        }
+        let set = match config.phase {
+            Phase::Sort => set,
+            Phase::Merge => phase::sort_halves(set),
+        };
+
+        let mut result = CaseSetResult {
+            spec,
+            pattern,
+            cases: set.cases.len(),
+            sizes: set.cases.iter().map(|case| case.len()).collect(),
+            seeds: config.seeds.clone(),
+            confidence: config.confidence,
+            targets: Vec::new(),
+        };
+        reporter.start_set(output, &result)?;
+
+        // Every target at each of its thread counts, in the order they run.
+        let mut plans = Vec::new();
+        for &target in &config.targets {
+            for threads in config.thread_counts(target) {
+                plans.push((target, threads));
+            }
+        }
+        let runs = config.warmup + config.repeat;
+        let total = plans.len() * runs * set.cases.len();
+        let mut progress = Progress::new(show_progress, spec.name, total);
+
+        let mut shuffler = order::shuffler(spec, config.seeds[0]);
+        if config.order == Order::Shuffled {
+            plans.shuffle(&mut shuffler);
+        }
+        // Interleaved targets are all measured before any is reported, those
+        // of a resumed run that it measured already left out.
+        let pending = plans.iter().copied().filter(|&(target, threads)| {
+            resumed(config, index, spec, target, threads).is_none()
+        });
+        let pending = pending.collect::<Vec<_>>();
+        let interleaved = match config.order {
+            Order::Interleaved => measure_interleaved(
+                config,
+                spec,
+                &set,
+                &pending,
+                &mut instruments,
+                &mut shuffler,
+                &mut progress,
+            )?,
+            _ => Vec::new(),
+        };
+        let mut interleaved = interleaved.into_iter();
+
+        for &(target, threads) in &plans {
+            let count = threads.unwrap_or(config.threads[0]);
+            let planned = progress.done() + runs * set.cases.len();
+            let resumed = resumed(config, index, spec, target, threads);
+            let fresh = resumed.is_none();
+            let mut measured = match resumed.or_else(|| interleaved.next()) {
+                Some(measured) => measured,
+                None if config.isolate => {
+                    let child = ChildTarget {
+                        set: index,
+                        target,
+                        threads: count,
+                        seeds: config.seeds.clone(),
+                    };
+                    isolate::measure(&child, &config.args).map_err(|error| {
+                        RunError::Child(target, threads, error)
+                    })?
+                },
+                None => measure_target(
+                    config,
+                    spec,
+                    &set,
+                    target,
+                    threads,
+                    &mut instruments,
+                    &mut progress,
+                )?,
+            };
+            measured.threads = threads;
+            progress.skip_to(planned);
+            if let (Some(writer), true) = (&mut checkpoint, fresh) {
+                let path = config.checkpoint.as_deref().expect("checkpoint");
+                writer
+                    .write(index, spec.name, &measured)
+                    .map_err(RunError::checkpoint(path))?;
+            }
+            if let Some(mads) = config.reject_outliers {
+                measured.reject_outliers(mads);
+            }
+
+            // The trace would take as long as the run that timed out, and is
+            // of the whole sort.
+            let traced = config.phase == Phase::Sort
+                && config.verbosity == Verbosity::Debug;
+            if !measured.timed_out && traced {
+                let cases = set.cases.iter();
+                let biggest = cases.max_by_key(|case| case.len());
+                #[cfg(not(feature = "log"))]
+                let trace = |case: &Arc<[T]>| target.trace(count, case);
+                // The decisions behind the trace go to stderr as they are
+                // made.
+                #[cfg(feature = "log")]
+                let trace = |case: &Arc<[T]>| {
+                    progress.clear();
+                    let label = measured.label();
+                    logger::during(&label, || target.trace(count, case))
+                };
+                measured.trace = biggest.and_then(trace);
+            }
+            result.targets.push(measured);
+            progress.clear();
+            let target = result.targets.last().expect("just pushed");
+            reporter.target(output, &result, target)?;
+        }
+
+        progress.clear();
+        reporter.end_set(output, &result)?;
+        report.sets.push(result);
+    }
+
+    reporter.finish(output, &report)?;
+    output.flush()?;
+    Ok(report)
+}
+
+/// What a resumed run measured of the target on the set of the given index
+/// before it was interrupted, if it did.
+fn resumed(
+    config: &Config,
+    index: usize,
+    spec: &SetSpec,
+    target: Target,
+    threads: Option<usize>,
+) -> Option<TargetResult> {
+    let completed = config.resumed.as_ref()?.iter().find(|completed| {
+        completed.set == index
+            && completed.set_name == spec.name
+            && completed.target == target
+            && completed.threads == threads
+    })?;
+    Some(completed.result.clone())
+}
+
+/// A set of cases with its parameters and pattern.
+type NextSet<T> = (&'static SetSpec, Pattern, CaseSet<T>);
+
+/// Generates the next set, or reads it from the case file if there is one.
+/// `None` once there are no more sets.
+fn next_set<T>(
+    config: &Config,
+    shape: &T::Shape,
+    cases: &mut Option<CaseReader>,
+    specs: &mut slice::Iter<&'static SetSpec>,
+) -> Result<Option<NextSet<T>>, RunError>
+where
+    T: Element,
+{
+    if let Some(cases) = cases {
+        let path = config.cases.as_deref().expect("case file");
+        return cases.next_set::<T>().map_err(RunError::cases(path));
+    }
+    Ok(specs.next().map(|&spec| {
+        let pattern = config.pattern_of(spec);
+        let set = CaseSet::generate_seeds(spec, pattern, shape, &config.seeds);
+        (spec, pattern, set)
+    }))
+}
+
+/// Measures the single target of a child process, on its set.
+fn run_child<T>(
+    config: &Config,
+    shape: &T::Shape,
+    mut cases: Option<CaseReader>,
+    child: &ChildTarget,
+    instruments: &mut Instruments,
+) -> Result<CaseSetResult, RunError>
+where
+    T: Element,
+{
+    // Saved sets before the one asked for are only read to get past them,
+    // while generated ones are not generated at all.
+    let mut specs = config.sets[child.set.min(config.sets.len()) ..].iter();
+    if cases.is_some() {
+        for _ in 0 .. child.set {
+            next_set::<T>(config, shape, &mut cases, &mut specs)?;
+        }
+    }
+    let (spec, pattern, set) =
+        match next_set::<T>(config, shape, &mut cases, &mut specs)? {
+            Some(set) => set,
+            None => {
+                return Err(RunError::Io(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("there is no case set #{}", child.set),
+                )))
+            },
+        };
+
+    let set = match config.phase {
+        Phase::Sort => set,
+        Phase::Merge => phase::sort_halves(set),
+    };
+
+    let mut progress = Progress::new(false, spec.name, 0);
+    let measured = measure_target(
+        config,
+        spec,
+        &set,
+        child.target,
+        Some(child.threads),
+        instruments,
+        &mut progress,
+    )?;
+    Ok(CaseSetResult {
+        spec,
+        pattern,
+        cases: set.cases.len(),
+        sizes: set.cases.iter().map(|case| case.len()).collect(),
+        seeds: config.seeds.clone(),
+        confidence: config.confidence,
+        targets: vec![measured],
+    })
+}
+
+/// What measures the targets besides the clock and the allocator, opened
+/// once for the whole run.
+struct Instruments {
+    /// The hardware counters, if they are collected.
+    counters: Option<Counters>,
+    /// The energy meter, if the energy is measured.
+    meter: Option<Meter>,
+    /// What evicts the caches before every timed run, if the targets are
+    /// measured with cold caches.
+    evictor: Option<Evictor>,
+}
+
+impl Instruments {
+    /// Opens what the configuration asks for, failing early if something
+    /// cannot be used.
+    fn open(config: &Config) -> Result<Self, RunError> {
+        let counters = Counters::open().map_err(RunError::Counters)?;
+        let meter = match config.energy {
+            true => Some(Meter::open().map_err(RunError::Energy)?),
+            false => None,
+        };
+        let evictor = config.cold_cache.then(Evictor::new);
+        Ok(Self { counters, meter, evictor })
+    }
+}
+
+/// Measures the target over the case set, advancing the progress for every
+/// case sorted.
+fn measure_target<T>(
+    config: &Config,
+    spec: &SetSpec,
+    set: &CaseSet<T>,
+    target: Target,
+    threads: Option<usize>,
+    instruments: &mut Instruments,
+    progress: &mut Progress,
+) -> Result<TargetResult, RunError>
+where
+    T: Element,
+{
+    let count = threads.unwrap_or(config.threads[0]);
+    let cancellation = Cancellation::new();
+    let mut sorter = timed(config, target, count, T::compare, &cancellation);
+    let mut measured = TargetResult::new(target, threads);
+    let deadline = config.timeout.map(|timeout| Instant::now() + timeout);
+    let _watchdog =
+        deadline.map(|deadline| Watchdog::arm(&cancellation, deadline));
+    let mut run_once = |progress: &mut Progress| {
+        let run = run_for_target(
+            set,
+            &mut sorter,
+            config.verify,
+            deadline,
+            progress,
+        );
+        run.map_err(|(case, failure)| {
+            verify_error(config, spec, set, &measured, case, failure)
+        })
+    };
+
+    // Faults the pages in and warms the caches up for this target.
+    let mut timed_out = false;
+    for _ in 0 .. config.warmup {
+        if run_once(progress)?.timed_out {
+            timed_out = true;
+            break;
+        }
+    }
+
+    let meter = instruments.meter.as_ref();
+    let start = match meter {
+        Some(meter) => Some(meter.read().map_err(RunError::Energy)?),
+        None => None,
+    };
+    // Only the runs over every case are kept. When converging, the runs go
+    // on past those asked for until the interval is narrow enough, or the
+    // budget is spent.
+    let budget = Instant::now() + config.converge_budget;
+    let mut runs = Vec::with_capacity(config.repeat);
+    while !timed_out {
+        if runs.len() >= config.repeat {
+            let over = Instant::now() >= budget;
+            match config.converge {
+                Some(_) if !over && !converged(config, &runs) => {
+                    progress.extend(set.cases.len())
+                },
+                _ => break,
+            }
+        }
+        if let Some(evictor) = &mut instruments.evictor {
+            evictor.evict();
+        }
+        let run = run_once(progress)?;
+        match run.timed_out {
+            true => timed_out = true,
+            false => runs.push(run),
+        }
+    }
+    if let (Some(meter), Some(start)) = (meter, &start) {
+        let joules = meter.joules_since(start).map_err(RunError::Energy)?;
+        if !timed_out {
+            measured.energy = Some(joules / runs.len() as f64);
+        }
+    }
+    measured.timed_out = timed_out;
+    if config.converge.is_some() && !timed_out {
+        measured.converged = Some(converged(config, &runs));
+    }
+    complete_target(config, set, &mut sorter, runs, instruments, measured)
+}
+
+/// Whether the confidence interval of the mean of the runs is as narrow as
+/// the configuration asks runs to converge to.
+fn converged(config: &Config, runs: &[SetRun]) -> bool {
+    let totals = runs.iter().map(|run| run.times.iter().sum::<Duration>());
+    let totals = totals.map(|total| total.as_secs_f64()).collect::<Vec<_>>();
+    let interval = stats::relative_interval(&totals, config.confidence);
+    match (interval, config.converge) {
+        (Some(interval), Some(width)) => interval <= width,
+        _ => false,
+    }
+}
+
+/// A target taking turns with the others on every case.
+struct Turn<T> {
+    /// What sorts for the target.
+    sorter: Sorter<T>,
+    /// What cancels the sort of the target running past its timeout.
+    cancellation: Cancellation,
+    /// What was measured of the target so far.
+    measured: TargetResult,
+    /// The run over the set in progress.
+    run: SetRun,
+    /// The timed runs over the whole set.
+    runs: Vec<SetRun>,
+    /// How long the sorts of the target took so far, the warmup included.
+    spent: Duration,
+}
+
+/// Measures the targets together, taking turns on every case in an order
+/// shuffled for every case, so none always finds the caches as the same other
+/// target left them. A target leaves the turns once its own sorts took longer
+/// than the timeout. The results are in the order of the targets given.
+fn measure_interleaved<T>(
+    config: &Config,
+    spec: &SetSpec,
+    set: &CaseSet<T>,
+    plans: &[(Target, Option<usize>)],
+    instruments: &mut Instruments,
+    shuffler: &mut StdRng,
+    progress: &mut Progress,
+) -> Result<Vec<TargetResult>, RunError>
+where
+    T: Element,
+{
+    let mut turns = plans
+        .iter()
+        .map(|&(target, threads)| {
+            let cancellation = Cancellation::new();
+            Turn {
+                sorter: timed(
+                    config,
+                    target,
+                    threads.unwrap_or(config.threads[0]),
+                    T::compare,
+                    &cancellation,
+                ),
+                cancellation,
+                measured: TargetResult::new(target, threads),
+                run: SetRun::new(set.cases.len()),
+                runs: Vec::new(),
+                spent: Duration::ZERO,
+            }
+        })
+        .collect::<Vec<_>>();
+    let mut order = (0 .. turns.len()).collect::<Vec<_>>();
+
+    for round in 0 .. config.warmup + config.repeat {
+        for (index, case) in set.cases.iter().enumerate() {
+            order.shuffle(shuffler);
+            for &next in &order {
+                let turn = &mut turns[next];
+                if turn.measured.timed_out {
+                    continue;
+                }
+                if config.timeout.is_some_and(|timeout| turn.spent >= timeout) {
+                    turn.measured.timed_out = true;
+                    continue;
+                }
+                // Only the time left to the target counts to its deadline.
+                let then = Instant::now();
+                let deadline = config.timeout.map(|timeout| {
+                    then + timeout.saturating_sub(turn.spent)
+                });
+                let _watchdog = deadline.map(|deadline| {
+                    Watchdog::arm(&turn.cancellation, deadline)
+                });
+                let sorted = watchdog::unless_cancelled(|| {
+                    sort_case(case, &mut turn.sorter, config.verify)
+                });
+                turn.spent += then.elapsed();
+                let sorted = match sorted {
+                    Some(sorted) => sorted,
+                    None => {
+                        turn.measured.timed_out = true;
+                        continue;
+                    },
+                };
+                let measured = &turn.measured;
+                let sorted = sorted.map_err(|failure| {
+                    verify_error(config, spec, set, measured, index, failure)
+                })?;
+                turn.run.push(sorted);
+                progress.advance();
+            }
+        }
+
+        // Only the runs over every case are kept, and not the warmup.
+        for turn in &mut turns {
+            let run = mem::replace(&mut turn.run, SetRun::new(set.cases.len()));
+            if round >= config.warmup && !turn.measured.timed_out {
+                turn.runs.push(run);
+            }
+        }
+    }
+
+    let complete = |mut turn: Turn<T>| {
+        let (sorter, runs) = (&mut turn.sorter, turn.runs);
+        complete_target(config, set, sorter, runs, instruments, turn.measured)
+    };
+    turns.into_iter().map(complete).collect()
+}
+
+/// Fills in what the timed runs of a target measured, and what extra runs
+/// count, unless it timed out.
+fn complete_target<T>(
+    config: &Config,
+    set: &CaseSet<T>,
+    sorter: &mut Sorter<T>,
+    runs: Vec<SetRun>,
+    instruments: &Instruments,
+    mut measured: TargetResult,
+) -> Result<TargetResult, RunError>
+where
+    T: Element,
+{
+    measured.memory =
+        MemoryStats::over(runs.iter().filter_map(|run| run.memory));
+    let cpu = runs.iter().map(|run| run.cpu);
+    measured.cpu = cpu
+        .sum::<Option<Duration>>()
+        .filter(|_| !runs.is_empty())
+        .map(|cpu| cpu.as_secs_f64() / runs.len() as f64);
+    measured.runs = runs.into_iter().map(|run| run.times).collect();
+
+    // The extra runs would take as long as the one that timed out.
+    let extra = !measured.timed_out;
+    let target = measured.target;
+    if extra && config.count_comparisons && target.uses_compare() {
+        let count = measured.threads.unwrap_or(config.threads[0]);
+        let comparisons = count_comparisons(config, set, target, count);
+        measured.comparisons = Some(comparisons);
+    }
+    if extra && config.utilization {
+        let count = measured.threads.unwrap_or(config.threads[0]);
+        measured.utilization = utilization(set, target, count);
+    }
+    if let Some(counters) = instruments.counters.as_ref().filter(|_| extra) {
+        let events =
+            count_events(set, sorter, counters).map_err(RunError::Counters)?;
+        measured.counters = Some(events);
+    }
+    Ok(measured)
+}
+
+/// The error of a target that sorted the case of the given index wrong, with
+/// the case found among those of its seed.
+fn verify_error<T>(
+    config: &Config,
+    spec: &SetSpec,
+    set: &CaseSet<T>,
+    target: &TargetResult,
+    case: usize,
+    failure: Failure,
+) -> VerifyError {
+    let cases_per_seed = (set.cases.len() / config.seeds.len()).max(1);
+    VerifyError {
+        seed: config.seeds[case / cases_per_seed],
+        set: spec.name,
+        case: case % cases_per_seed,
+        target: target.label().into_owned(),
+        failure,
+    }
+}
+
+/// What the target is timed by: its sort function, or its merge alone with
+/// `--phase merge`, comparing with the given function, slowed down by the
+/// `--cmp-cost` of every comparison, and given up once the cancellation is
+/// cancelled.
+fn timed<T, C>(
+    config: &Config,
+    target: Target,
+    threads: usize,
+    compare: C,
+    cancellation: &Cancellation,
+) -> Sorter<T>
+where
+    T: Element,
+    C: Fn(&T, &T) -> cmp::Ordering + Clone + Send + Sync + 'static,
+{
+    let cost = config.cmp_cost;
+    match cost {
+        0 => timed_by(config.phase, target, threads, compare, cancellation),
+        _ => {
+            let costly = move |left: &T, right: &T| {
+                spin(cost);
+                compare(left, right)
+            };
+            timed_by(config.phase, target, threads, costly, cancellation)
+        },
+    }
+}
+
+/// What the target is timed by in the phase, comparing with the function.
+fn timed_by<T, C>(
+    phase: Phase,
+    target: Target,
+    threads: usize,
+    compare: C,
+    cancellation: &Cancellation,
+) -> Sorter<T>
+where
+    T: Element,
+    C: Fn(&T, &T) -> cmp::Ordering + Clone + Send + Sync + 'static,
+{
+    match phase {
+        Phase::Sort => target.sorter_by(threads, compare, cancellation),
+        Phase::Merge => target
+            .merger_by(threads, compare, cancellation)
+            .expect("a target that merges"),
+    }
+}
+
+/// Does the given iterations of work the optimizer cannot remove, the cost of
+/// a comparison with `--cmp-cost`.
+fn spin(iterations: u64) {
+    for iteration in 0 .. iterations {
+        hint::black_box(iteration);
+    }
+}
+
+/// Counts the comparisons the target makes to sort every case of the set
+/// once, or to merge it with `--phase merge`. The counting slows the sorts
+/// down, so they are not timed.
+fn count_comparisons<T>(
+    config: &Config,
+    set: &CaseSet<T>,
+    target: Target,
+    threads: usize,
+) -> u64
+where
+    T: Element,
+{
+    let counter = CountingComparator::new(T::compare);
+    let cancellation = Cancellation::new();
+    let function = counter.function();
+    let mut sorter = timed(config, target, threads, function, &cancellation);
+    for case in &set.cases {
+        sorter(case);
+    }
+    counter.count()
+}
+
+/// How busy the threads of the target were over a run of the whole set,
+/// tracing every case, weighting each by how long it took. `None` for the
+/// targets that are not the parallel merge sort, or without cases.
+fn utilization<T>(
+    set: &CaseSet<T>,
+    target: Target,
+    threads: usize,
+) -> Option<f64>
+where
+    T: Element,
+{
+    let (mut busy, mut total) = (0.0, 0.0);
+    for case in &set.cases {
+        let trace = target.trace(threads, case)?;
+        let span = (trace.root.end - trace.root.start).as_secs_f64();
+        busy += trace.utilization() * span;
+        total += span;
+    }
+    match total > 0.0 {
+        true => Some(busy / total),
+        false => None,
+    }
+}
+
+/// Counts the hardware events of the target sorting every case of the set
+/// once, apart from the timed runs.
+fn count_events<T>(
+    set: &CaseSet<T>,
+    sorter: &mut Sorter<T>,
+    counters: &Counters,
+) -> io::Result<CounterStats>
+where
+    T: Element,
+{
+    let mut total = CounterStats::default();
+    for case in &set.cases {
+        let (_, events) = counters.measure(|| sorter(case))?;
+        total = total.then(events);
+    }
+    Ok(total)
+}
+
+/// What a run of a target over a case set measured.
+#[derive(Debug, Clone)]
+struct SetRun {
+    /// How long it took to sort each case.
+    times: Vec<Duration>,
+    /// What the sorts allocated, `None` if there were no cases.
+    memory: Option<MemoryStats>,
+    /// The CPU time of the sorts, `None` if it cannot be read.
+    cpu: Option<Duration>,
+    /// Whether the deadline passed before every case was sorted.
+    timed_out: bool,
+}
+
+impl SetRun {
+    /// Starts a run over a set of the given number of cases.
+    fn new(cases: usize) -> Self {
+        Self {
+            times: Vec::with_capacity(cases),
+            memory: None,
+            cpu: Some(Duration::ZERO),
+            timed_out: false,
+        }
+    }
+
+    /// Adds what sorting the next case measured.
+    fn push(&mut self, case: CaseRun) {
+        self.times.push(case.time);
+        self.memory = Some(
+            self.memory.map_or(case.memory, |memory| case.memory.then(memory)),
+        );
+        self.cpu = self.cpu.zip(case.cpu).map(|(run, case)| run + case);
+    }
+}
+
+/// What sorting a single case measured.
+#[derive(Debug, Clone, Copy)]
+struct CaseRun {
+    /// How long the sort took.
+    time: Duration,
+    /// What the sort allocated.
+    memory: MemoryStats,
+    /// The CPU time of the sort, `None` if it cannot be read.
+    cpu: Option<Duration>,
+}
+
+/// Sorts a case, timing it. If `verify` is set, checks the output.
+fn sort_case<T>(
+    case: &Arc<[T]>,
+    sorter: &mut Sorter<T>,
+    verify: bool,
+) -> Result<CaseRun, Failure>
+where
+    T: Element,
+{
+    let ((sorted, time, cpu), stats) = alloc::measure(|| {
+        let cpu_then = cputime::now();
+        let then = Instant::now();
+        let sorted = sorter(case);
+        let time = then.elapsed();
+        let cpu = match (cpu_then, cputime::now()) {
+            (Some(then), Some(now)) => Some(now - then),
+            _ => None,
+        };
+        (sorted, time, cpu)
+    });
+    // The counting allocator is the global one.
+    let stats = stats.unwrap_or_default();
+    let memory = MemoryStats {
+        allocations: stats.allocations,
+        peak_bytes: Some(stats.peak_bytes as usize),
+    };
+
+    if verify {
+        verify::check(case, &sorted)?;
+    }
+    Ok(CaseRun { time, memory, cpu })
+}
+
+/// Runs the case set for the given target sort function, timing each case.
+/// If `verify` is set, checks each output, returning the index of the first
+/// case sorted wrong. Stops before the next case once past the deadline, or
+/// as soon as the watchdog cancels the sort running.
+fn run_for_target<T>(
+    set: &CaseSet<T>,
+    sorter: &mut Sorter<T>,
+    verify: bool,
+    deadline: Option<Instant>,
+    progress: &mut Progress,
+) -> Result<SetRun, (usize, Failure)>
+where
+    T: Element,
+{
+    let mut run = SetRun::new(set.cases.len());
+
+    for (index, case) in set.cases.iter().enumerate() {
+        if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+            run.timed_out = true;
+            return Ok(run);
+        }
+        let case = match watchdog::unless_cancelled(|| {
+            sort_case(case, sorter, verify)
+        }) {
+            Some(case) => case.map_err(|failure| (index, failure))?,
+            None => {
+                run.timed_out = true;
+                return Ok(run);
+            },
+        };
+        run.push(case);
+        progress.advance();
+    }
+
+    Ok(run)
+}
@@ -0,0 +1,155 @@
+//! How a run was produced, recorded in the reports so any number published
+//! from them can be traced back to the command, the build and the
+//! environment behind it.
+
+use crate::cli::Config;
+use std::{
+    env,
+    fs::File,
+    io::{self, Read},
+    path::Path,
+};
+
+/// The prefixes of the environment variables that can change a run: those
+/// of Rust and Cargo, of the allocator, of the dynamic linker and of the
+/// thread pools.
+const ENV_PREFIXES: &[&str] =
+    &["RUST", "CARGO", "MALLOC", "LD_", "RAYON", "WGPU", "OMP_"];
+
+/// The parts of the names of environment variables that are never recorded,
+/// whatever their prefix, since they hold credentials.
+const SECRET_PARTS: &[&str] = &["TOKEN", "SECRET", "PASSWORD", "KEY", "AUTH"];
+
+/// The commit the benchmark was built from, empty outside of a checkout.
+const COMMIT: &str = env!("MERGESORT_CMP_COMMIT");
+
+/// The features the benchmark can be built with.
+const FEATURES: &[(&str, bool)] = &[
+    ("gpu", cfg!(feature = "gpu")),
+    ("thread-priority", cfg!(feature = "thread-priority")),
+    ("rayon", cfg!(feature = "rayon")),
+    ("perf", cfg!(feature = "perf")),
+    ("cpu-time", cfg!(feature = "cpu-time")),
+    ("affinity", cfg!(feature = "affinity")),
+    ("timsort", cfg!(feature = "timsort")),
+    ("glidesort", cfg!(feature = "glidesort")),
+    ("serde", cfg!(feature = "serde")),
+    ("trace", cfg!(feature = "trace")),
+    ("ffi", cfg!(feature = "ffi")),
+    ("wasm", cfg!(feature = "wasm")),
+    ("lz4", cfg!(feature = "lz4")),
+    ("zstd", cfg!(feature = "zstd")),
+    ("collation", cfg!(feature = "collation")),
+    ("icu", cfg!(feature = "icu")),
+    ("proptest-harness", cfg!(feature = "proptest-harness")),
+    ("python", cfg!(feature = "python")),
+    ("log", cfg!(feature = "log")),
+];
+
+/// How a run was produced.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Manifest {
+    /// The arguments of the command line, the binary left out.
+    pub args: Vec<String>,
+    /// The hash of the file the cases were read from, if they were, as
+    /// `fnv1a64:` and 16 hexadecimal digits.
+    pub cases_hash: Option<String>,
+    /// The version of the crate.
+    pub version: &'static str,
+    /// The commit the benchmark was built from, if it was built from a
+    /// checkout.
+    pub commit: Option<&'static str>,
+    /// The version of the compiler.
+    pub rustc: &'static str,
+    /// The Cargo profile the benchmark was built with.
+    pub profile: &'static str,
+    /// The optimization level of the build.
+    pub opt_level: &'static str,
+    /// The target triple of the build.
+    pub target: &'static str,
+    /// The extra flags the compiler was given, separated by spaces.
+    pub rustflags: &'static str,
+    /// Whether the build has debug assertions.
+    pub debug_assertions: bool,
+    /// The features the benchmark was built with.
+    pub features: Vec<&'static str>,
+    /// The environment variables of the run that can change it, sorted by
+    /// name.
+    pub env: Vec<(String, String)>,
+}
+
+impl Manifest {
+    /// Records how the run with the given configuration is being produced.
+    /// The case file is read once more, to hash it.
+    pub fn capture(config: &Config) -> io::Result<Self> {
+        let cases_hash = match &config.cases {
+            Some(path) => Some(format!("fnv1a64:{:016x}", hash_file(path)?)),
+            None => None,
+        };
+        let mut variables = env::vars()
+            .filter(|(name, _)| recorded(name))
+            .collect::<Vec<_>>();
+        variables.sort();
+
+        Ok(Self {
+            args: env::args().skip(1).collect(),
+            cases_hash,
+            version: env!("CARGO_PKG_VERSION"),
+            commit: Some(COMMIT).filter(|commit| !commit.is_empty()),
+            rustc: env!("MERGESORT_CMP_RUSTC"),
+            profile: env!("MERGESORT_CMP_PROFILE"),
+            opt_level: env!("MERGESORT_CMP_OPT_LEVEL"),
+            target: env!("MERGESORT_CMP_TARGET"),
+            rustflags: env!("MERGESORT_CMP_RUSTFLAGS"),
+            debug_assertions: cfg!(debug_assertions),
+            features: FEATURES
+                .iter()
+                .filter(|&&(_, enabled)| enabled)
+                .map(|&(name, _)| name)
+                .collect(),
+            env: variables,
+        })
+    }
+
+    /// The command line, as it could be typed again.
+    pub fn command_line(&self) -> String {
+        let mut line = "main".to_owned();
+        for arg in &self.args {
+            line.push(' ');
+            line.push_str(&quote(arg));
+        }
+        line
+    }
+}
+
+/// Whether the environment variable of the given name is recorded.
+fn recorded(name: &str) -> bool {
+    let upper = name.to_ascii_uppercase();
+    ENV_PREFIXES.iter().any(|prefix| upper.starts_with(prefix))
+        && !SECRET_PARTS.iter().any(|part| upper.contains(part))
+}
+
+/// Hashes the contents of a file with 64-bit FNV-1a.
+fn hash_file(path: &Path) -> io::Result<u64> {
+    let mut file = File::open(path)?;
+    let mut buffer = [0; 1 << 16];
+    let mut hash = 0xcbf29ce484222325;
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            return Ok(hash);
+        }
+        for &byte in &buffer[.. read] {
+            hash = (hash ^ u64::from(byte)).wrapping_mul(0x100000001b3);
+        }
+    }
+}
+
+/// Quotes an argument for a shell if it needs it.
+fn quote(arg: &str) -> String {
+    let plain = |c: char| c.is_ascii_alphanumeric() || "-_.,:=/@%+".contains(c);
+    match !arg.is_empty() && arg.chars().all(plain) {
+        true => arg.to_owned(),
+        false => format!("'{}'", arg.replace('\'', "'\\''")),
+    }
+}
@@ -0,0 +1,56 @@
+//! The order the targets run in. Whatever runs later finds the caches warm
+//! and the pages faulted in by what ran before, so a fixed order favors the
+//! last targets.
+
+use crate::cases::SetSpec;
+use rand::{rngs::StdRng, SeedableRng};
+
+/// An order the targets run in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Order {
+    /// In the order they were given, each running every case before the
+    /// next one starts.
+    Fixed,
+    /// Shuffled for every set, each still running every case before the next
+    /// one starts.
+    Shuffled,
+    /// Taking turns on every case, in an order shuffled for every case.
+    Interleaved,
+}
+
+impl Order {
+    /// Every order.
+    pub const ALL: &'static [Self] =
+        &[Order::Fixed, Order::Shuffled, Order::Interleaved];
+
+    /// The name the order is selected by.
+    pub fn name(self) -> &'static str {
+        match self {
+            Order::Fixed => "fixed",
+            Order::Shuffled => "shuffled",
+            Order::Interleaved => "interleaved",
+        }
+    }
+
+    /// Finds the order of the given name.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|order| order.name() == name)
+    }
+
+    /// How the targets run, for the reports.
+    pub fn description(self) -> &'static str {
+        match self {
+            Order::Fixed => "targets in the order given",
+            Order::Shuffled => "targets shuffled for every set",
+            Order::Interleaved => "targets taking turns on every case",
+        }
+    }
+}
+
+/// The generator shuffling the targets of a set. Like the cases, it only
+/// depends on the run's seed and the set's name, so the order can be run
+/// again.
+pub fn shuffler(spec: &SetSpec, seed: u64) -> StdRng {
+    // Apart from the generators of the sizes and the elements.
+    StdRng::seed_from_u64(spec.seed(seed).rotate_left(32))
+}
@@ -0,0 +1,199 @@
+//! The formats the results can be written in.
+
+mod csv;
+pub mod json;
+mod jsonl;
+mod markdown;
+mod text;
+
+use crate::report::{BenchReport, CaseSetResult, TargetResult};
+use std::{
+    env,
+    io::{self, IsTerminal, Write},
+};
+
+/// A format of the results.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// Human readable lines.
+    Text,
+    /// Comma-separated values, one row per measurement.
+    Csv,
+    /// A single JSON document.
+    Json,
+    /// A JSON object per line, written as each target finishes.
+    Jsonl,
+    /// A Markdown table per case set.
+    Markdown,
+}
+
+impl Format {
+    /// Every format.
+    pub const ALL: &'static [Self] = &[
+        Format::Text,
+        Format::Csv,
+        Format::Json,
+        Format::Jsonl,
+        Format::Markdown,
+    ];
+
+    /// The name the format is selected by.
+    pub fn name(self) -> &'static str {
+        match self {
+            Format::Text => "text",
+            Format::Csv => "csv",
+            Format::Json => "json",
+            Format::Jsonl => "jsonl",
+            Format::Markdown => "markdown",
+        }
+    }
+
+    /// Finds the format of the given name.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|format| format.name() == name)
+    }
+
+    /// Creates a writer of results in this format, with the given detail,
+    /// and colored if asked to. Only the text format has more or less detail,
+    /// and colors.
+    pub fn reporter(
+        self,
+        verbosity: Verbosity,
+        color: bool,
+    ) -> Box<dyn Reporter> {
+        match self {
+            Format::Text => Box::new(text::TextReporter { verbosity, color }),
+            Format::Csv => Box::new(csv::CsvReporter),
+            Format::Json => Box::new(json::JsonReporter),
+            Format::Jsonl => Box::new(jsonl::JsonlReporter::default()),
+            Format::Markdown => Box::new(markdown::MarkdownReporter),
+        }
+    }
+}
+
+/// How much detail the results are written with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Verbosity {
+    /// Only the summary at the end, without progress.
+    Quiet,
+    /// The results of each target, and the summary.
+    Normal,
+    /// Also the time of each repetition.
+    Verbose,
+    /// Also the time of each case, and how the parallel sorts split the
+    /// biggest case.
+    Debug,
+}
+
+impl Verbosity {
+    /// One level more detailed, up to the most detailed.
+    pub fn more(self) -> Self {
+        match self {
+            Verbosity::Quiet => Verbosity::Normal,
+            Verbosity::Normal => Verbosity::Verbose,
+            Verbosity::Verbose | Verbosity::Debug => Verbosity::Debug,
+        }
+    }
+}
+
+/// Whether the results are colored: only when they go to a terminal, and
+/// `NO_COLOR` is not set.
+pub fn color_wanted(to_stdout: bool) -> bool {
+    let disabled = env::var_os("NO_COLOR");
+    let disabled = disabled.is_some_and(|value| !value.is_empty());
+    to_stdout && !disabled && io::stdout().is_terminal()
+}
+
+/// Writes the results while the benchmark runs. Every method gets the results
+/// collected so far; formats write whatever they can at each step.
+pub trait Reporter {
+    /// The run started, no case set ran yet.
+    fn start(
+        &mut self,
+        _output: &mut dyn Write,
+        _report: &BenchReport,
+    ) -> io::Result<()> {
+        Ok(())
+    }
+
+    /// A case set was generated, no target ran it yet.
+    fn start_set(
+        &mut self,
+        _output: &mut dyn Write,
+        _set: &CaseSetResult,
+    ) -> io::Result<()> {
+        Ok(())
+    }
+
+    /// A target finished running a case set.
+    fn target(
+        &mut self,
+        _output: &mut dyn Write,
+        _set: &CaseSetResult,
+        _target: &TargetResult,
+    ) -> io::Result<()> {
+        Ok(())
+    }
+
+    /// Every target finished running a case set.
+    fn end_set(
+        &mut self,
+        _output: &mut dyn Write,
+        _set: &CaseSetResult,
+    ) -> io::Result<()> {
+        Ok(())
+    }
+
+    /// The run finished.
+    fn finish(
+        &mut self,
+        _output: &mut dyn Write,
+        _report: &BenchReport,
+    ) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Formats seconds in the most readable unit.
+pub fn human(seconds: f64) -> String {
+    if seconds >= 1.0 {
+        format!("{:.3}s", seconds)
+    } else if seconds >= 1e-3 {
+        format!("{:.3}ms", seconds * 1e3)
+    } else if seconds >= 1e-6 {
+        format!("{:.3}µs", seconds * 1e6)
+    } else {
+        format!("{:.0}ns", seconds * 1e9)
+    }
+}
+
+/// Formats elements per second in millions, the same unit whatever the
+/// sizes, so they compare across sets.
+pub fn human_throughput(per_second: f64) -> String {
+    format!("{:.2} Melem/s", per_second / 1e6)
+}
+
+/// Formats a p-value with 3 decimals, as `p = 0.123`, or `p < 0.001` below
+/// what they show.
+pub fn human_p_value(p_value: f64) -> String {
+    match p_value < 0.001 {
+        true => "p < 0.001".to_owned(),
+        false => format!("p = {:.3}", p_value),
+    }
+}
+
+/// Formats bytes in the most readable binary unit.
+pub fn human_bytes(bytes: usize) -> String {
+    const UNITS: &[&str] = &["KiB", "MiB", "GiB", "TiB"];
+
+    if bytes < 1024 {
+        return format!("{}B", bytes);
+    }
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit + 1 < UNITS.len() {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1}{}", value, UNITS[unit])
+}
@@ -0,0 +1,201 @@
+//! Comma-separated values, with a header and one row per measurement.
+
+use super::Reporter;
+use crate::report::{BenchReport, CaseSetResult, TargetResult};
+use std::{
+    borrow::Cow,
+    io::{self, Write},
+};
+
+/// Writes the results as comma-separated values.
+#[derive(Debug, Clone, Copy)]
+pub struct CsvReporter;
+
+impl Reporter for CsvReporter {
+    fn start(
+        &mut self,
+        output: &mut dyn Write,
+        _report: &BenchReport,
+    ) -> io::Result<()> {
+        writeln!(output, "case_set,pattern,target,repetition,metric,value")
+    }
+
+    fn target(
+        &mut self,
+        output: &mut dyn Write,
+        set: &CaseSetResult,
+        target: &TargetResult,
+    ) -> io::Result<()> {
+        let set_name = field(set.spec.name);
+        let pattern = set.pattern.to_string();
+        let pattern = field(&pattern);
+        let label = target.label();
+        let target_name = field(&label);
+
+        for (repetition, total) in target.totals().iter().enumerate() {
+            writeln!(
+                output,
+                "{},{},{},{},seconds,{}",
+                set_name,
+                pattern,
+                target_name,
+                repetition + 1,
+                total
+            )?;
+        }
+
+        // Statistics over all repetitions leave the repetition empty.
+        if let Some(stats) = target.total_stats() {
+            for (name, value) in stats.named().iter() {
+                writeln!(
+                    output,
+                    "{},{},{},,total_{}_seconds,{}",
+                    set_name, pattern, target_name, name, value
+                )?;
+            }
+            writeln!(
+                output,
+                "{},{},{},,total_relative_stddev_percent,{}",
+                set_name,
+                pattern,
+                target_name,
+                stats.relative_stddev()
+            )?;
+        }
+
+        if let Some(stats) = target.case_stats() {
+            for (name, value) in stats.named().iter() {
+                writeln!(
+                    output,
+                    "{},{},{},,case_{}_seconds,{}",
+                    set_name, pattern, target_name, name, value
+                )?;
+            }
+        }
+
+        if let Some(throughput) = target.throughput(set.elements()) {
+            writeln!(
+                output,
+                "{},{},{},,elements_per_second,{}",
+                set_name, pattern, target_name, throughput
+            )?;
+        }
+
+        if let Some(p_value) = set.p_value(target) {
+            writeln!(
+                output,
+                "{},{},{},,p_value,{}",
+                set_name, pattern, target_name, p_value
+            )?;
+        }
+        if let Some(significant) = set.significant(target) {
+            writeln!(
+                output,
+                "{},{},{},,significant,{}",
+                set_name, pattern, target_name, significant
+            )?;
+        }
+
+        if target.timed_out {
+            writeln!(
+                output,
+                "{},{},{},,timed_out,true",
+                set_name, pattern, target_name
+            )?;
+        }
+
+        if let Some(interval) = target.relative_interval(set.confidence) {
+            writeln!(
+                output,
+                "{},{},{},,interval_percent,{}",
+                set_name,
+                pattern,
+                target_name,
+                interval * 100.0
+            )?;
+        }
+        if let Some(converged) = target.converged {
+            writeln!(
+                output,
+                "{},{},{},,converged,{}",
+                set_name, pattern, target_name, converged
+            )?;
+        }
+
+        if let Some(utilization) = target.utilization {
+            writeln!(
+                output,
+                "{},{},{},,utilization,{}",
+                set_name, pattern, target_name, utilization
+            )?;
+        }
+
+        if let Some(outliers) = target.outliers {
+            writeln!(
+                output,
+                "{},{},{},,outliers,{}",
+                set_name, pattern, target_name, outliers
+            )?;
+        }
+
+        if let Some(comparisons) = target.comparisons {
+            writeln!(
+                output,
+                "{},{},{},,comparisons,{}",
+                set_name, pattern, target_name, comparisons
+            )?;
+        }
+
+        if let Some(counters) = target.counters {
+            for (name, value) in counters.named().iter() {
+                writeln!(
+                    output,
+                    "{},{},{},,{},{}",
+                    set_name, pattern, target_name, name, value
+                )?;
+            }
+        }
+
+        if let Some(seconds) = target.cpu {
+            writeln!(
+                output,
+                "{},{},{},,cpu_seconds,{}",
+                set_name, pattern, target_name, seconds
+            )?;
+        }
+
+        if let Some(joules) = target.energy {
+            writeln!(
+                output,
+                "{},{},{},,energy_joules,{}",
+                set_name, pattern, target_name, joules
+            )?;
+        }
+
+        if let Some(memory) = target.memory {
+            writeln!(
+                output,
+                "{},{},{},,allocations,{}",
+                set_name, pattern, target_name, memory.allocations
+            )?;
+            if let Some(peak) = memory.peak_bytes {
+                writeln!(
+                    output,
+                    "{},{},{},,peak_bytes,{}",
+                    set_name, pattern, target_name, peak
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Quotes a field if it is needed.
+fn field(value: &str) -> Cow<'_, str> {
+    if value.contains([',', '"', '\n', '\r']) {
+        Cow::Owned(format!("\"{}\"", value.replace('"', "\"\"")))
+    } else {
+        Cow::Borrowed(value)
+    }
+}
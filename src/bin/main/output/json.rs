@@ -0,0 +1,373 @@
+//! A single JSON document, written once the run finishes. The document is
+//! the [schema](mergesort_cmp::report) of the library, which tools reading it
+//! can deserialize it into.
+
+use super::Reporter;
+use crate::{
+    complexity::{self, Fit, ModelFit},
+    elements::StringShape,
+    json::Json,
+    manifest::Manifest,
+    report::{
+        BenchReport,
+        CaseSetResult,
+        CounterStats,
+        Machine,
+        MemoryStats,
+        TargetResult,
+    },
+    stats::Stats,
+};
+use mergesort_cmp::report as schema;
+use std::io::{self, Write};
+
+/// Writes the results as a JSON document.
+#[derive(Debug, Clone, Copy)]
+pub struct JsonReporter;
+
+impl Reporter for JsonReporter {
+    fn finish(
+        &mut self,
+        output: &mut dyn Write,
+        report: &BenchReport,
+    ) -> io::Result<()> {
+        writeln!(output, "{}", report_json(&report_schema(report)))
+    }
+}
+
+/// Converts the whole report to the schema.
+pub fn report_schema(report: &BenchReport) -> schema::BenchReport {
+    let fits = complexity::fit(report);
+    schema::BenchReport {
+        seed: report.seeds[0],
+        seeds: report.seeds.clone(),
+        order: report.order.name().to_owned(),
+        cold_cache: report.cold_cache,
+        phase: report.phase.name().to_owned(),
+        cpus: report.cpus.clone(),
+        cmp_cost: report.cmp_cost,
+        confidence: report.sets.first().map(|set| set.confidence),
+        element_type: report.element.name().to_owned(),
+        strings: report.strings.as_ref().map(strings_schema),
+        payload_bytes: report.payload_bytes,
+        machine: machine_schema(&report.machine),
+        manifest: report.manifest.as_ref().map(manifest_schema),
+        case_sets: report.sets.iter().map(set_schema).collect(),
+        complexity: fits.iter().map(fit_schema).collect(),
+    }
+}
+
+/// Converts how the time of a target grows to the schema.
+pub fn fit_schema(fit: &Fit) -> schema::Fit {
+    schema::Fit {
+        target: fit.label.clone().into_owned(),
+        points: fit.points,
+        n_log_n: model_schema(&fit.n_log_n),
+        linear: model_schema(&fit.linear),
+        exponent: fit.exponent,
+        expected_exponent: fit.expected_exponent,
+        deviates: fit.deviates(),
+    }
+}
+
+/// Converts the fit of a model to the schema.
+pub fn model_schema(model: &ModelFit) -> schema::ModelFit {
+    schema::ModelFit {
+        constant_seconds: model.constant,
+        r_squared: model.r_squared,
+    }
+}
+
+/// Converts the shape of the strings to the schema.
+pub fn strings_schema(shape: &StringShape) -> schema::StringShape {
+    schema::StringShape {
+        min_length: shape.min_length,
+        max_length: shape.max_length,
+        alphabet: shape.alphabet.iter().collect(),
+    }
+}
+
+/// Converts the machine information to the schema.
+pub fn machine_schema(machine: &Machine) -> schema::Machine {
+    schema::Machine {
+        os: machine.os.to_owned(),
+        arch: machine.arch.to_owned(),
+        cpu_model: machine.cpu_model.clone(),
+        base_mhz: machine.base_mhz,
+        logical_cpus: machine.logical_cpus,
+        physical_cpus: machine.physical_cpus,
+        memory_bytes: machine.memory_bytes,
+        rustc: machine.rustc.to_owned(),
+    }
+}
+
+/// Converts how the run was produced to the schema.
+pub fn manifest_schema(manifest: &Manifest) -> schema::Manifest {
+    schema::Manifest {
+        command_line: manifest.command_line(),
+        args: manifest.args.clone(),
+        cases_hash: manifest.cases_hash.clone(),
+        version: manifest.version.to_owned(),
+        commit: manifest.commit.map(str::to_owned),
+        rustc: manifest.rustc.to_owned(),
+        profile: manifest.profile.to_owned(),
+        opt_level: manifest.opt_level.to_owned(),
+        target: manifest.target.to_owned(),
+        rustflags: manifest.rustflags.to_owned(),
+        debug_assertions: manifest.debug_assertions,
+        features: manifest.features.iter().map(|&name| name.into()).collect(),
+        env: manifest.env.iter().cloned().collect(),
+    }
+}
+
+/// Converts the results of a case set to the schema.
+pub fn set_schema(set: &CaseSetResult) -> schema::CaseSetResult {
+    let targets = set.targets.iter().map(|target| target_schema(set, target));
+    schema::CaseSetResult {
+        name: set.spec.name.to_owned(),
+        pattern: set.pattern.to_string(),
+        cases: set.cases,
+        min_size: set.spec.min_size,
+        max_size: set.spec.max_size,
+        targets: targets.collect(),
+    }
+}
+
+/// Converts the results of a target on the given set to the schema.
+pub fn target_schema(
+    set: &CaseSetResult,
+    target: &TargetResult,
+) -> schema::TargetResult {
+    let interval = target.relative_interval(set.confidence);
+    let seed_means = match target.runs.is_empty() {
+        true => Vec::new(),
+        false => target.seed_means(set.cases_per_seed()),
+    };
+    schema::TargetResult {
+        name: target.target.name().to_owned(),
+        threads: target.threads,
+        seconds: target.totals(),
+        total_seconds: target.total_stats().as_ref().map(stats_schema),
+        case_seconds: target.case_stats().as_ref().map(stats_schema),
+        seed_seconds: seed_means,
+        elements_per_second: target.throughput(set.elements()),
+        p_value: set.p_value(target),
+        significant: set.significant(target),
+        memory: target.memory.map(memory_schema),
+        comparisons: target.comparisons,
+        counters: target.counters.map(counters_schema),
+        cpu_seconds: target.cpu,
+        energy_joules: target.energy,
+        timed_out: target.timed_out,
+        outliers: target.outliers,
+        converged: target.converged,
+        utilization: target.utilization,
+        interval_percent: interval.map(|interval| interval * 100.0),
+    }
+}
+
+/// Converts summary statistics to the schema.
+pub fn stats_schema(stats: &Stats) -> schema::Stats {
+    schema::Stats {
+        mean: stats.mean,
+        median: stats.median,
+        stddev: stats.stddev,
+        min: stats.min,
+        max: stats.max,
+        p95: stats.p95,
+        trimmed_mean: stats.trimmed_mean,
+        relative_stddev_percent: stats.relative_stddev(),
+    }
+}
+
+/// Converts the hardware events of a target to the schema.
+pub fn counters_schema(counters: CounterStats) -> schema::CounterStats {
+    schema::CounterStats {
+        instructions: counters.instructions,
+        cache_misses: counters.cache_misses,
+        branch_misses: counters.branch_misses,
+    }
+}
+
+/// Converts what a target allocated to the schema.
+pub fn memory_schema(memory: MemoryStats) -> schema::MemoryStats {
+    schema::MemoryStats {
+        allocations: memory.allocations,
+        peak_bytes: memory.peak_bytes,
+    }
+}
+
+/// An optional member.
+fn optional<T, F>(value: Option<T>, convert: F) -> Json
+where
+    F: FnOnce(T) -> Json,
+{
+    value.map_or(Json::Null, convert)
+}
+
+/// Writes the whole report.
+pub fn report_json(report: &schema::BenchReport) -> Json {
+    let sets = report.case_sets.iter().map(set_json);
+    let fits = report.complexity.iter().map(fit_json);
+    Json::object(vec![
+        ("seed", Json::from(report.seed)),
+        ("seeds", Json::from(report.seeds.clone())),
+        ("order", Json::from(&*report.order)),
+        ("cold_cache", Json::from(report.cold_cache)),
+        ("phase", Json::from(&*report.phase)),
+        ("cpus", optional(report.cpus.clone(), Json::from)),
+        ("cmp_cost", Json::from(report.cmp_cost)),
+        ("confidence", optional(report.confidence, Json::from)),
+        ("element_type", Json::from(&*report.element_type)),
+        ("strings", optional(report.strings.as_ref(), strings_json)),
+        ("payload_bytes", optional(report.payload_bytes, Json::from)),
+        ("machine", machine_json(&report.machine)),
+        ("manifest", optional(report.manifest.as_ref(), manifest_json)),
+        ("case_sets", Json::Array(sets.collect())),
+        ("complexity", Json::Array(fits.collect())),
+    ])
+}
+
+/// Writes how the time of a target grows.
+pub fn fit_json(fit: &schema::Fit) -> Json {
+    Json::object(vec![
+        ("target", Json::from(&*fit.target)),
+        ("points", Json::from(fit.points)),
+        ("n_log_n", model_json(&fit.n_log_n)),
+        ("linear", model_json(&fit.linear)),
+        ("exponent", Json::from(fit.exponent)),
+        ("expected_exponent", Json::from(fit.expected_exponent)),
+        ("deviates", Json::from(fit.deviates)),
+    ])
+}
+
+/// Writes the fit of a model.
+pub fn model_json(model: &schema::ModelFit) -> Json {
+    Json::object(vec![
+        ("constant_seconds", Json::from(model.constant_seconds)),
+        ("r_squared", Json::from(model.r_squared)),
+    ])
+}
+
+/// Writes the shape of the strings.
+pub fn strings_json(shape: &schema::StringShape) -> Json {
+    Json::object(vec![
+        ("min_length", Json::from(shape.min_length)),
+        ("max_length", Json::from(shape.max_length)),
+        ("alphabet", Json::from(&*shape.alphabet)),
+    ])
+}
+
+/// Writes the machine information.
+pub fn machine_json(machine: &schema::Machine) -> Json {
+    Json::object(vec![
+        ("os", Json::from(&*machine.os)),
+        ("arch", Json::from(&*machine.arch)),
+        ("cpu_model", optional(machine.cpu_model.as_deref(), Json::from)),
+        ("base_mhz", optional(machine.base_mhz, Json::from)),
+        ("logical_cpus", Json::from(machine.logical_cpus)),
+        ("physical_cpus", Json::from(machine.physical_cpus)),
+        ("memory_bytes", optional(machine.memory_bytes, Json::from)),
+        ("rustc", Json::from(&*machine.rustc)),
+    ])
+}
+
+/// Writes how the run was produced.
+pub fn manifest_json(manifest: &schema::Manifest) -> Json {
+    let strings = |values: &[String]| {
+        Json::Array(values.iter().map(|value| Json::from(&**value)).collect())
+    };
+    let env = manifest.env.iter().map(|(name, value)| {
+        (name.clone(), Json::from(&**value))
+    });
+    Json::object(vec![
+        ("command_line", Json::from(&*manifest.command_line)),
+        ("args", strings(&manifest.args)),
+        ("cases_hash", optional(manifest.cases_hash.as_deref(), Json::from)),
+        ("version", Json::from(&*manifest.version)),
+        ("commit", optional(manifest.commit.as_deref(), Json::from)),
+        ("rustc", Json::from(&*manifest.rustc)),
+        ("profile", Json::from(&*manifest.profile)),
+        ("opt_level", Json::from(&*manifest.opt_level)),
+        ("target", Json::from(&*manifest.target)),
+        ("rustflags", Json::from(&*manifest.rustflags)),
+        ("debug_assertions", Json::from(manifest.debug_assertions)),
+        ("features", strings(&manifest.features)),
+        ("env", Json::object(env)),
+    ])
+}
+
+/// Writes the results of a case set.
+pub fn set_json(set: &schema::CaseSetResult) -> Json {
+    Json::object(vec![
+        ("name", Json::from(&*set.name)),
+        ("pattern", Json::from(&*set.pattern)),
+        ("cases", Json::from(set.cases)),
+        ("min_size", Json::from(set.min_size)),
+        ("max_size", Json::from(set.max_size)),
+        ("targets", Json::Array(set.targets.iter().map(target_json).collect())),
+    ])
+}
+
+/// Writes the results of a target on a set.
+pub fn target_json(target: &schema::TargetResult) -> Json {
+    let stats = |stats: Option<&schema::Stats>| optional(stats, stats_json);
+    Json::object(vec![
+        ("name", Json::from(&*target.name)),
+        ("threads", optional(target.threads, Json::from)),
+        ("seconds", Json::from(target.seconds.clone())),
+        ("total_seconds", stats(target.total_seconds.as_ref())),
+        ("case_seconds", stats(target.case_seconds.as_ref())),
+        ("seed_seconds", Json::from(target.seed_seconds.clone())),
+        (
+            "elements_per_second",
+            optional(target.elements_per_second, Json::from),
+        ),
+        ("p_value", optional(target.p_value, Json::from)),
+        ("significant", optional(target.significant, Json::from)),
+        ("memory", optional(target.memory, memory_json)),
+        ("comparisons", optional(target.comparisons, Json::from)),
+        ("counters", optional(target.counters, counters_json)),
+        ("cpu_seconds", optional(target.cpu_seconds, Json::from)),
+        ("energy_joules", optional(target.energy_joules, Json::from)),
+        ("timed_out", Json::from(target.timed_out)),
+        ("outliers", optional(target.outliers, Json::from)),
+        ("converged", optional(target.converged, Json::from)),
+        ("utilization", optional(target.utilization, Json::from)),
+        ("interval_percent", optional(target.interval_percent, Json::from)),
+    ])
+}
+
+/// Writes summary statistics.
+pub fn stats_json(stats: &schema::Stats) -> Json {
+    Json::object(vec![
+        ("mean", Json::from(stats.mean)),
+        ("median", Json::from(stats.median)),
+        ("stddev", Json::from(stats.stddev)),
+        ("min", Json::from(stats.min)),
+        ("max", Json::from(stats.max)),
+        ("p95", Json::from(stats.p95)),
+        ("trimmed_mean", Json::from(stats.trimmed_mean)),
+        (
+            "relative_stddev_percent",
+            Json::from(stats.relative_stddev_percent),
+        ),
+    ])
+}
+
+/// Writes the hardware events of a target.
+pub fn counters_json(counters: schema::CounterStats) -> Json {
+    Json::object(vec![
+        ("instructions", Json::from(counters.instructions)),
+        ("cache_misses", Json::from(counters.cache_misses)),
+        ("branch_misses", Json::from(counters.branch_misses)),
+    ])
+}
+
+/// Writes what a target allocated.
+pub fn memory_json(memory: schema::MemoryStats) -> Json {
+    Json::object(vec![
+        ("allocations", Json::from(memory.allocations)),
+        ("peak_bytes", optional(memory.peak_bytes, Json::from)),
+    ])
+}
@@ -0,0 +1,69 @@
+//! JSON lines, one per target as soon as it finishes a case set, so a run can
+//! be followed or analyzed even if it does not finish.
+
+use super::{json, Reporter};
+use crate::{
+    json::Json,
+    report::{BenchReport, CaseSetResult, TargetResult},
+};
+use std::io::{self, Write};
+
+/// Writes the results as a JSON object per line.
+#[derive(Debug, Clone, Default)]
+pub struct JsonlReporter {
+    /// The members every line starts with, about the whole run.
+    run: Vec<(String, Json)>,
+}
+
+impl Reporter for JsonlReporter {
+    fn start(
+        &mut self,
+        _output: &mut dyn Write,
+        report: &BenchReport,
+    ) -> io::Result<()> {
+        self.run = vec![
+            ("seed".to_owned(), Json::from(report.seeds[0])),
+            ("seeds".to_owned(), Json::from(report.seeds.clone())),
+            ("order".to_owned(), Json::from(report.order.name())),
+            ("cold_cache".to_owned(), Json::from(report.cold_cache)),
+            ("phase".to_owned(), Json::from(report.phase.name())),
+            (
+                "cpus".to_owned(),
+                report.cpus.clone().map_or(Json::Null, Json::from),
+            ),
+            ("cmp_cost".to_owned(), Json::from(report.cmp_cost)),
+            ("element_type".to_owned(), Json::from(report.element.name())),
+        ];
+        Ok(())
+    }
+
+    fn target(
+        &mut self,
+        output: &mut dyn Write,
+        set: &CaseSetResult,
+        target: &TargetResult,
+    ) -> io::Result<()> {
+        let mut members = self.run.clone();
+        let set_members = vec![
+            ("case_set", Json::from(set.spec.name)),
+            ("pattern", Json::from(set.pattern.to_string())),
+            ("cases", Json::from(set.cases)),
+            ("min_size", Json::from(set.spec.min_size)),
+            ("max_size", Json::from(set.spec.max_size)),
+        ];
+        members.extend(set_members.into_iter().map(|(k, v)| (k.to_owned(), v)));
+        let target = json::target_json(&json::target_schema(set, target));
+        if let Json::Object(target) = target {
+            // A line has many names, it is the one of the target.
+            let target = target.into_iter().map(|(key, value)| match &*key {
+                "name" => ("target".to_owned(), value),
+                _ => (key, value),
+            });
+            members.extend(target);
+        }
+
+        writeln!(output, "{}", Json::Object(members).compact())?;
+        // A line is only useful if it gets out before the run is killed.
+        output.flush()
+    }
+}
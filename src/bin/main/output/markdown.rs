@@ -0,0 +1,260 @@
+//! A Markdown table per case set, with the targets as columns.
+
+use super::{
+    human,
+    human_bytes,
+    human_p_value,
+    human_throughput,
+    Reporter,
+};
+use crate::report::{BenchReport, CaseSetResult, CounterStats};
+use std::io::{self, Write};
+
+/// The statistics of single cases shown in the tables.
+const CASE_ROWS: &[&str] = &["median", "p95"];
+
+/// Writes the results as Markdown tables.
+#[derive(Debug, Clone, Copy)]
+pub struct MarkdownReporter;
+
+impl Reporter for MarkdownReporter {
+    fn start(
+        &mut self,
+        output: &mut dyn Write,
+        report: &BenchReport,
+    ) -> io::Result<()> {
+        writeln!(
+            output,
+            "Using {}, elements of type `{}`.",
+            report.run_description(),
+            report.element_description(),
+        )?;
+        writeln!(output)?;
+        writeln!(output, "Machine: {}.", report.machine.description())
+    }
+
+    fn end_set(
+        &mut self,
+        output: &mut dyn Write,
+        set: &CaseSetResult,
+    ) -> io::Result<()> {
+        writeln!(output)?;
+        writeln!(
+            output,
+            "### {} ({} cases, {}, sizes {} to {})",
+            set.spec.name,
+            set.cases,
+            set.pattern,
+            set.spec.min_size,
+            set.spec.max_size
+        )?;
+        writeln!(output)?;
+
+        let baseline = match set.baseline() {
+            Some(baseline) => baseline,
+            None => return writeln!(output, "No targets ran."),
+        };
+
+        write!(output, "| |")?;
+        for target in &set.targets {
+            write!(output, " {} |", target.label())?;
+        }
+        writeln!(output)?;
+
+        write!(output, "|---|")?;
+        for _ in &set.targets {
+            write!(output, "---:|")?;
+        }
+        writeln!(output)?;
+
+        write!(output, "| time |")?;
+        for target in &set.targets {
+            match target.total_stats() {
+                Some(stats) if target.runs.len() > 1 => write!(
+                    output,
+                    " {} ± {:.1}% |",
+                    human(stats.mean),
+                    stats.relative_stddev()
+                )?,
+                Some(stats) => write!(output, " {} |", human(stats.mean))?,
+                None if target.timed_out => write!(output, " timed out |")?,
+                None => write!(output, " - |")?,
+            }
+        }
+        writeln!(output)?;
+
+        write!(output, "| throughput |")?;
+        for target in &set.targets {
+            match target.throughput(set.elements()) {
+                Some(rate) => write!(output, " {} |", human_throughput(rate))?,
+                None => write!(output, " - |")?,
+            }
+        }
+        writeln!(output)?;
+
+        if set.targets.iter().any(|target| target.cpu.is_some()) {
+            write!(output, "| cpu time per run |")?;
+            for target in &set.targets {
+                match target.cpu {
+                    Some(seconds) => write!(output, " {} |", human(seconds))?,
+                    None => write!(output, " - |")?,
+                }
+            }
+            writeln!(output)?;
+        }
+
+        for row in CASE_ROWS {
+            write!(output, "| {} per case |", row)?;
+            for target in &set.targets {
+                match target.case_stats().and_then(|stats| stats.get(row)) {
+                    Some(value) => write!(output, " {} |", human(value))?,
+                    None => write!(output, " - |")?,
+                }
+            }
+            writeln!(output)?;
+        }
+
+        if set.targets.iter().any(|target| target.comparisons.is_some()) {
+            write!(output, "| comparisons per run |")?;
+            for target in &set.targets {
+                match target.comparisons {
+                    Some(comparisons) => write!(output, " {} |", comparisons)?,
+                    None => write!(output, " - |")?,
+                }
+            }
+            writeln!(output)?;
+        }
+
+        if set.targets.iter().any(|target| target.counters.is_some()) {
+            let names = CounterStats::default().named();
+            for (event, (name, _)) in names.iter().enumerate() {
+                write!(output, "| {} per run |", name.replace('_', " "))?;
+                for target in &set.targets {
+                    match target.counters {
+                        Some(counters) => {
+                            write!(output, " {} |", counters.named()[event].1)?
+                        },
+                        None => write!(output, " - |")?,
+                    }
+                }
+                writeln!(output)?;
+            }
+        }
+
+        if set.targets.iter().any(|target| target.energy.is_some()) {
+            write!(output, "| energy per run |")?;
+            for target in &set.targets {
+                match target.energy {
+                    Some(joules) => write!(output, " {:.3}J |", joules)?,
+                    None => write!(output, " - |")?,
+                }
+            }
+            writeln!(output)?;
+        }
+
+        if set.targets.iter().any(|target| target.utilization.is_some()) {
+            write!(output, "| threads busy |")?;
+            for target in &set.targets {
+                match target.utilization {
+                    Some(busy) => write!(output, " {:.1}% |", busy * 100.0)?,
+                    None => write!(output, " - |")?,
+                }
+            }
+            writeln!(output)?;
+        }
+
+        if set.targets.iter().any(|target| target.memory.is_some()) {
+            write!(output, "| allocations per run |")?;
+            for target in &set.targets {
+                match target.memory {
+                    Some(memory) => {
+                        write!(output, " {} |", memory.allocations)?
+                    },
+                    None => write!(output, " - |")?,
+                }
+            }
+            writeln!(output)?;
+        }
+
+        let peaks = set
+            .targets
+            .iter()
+            .map(|target| target.memory.and_then(|memory| memory.peak_bytes))
+            .collect::<Vec<_>>();
+        if peaks.iter().any(Option::is_some) {
+            write!(output, "| peak memory |")?;
+            for peak in peaks {
+                match peak {
+                    Some(peak) => write!(output, " {} |", human_bytes(peak))?,
+                    None => write!(output, " - |")?,
+                }
+            }
+            writeln!(output)?;
+        }
+
+        write!(output, "| speedup over {} |", baseline.label())?;
+        for target in &set.targets {
+            match target.speedup_over(baseline) {
+                Some(speedup) => write!(output, " {:.2}x |", speedup)?,
+                None => write!(output, " - |")?,
+            }
+        }
+        writeln!(output)?;
+
+        if set.targets.iter().any(|target| set.p_value(target).is_some()) {
+            write!(
+                output,
+                "| p-value, significant at {}% |",
+                set.confidence * 100.0
+            )?;
+            for target in &set.targets {
+                match (set.p_value(target), set.significant(target)) {
+                    (Some(p_value), Some(true)) => {
+                        write!(output, " **{}** |", human_p_value(p_value))?
+                    },
+                    (Some(p_value), _) => {
+                        write!(output, " {} |", human_p_value(p_value))?
+                    },
+                    (None, _) => write!(output, " - |")?,
+                }
+            }
+            writeln!(output)?;
+        }
+
+        let counts = set.thread_counts();
+        if counts.is_empty() {
+            return Ok(());
+        }
+
+        // The sweep gets a table of its own, a row per thread count.
+        let swept = set.swept_targets();
+        writeln!(output)?;
+        writeln!(output, "Speedup over {} by thread count:", baseline.label())?;
+        writeln!(output)?;
+        write!(output, "| threads |")?;
+        for target in &swept {
+            write!(output, " {} |", target.name())?;
+        }
+        writeln!(output)?;
+
+        write!(output, "|---:|")?;
+        for _ in &swept {
+            write!(output, "---:|")?;
+        }
+        writeln!(output)?;
+
+        for threads in counts {
+            write!(output, "| {} |", threads)?;
+            for &target in &swept {
+                let result = set.find(target, threads);
+                match result.and_then(|result| result.speedup_over(baseline)) {
+                    Some(speedup) => write!(output, " {:.2}x |", speedup)?,
+                    None => write!(output, " - |")?,
+                }
+            }
+            writeln!(output)?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,510 @@
+//! Human readable tables, written as soon as each case set finishes.
+
+use super::{human, human_bytes, human_p_value, Reporter, Verbosity};
+use crate::{
+    complexity,
+    report::{BenchReport, CaseSetResult, TargetResult},
+};
+use std::{
+    io::{self, Write},
+    ptr,
+};
+
+/// The escape sequence coloring the fastest target.
+const GREEN: &str = "\x1b[32m";
+/// The escape sequence coloring the targets slower than the baseline.
+const RED: &str = "\x1b[31m";
+/// The escape sequence going back to the default color.
+const RESET: &str = "\x1b[0m";
+
+/// Writes the results as human readable tables.
+#[derive(Debug, Clone, Copy)]
+pub struct TextReporter {
+    /// How much detail the results are written with.
+    pub verbosity: Verbosity,
+    /// Whether the tables are colored.
+    pub color: bool,
+}
+
+impl TextReporter {
+    /// Writes a row per target of the set, with the fastest one in green and
+    /// those slower than the baseline in red.
+    fn write_table(
+        &self,
+        output: &mut dyn Write,
+        set: &CaseSetResult,
+    ) -> io::Result<()> {
+        let baseline = set.baseline();
+        let fastest = set
+            .targets
+            .iter()
+            .filter(|target| !target.runs.is_empty())
+            .map(TargetResult::mean_seconds)
+            .min_by(f64::total_cmp);
+
+        writeln!(
+            output,
+            "  {:<24} {:>11} {:>7} {:>11} {:>12} {:>8} {:>10}",
+            "target",
+            "mean",
+            "±",
+            "cpu time",
+            "median case",
+            "speedup",
+            "allocs"
+        )?;
+        for target in &set.targets {
+            let stats = target.total_stats();
+            let mean = match stats {
+                Some(stats) => human(stats.mean),
+                None if target.timed_out => "timed out".to_owned(),
+                None => "-".to_owned(),
+            };
+            let spread = match stats {
+                Some(stats) if target.runs.len() > 1 => {
+                    format!("{:.1}%", stats.relative_stddev())
+                },
+                _ => "-".to_owned(),
+            };
+            let cpu = target.cpu.map_or("-".to_owned(), human);
+            let median = target.case_stats();
+            let median = median.map_or("-".to_owned(), |s| human(s.median));
+            let speedup =
+                baseline.and_then(|baseline| target.speedup_over(baseline));
+            let allocations = target.memory.map(|memory| memory.allocations);
+            let row = format!(
+                "{:<24} {:>11} {:>7} {:>11} {:>12} {:>8} {:>10}",
+                target.label(),
+                mean,
+                spread,
+                cpu,
+                median,
+                speedup.map_or("-".to_owned(), |x| format!("{:.2}x", x)),
+                allocations.map_or("-".to_owned(), |x| x.to_string())
+            );
+
+            let color = match stats {
+                _ if !self.color || set.targets.len() < 2 => None,
+                Some(stats) if Some(stats.mean) == fastest => Some(GREEN),
+                _ if speedup.is_some_and(|speedup| speedup < 1.0) => Some(RED),
+                _ => None,
+            };
+            match color {
+                Some(color) => {
+                    writeln!(output, "  {}{}{}", color, row, RESET)?
+                },
+                None => writeln!(output, "  {}", row)?,
+            }
+        }
+        Ok(())
+    }
+
+    /// The lines with what else was measured of a target, as detailed as
+    /// asked for.
+    fn details(
+        &self,
+        set: &CaseSetResult,
+        target: &TargetResult,
+    ) -> Vec<String> {
+        let mut lines = Vec::new();
+
+        if target.timed_out && !target.runs.is_empty() {
+            let runs = target.runs.len();
+            let plural = if runs == 1 { "" } else { "s" };
+            lines.push(format!("timed out after {} run{}", runs, plural));
+        }
+
+        if set.seeds.len() > 1 && !target.runs.is_empty() {
+            lines.push(seed_line(set, target));
+        }
+
+        if let Some(converged) = target.converged {
+            let interval = target.relative_interval(set.confidence);
+            let interval = interval.map_or("-".to_owned(), |interval| {
+                format!("±{:.2}%", interval * 100.0)
+            });
+            let runs = target.runs.len();
+            lines.push(match converged {
+                true => format!("converged to {} in {} runs", interval, runs),
+                false => format!(
+                    "stopped at {} after {} runs, the budget spent",
+                    interval, runs
+                ),
+            });
+        }
+
+        if let Some(utilization) = target.utilization {
+            lines.push(format!(
+                "utilization: {:.1}% of the threads busy",
+                utilization * 100.0
+            ));
+        }
+
+        if let Some(outliers) = target.outliers.filter(|&count| count > 0) {
+            let times = target.runs.len() * set.cases;
+            lines.push(format!(
+                "outliers: {} of {} times of cases replaced by their median",
+                outliers, times
+            ));
+        }
+
+        if let Some(comparisons) = target.comparisons {
+            lines.push(format!("comparisons: {} per run", comparisons));
+        }
+
+        if let Some(counters) = target.counters {
+            lines.push(format!(
+                "counters: {} instructions, {} cache misses, {} branch \
+                 misses per run",
+                counters.instructions,
+                counters.cache_misses,
+                counters.branch_misses
+            ));
+        }
+
+        if let Some(joules) = target.energy {
+            lines.push(format!("energy: {:.3}J per run", joules));
+        }
+
+        let peak = target.memory.and_then(|memory| memory.peak_bytes);
+        if let Some(peak) = peak {
+            lines.push(format!("peak memory: {}", human_bytes(peak)));
+        }
+
+        if self.verbosity < Verbosity::Verbose {
+            return lines;
+        }
+
+        if let Some(stats) = target.case_stats() {
+            let stats = stats.named();
+            let stats = stats
+                .iter()
+                .map(|(name, value)| format!("{} {}", name, human(*value)));
+            let stats = stats.collect::<Vec<_>>();
+            lines.push(format!("per case: {}", stats.join(", ")));
+        }
+
+        if !target.runs.is_empty() {
+            let totals = target.totals().into_iter().map(human);
+            let totals = totals.collect::<Vec<_>>();
+            lines.push(format!("runs: {}", totals.join(", ")));
+        }
+
+        if let Some(ratio) = target.cpu_ratio() {
+            lines.push(format!("cpu time: {:.2}x the wall time", ratio));
+        }
+
+        if self.verbosity >= Verbosity::Debug {
+            lines.push("cases, mean over the runs:".to_owned());
+            let cases = set.sizes.iter().zip(target.case_means());
+            for (index, (size, seconds)) in cases.enumerate() {
+                lines.push(format!(
+                    "  #{} of {} elements: {}",
+                    index,
+                    size,
+                    human(seconds)
+                ));
+            }
+            if let Some(trace) = &target.trace {
+                lines.push("split of the biggest case:".to_owned());
+                for line in trace.to_string().lines() {
+                    lines.push(format!("  {}", line));
+                }
+            }
+        }
+
+        lines
+    }
+}
+
+impl Reporter for TextReporter {
+    fn start(
+        &mut self,
+        output: &mut dyn Write,
+        report: &BenchReport,
+    ) -> io::Result<()> {
+        if self.verbosity == Verbosity::Quiet {
+            return Ok(());
+        }
+        writeln!(output, "Machine: {}", report.machine.description())?;
+        writeln!(
+            output,
+            "Using {}, elements of type {}",
+            report.run_description(),
+            report.element_description()
+        )
+    }
+
+    fn start_set(
+        &mut self,
+        output: &mut dyn Write,
+        set: &CaseSetResult,
+    ) -> io::Result<()> {
+        if self.verbosity == Verbosity::Quiet {
+            return Ok(());
+        }
+        writeln!(output)?;
+        writeln!(
+            output,
+            "Case set {}, pattern = {}, min size = {}, max size = {}, \
+             cases = {}",
+            set.spec.name,
+            set.pattern,
+            set.spec.min_size,
+            set.spec.max_size,
+            set.cases
+        )
+    }
+
+    fn end_set(
+        &mut self,
+        output: &mut dyn Write,
+        set: &CaseSetResult,
+    ) -> io::Result<()> {
+        if self.verbosity == Verbosity::Quiet {
+            return Ok(());
+        }
+        self.write_table(output, set)?;
+        for target in &set.targets {
+            let details = self.details(set, target);
+            if !details.is_empty() {
+                writeln!(output, "  {}:", target.label())?;
+                for line in details {
+                    writeln!(output, "    {}", line)?;
+                }
+            }
+        }
+
+        let counts = set.thread_counts();
+        let baseline = match set.baseline() {
+            Some(baseline) if !counts.is_empty() => baseline,
+            _ => return Ok(()),
+        };
+
+        // A row per thread count, a column per swept target, each as wide as
+        // its name.
+        let swept = set.swept_targets();
+        writeln!(output, "  Speedup over {}:", baseline.label())?;
+        write!(output, "    {:>7}", "threads")?;
+        for target in &swept {
+            write!(output, "  {}", target.name())?;
+        }
+        writeln!(output)?;
+
+        for threads in counts {
+            write!(output, "    {:>7}", threads)?;
+            for &target in &swept {
+                let width = target.name().len();
+                let result = set.find(target, threads);
+                let speedup = result.and_then(|r| r.speedup_over(baseline));
+                let speedup = speedup.map_or("-".to_owned(), |speedup| {
+                    format!("{:.2}x", speedup)
+                });
+                write!(output, "  {:>width$}", speedup, width = width)?;
+            }
+            writeln!(output)?;
+        }
+
+        let utilized = |result: &TargetResult| result.utilization.is_some();
+        if !set.targets.iter().any(utilized) {
+            return Ok(());
+        }
+        writeln!(output, "  Utilization of the threads:")?;
+        write!(output, "    {:>7}", "threads")?;
+        for target in &swept {
+            write!(output, "  {}", target.name())?;
+        }
+        writeln!(output)?;
+
+        for threads in set.thread_counts() {
+            write!(output, "    {:>7}", threads)?;
+            for &target in &swept {
+                let width = target.name().len();
+                let result = set.find(target, threads);
+                let busy = result.and_then(|result| result.utilization);
+                let busy = busy.map_or("-".to_owned(), |busy| {
+                    format!("{:.1}%", busy * 100.0)
+                });
+                write!(output, "  {:>width$}", busy, width = width)?;
+            }
+            writeln!(output)?;
+        }
+
+        Ok(())
+    }
+
+    fn finish(
+        &mut self,
+        output: &mut dyn Write,
+        report: &BenchReport,
+    ) -> io::Result<()> {
+        match self.verbosity {
+            Verbosity::Quiet => {
+                let mut seeds = report.seeds_description();
+                seeds[.. 1].make_ascii_uppercase();
+                writeln!(output, "{}", seeds)?
+            },
+            _ => writeln!(output)?,
+        }
+        writeln!(output, "Summary:")?;
+        let mut tested = None;
+        for set in &report.sets {
+            // Every target may have timed out, leaving none to compare to.
+            let baseline = set.baseline();
+            match baseline {
+                Some(baseline) => writeln!(
+                    output,
+                    "  {}, pattern = {}, speedup over {}:",
+                    set.spec.name,
+                    set.pattern,
+                    baseline.label()
+                )?,
+                None => writeln!(
+                    output,
+                    "  {}, pattern = {}, no target completed:",
+                    set.spec.name, set.pattern
+                )?,
+            }
+            for target in &set.targets {
+                let label = target.label();
+                let speedup = baseline.and_then(|baseline| {
+                    target.speedup_over(baseline)
+                });
+                let speedup = match speedup {
+                    Some(speedup) => speedup,
+                    None => {
+                        let note = match target.timed_out {
+                            true => "timed out",
+                            false => "-",
+                        };
+                        writeln!(output, "    {:<24} {:>12}", label, note)?;
+                        continue;
+                    },
+                };
+                let significance = match set.p_value(target) {
+                    Some(p_value) => {
+                        tested = Some(set.confidence);
+                        let verdict = match set.significant(target) {
+                            Some(true) => "significant",
+                            _ => "not significant",
+                        };
+                        format!("  {}, {}", human_p_value(p_value), verdict)
+                    },
+                    None => String::new(),
+                };
+                writeln!(
+                    output,
+                    "    {:<24} {:>12} {:>7.2}x{}",
+                    label,
+                    human(target.mean_seconds()),
+                    speedup,
+                    significance
+                )?;
+            }
+        }
+        if let Some(confidence) = tested {
+            writeln!(
+                output,
+                "  Significant at {}% confidence, by Welch's t-test over the \
+                 repetitions.",
+                confidence * 100.0
+            )?;
+        }
+
+        if self.verbosity == Verbosity::Quiet {
+            return Ok(());
+        }
+        write_throughputs(output, report)?;
+
+        let fits = complexity::fit(report);
+        if fits.is_empty() {
+            return Ok(());
+        }
+        writeln!(output)?;
+        writeln!(output, "Complexity, over the cases of every set:")?;
+        writeln!(
+            output,
+            "    {:<24} {:>10} {:>6} {:>10} {:>6} {:>6} {:>6}",
+            "target", "c n log n", "R²", "c n", "R²", "n^k", "ideal"
+        )?;
+        for fit in &fits {
+            writeln!(
+                output,
+                "    {:<24} {:>8.3}ns {:>6.3} {:>8.3}ns {:>6.3} {:>6.3} \
+                 {:>6.3}{}",
+                fit.label,
+                fit.n_log_n.constant * 1e9,
+                fit.n_log_n.r_squared,
+                fit.linear.constant * 1e9,
+                fit.linear.r_squared,
+                fit.exponent,
+                fit.expected_exponent,
+                if fit.deviates() { "  deviates" } else { "" }
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Writes the throughput of every target on every set, targets as rows and
+/// sets as columns, in millions of elements per second.
+fn write_throughputs(
+    output: &mut dyn Write,
+    report: &BenchReport,
+) -> io::Result<()> {
+    let mut labels = Vec::new();
+    for target in report.sets.iter().flat_map(|set| &set.targets) {
+        let label = target.label();
+        if !labels.contains(&label) {
+            labels.push(label);
+        }
+    }
+    if labels.is_empty() {
+        return Ok(());
+    }
+
+    writeln!(output)?;
+    writeln!(output, "Throughput, in millions of elements per second:")?;
+    write!(output, "    {:<24}", "target")?;
+    for set in &report.sets {
+        write!(output, " {:>10}", set.spec.name)?;
+    }
+    writeln!(output)?;
+    for label in &labels {
+        write!(output, "    {:<24}", label)?;
+        for set in &report.sets {
+            let mut targets = set.targets.iter();
+            let target = targets.find(|target| target.label() == *label);
+            let throughput =
+                target.and_then(|target| target.throughput(set.elements()));
+            match throughput {
+                Some(throughput) => {
+                    write!(output, " {:>10.2}", throughput / 1e6)?
+                },
+                None => write!(output, " {:>10}", "-")?,
+            }
+        }
+        writeln!(output)?;
+    }
+    Ok(())
+}
+
+/// The line with the time of a target on the cases of each seed, and how far
+/// apart its speedups over the baseline were between the seeds.
+fn seed_line(set: &CaseSetResult, target: &TargetResult) -> String {
+    let cases = set.cases_per_seed();
+    let means = target.seed_means(cases);
+    let times = means.iter().copied().map(human).collect::<Vec<_>>();
+    let mut line = format!("per seed: {}", times.join(", "));
+
+    // The baseline is always as fast as itself.
+    let baseline = set.baseline().filter(|&other| !ptr::eq(other, target));
+    if let Some(baseline) = baseline.map(|other| other.seed_means(cases)) {
+        let speedups = baseline.iter().zip(&means);
+        let speedups = speedups.map(|(baseline, mean)| baseline / mean);
+        let low = speedups.clone().fold(f64::INFINITY, f64::min);
+        let high = speedups.fold(f64::NEG_INFINITY, f64::max);
+        line.push_str(&format!(", speedups {:.2}x to {:.2}x", low, high));
+    }
+    line
+}
@@ -0,0 +1,29 @@
+//! The shapes the generated arrays can have, with elements of the types the
+//! benchmark sorts.
+
+use crate::elements::Element;
+pub use mergesort_cmp::gen::{ParsePatternError, Pattern};
+use rand::Rng;
+
+/// Generates an array of the given size with the pattern, made of elements
+/// of the given shape. Reversed integers are strictly descending.
+pub fn generate<T, R>(
+    pattern: Pattern,
+    shape: &T::Shape,
+    size: usize,
+    rng: &mut R,
+) -> Vec<T>
+where
+    T: Element,
+    R: Rng,
+{
+    match pattern {
+        Pattern::Reverse => T::descending(shape, size, rng),
+        _ => pattern.generate_by(
+            size,
+            rng,
+            |rng| T::random(shape, rng),
+            T::compare,
+        ),
+    }
+}
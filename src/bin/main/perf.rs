@@ -0,0 +1,165 @@
+//! Hardware performance counters of the targets, read through
+//! `perf_event_open`. Without the `perf` feature, or off Linux, nothing is
+//! counted.
+
+use crate::report::CounterStats;
+use std::io;
+
+/// The counters of the calling thread and of the threads it starts.
+#[derive(Debug)]
+pub struct Counters {
+    /// The file descriptors of the events, in the order of [`EVENTS`].
+    #[cfg(all(feature = "perf", target_os = "linux"))]
+    events: Vec<libc::c_int>,
+}
+
+/// `perf_event_open` and what it takes, which the `libc` crate does not have.
+#[cfg(all(feature = "perf", target_os = "linux"))]
+mod sys {
+    /// The first version of `perf_event_attr`, which every kernel with the
+    /// system call knows. The later fields are left out.
+    #[repr(C)]
+    #[derive(Debug, Default)]
+    pub struct EventAttr {
+        pub kind: u32,
+        pub size: u32,
+        pub config: u64,
+        pub sample_period: u64,
+        pub sample_type: u64,
+        pub read_format: u64,
+        pub flags: u64,
+        pub wakeup_events: u32,
+        pub bp_type: u32,
+        pub config1: u64,
+    }
+
+    /// `PERF_TYPE_HARDWARE`.
+    pub const TYPE_HARDWARE: u32 = 0;
+    /// `PERF_COUNT_HW_INSTRUCTIONS`.
+    pub const INSTRUCTIONS: u64 = 1;
+    /// `PERF_COUNT_HW_CACHE_MISSES`.
+    pub const CACHE_MISSES: u64 = 3;
+    /// `PERF_COUNT_HW_BRANCH_MISSES`.
+    pub const BRANCH_MISSES: u64 = 5;
+
+    /// The `disabled` flag: the event starts stopped.
+    pub const DISABLED: u64 = 1 << 0;
+    /// The `inherit` flag: threads started later are counted too.
+    pub const INHERIT: u64 = 1 << 1;
+    /// The `exclude_kernel` flag, needed by default by unprivileged users.
+    pub const EXCLUDE_KERNEL: u64 = 1 << 5;
+    /// The `exclude_hv` flag.
+    pub const EXCLUDE_HV: u64 = 1 << 6;
+
+    /// `PERF_EVENT_IOC_ENABLE`.
+    pub const IOC_ENABLE: libc::c_ulong = 0x2400;
+    /// `PERF_EVENT_IOC_DISABLE`.
+    pub const IOC_DISABLE: libc::c_ulong = 0x2401;
+    /// `PERF_EVENT_IOC_RESET`.
+    pub const IOC_RESET: libc::c_ulong = 0x2403;
+    /// `PERF_FLAG_FD_CLOEXEC`.
+    pub const FLAG_FD_CLOEXEC: libc::c_ulong = 1 << 3;
+}
+
+/// The events counted, in the order of the fields of [`CounterStats`].
+#[cfg(all(feature = "perf", target_os = "linux"))]
+const EVENTS: [u64; 3] =
+    [sys::INSTRUCTIONS, sys::CACHE_MISSES, sys::BRANCH_MISSES];
+
+impl Counters {
+    /// Opens the counters, stopped. `None` if they are not collected.
+    #[cfg(all(feature = "perf", target_os = "linux"))]
+    pub fn open() -> io::Result<Option<Self>> {
+        let mut counters = Self { events: Vec::new() };
+        for &event in &EVENTS {
+            let attr = sys::EventAttr {
+                kind: sys::TYPE_HARDWARE,
+                size: std::mem::size_of::<sys::EventAttr>() as u32,
+                config: event,
+                flags: sys::DISABLED
+                    | sys::INHERIT
+                    | sys::EXCLUDE_KERNEL
+                    | sys::EXCLUDE_HV,
+                ..sys::EventAttr::default()
+            };
+            // The calling thread, on any CPU, in no group.
+            let descriptor = unsafe {
+                libc::syscall(
+                    libc::SYS_perf_event_open,
+                    &attr as *const sys::EventAttr,
+                    0 as libc::pid_t,
+                    -1 as libc::c_int,
+                    -1 as libc::c_int,
+                    sys::FLAG_FD_CLOEXEC,
+                )
+            };
+            if descriptor < 0 {
+                // The events opened so far are closed on drop.
+                return Err(io::Error::last_os_error());
+            }
+            counters.events.push(descriptor as libc::c_int);
+        }
+        Ok(Some(counters))
+    }
+
+    /// Opens the counters, stopped. `None` if they are not collected.
+    #[cfg(not(all(feature = "perf", target_os = "linux")))]
+    pub fn open() -> io::Result<Option<Self>> {
+        Ok(None)
+    }
+
+    /// Counts the events while `action` runs. Threads that finish before it
+    /// returns are counted with it, but not the ones that outlive it.
+    #[cfg(all(feature = "perf", target_os = "linux"))]
+    pub fn measure<F, R>(&self, action: F) -> io::Result<(R, CounterStats)>
+    where
+        F: FnOnce() -> R,
+    {
+        let control = |request| {
+            for &descriptor in &self.events {
+                if unsafe { libc::ioctl(descriptor, request, 0) } < 0 {
+                    return Err(io::Error::last_os_error());
+                }
+            }
+            Ok(())
+        };
+
+        control(sys::IOC_RESET)?;
+        control(sys::IOC_ENABLE)?;
+        let result = action();
+        control(sys::IOC_DISABLE)?;
+
+        let mut values = [0; 3];
+        for (value, &descriptor) in values.iter_mut().zip(&self.events) {
+            let buffer = value as *mut u64 as *mut libc::c_void;
+            let size = std::mem::size_of::<u64>();
+            let read = unsafe { libc::read(descriptor, buffer, size) };
+            if read < 0 {
+                return Err(io::Error::last_os_error());
+            }
+        }
+        let [instructions, cache_misses, branch_misses] = values;
+        Ok((result, CounterStats { instructions, cache_misses, branch_misses }))
+    }
+
+    /// Counts the events while `action` runs.
+    #[cfg(not(all(feature = "perf", target_os = "linux")))]
+    pub fn measure<F, R>(&self, action: F) -> io::Result<(R, CounterStats)>
+    where
+        F: FnOnce() -> R,
+    {
+        let _ = action;
+        unreachable!("the counters are never opened")
+    }
+}
+
+#[cfg(all(feature = "perf", target_os = "linux"))]
+impl Drop for Counters {
+    fn drop(&mut self) {
+        for &descriptor in &self.events {
+            unsafe {
+                libc::close(descriptor);
+            }
+        }
+    }
+}
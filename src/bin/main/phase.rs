@@ -0,0 +1,55 @@
+//! What of the targets is timed. End-to-end sorts hide how fast their
+//! merges are behind the splits and the smaller merges, so the merge can be
+//! timed alone, on arrays whose halves are already sorted.
+
+use crate::{cases::CaseSet, elements::Element};
+use std::sync::Arc;
+
+/// What of the targets is timed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    /// The whole sort.
+    Sort,
+    /// Only the last merge, of the two halves of each case sorted before.
+    Merge,
+}
+
+impl Phase {
+    /// Every phase.
+    pub const ALL: &'static [Self] = &[Phase::Sort, Phase::Merge];
+
+    /// The name the phase is selected by.
+    pub fn name(self) -> &'static str {
+        match self {
+            Phase::Sort => "sort",
+            Phase::Merge => "merge",
+        }
+    }
+
+    /// Finds the phase of the given name.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|phase| phase.name() == name)
+    }
+}
+
+/// Where the merges split an array in halves: past the middle element, like
+/// the merge sorts do.
+pub fn middle(len: usize) -> usize {
+    len.div_ceil(2)
+}
+
+/// Sorts each half of every case of the set on its own, once, so what is
+/// left to time is the merge of the halves.
+pub fn sort_halves<T>(set: CaseSet<T>) -> CaseSet<T>
+where
+    T: Element,
+{
+    let cases = set.cases.into_iter().map(|case| {
+        let mut halves = case.to_vec();
+        let (lower, upper) = halves.split_at_mut(middle(case.len()));
+        lower.sort_by(T::compare);
+        upper.sort_by(T::compare);
+        Arc::from(halves)
+    });
+    CaseSet { cases: cases.collect() }
+}
@@ -0,0 +1,104 @@
+//! Data files of the time each target took by the size of each case, with a
+//! gnuplot script that plots them.
+
+use crate::report::BenchReport;
+use std::{
+    borrow::Cow,
+    fs::{self, File},
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+/// The name of the generated script.
+const SCRIPT: &str = "plot.gp";
+
+/// The name of the chart the script draws.
+const CHART: &str = "scaling.svg";
+
+/// Writes a CSV file per target to the directory, with a point per case of
+/// every set, and a script plotting them all. The directory is created if it
+/// does not exist.
+pub fn write(directory: &Path, report: &BenchReport) -> io::Result<()> {
+    fs::create_dir_all(directory)?;
+
+    // Every target gets a file, in the order they first ran.
+    let mut labels = Vec::<Cow<str>>::new();
+    for set in &report.sets {
+        for target in &set.targets {
+            let label = target.label();
+            if !labels.contains(&label) {
+                labels.push(label);
+            }
+        }
+    }
+
+    for label in &labels {
+        let path = directory.join(data_file(label));
+        let mut output = BufWriter::new(File::create(path)?);
+        writeln!(output, "size,seconds,set")?;
+        for set in &report.sets {
+            let mut targets = set.targets.iter();
+            let found = targets.find(|target| target.label() == *label);
+            let target = match found {
+                Some(target) => target,
+                None => continue,
+            };
+            for (size, seconds) in set.sizes.iter().zip(target.case_means()) {
+                let name = set.spec.name;
+                writeln!(output, "{},{:e},{}", size, seconds, name)?;
+            }
+        }
+        output.flush()?;
+    }
+
+    let mut script = BufWriter::new(File::create(directory.join(SCRIPT))?);
+    write_script(&mut script, report, &labels)?;
+    script.flush()
+}
+
+/// The name of the data file of a target.
+fn data_file(label: &str) -> String {
+    format!("{}.csv", label)
+}
+
+/// Writes the script plotting every data file on log-log axes.
+fn write_script<W>(
+    output: &mut W,
+    report: &BenchReport,
+    labels: &[Cow<str>],
+) -> io::Result<()>
+where
+    W: Write,
+{
+    writeln!(output, "# Plots the time of each target by the size of a case.")?;
+    writeln!(output, "# Run `gnuplot {}` in this directory.", SCRIPT)?;
+    writeln!(output, "set terminal svg size 1000,700 dynamic")?;
+    writeln!(output, "set output '{}'", CHART)?;
+    writeln!(output, "set datafile separator ','")?;
+    writeln!(
+        output,
+        "set title 'Time by case size, {}, elements of type {}'",
+        report.seeds_description(),
+        report.element_description().replace('\'', "''")
+    )?;
+    writeln!(output, "set xlabel 'elements'")?;
+    writeln!(output, "set ylabel 'seconds'")?;
+    writeln!(output, "set logscale xy")?;
+    writeln!(output, "set key top left")?;
+    writeln!(output, "set grid")?;
+
+    for (index, label) in labels.iter().enumerate() {
+        let start = if index == 0 { "plot" } else { "    " };
+        let end = if index + 1 < labels.len() { ", \\" } else { "" };
+        writeln!(
+            output,
+            "{} '{}' every ::1 using 1:2 with points pt 7 ps 0.3 \
+             title '{}'{}",
+            start,
+            data_file(label),
+            label,
+            end
+        )?;
+    }
+    Ok(())
+}
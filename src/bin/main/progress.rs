@@ -0,0 +1,126 @@
+//! The progress of the runs over a case set, drawn on stderr so the results
+//! stay clean.
+
+use crate::output::Verbosity;
+use std::{
+    io::{self, IsTerminal, Write},
+    time::{Duration, Instant},
+};
+
+/// How often the progress is drawn again, at most.
+const REDRAW: Duration = Duration::from_millis(100);
+
+/// The progress of the runs over a case set.
+#[derive(Debug)]
+pub struct Progress {
+    /// Whether the progress is drawn at all.
+    enabled: bool,
+    /// The name of the case set.
+    set: &'static str,
+    /// How many cases are sorted over the set, counting every target.
+    total: usize,
+    /// How many cases were sorted so far.
+    done: usize,
+    /// When the first case started.
+    start: Instant,
+    /// When the progress was last drawn, if it is on the screen.
+    drawn: Option<Instant>,
+}
+
+impl Progress {
+    /// Whether progress can be drawn: not asked to be quiet, and the results
+    /// go to a terminal someone is watching.
+    pub fn wanted(verbosity: Verbosity) -> bool {
+        verbosity > Verbosity::Quiet
+            && io::stdout().is_terminal()
+            && io::stderr().is_terminal()
+    }
+
+    /// Starts the progress of a set, of `total` cases to sort.
+    pub fn new(enabled: bool, set: &'static str, total: usize) -> Self {
+        Self {
+            enabled,
+            set,
+            total,
+            done: 0,
+            start: Instant::now(),
+            drawn: None,
+        }
+    }
+
+    /// Records that another case was sorted.
+    pub fn advance(&mut self) {
+        self.done += 1;
+        if !self.enabled {
+            return;
+        }
+        let now = Instant::now();
+        if self.drawn.is_none_or(|drawn| now - drawn >= REDRAW) {
+            self.draw(now);
+        }
+    }
+
+    /// Records that `cases` more cases are to be sorted than planned.
+    pub fn extend(&mut self, cases: usize) {
+        self.total += cases;
+    }
+
+    /// How many cases were sorted or skipped so far.
+    pub fn done(&self) -> usize {
+        self.done
+    }
+
+    /// Records that the cases up to `done` are over, some of them skipped
+    /// without being sorted.
+    pub fn skip_to(&mut self, done: usize) {
+        self.done = self.done.max(done);
+    }
+
+    /// Draws the progress over the previous one.
+    fn draw(&mut self, now: Instant) {
+        let elapsed = (now - self.start).as_secs_f64();
+        let left = self.total.saturating_sub(self.done);
+        let eta = elapsed / self.done.max(1) as f64 * left as f64;
+        let percent = self.done * 100 / self.total.max(1);
+
+        let mut stderr = io::stderr();
+        // Progress is only a convenience, failing to draw it is not an error.
+        let _ = write!(
+            stderr,
+            "\r\x1b[K{}: {}/{} cases ({}%), ETA {}",
+            self.set,
+            self.done,
+            self.total,
+            percent,
+            clock(eta)
+        );
+        let _ = stderr.flush();
+        self.drawn = Some(now);
+    }
+
+    /// Erases the progress, so results can be written where it was.
+    pub fn clear(&mut self) {
+        if self.drawn.take().is_some() {
+            let mut stderr = io::stderr();
+            let _ = write!(stderr, "\r\x1b[K");
+            let _ = stderr.flush();
+        }
+    }
+}
+
+impl Drop for Progress {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+/// Formats seconds as a clock, e.g. `1:05` or `2:03:10`.
+fn clock(seconds: f64) -> String {
+    let seconds = seconds.round() as u64;
+    let (hours, minutes, seconds) =
+        (seconds / 3600, seconds / 60 % 60, seconds % 60);
+    match hours {
+        0 => format!("{}:{:02}", minutes, seconds),
+        _ => format!("{}:{:02}:{:02}", hours, minutes, seconds),
+    }
+}
@@ -0,0 +1,536 @@
+//! The results of a benchmark run.
+
+use crate::{
+    affinity,
+    cases::SetSpec,
+    output::human_bytes,
+    elements::{ElementType, StringShape},
+    manifest::Manifest,
+    order::Order,
+    patterns::Pattern,
+    phase::Phase,
+    stats::{self, Stats},
+    targets::Target,
+};
+use mergesort_cmp::parallel::ExecutionTrace;
+use std::{borrow::Cow, env, fs, ptr, time::Duration};
+
+/// The results of a whole run.
+#[derive(Debug, Clone)]
+pub struct BenchReport {
+    /// The seeds the cases were generated from, in order.
+    pub seeds: Vec<u64>,
+    /// The type of the elements of the cases.
+    pub element: ElementType,
+    /// What the strings looked like, if the elements were strings.
+    pub strings: Option<StringShape>,
+    /// The size of the payloads, if the elements had payloads.
+    pub payload_bytes: Option<usize>,
+    /// The machine the run happened on.
+    pub machine: Machine,
+    /// The order the targets ran in.
+    pub order: Order,
+    /// Whether the caches were evicted before every timed run.
+    pub cold_cache: bool,
+    /// What of the targets was timed.
+    pub phase: Phase,
+    /// The CPUs the run was pinned to, if it was.
+    pub cpus: Option<Vec<usize>>,
+    /// The iterations of dummy work every comparison did first.
+    pub cmp_cost: u64,
+    /// How the run was produced. `None` in the child processes of
+    /// `--isolate`, which write no report.
+    pub manifest: Option<Manifest>,
+    /// The results of each case set, in the order they ran.
+    pub sets: Vec<CaseSetResult>,
+}
+
+impl BenchReport {
+    /// The seeds, as `seed 1` or `seeds 1, 2, 3`.
+    pub fn seeds_description(&self) -> String {
+        let seeds = self.seeds.iter().map(u64::to_string);
+        let seeds = seeds.collect::<Vec<_>>().join(", ");
+        match self.seeds.len() {
+            1 => format!("seed {}", seeds),
+            _ => format!("seeds {}", seeds),
+        }
+    }
+
+    /// The seeds, the order of the targets if they did not run in the order
+    /// given, and whether the caches were cold, as `seed 1, targets shuffled
+    /// for every set, cold caches`.
+    pub fn run_description(&self) -> String {
+        let mut parts = vec![self.seeds_description()];
+        if self.order != Order::Fixed {
+            parts.push(self.order.description().to_owned());
+        }
+        if self.cold_cache {
+            parts.push("cold caches".to_owned());
+        }
+        if let Some(cpus) = &self.cpus {
+            let plural = if cpus.len() == 1 { "" } else { "s" };
+            let cpus = affinity::describe(cpus);
+            parts.push(format!("pinned to CPU{} {}", plural, cpus));
+        }
+        if self.cmp_cost > 0 {
+            parts.push(format!(
+                "{} iterations of work per comparison",
+                self.cmp_cost
+            ));
+        }
+        if self.phase == Phase::Merge {
+            parts.push("timing only the merges".to_owned());
+        }
+        parts.join(", ")
+    }
+
+    /// The type of the elements, and what they looked like if it has more to
+    /// it than the type.
+    pub fn element_description(&self) -> String {
+        let name = self.element.name();
+        match (&self.strings, self.payload_bytes) {
+            (Some(shape), _) => format!("{} ({})", name, shape),
+            (None, Some(bytes)) => format!("{} ({} bytes)", name, bytes),
+            (None, None) => name.to_owned(),
+        }
+    }
+}
+
+/// What is known about the machine running the benchmark.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Machine {
+    /// The operating system.
+    pub os: &'static str,
+    /// The CPU architecture.
+    pub arch: &'static str,
+    /// The model of the CPU, if it could be found.
+    pub cpu_model: Option<String>,
+    /// The base frequency of the CPU, in MHz, if it could be found.
+    pub base_mhz: Option<u64>,
+    /// How many logical CPUs there are.
+    pub logical_cpus: usize,
+    /// How many physical CPUs there are.
+    pub physical_cpus: usize,
+    /// How much memory there is, in bytes, if it could be found.
+    pub memory_bytes: Option<usize>,
+    /// The version of the compiler the benchmark was built with.
+    pub rustc: &'static str,
+}
+
+impl Machine {
+    /// Finds out about the current machine. The CPU model, its frequency and
+    /// the memory are only found on Linux.
+    pub fn detect() -> Self {
+        let cpuinfo = fs::read_to_string("/proc/cpuinfo").unwrap_or_default();
+        let meminfo = fs::read_to_string("/proc/meminfo").unwrap_or_default();
+        let cpu = |key| info_field(&cpuinfo, key);
+
+        // The base frequency is only known with some drivers, otherwise the
+        // current frequency is the best there is.
+        let base_khz = CPU_FREQUENCIES.iter().find_map(|path| {
+            fs::read_to_string(path).ok()?.trim().parse::<u64>().ok()
+        });
+        let current_mhz = cpu("cpu MHz").and_then(|mhz| mhz.parse().ok());
+        let base_mhz = base_khz
+            .map(|khz| khz / 1000)
+            .or_else(|| current_mhz.map(|mhz: f64| mhz.round() as u64));
+
+        // The memory is given in kB.
+        let memory_bytes = info_field(&meminfo, "MemTotal").and_then(|total| {
+            let kilobytes = total.trim_end_matches("kB").trim();
+            kilobytes.parse::<usize>().ok().map(|kilobytes| kilobytes * 1024)
+        });
+
+        Self {
+            os: env::consts::OS,
+            arch: env::consts::ARCH,
+            cpu_model: cpu("model name").map(str::to_owned),
+            base_mhz,
+            logical_cpus: num_cpus::get(),
+            physical_cpus: num_cpus::get_physical(),
+            memory_bytes,
+            rustc: env!("MERGESORT_CMP_RUSTC"),
+        }
+    }
+
+    /// Describes the machine in a line.
+    pub fn description(&self) -> String {
+        let cpu = self.cpu_model.as_deref().unwrap_or("unknown CPU");
+        let mut parts = vec![match self.base_mhz {
+            Some(mhz) => format!("{} at {} MHz", cpu, mhz),
+            None => cpu.to_owned(),
+        }];
+        parts.push(format!(
+            "{} logical and {} physical CPUs",
+            self.logical_cpus, self.physical_cpus
+        ));
+        if let Some(bytes) = self.memory_bytes {
+            parts.push(format!("{} of memory", human_bytes(bytes)));
+        }
+        parts.push(format!("{} on {}", self.os, self.arch));
+        parts.push(self.rustc.to_owned());
+        parts.join(", ")
+    }
+}
+
+/// The files with the base frequency of the CPU, in kHz, from the best.
+const CPU_FREQUENCIES: &[&str] = &[
+    "/sys/devices/system/cpu/cpu0/cpufreq/base_frequency",
+    "/sys/devices/system/cpu/cpu0/cpufreq/cpuinfo_max_freq",
+];
+
+/// The value of a field of a `/proc` file, such as `model name : ...`.
+fn info_field<'info>(info: &'info str, key: &str) -> Option<&'info str> {
+    info.lines().find_map(|line| {
+        let (name, value) = line.split_once(':')?;
+        if name.trim() == key {
+            Some(value.trim())
+        } else {
+            None
+        }
+    })
+}
+
+/// The results of every target on a case set.
+#[derive(Debug, Clone)]
+pub struct CaseSetResult {
+    /// The parameters the cases were generated with.
+    pub spec: &'static SetSpec,
+    /// The pattern of the arrays.
+    pub pattern: Pattern,
+    /// How many cases the set has.
+    pub cases: usize,
+    /// The size of each case, in order.
+    pub sizes: Vec<usize>,
+    /// The seeds the cases were generated from. The cases of each come one
+    /// after the other, as many for every seed.
+    pub seeds: Vec<u64>,
+    /// The confidence level, as a fraction, at which a difference from the
+    /// baseline is significant.
+    pub confidence: f64,
+    /// The results of each target, in the order they ran.
+    pub targets: Vec<TargetResult>,
+}
+
+impl CaseSetResult {
+    /// The target others are compared against: the sequential sort, or the
+    /// first target if it did not run. Targets without a complete run are
+    /// left out.
+    pub fn baseline(&self) -> Option<&TargetResult> {
+        let mut complete =
+            self.targets.iter().filter(|target| !target.runs.is_empty());
+        complete
+            .clone()
+            .find(|target| target.target == Target::Sequential)
+            .or_else(|| complete.next())
+    }
+
+    /// The p-value of the difference between the target and the baseline,
+    /// by Welch's t-test over the repetitions. `None` for the baseline
+    /// itself, or if either has fewer than 2 complete runs.
+    pub fn p_value(&self, target: &TargetResult) -> Option<f64> {
+        let baseline = self.baseline()?;
+        match ptr::eq(baseline, target) {
+            true => None,
+            false => stats::welch_p_value(&baseline.totals(), &target.totals()),
+        }
+    }
+
+    /// Whether the difference between the target and the baseline is
+    /// significant at the confidence level. `None` without a p-value.
+    pub fn significant(&self, target: &TargetResult) -> Option<bool> {
+        let p_value = self.p_value(target)?;
+        Some(p_value < 1.0 - self.confidence)
+    }
+
+    /// How many cases each seed generated.
+    pub fn cases_per_seed(&self) -> usize {
+        self.cases / self.seeds.len().max(1)
+    }
+
+    /// How many elements the cases have altogether.
+    pub fn elements(&self) -> usize {
+        self.sizes.iter().sum()
+    }
+
+    /// The thread counts of a sweep, in the order they ran. Empty if there
+    /// was no sweep.
+    pub fn thread_counts(&self) -> Vec<usize> {
+        let mut counts = Vec::new();
+        for threads in self.targets.iter().filter_map(|target| target.threads)
+        {
+            if !counts.contains(&threads) {
+                counts.push(threads);
+            }
+        }
+        counts
+    }
+
+    /// The targets that ran at every count of a sweep, in the order they
+    /// ran.
+    pub fn swept_targets(&self) -> Vec<Target> {
+        let mut targets = Vec::new();
+        for result in &self.targets {
+            if result.threads.is_some() && !targets.contains(&result.target) {
+                targets.push(result.target);
+            }
+        }
+        targets
+    }
+
+    /// The results of the target with the given thread count of a sweep.
+    pub fn find(
+        &self,
+        target: Target,
+        threads: usize,
+    ) -> Option<&TargetResult> {
+        self.targets.iter().find(|result| {
+            result.target == target && result.threads == Some(threads)
+        })
+    }
+}
+
+/// The results of a target on a case set.
+#[derive(Debug, Clone)]
+pub struct TargetResult {
+    /// The target that ran.
+    pub target: Target,
+    /// The logical CPUs the target assumed, if it ran as part of a thread
+    /// count sweep.
+    pub threads: Option<usize>,
+    /// How long it took to sort each case, once per repetition.
+    pub runs: Vec<Vec<Duration>>,
+    /// What the target allocated, `None` if the set had no cases.
+    pub memory: Option<MemoryStats>,
+    /// How many comparisons a run of the whole set made, if they were
+    /// counted.
+    pub comparisons: Option<u64>,
+    /// The hardware events of a run of the whole set, if they were counted.
+    pub counters: Option<CounterStats>,
+    /// The CPU time of a run of the whole set, summed over the threads and
+    /// averaged over the repetitions, in seconds. `None` without complete
+    /// runs, or where it cannot be measured.
+    pub cpu: Option<f64>,
+    /// The joules the CPUs used in a run of the whole set, averaged over the
+    /// repetitions, if it was measured.
+    pub energy: Option<f64>,
+    /// How the target split the biggest case of the set, if it is the
+    /// parallel merge sort and it was asked for.
+    pub trace: Option<ExecutionTrace>,
+    /// Whether the target took longer than the timeout, leaving cases
+    /// unsorted. Only its complete runs are kept, if any.
+    pub timed_out: bool,
+    /// How many times of cases were outliers, replaced by the median of
+    /// their case, if outliers were rejected.
+    pub outliers: Option<usize>,
+    /// Whether the confidence interval of the mean run got as narrow as it
+    /// was asked to, if runs went on until it did.
+    pub converged: Option<bool>,
+    /// The share of the time the threads of the parallel merge sort were
+    /// busy over a run of the whole set, if it was measured.
+    pub utilization: Option<f64>,
+}
+
+/// What a target allocated while sorting a case set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MemoryStats {
+    /// How many allocations a run of the whole set made, reallocations
+    /// included, averaged over the repetitions.
+    pub allocations: u64,
+    /// The most bytes allocated at once by a single sort, its output
+    /// included, over every repetition. `None` in reports of builds that did
+    /// not count it.
+    pub peak_bytes: Option<usize>,
+}
+
+/// The hardware events of a target while sorting a case set.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CounterStats {
+    /// How many instructions ran.
+    pub instructions: u64,
+    /// How many references to memory missed the last level cache.
+    pub cache_misses: u64,
+    /// How many branches were mispredicted.
+    pub branch_misses: u64,
+}
+
+impl CounterStats {
+    /// The events with their names, in the order they are reported.
+    pub fn named(&self) -> [(&'static str, u64); 3] {
+        [
+            ("instructions", self.instructions),
+            ("cache_misses", self.cache_misses),
+            ("branch_misses", self.branch_misses),
+        ]
+    }
+
+    /// The events of two sorts, one after the other.
+    pub fn then(self, other: Self) -> Self {
+        Self {
+            instructions: self.instructions + other.instructions,
+            cache_misses: self.cache_misses + other.cache_misses,
+            branch_misses: self.branch_misses + other.branch_misses,
+        }
+    }
+}
+
+impl MemoryStats {
+    /// What two sorts allocated, one after the other.
+    pub fn then(self, other: Self) -> Self {
+        Self {
+            allocations: self.allocations + other.allocations,
+            peak_bytes: self.peak_bytes.max(other.peak_bytes),
+        }
+    }
+
+    /// What the runs of a whole set allocated, with the allocations averaged
+    /// over them. `None` if there are no runs.
+    pub fn over<I>(runs: I) -> Option<Self>
+    where
+        I: IntoIterator<Item = Self>,
+    {
+        let mut count = 0;
+        let total = runs.into_iter().fold(None, |total: Option<Self>, run| {
+            count += 1;
+            Some(total.map_or(run, |total| total.then(run)))
+        })?;
+        Some(Self { allocations: total.allocations / count, ..total })
+    }
+}
+
+impl TargetResult {
+    /// The results of a target before anything is measured.
+    pub fn new(target: Target, threads: Option<usize>) -> Self {
+        Self {
+            target,
+            threads,
+            runs: Vec::new(),
+            memory: None,
+            comparisons: None,
+            counters: None,
+            cpu: None,
+            energy: None,
+            trace: None,
+            timed_out: false,
+            outliers: None,
+            converged: None,
+            utilization: None,
+        }
+    }
+
+    /// Replaces the time of a case in a run with the median of the case over
+    /// the runs, when it is further from it than `mads` times the median
+    /// absolute deviation, scaled to estimate the standard deviation. A
+    /// process taking the CPU away for a moment slows down a single case of
+    /// a single run, which this leaves out.
+    pub fn reject_outliers(&mut self, mads: f64) {
+        let cases = self.runs.first().map_or(0, Vec::len);
+        let mut outliers = 0;
+        for case in 0 .. cases {
+            let times = self.runs.iter().map(|run| run[case].as_secs_f64());
+            let times = times.collect::<Vec<_>>();
+            let (median, deviation) = match stats::median_deviation(&times) {
+                Some(found) => found,
+                None => continue,
+            };
+            for run in &mut self.runs {
+                let distance = (run[case].as_secs_f64() - median).abs();
+                if deviation > 0.0 && distance > mads * deviation {
+                    run[case] = Duration::from_secs_f64(median);
+                    outliers += 1;
+                }
+            }
+        }
+        self.outliers = Some(outliers);
+    }
+
+    /// The name the results are reported under, with the thread count if the
+    /// target ran as part of a sweep.
+    pub fn label(&self) -> Cow<'static, str> {
+        match self.threads {
+            Some(threads) => {
+                Cow::Owned(format!("{}@{}", self.target.name(), threads))
+            },
+            None => Cow::Borrowed(self.target.name()),
+        }
+    }
+
+    /// How long it took to sort all the cases, in seconds, for each
+    /// repetition.
+    pub fn totals(&self) -> Vec<f64> {
+        let total = |run: &Vec<Duration>| run.iter().sum::<Duration>();
+        self.runs.iter().map(|run| total(run).as_secs_f64()).collect()
+    }
+
+    /// The mean time to sort all the cases over the repetitions, in seconds.
+    pub fn mean_seconds(&self) -> f64 {
+        let totals = self.totals();
+        totals.iter().sum::<f64>() / totals.len() as f64
+    }
+
+    /// How many times faster than the baseline this target was. `None` if
+    /// either has no complete run.
+    pub fn speedup_over(&self, baseline: &TargetResult) -> Option<f64> {
+        match self.runs.is_empty() || baseline.runs.is_empty() {
+            true => None,
+            false => Some(baseline.mean_seconds() / self.mean_seconds()),
+        }
+    }
+
+    /// The half width of the confidence interval of the mean run, relative
+    /// to the mean, at the given confidence level. `None` with fewer than 2
+    /// complete runs.
+    pub fn relative_interval(&self, confidence: f64) -> Option<f64> {
+        stats::relative_interval(&self.totals(), confidence)
+    }
+
+    /// How many elements of the set the target sorted per second, given how
+    /// many the set has. `None` without a complete run.
+    pub fn throughput(&self, elements: usize) -> Option<f64> {
+        match self.runs.is_empty() {
+            true => None,
+            false => Some(elements as f64 / self.mean_seconds()),
+        }
+    }
+
+    /// How many times the wall time the CPU time was, about how many CPUs
+    /// were kept busy. `None` if the CPU time was not measured.
+    pub fn cpu_ratio(&self) -> Option<f64> {
+        self.cpu.map(|cpu| cpu / self.mean_seconds())
+    }
+
+    /// The mean time to sort each case over the repetitions, in seconds, in
+    /// the order of the cases.
+    pub fn case_means(&self) -> Vec<f64> {
+        let cases = self.runs.first().map_or(0, Vec::len);
+        (0 .. cases)
+            .map(|case| {
+                let times = self.runs.iter().map(|run| run[case]);
+                let total = times.sum::<Duration>().as_secs_f64();
+                total / self.runs.len() as f64
+            })
+            .collect()
+    }
+
+    /// The mean time to sort the cases of each seed over the repetitions, in
+    /// seconds, given how many cases a seed has.
+    pub fn seed_means(&self, cases_per_seed: usize) -> Vec<f64> {
+        self.case_means()
+            .chunks(cases_per_seed.max(1))
+            .map(|chunk| chunk.iter().sum())
+            .collect()
+    }
+
+    /// The statistics of the time to sort all the cases, in seconds, over the
+    /// repetitions.
+    pub fn total_stats(&self) -> Option<Stats> {
+        Stats::of(&self.totals())
+    }
+
+    /// The statistics of the time to sort a single case, in seconds, over
+    /// every case of every repetition.
+    pub fn case_stats(&self) -> Option<Stats> {
+        let seconds = self.runs.iter().flatten().map(Duration::as_secs_f64);
+        Stats::of(&seconds.collect::<Vec<_>>())
+    }
+}
@@ -0,0 +1,126 @@
+//! The `sort` subcommand, which sorts the lines of a file with one of the
+//! targets.
+
+use crate::{cli::SortConfig, elements::Element};
+use std::{
+    error::Error,
+    fmt,
+    fs::{self, File},
+    io::{self, BufWriter, Read, Write},
+    num::ParseIntError,
+    path::PathBuf,
+    sync::Arc,
+};
+
+/// Why the lines could not be sorted.
+#[derive(Debug)]
+pub enum SortError {
+    /// The input could not be read.
+    Read(Option<PathBuf>, io::Error),
+    /// The output could not be written.
+    Write(Option<PathBuf>, io::Error),
+    /// A line is not an integer.
+    Parse {
+        /// The number of the line, starting at 1.
+        line: usize,
+        /// What the line has.
+        text: String,
+        /// Why it is not an integer.
+        error: ParseIntError,
+    },
+}
+
+/// Names a file, or the standard stream if there is none.
+fn name_of(path: &Option<PathBuf>, stream: &str) -> String {
+    match path {
+        Some(path) => path.display().to_string(),
+        None => stream.to_owned(),
+    }
+}
+
+impl fmt::Display for SortError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SortError::Read(path, error) => {
+                write!(fmt, "cannot read {}: {}", name_of(path, "stdin"), error)
+            },
+            SortError::Write(path, error) => write!(
+                fmt,
+                "cannot write {}: {}",
+                name_of(path, "stdout"),
+                error
+            ),
+            SortError::Parse { line, text, error } => write!(
+                fmt,
+                "line {}: invalid integer {:?}: {}",
+                line, text, error
+            ),
+        }
+    }
+}
+
+impl Error for SortError {}
+
+/// Reads the lines, sorts them and writes them one per line.
+pub fn run(config: &SortConfig) -> Result<(), SortError> {
+    let text = match &config.input {
+        Some(path) => fs::read_to_string(path),
+        None => {
+            let mut text = String::new();
+            io::stdin().read_to_string(&mut text).map(|_| text)
+        },
+    };
+    let text =
+        text.map_err(|error| SortError::Read(config.input.clone(), error))?;
+
+    match config.lines {
+        true => {
+            let lines = text.lines().map(str::to_owned).collect();
+            sort_and_write(config, lines)
+        },
+        false => {
+            let integers = parse_integers(&text)?;
+            sort_and_write::<i64>(config, integers)
+        },
+    }
+}
+
+/// Parses an integer per line, skipping blank lines.
+fn parse_integers(text: &str) -> Result<Vec<i64>, SortError> {
+    text.lines()
+        .enumerate()
+        .map(|(index, line)| (index, line.trim()))
+        .filter(|(_, line)| !line.is_empty())
+        .map(|(index, line)| {
+            line.parse().map_err(|error| SortError::Parse {
+                line: index + 1,
+                text: line.to_owned(),
+                error,
+            })
+        })
+        .collect()
+}
+
+/// Sorts the elements with the configured target and writes them.
+fn sort_and_write<T>(
+    config: &SortConfig,
+    elements: Vec<T>,
+) -> Result<(), SortError>
+where
+    T: Element + fmt::Display,
+{
+    let array = Arc::<[T]>::from(elements);
+    let mut sorter = config.target.sorter::<T>(config.threads);
+    let sorted = sorter(&array);
+
+    let write_error = |error| SortError::Write(config.output.clone(), error);
+    let output: Box<dyn Write> = match &config.output {
+        Some(path) => Box::new(File::create(path).map_err(write_error)?),
+        None => Box::new(io::stdout()),
+    };
+    let mut output = BufWriter::new(output);
+    for element in &sorted {
+        writeln!(output, "{}", element).map_err(write_error)?;
+    }
+    output.flush().map_err(write_error)
+}
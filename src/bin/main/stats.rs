@@ -0,0 +1,250 @@
+//! Summary statistics of timings.
+
+/// Summary statistics of a sample.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Stats {
+    /// The arithmetic mean.
+    pub mean: f64,
+    /// The middle value, or the mean of the two middle values.
+    pub median: f64,
+    /// The sample standard deviation, zero for a single value.
+    pub stddev: f64,
+    /// The smallest value.
+    pub min: f64,
+    /// The biggest value.
+    pub max: f64,
+    /// The 95th percentile, by the nearest rank.
+    pub p95: f64,
+    /// The mean without the smallest and the biggest tenth of the values,
+    /// which a few far off values do not move.
+    pub trimmed_mean: f64,
+}
+
+/// The share of the values left out at each end by the trimmed mean.
+const TRIMMED: f64 = 0.1;
+
+/// The factor making the median absolute deviation estimate the standard
+/// deviation of normally distributed values.
+const MAD_SCALE: f64 = 1.4826;
+
+impl Stats {
+    /// Computes the statistics of the given values. Returns `None` if there
+    /// are no values.
+    pub fn of(values: &[f64]) -> Option<Self> {
+        if values.is_empty() {
+            return None;
+        }
+
+        let mut sorted = values.to_vec();
+        sorted.sort_by(f64::total_cmp);
+        let count = sorted.len();
+
+        let mean = sorted.iter().sum::<f64>() / count as f64;
+
+        let median = if count.is_multiple_of(2) {
+            (sorted[count / 2 - 1] + sorted[count / 2]) / 2.0
+        } else {
+            sorted[count / 2]
+        };
+
+        let stddev = if count > 1 {
+            let squares = sorted.iter().map(|value| (value - mean).powi(2));
+            (squares.sum::<f64>() / (count - 1) as f64).sqrt()
+        } else {
+            0.0
+        };
+
+        // The nearest rank: ceil(95% of count), counting from 1.
+        let rank = (count * 95).div_ceil(100);
+
+        let trimmed = (count as f64 * TRIMMED) as usize;
+        let kept = &sorted[trimmed .. count - trimmed];
+        let trimmed_mean = kept.iter().sum::<f64>() / kept.len() as f64;
+
+        Some(Self {
+            mean,
+            median,
+            stddev,
+            min: sorted[0],
+            max: sorted[count - 1],
+            p95: sorted[rank - 1],
+            trimmed_mean,
+        })
+    }
+
+    /// The statistics with their names, in the order they are reported.
+    pub fn named(&self) -> [(&'static str, f64); 7] {
+        [
+            ("mean", self.mean),
+            ("median", self.median),
+            ("stddev", self.stddev),
+            ("min", self.min),
+            ("max", self.max),
+            ("p95", self.p95),
+            ("trimmed_mean", self.trimmed_mean),
+        ]
+    }
+
+    /// The standard deviation relative to the mean, as a percentage. Zero if
+    /// the mean is zero.
+    pub fn relative_stddev(&self) -> f64 {
+        if self.mean == 0.0 {
+            0.0
+        } else {
+            self.stddev / self.mean * 100.0
+        }
+    }
+
+    /// The statistic of the given name, as in [`Stats::named`].
+    pub fn get(&self, name: &str) -> Option<f64> {
+        let named = self.named();
+        let found = named.iter().find(|&&(other, _)| other == name);
+        found.map(|&(_, value)| value)
+    }
+}
+
+/// The median of the values and their median absolute deviation from it,
+/// scaled to estimate the standard deviation. `None` if there are no values.
+pub fn median_deviation(values: &[f64]) -> Option<(f64, f64)> {
+    let median = Stats::of(values)?.median;
+    let deviations = values.iter().map(|value| (value - median).abs());
+    let deviation = Stats::of(&deviations.collect::<Vec<_>>())?.median;
+    Some((median, deviation * MAD_SCALE))
+}
+
+/// The two-sided p-value of Welch's t-test, how likely samples as far apart
+/// as these are if both come from distributions of the same mean. `None` if
+/// either sample has fewer than two values.
+pub fn welch_p_value(left: &[f64], right: &[f64]) -> Option<f64> {
+    if left.len() < 2 || right.len() < 2 {
+        return None;
+    }
+    let (left_stats, right_stats) = (Stats::of(left)?, Stats::of(right)?);
+    let left_variance = left_stats.stddev.powi(2) / left.len() as f64;
+    let right_variance = right_stats.stddev.powi(2) / right.len() as f64;
+    let variance = left_variance + right_variance;
+    let difference = left_stats.mean - right_stats.mean;
+
+    // Samples without any spread differ for sure, unless they are equal.
+    if variance == 0.0 {
+        return Some(if difference == 0.0 { 1.0 } else { 0.0 });
+    }
+    let t = difference / variance.sqrt();
+    // The Welch–Satterthwaite degrees of freedom.
+    let freedom = variance.powi(2)
+        / (left_variance.powi(2) / (left.len() - 1) as f64
+            + right_variance.powi(2) / (right.len() - 1) as f64);
+    Some(t_p_value(t, freedom))
+}
+
+/// The half width of the confidence interval of the mean of the values, at
+/// the given confidence level, relative to the mean. `None` with fewer than
+/// two values, or a mean of zero.
+pub fn relative_interval(values: &[f64], confidence: f64) -> Option<f64> {
+    if values.len() < 2 {
+        return None;
+    }
+    let stats = Stats::of(values)?;
+    if stats.mean == 0.0 {
+        return None;
+    }
+    let error = stats.stddev / (values.len() as f64).sqrt();
+    let t = t_critical(confidence, (values.len() - 1) as f64);
+    Some(t * error / stats.mean.abs())
+}
+
+/// The two-sided p-value of Student's t distribution, how likely a value at
+/// least as far from zero as `t` is.
+fn t_p_value(t: f64, freedom: f64) -> f64 {
+    incomplete_beta(freedom / 2.0, 0.5, freedom / (freedom + t * t))
+}
+
+/// How far from zero Student's t distribution is, in either direction, with
+/// a probability of one minus the confidence level, found by bisection.
+fn t_critical(confidence: f64, freedom: f64) -> f64 {
+    let (mut low, mut high) = (0.0, 1e4);
+    for _ in 0 .. 100 {
+        let middle = (low + high) / 2.0;
+        match t_p_value(middle, freedom) > 1.0 - confidence {
+            true => low = middle,
+            false => high = middle,
+        }
+    }
+    (low + high) / 2.0
+}
+
+/// The regularized incomplete beta function `I_x(a, b)`, by its continued
+/// fraction.
+fn incomplete_beta(a: f64, b: f64, x: f64) -> f64 {
+    if x <= 0.0 {
+        return 0.0;
+    }
+    if x >= 1.0 {
+        return 1.0;
+    }
+    let front = (ln_gamma(a + b) - ln_gamma(a) - ln_gamma(b)
+        + a * x.ln()
+        + b * (1.0 - x).ln())
+    .exp();
+    // The fraction converges quickly on this side, the other one follows by
+    // symmetry.
+    match x < (a + 1.0) / (a + b + 2.0) {
+        true => front * beta_fraction(a, b, x) / a,
+        false => 1.0 - front * beta_fraction(b, a, 1.0 - x) / b,
+    }
+}
+
+/// The continued fraction of the incomplete beta function, by the modified
+/// Lentz method.
+fn beta_fraction(a: f64, b: f64, x: f64) -> f64 {
+    const TINY: f64 = 1e-300;
+    const EPSILON: f64 = 1e-14;
+
+    let clamp = |value: f64| if value.abs() < TINY { TINY } else { value };
+    let mut c = 1.0;
+    let mut d = 1.0 / clamp(1.0 - (a + b) * x / (a + 1.0));
+    let mut fraction = d;
+
+    for m in 1 .. 300 {
+        let m = m as f64;
+        let even = m * (b - m) * x / ((a + 2.0 * m - 1.0) * (a + 2.0 * m));
+        d = 1.0 / clamp(1.0 + even * d);
+        c = clamp(1.0 + even / c);
+        fraction *= d * c;
+
+        let odd = -(a + m) * (a + b + m) * x
+            / ((a + 2.0 * m) * (a + 2.0 * m + 1.0));
+        d = 1.0 / clamp(1.0 + odd * d);
+        c = clamp(1.0 + odd / c);
+        let step = d * c;
+        fraction *= step;
+        if (step - 1.0).abs() < EPSILON {
+            break;
+        }
+    }
+    fraction
+}
+
+/// The natural logarithm of the gamma function of a positive number, by the
+/// Lanczos approximation.
+fn ln_gamma(x: f64) -> f64 {
+    const COEFFICIENTS: [f64; 9] = [
+        0.999_999_999_999_809_9,
+        676.520_368_121_885_1,
+        -1_259.139_216_722_402_8,
+        771.323_428_777_653_1,
+        -176.615_029_162_140_6,
+        12.507_343_278_686_905,
+        -0.138_571_095_265_720_12,
+        9.984_369_578_019_572e-6,
+        1.505_632_735_149_311_6e-7,
+    ];
+
+    let x = x - 1.0;
+    let mut sum = COEFFICIENTS[0];
+    for (index, &coefficient) in COEFFICIENTS.iter().enumerate().skip(1) {
+        sum += coefficient / (x + index as f64);
+    }
+    let t = x + 7.5;
+    0.5 * (2.0 * std::f64::consts::PI).ln() + (x + 0.5) * t.ln() - t + sum.ln()
+}
@@ -0,0 +1,330 @@
+//! The sort functions being compared.
+
+use crate::{elements::Element, phase};
+use mergesort_cmp::{
+    parallel::{self, Cancellation, ExecutionTrace},
+    sequential,
+};
+use std::{cmp::Ordering, sync::Arc};
+
+pub use mergesort_cmp::bench::Sorter;
+
+/// A benchmark target.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Target {
+    /// The sequential merge sort.
+    Sequential,
+    /// The sequential merge sort, galloping through runs when merging.
+    Galloping,
+    /// The parallel merge sort, 1 thread per logical CPU.
+    ParallelLogical,
+    /// The parallel merge sort, 1 thread per physical CPU, but no more than
+    /// the logical CPUs assumed.
+    ParallelPhysical,
+    /// The parallel merge sort, 2 threads per logical CPU.
+    Parallel2xLogical,
+    /// The parallel merge sort, 4 threads per logical CPU.
+    Parallel4xLogical,
+    /// The standard library's stable sort, as a baseline.
+    StdSort,
+    /// The standard library's unstable sort, as a baseline.
+    StdSortUnstable,
+    /// Rayon's parallel stable sort, as a baseline.
+    #[cfg(feature = "rayon")]
+    RayonParSort,
+    /// Rayon's parallel unstable sort, as a baseline.
+    #[cfg(feature = "rayon")]
+    RayonParSortUnstable,
+    /// The `timsort` crate's port of Python's sort, as a baseline.
+    #[cfg(feature = "timsort")]
+    Timsort,
+    /// The `glidesort` crate's stable sort, as a baseline.
+    #[cfg(feature = "glidesort")]
+    Glidesort,
+    /// The GPU backend.
+    #[cfg(feature = "gpu")]
+    Gpu,
+}
+
+impl Target {
+    /// Every target, in the order they run by default.
+    pub const ALL: &'static [Self] = &[
+        Target::Sequential,
+        Target::Galloping,
+        Target::ParallelLogical,
+        Target::ParallelPhysical,
+        Target::Parallel2xLogical,
+        Target::Parallel4xLogical,
+        Target::StdSort,
+        Target::StdSortUnstable,
+        #[cfg(feature = "rayon")]
+        Target::RayonParSort,
+        #[cfg(feature = "rayon")]
+        Target::RayonParSortUnstable,
+        #[cfg(feature = "timsort")]
+        Target::Timsort,
+        #[cfg(feature = "glidesort")]
+        Target::Glidesort,
+        #[cfg(feature = "gpu")]
+        Target::Gpu,
+    ];
+
+    /// The name the target is selected by and reported as.
+    pub fn name(self) -> &'static str {
+        match self {
+            Target::Sequential => "sequential",
+            Target::Galloping => "galloping",
+            Target::ParallelLogical => "parallel_logical",
+            Target::ParallelPhysical => "parallel_physical",
+            Target::Parallel2xLogical => "parallel_2x_logical",
+            Target::Parallel4xLogical => "parallel_4x_logical",
+            Target::StdSort => "std_sort",
+            Target::StdSortUnstable => "std_sort_unstable",
+            #[cfg(feature = "rayon")]
+            Target::RayonParSort => "rayon_par_sort",
+            #[cfg(feature = "rayon")]
+            Target::RayonParSortUnstable => "rayon_par_sort_unstable",
+            #[cfg(feature = "timsort")]
+            Target::Timsort => "timsort",
+            #[cfg(feature = "glidesort")]
+            Target::Glidesort => "glidesort",
+            #[cfg(feature = "gpu")]
+            Target::Gpu => "gpu",
+        }
+    }
+
+    /// Finds the target of the given name.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|target| target.name() == name)
+    }
+
+    /// Whether the target depends on the number of logical CPUs, and so is
+    /// run at every count of a sweep.
+    pub fn uses_threads(self) -> bool {
+        matches!(
+            self,
+            Target::ParallelLogical
+                | Target::Parallel2xLogical
+                | Target::Parallel4xLogical
+        )
+    }
+
+    /// Whether the target compares through the comparison function it is
+    /// given, so its comparisons can be counted.
+    pub fn uses_compare(self) -> bool {
+        match self {
+            #[cfg(feature = "gpu")]
+            Target::Gpu => false,
+            _ => true,
+        }
+    }
+
+    /// Whether the target is a merge sort whose merge can be timed alone,
+    /// with [`Target::merger_by`].
+    pub fn merges(self) -> bool {
+        matches!(
+            self,
+            Target::Sequential
+                | Target::Galloping
+                | Target::ParallelLogical
+                | Target::ParallelPhysical
+                | Target::Parallel2xLogical
+                | Target::Parallel4xLogical
+        )
+    }
+
+    /// Builds the sort function of this target, sorting in the order of the
+    /// elements. `threads` stands for the number of logical CPUs.
+    pub fn sorter<T>(self, threads: usize) -> Sorter<T>
+    where
+        T: Element,
+    {
+        self.sorter_by(threads, T::compare, &Cancellation::new())
+    }
+
+    /// Builds the sort function of this target, sorting with the given
+    /// comparison function, which must be the order of the elements. Targets
+    /// that do not [use it](Target::uses_compare) ignore it. The parallel
+    /// merge sorts give up once the cancellation is cancelled; the other
+    /// targets ignore it.
+    pub fn sorter_by<T, C>(
+        self,
+        threads: usize,
+        compare: C,
+        cancellation: &Cancellation,
+    ) -> Sorter<T>
+    where
+        T: Element,
+        C: Fn(&T, &T) -> Ordering + Clone + Send + Sync + 'static,
+    {
+        match self {
+            Target::Sequential => Box::new(move |array| {
+                sequential::sort_by(array, compare.clone())
+            }),
+
+            Target::Galloping => Box::new(move |array| {
+                sequential::sort_galloping_by(array, compare.clone())
+            }),
+
+            Target::ParallelLogical => {
+                parallel_sorter(threads, compare, cancellation)
+            },
+
+            Target::ParallelPhysical => {
+                let threads = physical_threads(threads);
+                parallel_sorter(threads, compare, cancellation)
+            },
+
+            Target::Parallel2xLogical => {
+                parallel_sorter(threads * 2, compare, cancellation)
+            },
+
+            Target::Parallel4xLogical => {
+                parallel_sorter(threads * 4, compare, cancellation)
+            },
+
+            Target::StdSort => Box::new(move |array| {
+                let mut sorted = array.to_vec();
+                sorted.sort_by(compare.clone());
+                sorted
+            }),
+
+            Target::StdSortUnstable => Box::new(move |array| {
+                let mut sorted = array.to_vec();
+                sorted.sort_unstable_by(compare.clone());
+                sorted
+            }),
+
+            #[cfg(feature = "rayon")]
+            Target::RayonParSort => Box::new(move |array| {
+                use rayon::slice::ParallelSliceMut;
+                let mut sorted = array.to_vec();
+                sorted.par_sort_by(compare.clone());
+                sorted
+            }),
+
+            #[cfg(feature = "rayon")]
+            Target::RayonParSortUnstable => Box::new(move |array| {
+                use rayon::slice::ParallelSliceMut;
+                let mut sorted = array.to_vec();
+                sorted.par_sort_unstable_by(compare.clone());
+                sorted
+            }),
+
+            #[cfg(feature = "timsort")]
+            Target::Timsort => Box::new(move |array| {
+                let mut sorted = array.to_vec();
+                timsort::sort_by(&mut sorted, compare.clone());
+                sorted
+            }),
+
+            #[cfg(feature = "glidesort")]
+            Target::Glidesort => Box::new(move |array| {
+                let mut sorted = array.to_vec();
+                glidesort::sort_by(&mut sorted, compare.clone());
+                sorted
+            }),
+
+            #[cfg(feature = "gpu")]
+            Target::Gpu => {
+                let sorter = mergesort_cmp::gpu::GpuSorter::new();
+                Box::new(move |array| T::gpu_sort(&sorter, array))
+            },
+        }
+    }
+
+    /// Builds the merge of this target alone, merging the two halves of an
+    /// array split at its [middle](phase::middle), each already sorted with
+    /// the given comparison function. `None` for the targets that do not
+    /// [merge](Target::merges). The parallel merges give up once the
+    /// cancellation is cancelled.
+    pub fn merger_by<T, C>(
+        self,
+        threads: usize,
+        compare: C,
+        cancellation: &Cancellation,
+    ) -> Option<Sorter<T>>
+    where
+        T: Element,
+        C: Fn(&T, &T) -> Ordering + Clone + Send + Sync + 'static,
+    {
+        let halves = |array: &Arc<[T]>| phase::middle(array.len());
+        match self {
+            Target::Sequential => Some(Box::new(move |array| {
+                let (lower, upper) = array.split_at(halves(array));
+                sequential::merge_by(lower, upper, compare.clone())
+            })),
+
+            Target::Galloping => Some(Box::new(move |array| {
+                let (lower, upper) = array.split_at(halves(array));
+                sequential::merge_galloping_by(lower, upper, compare.clone())
+            })),
+
+            _ => {
+                let mut options = parallel::custom_order(compare);
+                match self {
+                    Target::ParallelLogical => options.threads(threads),
+                    Target::ParallelPhysical => {
+                        options.threads(physical_threads(threads))
+                    },
+                    Target::Parallel2xLogical => options.threads(threads * 2),
+                    Target::Parallel4xLogical => options.threads(threads * 4),
+                    _ => return None,
+                };
+                options.cancellation(cancellation);
+                Some(Box::new(move |array| {
+                    options.merge_halves(array, halves(array))
+                }))
+            },
+        }
+    }
+
+    /// Sorts the array once more, recording how the parallel merge sort split
+    /// it among threads. `None` for the targets that are not the parallel
+    /// merge sort.
+    pub fn trace<T>(
+        self,
+        threads: usize,
+        array: &Arc<[T]>,
+    ) -> Option<ExecutionTrace>
+    where
+        T: Element,
+    {
+        let mut options = parallel::custom_order(T::compare);
+        match self {
+            Target::ParallelLogical => options.threads(threads),
+            Target::ParallelPhysical => {
+                options.threads(physical_threads(threads))
+            },
+            Target::Parallel2xLogical => options.threads(threads * 2),
+            Target::Parallel4xLogical => options.threads(threads * 4),
+            _ => return None,
+        };
+        Some(options.sort_traced(array).1)
+    }
+}
+
+/// The threads of the target with 1 thread per physical CPU, given the
+/// number of logical CPUs assumed. The physical CPUs of the whole machine are
+/// capped at it, since they cannot outnumber the logical ones, so pinning to
+/// a few CPUs with `--cpus` does not leave a thread for every core of the
+/// machine.
+fn physical_threads(threads: usize) -> usize {
+    num_cpus::get_physical().min(threads)
+}
+
+/// A parallel sort with the given number of threads, given up once the
+/// cancellation is cancelled.
+fn parallel_sorter<T, C>(
+    threads: usize,
+    compare: C,
+    cancellation: &Cancellation,
+) -> Sorter<T>
+where
+    T: Element,
+    C: Fn(&T, &T) -> Ordering + Send + Sync + 'static,
+{
+    let mut options = parallel::custom_order(compare);
+    options.threads(threads).cancellation(cancellation);
+    Box::new(move |array| options.sort(array))
+}
@@ -0,0 +1,107 @@
+//! Checking that the targets actually sort.
+
+use crate::elements::Element;
+use mergesort_cmp::verify;
+use std::{error::Error, fmt};
+
+/// What is wrong with a sorted case.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Failure {
+    /// The output has a different length than the input.
+    Length {
+        /// The length of the input.
+        expected: usize,
+        /// The length of the output.
+        found: usize,
+    },
+    /// The element at this index is less than the one before it.
+    Unsorted {
+        /// The index of the out of order element.
+        index: usize,
+    },
+    /// The element at this index is not consistent anymore.
+    Corrupted {
+        /// The index of the corrupted element.
+        index: usize,
+    },
+    /// The output is sorted, but its elements are not the input's.
+    NotPermutation,
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Failure::Length { expected, found } => write!(
+                fmt,
+                "expected {} elements, found {}",
+                expected, found
+            ),
+            Failure::Unsorted { index } => write!(
+                fmt,
+                "element {} is less than the one before it",
+                index
+            ),
+            Failure::Corrupted { index } => {
+                write!(fmt, "element {} is corrupted", index)
+            },
+            Failure::NotPermutation => {
+                fmt.write_str("elements are not a permutation of the input")
+            },
+        }
+    }
+}
+
+/// A target that sorted a case wrong.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerifyError {
+    /// The seed of the run.
+    pub seed: u64,
+    /// The case set of the case.
+    pub set: &'static str,
+    /// The index of the case in its set.
+    pub case: usize,
+    /// The target that sorted it.
+    pub target: String,
+    /// What is wrong with the output.
+    pub failure: Failure,
+}
+
+impl fmt::Display for VerifyError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            fmt,
+            "target {} failed case {} of set {} (seed {}): {}",
+            self.target, self.case, self.set, self.seed, self.failure
+        )
+    }
+}
+
+impl Error for VerifyError {}
+
+/// Checks that the output is the input, sorted in the order of the elements.
+pub fn check<T>(input: &[T], output: &[T]) -> Result<(), Failure>
+where
+    T: Element,
+{
+    if input.len() != output.len() {
+        return Err(Failure::Length {
+            expected: input.len(),
+            found: output.len(),
+        });
+    }
+
+    if let Some(index) = output.iter().position(|element| !element.intact()) {
+        return Err(Failure::Corrupted { index });
+    }
+
+    if let Some(index) = verify::find_first_unsorted_pair(output, T::compare) {
+        return Err(Failure::Unsorted { index: index + 1 });
+    }
+
+    // Elements that compare equal may come in any order, so they are told
+    // apart by their identity.
+    match verify::is_permutation_of(output, input, T::identity) {
+        true => Ok(()),
+        false => Err(Failure::NotPermutation),
+    }
+}
@@ -0,0 +1,49 @@
+//! The timeout of the targets: a thread cancelling the sort running once the
+//! deadline passes, so a single huge case cannot overrun `--timeout`. Only the
+//! parallel merge sorts can be cancelled; the others finish the sort running.
+
+use mergesort_cmp::parallel::{Cancellation, Cancelled};
+use std::{
+    panic::{self, AssertUnwindSafe},
+    sync::mpsc::{self, RecvTimeoutError, Sender},
+    thread,
+    time::Instant,
+};
+
+/// Cancels the sorts of a target at a deadline, unless dropped before.
+pub struct Watchdog {
+    /// Dropped to wake the thread up early, telling it to leave the sorts
+    /// alone.
+    _disarm: Sender<()>,
+}
+
+impl Watchdog {
+    /// Starts a thread cancelling the sorts of the given handle at the
+    /// deadline.
+    pub fn arm(cancellation: &Cancellation, deadline: Instant) -> Self {
+        let (disarm, disarmed) = mpsc::channel::<()>();
+        let cancellation = cancellation.clone();
+        thread::spawn(move || {
+            let timeout = deadline.saturating_duration_since(Instant::now());
+            if let Err(RecvTimeoutError::Timeout) =
+                disarmed.recv_timeout(timeout)
+            {
+                cancellation.cancel();
+            }
+        });
+        Self { _disarm: disarm }
+    }
+}
+
+/// Runs a task sorting with a target, `None` if the sort was cancelled past
+/// the deadline. Other panics go on.
+pub fn unless_cancelled<R, G>(task: G) -> Option<R>
+where
+    G: FnOnce() -> R,
+{
+    match panic::catch_unwind(AssertUnwindSafe(task)) {
+        Ok(result) => Some(result),
+        Err(payload) if payload.is::<Cancelled>() => None,
+        Err(payload) => panic::resume_unwind(payload),
+    }
+}
@@ -0,0 +1,59 @@
+//! The tree of the nodes of a trace as text, one node per line.
+
+use crate::{duration, threads};
+use mergesort_cmp::trace::{MergeNode, MergeTree};
+use std::fmt::{self, Write as _};
+
+/// Renders the tree: every node with its range, thread and times, under the
+/// node that split it.
+pub fn render(tree: &MergeTree) -> String {
+    let mut text = String::new();
+    // Writing to a string does not fail.
+    let _ = writeln!(
+        text,
+        "{} elements, {}, {}",
+        tree.root.range.len(),
+        threads(tree),
+        duration(tree.span())
+    );
+    let _ = write_node(&mut text, &tree.root, "", "");
+    text
+}
+
+/// Writes the node after the given prefix, and its halves after the prefix
+/// of its children.
+fn write_node(
+    text: &mut String,
+    node: &MergeNode,
+    prefix: &str,
+    children: &str,
+) -> fmt::Result {
+    write!(
+        text,
+        "{}{}..{}  thread {}  {}",
+        prefix,
+        node.range.start,
+        node.range.end,
+        node.thread,
+        duration(node.duration())
+    )?;
+    if let Some(merge) = node.merge_duration() {
+        write!(text, " (merge {})", duration(merge))?;
+    }
+    writeln!(text)?;
+
+    let count = node.halves.len();
+    for (index, half) in node.halves.iter().enumerate() {
+        let (branch, indent) = match index + 1 == count {
+            true => ("`-- ", "    "),
+            false => ("|-- ", "|   "),
+        };
+        write_node(
+            text,
+            half,
+            &format!("{}{}", children, branch),
+            &format!("{}{}", children, indent),
+        )?;
+    }
+    Ok(())
+}
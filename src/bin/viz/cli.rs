@@ -0,0 +1,281 @@
+//! Parsing of the command line arguments.
+
+use std::{error::Error, fmt, path::PathBuf, str::FromStr};
+
+/// What the binary was asked to do.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    /// Renders a recorded trace.
+    Render(RenderConfig),
+    /// Records the trace of a sort.
+    Record(RecordConfig),
+    /// Prints the usage.
+    Help,
+}
+
+/// How a trace is rendered.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenderConfig {
+    /// What the trace is rendered as.
+    pub format: Format,
+    /// Where the trace is read from. `None` means stdin.
+    pub input: Option<PathBuf>,
+    /// Where the rendering is written to. `None` means stdout.
+    pub output: Option<PathBuf>,
+}
+
+/// What sort is recorded, and how.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecordConfig {
+    /// The number of elements sorted.
+    pub size: usize,
+    /// The threads of the parallel sort, or `None` for the sequential sort.
+    pub threads: Option<usize>,
+    /// The depth of the recursion of the sequential sort that is recorded.
+    pub depth: usize,
+    /// The seed of the random elements.
+    pub seed: u64,
+    /// Where the trace is written to. `None` means stdout.
+    pub output: Option<PathBuf>,
+}
+
+/// What a trace can be rendered as.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// A timeline of the nodes, a row per depth, colored by thread.
+    Svg,
+    /// The tree of the nodes, one per line.
+    Ascii,
+}
+
+impl Format {
+    /// Every format.
+    pub const ALL: &'static [Self] = &[Format::Svg, Format::Ascii];
+
+    /// The name the format is selected by.
+    pub fn name(self) -> &'static str {
+        match self {
+            Format::Svg => "svg",
+            Format::Ascii => "ascii",
+        }
+    }
+
+    /// Finds the format of the given name.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|format| format.name() == name)
+    }
+}
+
+/// An invalid command line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CliError {
+    /// What is wrong about it.
+    message: String,
+}
+
+impl CliError {
+    /// Creates an error with the given message.
+    fn new<S>(message: S) -> Self
+    where
+        S: Into<String>,
+    {
+        Self { message: message.into() }
+    }
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.write_str(&self.message)
+    }
+}
+
+impl Error for CliError {}
+
+/// The usage of the binary, as printed by `--help`.
+pub fn usage() -> String {
+    let formats = Format::ALL.iter().map(|format| format.name());
+    let formats = formats.collect::<Vec<_>>();
+
+    format!(
+        "Renders the recorded merge tree of a sort.
+
+Usage: mergesort-viz [OPTIONS] [TRACE]
+       mergesort-viz record [RECORD OPTIONS]
+
+Reads the trace from TRACE, or from stdin without it or with -, in the text
+format of mergesort_cmp::trace::MergeTree.
+
+Options:
+    --format <FORMAT>      What the trace is rendered as [default: svg]
+                           Available: {}
+    --output <FILE>        Writes the rendering to FILE [default: stdout]
+    -h, --help             Prints this message
+
+Record options:
+    --size <N>             Number of random elements sorted
+                           [default: 100000]
+    --threads <N>          Threads of the parallel sort [default: logical
+                           CPUs]
+    --sequential           Records the sequential sort instead
+    --depth <N>            Depth of the recursion of the sequential sort
+                           that is recorded [default: 6]
+    --seed <SEED>          Seed of the elements [default: 0]
+    --output <FILE>        Writes the trace to FILE [default: stdout]
+",
+        formats.join(", "),
+    )
+}
+
+/// Parses the arguments, the binary left out.
+pub fn parse<I>(args: I) -> Result<Command, CliError>
+where
+    I: IntoIterator<Item = String>,
+{
+    let mut args = args.into_iter().peekable();
+    match args.peek().map(String::as_str) {
+        Some("record") => {
+            args.next();
+            parse_record(args)
+        },
+        _ => parse_render(args),
+    }
+}
+
+/// Parses the arguments of rendering.
+fn parse_render<I>(mut args: I) -> Result<Command, CliError>
+where
+    I: Iterator<Item = String>,
+{
+    let mut format = Format::Svg;
+    let mut input = None;
+    let mut output = None;
+
+    while let Some(arg) = args.next() {
+        let (option, inline) = split_option(arg);
+
+        let mut value = || {
+            inline.clone().or_else(|| args.next()).ok_or_else(|| {
+                CliError::new(format!("missing value for {}", option))
+            })
+        };
+
+        match option.as_str() {
+            "-h" | "--help" => return Ok(Command::Help),
+            "--format" => {
+                let name = value()?;
+                format = Format::from_name(name.trim()).ok_or_else(|| {
+                    CliError::new(format!("unknown format {:?}", name))
+                })?;
+            },
+            "--output" => output = Some(PathBuf::from(value()?)),
+            // A lone dash is stdin, like no file at all.
+            "-" if input.is_none() => input = Some(None),
+            path if !path.starts_with('-') && input.is_none() => {
+                input = Some(Some(PathBuf::from(path)))
+            },
+            _ => {
+                return Err(CliError::new(format!(
+                    "unexpected argument {:?}",
+                    option
+                )))
+            },
+        }
+    }
+
+    Ok(Command::Render(RenderConfig {
+        format,
+        input: input.flatten(),
+        output,
+    }))
+}
+
+/// Parses the arguments of the `record` subcommand, after its name.
+fn parse_record<I>(mut args: I) -> Result<Command, CliError>
+where
+    I: Iterator<Item = String>,
+{
+    let mut size = 100_000;
+    let mut threads = None;
+    let mut sequential = false;
+    let mut depth = 6;
+    let mut seed = 0;
+    let mut output = None;
+
+    while let Some(arg) = args.next() {
+        let (option, inline) = split_option(arg);
+
+        let mut value = || {
+            inline.clone().or_else(|| args.next()).ok_or_else(|| {
+                CliError::new(format!("missing value for {}", option))
+            })
+        };
+
+        match option.as_str() {
+            "-h" | "--help" => return Ok(Command::Help),
+            "--size" => size = parse_number(&option, &value()?)?,
+            "--threads" => {
+                threads = Some(parse_positive(&option, &value()?)?)
+            },
+            "--sequential" if inline.is_none() => sequential = true,
+            "--sequential" => {
+                return Err(CliError::new("--sequential does not take a value"))
+            },
+            "--depth" => depth = parse_number(&option, &value()?)?,
+            "--seed" => seed = parse_number(&option, &value()?)?,
+            "--output" => output = Some(PathBuf::from(value()?)),
+            _ => {
+                return Err(CliError::new(format!(
+                    "unexpected argument {:?}",
+                    option
+                )))
+            },
+        }
+    }
+
+    if sequential && threads.is_some() {
+        return Err(CliError::new(
+            "--threads is of the parallel sort, not of --sequential",
+        ));
+    }
+
+    Ok(Command::Record(RecordConfig {
+        size,
+        threads: match sequential {
+            true => None,
+            false => Some(threads.unwrap_or_else(num_cpus::get)),
+        },
+        depth,
+        seed,
+        output,
+    }))
+}
+
+/// Splits an argument into the option and its value, if given inline. Both
+/// `--option value` and `--option=value` are accepted.
+fn split_option(arg: String) -> (String, Option<String>) {
+    match arg.find('=') {
+        Some(index) if arg.starts_with("--") => {
+            (arg[.. index].to_owned(), Some(arg[index + 1 ..].to_owned()))
+        },
+        _ => (arg, None),
+    }
+}
+
+/// Parses the number given to an option.
+fn parse_number<T>(option: &str, value: &str) -> Result<T, CliError>
+where
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    value.parse().map_err(|error| {
+        CliError::new(format!("invalid {} {:?}: {}", option, value, error))
+    })
+}
+
+/// Parses the number given to an option, which must not be zero.
+fn parse_positive(option: &str, value: &str) -> Result<usize, CliError> {
+    match parse_number(option, value)? {
+        0 => Err(CliError::new(format!("{} must be at least 1", option))),
+        number => Ok(number),
+    }
+}
@@ -0,0 +1,121 @@
+//! Renders the recorded merge tree of a sort, to see how the parallel sort
+//! split the array and merged the halves: as an SVG timeline or as a tree of
+//! text. It also records the trees it renders.
+
+mod ascii;
+mod cli;
+mod svg;
+
+use cli::{Command, Format, RecordConfig, RenderConfig};
+use mergesort_cmp::{
+    gen::Pattern,
+    parallel,
+    trace::{self, MergeTree},
+};
+use std::{
+    env,
+    fs,
+    io::{self, Read, Write},
+    path::Path,
+    process::exit,
+    sync::Arc,
+};
+
+fn main() {
+    let result = match cli::parse(env::args().skip(1)) {
+        Ok(Command::Render(config)) => render(&config),
+        Ok(Command::Record(config)) => record(&config),
+        Ok(Command::Help) => {
+            print!("{}", cli::usage());
+            return;
+        },
+        Err(error) => {
+            eprintln!("error: {}", error);
+            eprintln!("Try `--help` for more information.");
+            exit(2);
+        },
+    };
+    if let Err(error) = result {
+        eprintln!("error: {}", error);
+        exit(1);
+    }
+}
+
+/// Reads the trace and writes its rendering.
+fn render(config: &RenderConfig) -> Result<(), String> {
+    let text = match &config.input {
+        Some(path) => fs::read_to_string(path),
+        None => {
+            let mut text = String::new();
+            io::stdin().read_to_string(&mut text).map(|_| text)
+        },
+    };
+    let input = config.input.as_deref();
+    let text = text.map_err(|error| {
+        format!("cannot read {}: {}", name_of(input, "stdin"), error)
+    })?;
+    let tree = text.parse::<MergeTree>().map_err(|error| {
+        format!("invalid trace in {}: {}", name_of(input, "stdin"), error)
+    })?;
+
+    let rendered = match config.format {
+        Format::Svg => svg::render(&tree),
+        Format::Ascii => ascii::render(&tree),
+    };
+    write(config.output.as_deref(), &rendered)
+}
+
+/// Sorts random elements, recording the tree, and writes the trace.
+fn record(config: &RecordConfig) -> Result<(), String> {
+    let array =
+        Pattern::Random.generate_seeded::<i64>(config.size, config.seed);
+    let tree = match config.threads {
+        Some(threads) => {
+            let array: Arc<[i64]> = Arc::from(array);
+            let mut options = parallel::default_order();
+            options.threads(threads);
+            MergeTree::from(&options.sort_traced(&array).1)
+        },
+        None => trace::sort_by(&array, i64::cmp, config.depth).1,
+    };
+    write(config.output.as_deref(), &tree.to_string())
+}
+
+/// Writes the text to the file, or to stdout if there is none.
+fn write(path: Option<&Path>, text: &str) -> Result<(), String> {
+    let written = match path {
+        Some(path) => fs::write(path, text),
+        None => io::stdout().lock().write_all(text.as_bytes()),
+    };
+    written.map_err(|error| {
+        format!("cannot write {}: {}", name_of(path, "stdout"), error)
+    })
+}
+
+/// Names a file, or the standard stream if there is none.
+fn name_of(path: Option<&Path>, stream: &str) -> String {
+    match path {
+        Some(path) => path.display().to_string(),
+        None => stream.to_owned(),
+    }
+}
+
+/// How many threads ran the tree, as a phrase.
+fn threads(tree: &MergeTree) -> String {
+    match tree.threads() {
+        1 => "1 thread".to_owned(),
+        threads => format!("{} threads", threads),
+    }
+}
+
+/// Formats nanoseconds with a unit that keeps them short.
+fn duration(nanos: u64) -> String {
+    match nanos {
+        0 ..= 9_999 => format!("{} ns", nanos),
+        10_000 ..= 9_999_999 => format!("{:.1} us", nanos as f64 / 1e3),
+        10_000_000 ..= 9_999_999_999 => {
+            format!("{:.1} ms", nanos as f64 / 1e6)
+        },
+        _ => format!("{:.2} s", nanos as f64 / 1e9),
+    }
+}
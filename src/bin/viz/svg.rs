@@ -0,0 +1,206 @@
+//! An SVG timeline of the nodes of a trace: a bar per node from its start to
+//! its end, a row per depth, colored by the thread that ran it, the merge of
+//! its halves darker, like a flame chart with time going right.
+
+use crate::{duration, threads};
+use mergesort_cmp::trace::{MergeNode, MergeTree};
+use std::fmt::{self, Write as _};
+
+/// The width of the whole timeline, in pixels.
+const WIDTH: f64 = 1200.0;
+/// The space around the rows: left, right, top and bottom. The bottom has
+/// the axis and the legend.
+const MARGINS: (f64, f64, f64, f64) = (20.0, 20.0, 50.0, 70.0);
+/// The height of the row of a depth, in pixels.
+const ROW: f64 = 22.0;
+/// How many ticks the time axis has, besides zero.
+const TICKS: u32 = 8;
+/// The least width of a bar labeled with its range, in pixels.
+const LABEL_WIDTH: f64 = 70.0;
+
+/// The colors of the threads, in order, repeating if there are more threads.
+const COLORS: &[&str] = &[
+    "#1f77b4", "#ff7f0e", "#2ca02c", "#d62728", "#9467bd", "#8c564b",
+    "#e377c2", "#7f7f7f", "#bcbd22", "#17becf",
+];
+
+/// Where the nodes go in the timeline.
+struct Timeline {
+    /// When the root node started, at the left of the rows.
+    start: u64,
+    /// How long the root node took, the width of the rows.
+    span: u64,
+    /// The left of the rows.
+    left: f64,
+    /// The right of the rows.
+    right: f64,
+    /// The top of the first row.
+    top: f64,
+}
+
+impl Timeline {
+    /// The horizontal coordinate of a time.
+    fn x_of(&self, time: u64) -> f64 {
+        let offset = time.saturating_sub(self.start) as f64;
+        self.left + offset / self.span.max(1) as f64 * (self.right - self.left)
+    }
+
+    /// The vertical coordinate of the top of the row of a depth.
+    fn y_of(&self, depth: usize) -> f64 {
+        self.top + depth as f64 * ROW
+    }
+}
+
+/// Renders the timeline of the tree.
+pub fn render(tree: &MergeTree) -> String {
+    let (left, right, top, _) = MARGINS;
+    let timeline = Timeline {
+        start: tree.root.start,
+        span: tree.span(),
+        left,
+        right: WIDTH - right,
+        top,
+    };
+    let mut svg = String::new();
+    // Writing to a string does not fail.
+    let _ = write_timeline(&mut svg, tree, &timeline);
+    svg
+}
+
+/// Every part of the timeline.
+fn write_timeline(
+    svg: &mut String,
+    tree: &MergeTree,
+    timeline: &Timeline,
+) -> fmt::Result {
+    let (_, _, _, bottom) = MARGINS;
+    let rows_bottom = timeline.y_of(tree.depth() + 1);
+    let height = rows_bottom + bottom;
+    writeln!(
+        svg,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" \
+         height=\"{h}\" viewBox=\"0 0 {w} {h}\" font-family=\"sans-serif\" \
+         font-size=\"11\">",
+        w = WIDTH,
+        h = height
+    )?;
+    writeln!(svg, "<rect width=\"100%\" height=\"100%\" fill=\"white\"/>")?;
+    writeln!(
+        svg,
+        "<text x=\"{:.1}\" y=\"30\" text-anchor=\"middle\" font-size=\"16\">\
+         Merge tree of {} elements, {}, {}</text>",
+        WIDTH / 2.0,
+        tree.root.range.len(),
+        threads(tree),
+        duration(tree.span())
+    )?;
+
+    for tick in 0 ..= TICKS {
+        let time = timeline.start + tree.span() * u64::from(tick)
+            / u64::from(TICKS);
+        let x = timeline.x_of(time);
+        writeln!(
+            svg,
+            "<line x1=\"{x:.1}\" y1=\"{:.1}\" x2=\"{x:.1}\" y2=\"{:.1}\" \
+             stroke=\"#ddd\"/>",
+            timeline.top,
+            rows_bottom,
+            x = x
+        )?;
+        writeln!(
+            svg,
+            "<text x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"middle\">{}</text>",
+            x,
+            rows_bottom + 16.0,
+            duration(time - timeline.start)
+        )?;
+    }
+
+    for node in tree.nodes() {
+        write_node(svg, node, timeline)?;
+    }
+
+    let y = rows_bottom + 36.0;
+    for thread in 0 .. tree.threads() {
+        let x = timeline.left + thread as f64 * 90.0;
+        writeln!(
+            svg,
+            "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"12\" height=\"12\" \
+             fill=\"{}\"/>",
+            x,
+            y,
+            COLORS[thread % COLORS.len()]
+        )?;
+        writeln!(
+            svg,
+            "<text x=\"{:.1}\" y=\"{:.1}\">thread {}</text>",
+            x + 18.0,
+            y + 10.0,
+            thread
+        )?;
+    }
+
+    writeln!(svg, "</svg>")
+}
+
+/// The bar of a node, with its details in a tooltip.
+fn write_node(
+    svg: &mut String,
+    node: &MergeNode,
+    timeline: &Timeline,
+) -> fmt::Result {
+    let x = timeline.x_of(node.start);
+    // A node too short to be seen still gets a sliver.
+    let width = (timeline.x_of(node.end) - x).max(0.5);
+    let y = timeline.y_of(node.depth);
+    let color = COLORS[node.thread % COLORS.len()];
+
+    writeln!(svg, "<g>")?;
+    write!(
+        svg,
+        "<title>{}..{}, thread {}, {}",
+        node.range.start,
+        node.range.end,
+        node.thread,
+        duration(node.duration())
+    )?;
+    if let Some(merge) = node.merge_duration() {
+        write!(svg, ", merge {}", duration(merge))?;
+    }
+    writeln!(svg, "</title>")?;
+    writeln!(
+        svg,
+        "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" \
+         fill=\"{}\" fill-opacity=\"0.6\" stroke=\"white\" \
+         stroke-width=\"0.5\"/>",
+        x,
+        y,
+        width,
+        ROW - 2.0,
+        color
+    )?;
+    if let Some(merge) = node.merge {
+        let merge_x = timeline.x_of(merge);
+        writeln!(
+            svg,
+            "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" \
+             fill=\"{}\"/>",
+            merge_x,
+            y,
+            (x + width - merge_x).max(0.0),
+            ROW - 2.0,
+            color
+        )?;
+    }
+    if width >= LABEL_WIDTH {
+        writeln!(
+            svg,
+            "<text x=\"{:.1}\" y=\"{:.1}\">{}..{}</text>",
+            x + 4.0,
+            y + ROW - 8.0,
+            node.range.start,
+            node.range.end
+        )?;
+    }
+    writeln!(svg, "</g>")
+}
@@ -0,0 +1,335 @@
+//! Comparison functions decorated to observe the sorts calling them, and
+//! comparison functions for common orders the sorts can be given.
+//!
+//! # Examples
+//! ```rust
+//! use mergesort_cmp::{comparator::CountingComparator, parallel, sequential};
+//! use std::sync::Arc;
+//!
+//! let array: Arc<[i32]> = Arc::from((0 .. 1000).rev().collect::<Vec<_>>());
+//! let counter = CountingComparator::new(i32::cmp);
+//!
+//! sequential::sort_by(&array, counter.function());
+//! let sequential = counter.count();
+//!
+//! counter.reset();
+//! parallel::custom_order(counter.function()).threads(4).sort(&array);
+//! let parallel = counter.count();
+//!
+//! counter.reset();
+//! array.to_vec().sort_by(counter.function());
+//! let std = counter.count();
+//!
+//! assert!(sequential > 0 && parallel > 0 && std > 0);
+//! ```
+
+use std::{
+    cmp::Ordering,
+    collections::HashMap,
+    fmt,
+    hash::Hash,
+    sync::{
+        atomic::{self, AtomicU64},
+        Arc,
+        Mutex,
+    },
+};
+
+/// A comparison function counting how many times it is called. Clones share
+/// the count, so a clone can be given to a sort and the count read from the
+/// original, even while the sort runs on many threads.
+///
+/// Optionally, it also records which elements were compared, see
+/// [`CountingComparator::recording`].
+pub struct CountingComparator<T, F> {
+    /// The comparison function decorated.
+    compare: F,
+    /// How many times it was called.
+    count: Arc<AtomicU64>,
+    /// What records the elements compared, if they are recorded.
+    record: Option<Arc<Mutex<dyn Record<T> + Send>>>,
+}
+
+impl<T, F> CountingComparator<T, F>
+where
+    F: Fn(&T, &T) -> Ordering,
+{
+    /// Decorates the comparison function, counting its calls.
+    pub fn new(compare: F) -> Self {
+        Self { compare, count: Arc::new(AtomicU64::new(0)), record: None }
+    }
+
+    /// Compares the elements with the decorated function, counting the call.
+    pub fn compare(&self, left: &T, right: &T) -> Ordering {
+        self.count.fetch_add(1, atomic::Ordering::Relaxed);
+        if let Some(record) = &self.record {
+            let mut record = record.lock().expect("poisoned record");
+            record.observe(left);
+            record.observe(right);
+        }
+        (self.compare)(left, right)
+    }
+
+    /// How many times the function was called since it was created or
+    /// [reset](CountingComparator::reset), by this comparator or its clones.
+    pub fn count(&self) -> u64 {
+        self.count.load(atomic::Ordering::Relaxed)
+    }
+
+    /// Forgets the calls so far, and the elements recorded.
+    pub fn reset(&self) {
+        self.count.store(0, atomic::Ordering::Relaxed);
+        if let Some(record) = &self.record {
+            record.lock().expect("poisoned record").clear();
+        }
+    }
+
+    /// What was recorded of the elements compared so far. `None` if they are
+    /// not recorded.
+    pub fn compared(&self) -> Option<Compared<T>> {
+        let record = self.record.as_ref()?;
+        Some(record.lock().expect("poisoned record").compared())
+    }
+}
+
+impl<T, F> CountingComparator<T, F>
+where
+    T: Clone + Hash + Eq + Send + 'static,
+    F: Fn(&T, &T) -> Ordering + Clone + Send + 'static,
+{
+    /// Decorates the comparison function, counting its calls and recording
+    /// the least and the greatest element compared, and the element compared
+    /// the most. Recording takes a lock and a copy of the elements on every
+    /// call, so it slows the sorts down much more than counting.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use mergesort_cmp::{comparator::CountingComparator, sequential};
+    ///
+    /// let array = [3, 1, 4, 1, 5, 9, 2, 6];
+    /// let counter = CountingComparator::recording(i32::cmp);
+    ///
+    /// sequential::sort_by(&array, counter.function());
+    /// let compared = counter.compared().unwrap();
+    ///
+    /// assert_eq!(compared.min, Some(1));
+    /// assert_eq!(compared.max, Some(9));
+    /// assert!(compared.most_compared.is_some());
+    /// ```
+    pub fn recording(compare: F) -> Self {
+        let recorder = Recorder {
+            compare: compare.clone(),
+            min: None,
+            max: None,
+            comparisons: HashMap::new(),
+        };
+        Self {
+            compare,
+            count: Arc::new(AtomicU64::new(0)),
+            record: Some(Arc::new(Mutex::new(recorder))),
+        }
+    }
+}
+
+impl<T, F> CountingComparator<T, F>
+where
+    T: 'static,
+    F: Fn(&T, &T) -> Ordering + Clone + Send + Sync + 'static,
+{
+    /// A comparison function calling this one, to be given to the sorts of
+    /// this crate or of the standard library. It shares the count of this
+    /// comparator.
+    pub fn function(
+        &self,
+    ) -> impl Fn(&T, &T) -> Ordering + Clone + Send + Sync + 'static {
+        let comparator = self.clone();
+        move |left: &T, right: &T| comparator.compare(left, right)
+    }
+}
+
+impl<T, F> Clone for CountingComparator<T, F>
+where
+    F: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            compare: self.compare.clone(),
+            count: self.count.clone(),
+            record: self.record.clone(),
+        }
+    }
+}
+
+impl<T, F> fmt::Debug for CountingComparator<T, F> {
+    fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
+        fmtr.debug_struct("CountingComparator")
+            .field("count", &self.count.load(atomic::Ordering::Relaxed))
+            .field("recording", &self.record.is_some())
+            .finish()
+    }
+}
+
+/// What a [recording](CountingComparator::recording) comparator recorded of
+/// the elements it compared.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Compared<T> {
+    /// The least element compared, `None` before any comparison.
+    pub min: Option<T>,
+    /// The greatest element compared, `None` before any comparison.
+    pub max: Option<T>,
+    /// The element compared the most, with how many comparisons it was in.
+    /// Ties go to any of the elements. `None` before any comparison.
+    pub most_compared: Option<(T, u64)>,
+}
+
+/// Records the elements compared.
+trait Record<T> {
+    /// Records an element of a comparison.
+    fn observe(&mut self, element: &T);
+
+    /// Forgets every element recorded.
+    fn clear(&mut self);
+
+    /// What was recorded so far.
+    fn compared(&self) -> Compared<T>;
+}
+
+/// Records the elements compared with a comparison function.
+struct Recorder<T, F> {
+    /// The comparison function, finding the least and the greatest element.
+    compare: F,
+    /// The least element compared.
+    min: Option<T>,
+    /// The greatest element compared.
+    max: Option<T>,
+    /// How many comparisons each element was in.
+    comparisons: HashMap<T, u64>,
+}
+
+impl<T, F> Record<T> for Recorder<T, F>
+where
+    T: Clone + Hash + Eq,
+    F: Fn(&T, &T) -> Ordering,
+{
+    fn observe(&mut self, element: &T) {
+        let compare = &self.compare;
+        if self.min.as_ref().is_none_or(|min| compare(element, min).is_lt()) {
+            self.min = Some(element.clone());
+        }
+        if self.max.as_ref().is_none_or(|max| compare(element, max).is_gt()) {
+            self.max = Some(element.clone());
+        }
+        *self.comparisons.entry(element.clone()).or_insert(0) += 1;
+    }
+
+    fn clear(&mut self) {
+        self.min = None;
+        self.max = None;
+        self.comparisons.clear();
+    }
+
+    fn compared(&self) -> Compared<T> {
+        let most = self.comparisons.iter().max_by_key(|&(_, &count)| count);
+        let most = most.map(|(element, &count)| (element.clone(), count));
+        Compared {
+            min: self.min.clone(),
+            max: self.max.clone(),
+            most_compared: most,
+        }
+    }
+}
+
+/// Compares strings in natural order, the order people sort file names in:
+/// numbers embedded in the strings compare by their values, so `"file2"`
+/// goes before `"file10"`, and the rest compares character by character.
+/// Only ASCII digits make numbers, which can have any number of digits.
+/// Strings equal but for leading zeros, such as `"a01"` and `"a1"`, compare
+/// as plain strings, so the order is still total.
+///
+/// # Examples
+/// ```rust
+/// use mergesort_cmp::comparator;
+/// use std::cmp::Ordering;
+///
+/// assert_eq!(comparator::natural_cmp("file2", "file10"), Ordering::Less);
+/// assert_eq!(comparator::natural_cmp("v1.10", "v1.9"), Ordering::Greater);
+/// assert_eq!(comparator::natural_cmp("a01", "a1"), Ordering::Less);
+/// assert_eq!(comparator::natural_cmp("x7", "x7"), Ordering::Equal);
+/// ```
+pub fn natural_cmp(left: &str, right: &str) -> Ordering {
+    let mut left_rest = left.as_bytes();
+    let mut right_rest = right.as_bytes();
+
+    loop {
+        match (left_rest.first(), right_rest.first()) {
+            // Equal but maybe for leading zeros.
+            (None, None) => return left.cmp(right),
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(left_byte), Some(right_byte))
+                if left_byte.is_ascii_digit() && right_byte.is_ascii_digit() =>
+            {
+                let (left_number, left_after) = split_number(left_rest);
+                let (right_number, right_after) = split_number(right_rest);
+                // Without leading zeros, longer numbers are greater, and
+                // numbers as long compare digit by digit.
+                let ordering = left_number
+                    .len()
+                    .cmp(&right_number.len())
+                    .then_with(|| left_number.cmp(right_number));
+                if ordering.is_ne() {
+                    return ordering;
+                }
+                left_rest = left_after;
+                right_rest = right_after;
+            },
+            // Bytes of UTF-8 compare in the order of their characters.
+            (Some(left_byte), Some(right_byte)) => {
+                if left_byte != right_byte {
+                    return left_byte.cmp(right_byte);
+                }
+                left_rest = &left_rest[1 ..];
+                right_rest = &right_rest[1 ..];
+            },
+        }
+    }
+}
+
+/// A comparison function putting strings in natural order, as
+/// [`natural_cmp`] compares them, for the sorts of this crate or of the
+/// standard library.
+///
+/// # Examples
+/// ```rust
+/// use mergesort_cmp::{comparator, parallel, sequential};
+/// use std::sync::Arc;
+///
+/// let files = ["file10.txt", "file2.txt", "file1.txt", "File3.txt"];
+///
+/// let sorted = sequential::sort_by(&files, comparator::natural_order());
+/// assert_eq!(sorted, ["File3.txt", "file1.txt", "file2.txt", "file10.txt"]);
+///
+/// let files: Arc<[String]> = files.iter().map(|&file| file.into()).collect();
+/// let sorted = parallel::custom_order(comparator::natural_order())
+///     .threads(2)
+///     .sort(&files);
+/// assert_eq!(sorted[3], "file10.txt");
+/// ```
+pub fn natural_order<T>() -> impl Fn(&T, &T) -> Ordering + Copy + Send + Sync
+where
+    T: AsRef<str> + ?Sized,
+{
+    |left: &T, right: &T| natural_cmp(left.as_ref(), right.as_ref())
+}
+
+/// Splits the digits at the start of the bytes from the rest, leaving out
+/// the leading zeros of the digits.
+fn split_number(bytes: &[u8]) -> (&[u8], &[u8]) {
+    let end = bytes
+        .iter()
+        .position(|byte| !byte.is_ascii_digit())
+        .unwrap_or(bytes.len());
+    let (digits, rest) = bytes.split_at(end);
+    let zeros = digits.iter().take_while(|&&digit| digit == b'0').count();
+    (&digits[zeros ..], rest)
+}
@@ -0,0 +1,960 @@
+//! This module provides an external merge sort, for inputs that do not fit in
+//! memory. The input is read in chunks that fit in a memory budget, each chunk
+//! is sorted with the parallel sort and spilled to a temporary file as a
+//! sorted run, and then the runs are merged into the output, many at once.
+//! How records are read and written is up to a [`Codec`].
+//!
+//! # Examples
+//! ```rust
+//! use mergesort_cmp::external::{self, Lines};
+//! use std::io::Cursor;
+//!
+//! let input = "pear\napple\nfig\nbanana\ncherry\ndate\n";
+//! let mut output = Vec::new();
+//!
+//! // A budget this small makes every record a run of its own.
+//! let stats = external::default_order(Lines)
+//!     .memory_budget(1)
+//!     .fan_in(4)
+//!     .sort(Cursor::new(input), &mut output)
+//!     .unwrap();
+//!
+//! let output = String::from_utf8(output).unwrap();
+//! assert_eq!(output, "apple\nbanana\ncherry\ndate\nfig\npear\n");
+//! assert_eq!(stats.records, 6);
+//! assert_eq!(stats.runs, 6);
+//! assert_eq!(stats.merge_passes, 2);
+//! ```
+
+use crate::parallel::{self, SortPool};
+use std::{
+    cmp::Ordering,
+    env,
+    fs::{self, File},
+    io::{self, BufRead, BufReader, Read, Write},
+    iter,
+    mem,
+    panic,
+    path::{Path, PathBuf},
+    process,
+    sync::{
+        atomic::{AtomicUsize, Ordering as AtomicOrdering},
+        mpsc,
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+mod checkpoint;
+mod codec;
+mod compress;
+
+pub use checkpoint::ExternalSorter;
+pub use codec::{Binary, Codec, Lines, Number};
+pub use compress::Compression;
+
+use checkpoint::Progress;
+use compress::RunWriter;
+
+/// The memory budget of a sort, unless set otherwise: 256 MiB.
+const DEFAULT_MEMORY_BUDGET: usize = 256 << 20;
+
+/// How many runs are merged at once, unless set otherwise.
+const DEFAULT_FAN_IN: usize = 64;
+
+/// An external merge sort of records with the default order, see
+/// [`ExternalSortOptions`].
+///
+/// # Examples
+/// ```rust
+/// use mergesort_cmp::external::{self, Binary};
+/// use std::{convert::TryInto, io::Cursor};
+///
+/// let numbers = [-1i64, 5, 91293, 12, -95, 20000, 20001, -12, 7];
+/// let input = numbers.iter().flat_map(|n| n.to_le_bytes());
+/// let input = input.collect::<Vec<_>>();
+/// let mut output = Vec::new();
+///
+/// external::default_order(Binary::<i64>::new())
+///     .memory_budget(32)
+///     .sort(Cursor::new(input), &mut output)
+///     .unwrap();
+///
+/// let sorted = output
+///     .chunks(8)
+///     .map(|bytes| i64::from_le_bytes(bytes.try_into().unwrap()))
+///     .collect::<Vec<_>>();
+/// assert_eq!(sorted, &[-95, -12, -1, 5, 7, 12, 20000, 20001, 91293]);
+/// ```
+pub fn default_order<C>(
+    codec: C,
+) -> ExternalSortOptions<C, impl Fn(&C::Record, &C::Record) -> Ordering>
+where
+    C: Codec,
+    C::Record: Ord,
+{
+    ExternalSortOptions::new(codec, Ord::cmp)
+}
+
+/// An external merge sort of records with the given comparison function, see
+/// [`ExternalSortOptions`].
+///
+/// # Examples
+/// ```rust
+/// use mergesort_cmp::external::{self, Lines};
+/// use std::io::Cursor;
+///
+/// let input = "10\n-3\n7\n";
+/// let mut output = Vec::new();
+///
+/// let compare = |left: &String, right: &String| {
+///     left.parse::<i32>().unwrap().cmp(&right.parse().unwrap())
+/// };
+/// external::custom_order(Lines, compare)
+///     .sort(Cursor::new(input), &mut output)
+///     .unwrap();
+///
+/// assert_eq!(output, b"-3\n7\n10\n");
+/// ```
+pub fn custom_order<C, F>(codec: C, compare: F) -> ExternalSortOptions<C, F>
+where
+    C: Codec,
+    F: Fn(&C::Record, &C::Record) -> Ordering,
+{
+    ExternalSortOptions::new(codec, compare)
+}
+
+/// Merges files whose records are each already sorted with the given
+/// comparison function into the output, without sorting them again, such as
+/// logs of different machines into one. The merge is stable: records of
+/// earlier inputs go before equal records of later ones. Returns how many
+/// records were merged.
+///
+/// # Errors
+/// Fails if opening or reading an input or writing the output fails,
+/// including on records the codec cannot read, and on inputs that turn out
+/// not to be sorted. The output may have been partly written then.
+///
+/// # Examples
+/// ```rust
+/// use mergesort_cmp::external::{self, Lines};
+/// use std::{env, fs, process};
+///
+/// let dir = env::temp_dir().join(format!("merge-doc-{}", process::id()));
+/// fs::create_dir_all(&dir).unwrap();
+/// let inputs = [dir.join("a.log"), dir.join("b.log")];
+/// fs::write(&inputs[0], "09:00 a up\n09:05 a busy\n09:30 a down\n").unwrap();
+/// fs::write(&inputs[1], "09:01 b up\n09:05 b idle\n").unwrap();
+///
+/// // Lines compare by their times only.
+/// let time = |left: &String, right: &String| left[.. 5].cmp(&right[.. 5]);
+/// let mut output = Vec::new();
+/// let count = external::merge_files(&inputs, &mut output, Lines, time);
+///
+/// assert_eq!(count.unwrap(), 5);
+/// assert_eq!(
+///     String::from_utf8(output).unwrap(),
+///     "09:00 a up\n09:01 b up\n09:05 a busy\n09:05 b idle\n09:30 a down\n",
+/// );
+///
+/// fs::write(&inputs[1], "09:05 b idle\n09:01 b up\n").unwrap();
+/// let result = external::merge_files(&inputs, Vec::new(), Lines, time);
+/// assert!(result.is_err());
+/// # fs::remove_dir_all(&dir).unwrap();
+/// ```
+pub fn merge_files<P, W, C, F>(
+    inputs: &[P],
+    mut output: W,
+    codec: C,
+    compare: F,
+) -> io::Result<u64>
+where
+    P: AsRef<Path>,
+    W: Write,
+    C: Codec,
+    F: Fn(&C::Record, &C::Record) -> Ordering,
+{
+    let sources = inputs
+        .iter()
+        .map(|path| File::open(path).map(BufReader::new))
+        .collect::<io::Result<Vec<_>>>()?;
+    let count = merge_sources(&codec, sources, &mut output, &compare)?;
+    output.flush()?;
+    Ok(count)
+}
+
+/// Options to configure the external merge sort.
+pub struct ExternalSortOptions<C, F> {
+    /// How records are read and written.
+    codec: C,
+    /// Comparison function.
+    compare: Arc<F>,
+    /// How many bytes the records in memory may take.
+    memory_budget: usize,
+    /// How many runs are merged at once.
+    fan_in: usize,
+    /// On how many threads the chunks are sorted.
+    threads: usize,
+    /// Where the runs are spilled. `None` is the temporary directory of the
+    /// system.
+    temp_dir: Option<PathBuf>,
+    /// How the runs are compressed.
+    compression: Compression,
+}
+
+impl<C, F> ExternalSortOptions<C, F>
+where
+    C: Codec,
+{
+    /// Creates options with the given codec and comparison function and
+    /// everything else set to the defaults.
+    fn new(codec: C, compare: F) -> Self {
+        Self {
+            codec,
+            compare: Arc::new(compare),
+            memory_budget: DEFAULT_MEMORY_BUDGET,
+            fan_in: DEFAULT_FAN_IN,
+            threads: num_cpus::get(),
+            temp_dir: None,
+            compression: Compression::None,
+        }
+    }
+
+    /// Sets how many bytes the records in memory may take, as the codec
+    /// counts them, 256 MiB by default. Sorting a chunk needs room for a
+    /// sorted copy of it, so chunks take up to half of the budget, and at
+    /// least a record.
+    pub fn memory_budget(&mut self, bytes: usize) -> &mut Self {
+        self.memory_budget = bytes;
+        self
+    }
+
+    /// Sets how many runs are merged at once, at least 2, and 64 by default.
+    /// With more runs than that, groups of runs are first merged into longer
+    /// runs, in as many passes as needed. Each run merged keeps a file open.
+    pub fn fan_in(&mut self, runs: usize) -> &mut Self {
+        self.fan_in = runs.max(2);
+        self
+    }
+
+    /// Sets the number of threads sorting each chunk, one per logical CPU by
+    /// default.
+    pub fn threads(&mut self, threads: usize) -> &mut Self {
+        self.threads = threads;
+        self
+    }
+
+    /// Sets the directory where the runs are spilled, instead of the
+    /// temporary directory of the system. Each sort creates a directory of
+    /// its own in it, and removes it when done, even if the sort fails.
+    pub fn temp_dir<P>(&mut self, dir: P) -> &mut Self
+    where
+        P: Into<PathBuf>,
+    {
+        self.temp_dir = Some(dir.into());
+        self
+    }
+
+    /// Sets how the spilled runs are compressed, not at all by default. The
+    /// input and the output are never compressed.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use mergesort_cmp::external::{self, Compression, Lines};
+    /// use std::io::Cursor;
+    ///
+    /// let input = (0 .. 1000).rev().map(|n| format!("record {:04}\n", n));
+    /// let input = input.collect::<String>();
+    ///
+    /// let sort = |compression| {
+    ///     let mut output = Vec::new();
+    ///     let stats = external::default_order(Lines)
+    ///         .memory_budget(16384)
+    ///         .compression(compression)
+    ///         .sort(Cursor::new(&input), &mut output)
+    ///         .unwrap();
+    ///     assert!(output.starts_with(b"record 0000\nrecord 0001\n"));
+    ///     stats.spilled_bytes
+    /// };
+    ///
+    /// let plain = sort(Compression::None);
+    /// assert_eq!(plain, input.len() as u64);
+    ///
+    /// #[cfg(feature = "lz4")]
+    /// assert!(sort(Compression::Lz4) < plain);
+    /// #[cfg(feature = "zstd")]
+    /// assert!(sort(Compression::Zstd { level: 3 }) < plain);
+    /// ```
+    pub fn compression(&mut self, compression: Compression) -> &mut Self {
+        self.compression = compression;
+        self
+    }
+
+    /// Sorts the records of the input into the output. Inputs that fit in a
+    /// single chunk are sorted in memory, without spilling anything. The sort
+    /// is stable: records that compare equal keep their order.
+    ///
+    /// # Errors
+    /// Fails if reading the input, writing the output or using the temporary
+    /// files fails, including on records the codec cannot read. The output
+    /// may have been partly written then.
+    pub fn sort<R, W>(
+        &self,
+        input: R,
+        mut output: W,
+    ) -> io::Result<ExternalSortStats>
+    where
+        R: Read,
+        W: Write,
+        C: Sync,
+        C::Record: Clone + Send + Sync + 'static,
+        F: Fn(&C::Record, &C::Record) -> Ordering + Send + Sync + 'static,
+    {
+        let progress = Progress::new(self.fan_in, self.compression);
+        let mut spill = Spill::temporary(self.temp_parent(), progress);
+        let mut reader = Counted { inner: BufReader::new(input), consumed: 0 };
+        self.execute(&mut reader, &mut output, &mut spill, None)
+    }
+
+    /// Sorts the records of the input into the output like
+    /// [`ExternalSortOptions::sort`], on the threads of the given pool
+    /// instead of spawning new ones, ignoring the thread count of the options.
+    /// Reading the input, sorting chunks and spilling runs overlap, each on a
+    /// thread of its own, the chunks being sorted on the pool, and so chunks
+    /// take up to a quarter of the memory budget instead of half. The merges
+    /// of a pass that are not the last run at once too, as many as the pool
+    /// has threads.
+    ///
+    /// # Errors
+    /// Fails as [`ExternalSortOptions::sort`] does.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use mergesort_cmp::{
+    ///     external::{self, Lines},
+    ///     parallel::SortPool,
+    /// };
+    /// use std::io::Cursor;
+    ///
+    /// let input = (0 .. 5000).rev().map(|n| format!("{:05}\n", n));
+    /// let input = input.collect::<String>();
+    /// let mut output = Vec::new();
+    ///
+    /// let pool = SortPool::new(4);
+    /// let stats = external::default_order(Lines)
+    ///     .memory_budget(16384)
+    ///     .fan_in(8)
+    ///     .sort_in(&pool, Cursor::new(&input), &mut output)
+    ///     .unwrap();
+    ///
+    /// assert!(output.starts_with(b"00000\n00001\n00002\n"));
+    /// assert_eq!(stats.input_bytes, input.len() as u64);
+    /// assert!(stats.runs > 8 && stats.merge_passes > 1);
+    /// assert!(stats.megabytes_per_second() > 0.0);
+    /// ```
+    pub fn sort_in<R, W>(
+        &self,
+        pool: &SortPool,
+        input: R,
+        mut output: W,
+    ) -> io::Result<ExternalSortStats>
+    where
+        R: Read,
+        W: Write,
+        C: Sync,
+        C::Record: Clone + Send + Sync + 'static,
+        F: Fn(&C::Record, &C::Record) -> Ordering + Send + Sync + 'static,
+    {
+        let progress = Progress::new(self.fan_in, self.compression);
+        let mut spill = Spill::temporary(self.temp_parent(), progress);
+        let mut reader = Counted { inner: BufReader::new(input), consumed: 0 };
+        self.execute(&mut reader, &mut output, &mut spill, Some(pool))
+    }
+
+    /// Sorts the records of the input from where the spill got, spilling the
+    /// rest of them, merging the runs, and writing the output. With a pool,
+    /// reading, sorting and spilling overlap, and so do the merges of a pass.
+    fn execute<R, W>(
+        &self,
+        reader: &mut Counted<R>,
+        output: &mut W,
+        spill: &mut Spill,
+        pool: Option<&SortPool>,
+    ) -> io::Result<ExternalSortStats>
+    where
+        R: BufRead,
+        W: Write,
+        C: Sync,
+        C::Record: Clone + Send + Sync + 'static,
+        F: Fn(&C::Record, &C::Record) -> Ordering + Send + Sync + 'static,
+    {
+        // Reading, sorting and spilling at once hold four chunks.
+        let chunk_budget = match pool {
+            Some(_) => self.memory_budget / 4,
+            None => self.memory_budget / 2,
+        };
+
+        if !spill.progress.input_done {
+            let first = self.read_chunk(reader, chunk_budget)?;
+
+            // The whole input in a single chunk needs no run.
+            let at_end = reader.fill_buf()?.is_empty();
+            if spill.progress.runs.is_empty() && at_end {
+                let records = first.records.len() as u64;
+                let sorted = self.sort_chunk(first.records, pool);
+                self.write_records(output, &sorted)?;
+                output.flush()?;
+                spill.progress.stats.records += records;
+                spill.progress.stats.input_bytes = reader.consumed;
+                spill.progress.stats.elapsed = spill.elapsed();
+                return Ok(spill.progress.stats);
+            }
+
+            match pool {
+                Some(pool) => {
+                    self.spill_pipelined(reader, first, spill, pool)?;
+                },
+                None => {
+                    let mut chunk = first;
+                    while !chunk.records.is_empty() {
+                        let sorted = self.sort_chunk(chunk.records, None);
+                        self.spill_run(spill, &sorted, chunk.end)?;
+                        chunk = self.read_chunk(reader, chunk_budget)?;
+                    }
+                },
+            }
+            spill.progress.input_done = true;
+            spill.save()?;
+        }
+
+        if !spill.progress.runs.is_empty() {
+            let concurrency = pool.map_or(1, SortPool::threads);
+            self.merge_runs(spill, output, concurrency)?;
+        }
+        output.flush()?;
+        spill.progress.stats.input_bytes = reader.consumed;
+        spill.progress.stats.elapsed = spill.elapsed();
+        Ok(spill.progress.stats)
+    }
+
+    /// Spills the input on three threads at once: this one reads the chunks,
+    /// another sorts them on the pool, and another writes the runs. Each
+    /// hands its chunk to the next one as soon as the next one is done with
+    /// the chunk before, so at most four chunks are in memory: one read, one
+    /// sorted and its sorted copy, and one written.
+    fn spill_pipelined<R>(
+        &self,
+        reader: &mut Counted<R>,
+        first: Chunk<C::Record>,
+        spill: &mut Spill,
+        pool: &SortPool,
+    ) -> io::Result<()>
+    where
+        R: BufRead,
+        C: Sync,
+        C::Record: Clone + Send + Sync + 'static,
+        F: Fn(&C::Record, &C::Record) -> Ordering + Send + Sync + 'static,
+    {
+        let chunk_budget = self.memory_budget / 4;
+        let (read_sender, read_receiver) =
+            mpsc::sync_channel::<Chunk<C::Record>>(0);
+        let (sorted_sender, sorted_receiver) = mpsc::sync_channel(0);
+
+        let (read, written) = thread::scope(|scope| {
+            // Stops as soon as the writer stops taking sorted chunks.
+            let sorter = scope.spawn(move || {
+                for chunk in read_receiver {
+                    let records = self.sort_chunk(chunk.records, Some(pool));
+                    let sorted = Chunk { records, end: chunk.end };
+                    if sorted_sender.send(sorted).is_err() {
+                        break;
+                    }
+                }
+            });
+
+            let writer = scope.spawn(move || {
+                for chunk in sorted_receiver {
+                    self.spill_run(spill, &chunk.records, chunk.end)?;
+                }
+                Ok(())
+            });
+
+            // Stops at the end of the input, on errors, or as soon as the
+            // sorter stops taking chunks.
+            let read = (|| {
+                let mut chunk = first;
+                while !chunk.records.is_empty() {
+                    if read_sender.send(chunk).is_err() {
+                        break;
+                    }
+                    chunk = self.read_chunk(reader, chunk_budget)?;
+                }
+                Ok(())
+            })();
+            drop(read_sender);
+
+            let sorted = sorter.join();
+            let written = writer.join();
+            // A panic of the comparison function goes on on this thread.
+            if let Err(payload) = sorted {
+                panic::resume_unwind(payload);
+            }
+            let written = written.unwrap_or_else(|p| panic::resume_unwind(p));
+            (read, written)
+        });
+
+        written.and(read)
+    }
+
+    /// Writes sorted records to a new run of the spill, the input having been
+    /// read up to the given offset, and saves the progress.
+    fn spill_run(
+        &self,
+        spill: &mut Spill,
+        sorted: &[C::Record],
+        end: u64,
+    ) -> io::Result<()> {
+        let run = spill.new_run()?;
+        let mut writer = spill.create_run(run)?;
+        self.write_records(&mut writer, sorted)?;
+        spill.complete_run(run, writer.finish()?)?;
+
+        let progress = &mut spill.progress;
+        progress.runs.push(run);
+        progress.input_offset = end;
+        progress.stats.input_bytes = end;
+        progress.stats.records += sorted.len() as u64;
+        progress.stats.runs += 1;
+        spill.save()
+    }
+
+    /// Reads records until they fill the given budget, or the input ends.
+    /// Only empty at the end of the input.
+    fn read_chunk<R>(
+        &self,
+        reader: &mut Counted<R>,
+        budget: usize,
+    ) -> io::Result<Chunk<C::Record>>
+    where
+        R: BufRead,
+    {
+        let mut records = Vec::new();
+        let mut size = 0;
+        while records.is_empty() || size < budget {
+            match self.codec.read(reader)? {
+                Some(record) => {
+                    size += self.codec.size(&record);
+                    records.push(record);
+                },
+                None => break,
+            }
+        }
+        Ok(Chunk { records, end: reader.consumed })
+    }
+
+    /// Sorts a chunk with the parallel sort, on the pool if any.
+    fn sort_chunk(
+        &self,
+        chunk: Vec<C::Record>,
+        pool: Option<&SortPool>,
+    ) -> Vec<C::Record>
+    where
+        C::Record: Clone + Send + Sync + 'static,
+        F: Fn(&C::Record, &C::Record) -> Ordering + Send + Sync + 'static,
+    {
+        let chunk: Arc<[C::Record]> = Arc::from(chunk);
+        let compare = self.compare.clone();
+        let mut options = parallel::custom_order(
+            move |left: &C::Record, right: &C::Record| compare(left, right),
+        );
+        match pool {
+            Some(pool) => options.sort_in(pool, &chunk),
+            None => options.threads(self.threads).sort(&chunk),
+        }
+    }
+
+    /// Writes the given records, in order.
+    fn write_records<W>(
+        &self,
+        writer: &mut W,
+        records: &[C::Record],
+    ) -> io::Result<()>
+    where
+        W: Write,
+    {
+        for record in records {
+            self.codec.write(writer, record)?;
+        }
+        Ok(())
+    }
+
+    /// Merges the runs of the spill into the output, first merging groups of
+    /// them into longer runs while there are more than the fan-in. Up to
+    /// `concurrency` groups are merged at once, each on its own thread.
+    fn merge_runs<W>(
+        &self,
+        spill: &mut Spill,
+        output: &mut W,
+        concurrency: usize,
+    ) -> io::Result<()>
+    where
+        W: Write,
+        C: Sync,
+        F: Fn(&C::Record, &C::Record) -> Ordering + Send + Sync,
+    {
+        let fan_in = spill.progress.fan_in;
+
+        while spill.progress.runs.len() > fan_in {
+            // Groups of consecutive runs keep equal records in input order.
+            let start = spill.progress.merged.len() * fan_in;
+            if start >= spill.progress.runs.len() {
+                // The pass is over, its runs are merged in the next one.
+                let progress = &mut spill.progress;
+                progress.runs = mem::take(&mut progress.merged);
+                progress.stats.merge_passes += 1;
+                spill.save()?;
+                continue;
+            }
+            let groups = spill.progress.runs[start ..]
+                .chunks(fan_in)
+                .take(concurrency.max(1))
+                .map(<[usize]>::to_vec)
+                .collect::<Vec<_>>();
+            let merged = groups
+                .iter()
+                .map(|_| spill.new_run())
+                .collect::<io::Result<Vec<_>>>()?;
+
+            let shared = &*spill;
+            let merge = |group: &[usize], run| {
+                let mut writer = shared.create_run(run)?;
+                self.merge_group(shared, group, &mut writer)?;
+                writer.finish()
+            };
+            let files = thread::scope(|scope| {
+                // The first group is merged on this thread.
+                let handles = groups[1 ..]
+                    .iter()
+                    .zip(&merged[1 ..])
+                    .map(|(group, &run)| scope.spawn(move || merge(group, run)))
+                    .collect::<Vec<_>>();
+                let first = merge(&groups[0], merged[0]);
+                let rest = handles.into_iter().map(|handle| {
+                    handle.join().unwrap_or_else(|p| panic::resume_unwind(p))
+                });
+                iter::once(first).chain(rest).collect::<io::Result<Vec<_>>>()
+            })?;
+
+            for (&run, file) in merged.iter().zip(files) {
+                spill.complete_run(run, file)?;
+            }
+            spill.progress.merged.extend(merged);
+            spill.save()?;
+
+            for run in groups.into_iter().flatten() {
+                fs::remove_file(spill.path(run))?;
+            }
+        }
+
+        let runs = spill.progress.runs.clone();
+        self.merge_group(spill, &runs, output)?;
+        spill.progress.stats.merge_passes += 1;
+        Ok(())
+    }
+
+    /// Merges the records of the given runs of the spill into the output.
+    fn merge_group<W>(
+        &self,
+        spill: &Spill,
+        runs: &[usize],
+        output: &mut W,
+    ) -> io::Result<u64>
+    where
+        W: Write,
+        F: Fn(&C::Record, &C::Record) -> Ordering,
+    {
+        let compression = spill.progress.compression;
+        let sources = runs
+            .iter()
+            .map(|&run| compress::open_run(&spill.path(run), compression))
+            .collect::<io::Result<Vec<_>>>()?;
+        merge_sources(&self.codec, sources, output, &*self.compare)
+    }
+
+    /// The directory where the directory of the runs is created.
+    fn temp_parent(&self) -> PathBuf {
+        self.temp_dir.clone().unwrap_or_else(env::temp_dir)
+    }
+}
+
+/// What an external sort did.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ExternalSortStats {
+    /// How many records were sorted.
+    pub records: u64,
+    /// How many sorted runs were spilled from the input, `0` if it fit in a
+    /// single chunk.
+    pub runs: usize,
+    /// How many times the records were merged, each pass reading and writing
+    /// all of them. `0` if nothing was spilled.
+    pub merge_passes: usize,
+    /// How many bytes the runs took on disk, summed over every run written,
+    /// compressed if they were.
+    pub spilled_bytes: u64,
+    /// How many bytes of input were sorted.
+    pub input_bytes: u64,
+    /// How long the sort took, summed over its resumptions if it was
+    /// checkpointed.
+    pub elapsed: Duration,
+}
+
+impl ExternalSortStats {
+    /// How many megabytes, of a million bytes each, of input were sorted per
+    /// second. Zero if no time was measured.
+    pub fn megabytes_per_second(&self) -> f64 {
+        match self.elapsed.as_secs_f64() {
+            secs if secs > 0.0 => self.input_bytes as f64 / secs / 1e6,
+            _ => 0.0,
+        }
+    }
+}
+
+/// Records read from the input, with where they end in the input.
+struct Chunk<T> {
+    /// The records, in input order until sorted.
+    records: Vec<T>,
+    /// The offset of the input right after the records.
+    end: u64,
+}
+
+/// Merges the records of sorted sources into the output, records of earlier
+/// sources going before equal records of later ones. Keeps the head of every
+/// source in a binary heap. Returns how many records were merged, or an error
+/// on the first record found out of order in its source.
+fn merge_sources<C, R, W, F>(
+    codec: &C,
+    mut sources: Vec<R>,
+    output: &mut W,
+    compare: &F,
+) -> io::Result<u64>
+where
+    C: Codec,
+    R: BufRead,
+    W: Write,
+    F: Fn(&C::Record, &C::Record) -> Ordering,
+{
+    // The heads of the sources, with the index of their source.
+    let mut heap = Vec::with_capacity(sources.len());
+    for (index, source) in sources.iter_mut().enumerate() {
+        if let Some(record) = codec.read(source)? {
+            heap.push((record, index));
+        }
+    }
+
+    // Ties go to the earlier source, which keeps the merge stable.
+    let before = |left: &(C::Record, usize), right: &(C::Record, usize)| {
+        compare(&left.0, &right.0).then(left.1.cmp(&right.1)).is_lt()
+    };
+    for index in (0 .. heap.len() / 2).rev() {
+        sift_down(&mut heap, index, &before);
+    }
+
+    let mut count = 0;
+    while let Some((record, index)) = heap.first() {
+        codec.write(output, record)?;
+        count += 1;
+        // The next record of the same source replaces the head.
+        let index = *index;
+        match codec.read(&mut sources[index])? {
+            Some(next) if compare(&next, record).is_lt() => {
+                let message = format!("input {} is not sorted", index);
+                return Err(io::Error::new(io::ErrorKind::InvalidData, message));
+            },
+            Some(record) => heap[0] = (record, index),
+            None => {
+                heap.swap_remove(0);
+            },
+        }
+        sift_down(&mut heap, 0, &before);
+    }
+
+    Ok(count)
+}
+
+/// Moves the element at the given index of a binary heap down until it is not
+/// after its children, the first element being the one before all others.
+fn sift_down<E, P>(heap: &mut [E], mut index: usize, before: &P)
+where
+    P: Fn(&E, &E) -> bool,
+{
+    loop {
+        let left = 2 * index + 1;
+        let right = left + 1;
+        let mut first = index;
+        if left < heap.len() && before(&heap[left], &heap[first]) {
+            first = left;
+        }
+        if right < heap.len() && before(&heap[right], &heap[first]) {
+            first = right;
+        }
+        if first == index {
+            break;
+        }
+        heap.swap(index, first);
+        index = first;
+    }
+}
+
+/// The directory where a sort spills its runs, with how far the sort got.
+struct Spill {
+    /// The directory of the runs, or where to create it while not created.
+    dir: PathBuf,
+    /// Whether the directory was created yet.
+    created: bool,
+    /// Whether the sort is checkpointed: the manifest is saved after every
+    /// step, and the directory kept if the sort does not finish. Otherwise,
+    /// the directory is removed with everything in it when dropped.
+    checkpointed: bool,
+    /// How far the sort got.
+    progress: Progress,
+    /// When the sort started or was resumed.
+    resumed: Instant,
+    /// How long the sort took before it was resumed.
+    elapsed_before: Duration,
+}
+
+impl Spill {
+    /// The spill of a sort that is not checkpointed, whose directory is
+    /// created in the given one once the first run is spilled.
+    fn temporary(parent: PathBuf, progress: Progress) -> Self {
+        Self {
+            dir: parent,
+            created: false,
+            checkpointed: false,
+            elapsed_before: progress.stats.elapsed,
+            resumed: Instant::now(),
+            progress,
+        }
+    }
+
+    /// The spill of a checkpointed sort, in the given directory, which exists.
+    fn checkpointed(dir: PathBuf, progress: Progress) -> Self {
+        Self {
+            dir,
+            created: true,
+            checkpointed: true,
+            elapsed_before: progress.stats.elapsed,
+            resumed: Instant::now(),
+            progress,
+        }
+    }
+
+    /// How long the sort took so far.
+    fn elapsed(&self) -> Duration {
+        self.elapsed_before + self.resumed.elapsed()
+    }
+
+    /// Numbers a new run, creating the directory if it was not yet.
+    fn new_run(&mut self) -> io::Result<usize> {
+        if !self.created {
+            self.dir = unique_dir(&self.dir)?;
+            self.created = true;
+        }
+        let run = self.progress.next_run;
+        self.progress.next_run += 1;
+        Ok(run)
+    }
+
+    /// The path of the given run.
+    fn path(&self, run: usize) -> PathBuf {
+        self.dir.join(format!("run-{}", run))
+    }
+
+    /// Creates the file of the given run.
+    fn create_run(&self, run: usize) -> io::Result<RunWriter> {
+        RunWriter::create(&self.path(run), self.progress.compression)
+    }
+
+    /// Completes the given run, whose writer was finished, counting its
+    /// bytes. The run is synced to the disk if the sort is checkpointed, so
+    /// the manifest never names a run that is not all there.
+    fn complete_run(&mut self, run: usize, file: File) -> io::Result<()> {
+        if self.checkpointed {
+            file.sync_all()?;
+        }
+        let bytes = fs::metadata(self.path(run))?.len();
+        self.progress.stats.spilled_bytes += bytes;
+        Ok(())
+    }
+
+    /// Saves the manifest, if the sort is checkpointed.
+    fn save(&mut self) -> io::Result<()> {
+        if !self.checkpointed {
+            return Ok(());
+        }
+        self.progress.stats.elapsed = self.elapsed();
+        self.progress.save(&self.dir)
+    }
+}
+
+impl Drop for Spill {
+    fn drop(&mut self) {
+        // Best effort: a leftover directory only takes disk space.
+        if self.created && !self.checkpointed {
+            let _ = fs::remove_dir_all(&self.dir);
+        }
+    }
+}
+
+/// Creates a directory of its own in the given one.
+fn unique_dir(parent: &Path) -> io::Result<PathBuf> {
+    // Distinct across the sorts of this process and of other processes,
+    // skipping those left behind by dead processes of the same id.
+    static NEXT_SPILL: AtomicUsize = AtomicUsize::new(0);
+    fs::create_dir_all(parent)?;
+    loop {
+        let number = NEXT_SPILL.fetch_add(1, AtomicOrdering::Relaxed);
+        let name = format!("mergesort-cmp-{}-{}", process::id(), number);
+        let dir = parent.join(name);
+        match fs::create_dir(&dir) {
+            Ok(()) => return Ok(dir),
+            Err(error) if error.kind() == io::ErrorKind::AlreadyExists => {},
+            Err(error) => return Err(error),
+        }
+    }
+}
+
+/// A reader counting the bytes consumed from it, so a checkpointed sort
+/// knows where in the input its last run ends.
+struct Counted<R> {
+    /// The reader counted.
+    inner: R,
+    /// How many bytes were consumed.
+    consumed: u64,
+}
+
+impl<R> Read for Counted<R>
+where
+    R: BufRead,
+{
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let count = self.inner.read(buf)?;
+        self.consumed += count as u64;
+        Ok(count)
+    }
+}
+
+impl<R> BufRead for Counted<R>
+where
+    R: BufRead,
+{
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        self.inner.fill_buf()
+    }
+
+    fn consume(&mut self, amount: usize) {
+        self.consumed += amount as u64;
+        self.inner.consume(amount);
+    }
+}
@@ -0,0 +1,350 @@
+//! Checkpoints of external sorts, so a sort interrupted after hours goes on
+//! from where it stopped instead of starting over.
+//!
+//! A checkpointed sort spills its runs into a directory of its own, with a
+//! manifest of how far it got: where in the input its last run ends, which
+//! runs it has, and which of them the merge pass going on already merged. The
+//! manifest is replaced after every run written, so it only ever names runs
+//! that are all there.
+
+use super::{
+    parallel::SortPool,
+    Codec,
+    Compression,
+    Counted,
+    ExternalSortOptions,
+    ExternalSortStats,
+    Spill,
+};
+use std::{
+    cmp::Ordering,
+    fs::{self, File},
+    io::{self, BufReader, Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::Duration,
+};
+
+/// The first line of a manifest, with the version of the format.
+const HEADER: &str = "mergesort-cmp external sort 1";
+
+/// The name of the manifest in the directory of a sort.
+const MANIFEST: &str = "manifest";
+
+/// The name a manifest is written with, before replacing the previous one.
+const MANIFEST_TEMP: &str = "manifest.tmp";
+
+/// An external sort that can be resumed if it is interrupted, spilling its
+/// runs into a directory of its own and recording how far it got in it, see
+/// [`ExternalSortOptions::checkpointed`].
+///
+/// # Examples
+/// ```rust
+/// use mergesort_cmp::external::{self, ExternalSorter, Lines};
+/// use std::{env, io::Cursor, process};
+///
+/// let dir = env::temp_dir().join(format!("resume-doc-{}", process::id()));
+/// let mut input = (0 .. 100).rev().map(|n| format!("{:03}\n", n));
+/// let mut input = input.collect::<String>().into_bytes();
+/// // A byte that is not UTF-8 near the end stops the sort.
+/// let byte = input[390];
+/// input[390] = 0xff;
+///
+/// let mut options = external::default_order(Lines);
+/// options.memory_budget(1024);
+///
+/// let sorter = options.checkpointed(&dir).unwrap();
+/// let result = sorter.sort(Cursor::new(&input), Vec::new());
+/// assert!(result.is_err());
+///
+/// // Once the input is fixed, the runs spilled before are kept.
+/// input[390] = byte;
+/// let sorter = ExternalSorter::resume(&dir, &options).unwrap();
+/// let done = sorter.progress().records;
+/// assert!(done > 0);
+///
+/// let mut output = Vec::new();
+/// let stats = sorter.sort(Cursor::new(&input), &mut output).unwrap();
+///
+/// assert_eq!(stats.records, 100);
+/// assert!(output.starts_with(b"000\n001\n002\n"));
+/// assert!(!dir.exists());
+/// ```
+pub struct ExternalSorter<'options, C, F> {
+    /// The options of the sort.
+    options: &'options ExternalSortOptions<C, F>,
+    /// Where the runs are spilled, with how far the sort got.
+    spill: Spill,
+}
+
+impl<C, F> ExternalSortOptions<C, F>
+where
+    C: Codec,
+{
+    /// Starts an external sort that can be resumed with
+    /// [`ExternalSorter::resume`] if it does not finish, spilling its runs
+    /// into the given directory, which is created if needed, instead of a
+    /// temporary one. The directory is the sort's own, and is removed once
+    /// the sort succeeds. Runs are synced to the disk as they are written,
+    /// so the sort survives the machine going down too.
+    ///
+    /// # Errors
+    /// Fails if the directory cannot be created, or already has a sort, which
+    /// must be resumed or removed instead.
+    pub fn checkpointed<P>(
+        &self,
+        dir: P,
+    ) -> io::Result<ExternalSorter<'_, C, F>>
+    where
+        P: Into<PathBuf>,
+    {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        if dir.join(MANIFEST).exists() {
+            let message = format!(
+                "{} already has a sort, resume or remove it",
+                dir.display()
+            );
+            return Err(io::Error::new(io::ErrorKind::AlreadyExists, message));
+        }
+        let progress = Progress::new(self.fan_in, self.compression);
+        let mut spill = Spill::checkpointed(dir, progress);
+        spill.save()?;
+        Ok(ExternalSorter { options: self, spill })
+    }
+}
+
+impl<'options, C, F> ExternalSorter<'options, C, F>
+where
+    C: Codec,
+{
+    /// Resumes the sort checkpointed in the given directory, with the given
+    /// options, which must have the codec and the comparison function the
+    /// sort started with. The fan-in and the compression are those the sort
+    /// started with, whatever the options say.
+    ///
+    /// # Errors
+    /// Fails if the directory has no sort, or its manifest cannot be read,
+    /// such as when it names a compression this build does not have.
+    pub fn resume<P>(
+        dir: P,
+        options: &'options ExternalSortOptions<C, F>,
+    ) -> io::Result<Self>
+    where
+        P: Into<PathBuf>,
+    {
+        let dir = dir.into();
+        let progress = Progress::load(&dir)?;
+        Ok(Self { options, spill: Spill::checkpointed(dir, progress) })
+    }
+
+    /// What the sort did so far, counting only the runs it completed.
+    pub fn progress(&self) -> ExternalSortStats {
+        self.spill.progress.stats
+    }
+
+    /// Sorts the records of the input into the output, going on from where
+    /// the sort stopped, and then removes the directory of the sort. The
+    /// input must be the one the sort started with: the records already in
+    /// runs are skipped, seeking past them. The output is written from its
+    /// start, since the last pass of the merge always runs again. The sort is
+    /// stable: records that compare equal keep their order.
+    ///
+    /// # Errors
+    /// Fails as [`ExternalSortOptions::sort`] does, keeping the directory
+    /// so the sort can be resumed again.
+    pub fn sort<R, W>(
+        self,
+        input: R,
+        output: W,
+    ) -> io::Result<ExternalSortStats>
+    where
+        R: Read + Seek,
+        W: Write,
+        C: Sync,
+        C::Record: Clone + Send + Sync + 'static,
+        F: Fn(&C::Record, &C::Record) -> Ordering + Send + Sync + 'static,
+    {
+        self.execute(input, output, None)
+    }
+
+    /// Sorts the records of the input into the output like
+    /// [`ExternalSorter::sort`], on the threads of the given pool, as
+    /// [`ExternalSortOptions::sort_in`] does.
+    ///
+    /// # Errors
+    /// Fails as [`ExternalSortOptions::sort`] does, keeping the directory
+    /// so the sort can be resumed again.
+    pub fn sort_in<R, W>(
+        self,
+        pool: &SortPool,
+        input: R,
+        output: W,
+    ) -> io::Result<ExternalSortStats>
+    where
+        R: Read + Seek,
+        W: Write,
+        C: Sync,
+        C::Record: Clone + Send + Sync + 'static,
+        F: Fn(&C::Record, &C::Record) -> Ordering + Send + Sync + 'static,
+    {
+        self.execute(input, output, Some(pool))
+    }
+
+    /// Sorts from where the sort stopped, on the pool if any.
+    fn execute<R, W>(
+        mut self,
+        mut input: R,
+        mut output: W,
+        pool: Option<&SortPool>,
+    ) -> io::Result<ExternalSortStats>
+    where
+        R: Read + Seek,
+        W: Write,
+        C: Sync,
+        C::Record: Clone + Send + Sync + 'static,
+        F: Fn(&C::Record, &C::Record) -> Ordering + Send + Sync + 'static,
+    {
+        let offset = self.spill.progress.input_offset;
+        input.seek(SeekFrom::Start(offset))?;
+        let inner = BufReader::new(input);
+        let mut reader = Counted { inner, consumed: offset };
+
+        let (options, spill) = (self.options, &mut self.spill);
+        let stats = options.execute(&mut reader, &mut output, spill, pool)?;
+        fs::remove_dir_all(&self.spill.dir)?;
+        Ok(stats)
+    }
+}
+
+/// How far an external sort got, as its manifest has it.
+#[derive(Debug, Clone)]
+pub(super) struct Progress {
+    /// How many runs are merged at once.
+    pub(super) fan_in: usize,
+    /// How the runs are compressed.
+    pub(super) compression: Compression,
+    /// Where in the input the last run ends.
+    pub(super) input_offset: u64,
+    /// Whether the whole input is in runs.
+    pub(super) input_done: bool,
+    /// The runs to be merged, in input order.
+    pub(super) runs: Vec<usize>,
+    /// The runs the merge pass going on merged so far, each from a group of
+    /// consecutive runs, in order.
+    pub(super) merged: Vec<usize>,
+    /// The number of the next run.
+    pub(super) next_run: usize,
+    /// What the sort did so far.
+    pub(super) stats: ExternalSortStats,
+}
+
+impl Progress {
+    /// The progress of a sort that has not started.
+    pub(super) fn new(fan_in: usize, compression: Compression) -> Self {
+        Self {
+            fan_in,
+            compression,
+            input_offset: 0,
+            input_done: false,
+            runs: Vec::new(),
+            merged: Vec::new(),
+            next_run: 0,
+            stats: ExternalSortStats::default(),
+        }
+    }
+
+    /// Replaces the manifest in the given directory with this progress. The
+    /// new manifest is written aside, synced, and then renamed over the old
+    /// one, so an interruption leaves either of them whole.
+    pub(super) fn save(&self, dir: &Path) -> io::Result<()> {
+        let numbers = |runs: &[usize]| {
+            runs.iter().map(|run| format!(" {}", run)).collect::<String>()
+        };
+        let mut text = format!("{}\n", HEADER);
+        text += &format!("fan-in {}\n", self.fan_in);
+        text += &format!("compression {}\n", self.compression.name());
+        text += &format!("input-offset {}\n", self.input_offset);
+        text += &format!("input-done {}\n", self.input_done);
+        text += &format!("runs{}\n", numbers(&self.runs));
+        text += &format!("merged{}\n", numbers(&self.merged));
+        text += &format!("next-run {}\n", self.next_run);
+        text += &format!("records {}\n", self.stats.records);
+        text += &format!("input-runs {}\n", self.stats.runs);
+        text += &format!("merge-passes {}\n", self.stats.merge_passes);
+        text += &format!("spilled-bytes {}\n", self.stats.spilled_bytes);
+        text += &format!("elapsed-nanos {}\n", self.stats.elapsed.as_nanos());
+
+        let temp = dir.join(MANIFEST_TEMP);
+        let mut file = File::create(&temp)?;
+        file.write_all(text.as_bytes())?;
+        file.sync_all()?;
+        fs::rename(temp, dir.join(MANIFEST))
+    }
+
+    /// Loads the manifest in the given directory.
+    pub(super) fn load(dir: &Path) -> io::Result<Self> {
+        let path = dir.join(MANIFEST);
+        let invalid = |message: String| {
+            let message = format!("{}: {}", path.display(), message);
+            io::Error::new(io::ErrorKind::InvalidData, message)
+        };
+        let text = fs::read_to_string(&path)?;
+        let mut lines = text.lines();
+        if lines.next() != Some(HEADER) {
+            return Err(invalid("not a manifest of an external sort".into()));
+        }
+
+        let mut progress = Self::new(0, Compression::None);
+        for line in lines {
+            let (key, value) = line.split_once(' ').unwrap_or((line, ""));
+            let parsed = match key {
+                "fan-in" => parse(value, &mut progress.fan_in),
+                "compression" => Compression::from_name(value)
+                    .map(|compression| progress.compression = compression),
+                "input-offset" => parse(value, &mut progress.input_offset),
+                "input-done" => parse(value, &mut progress.input_done),
+                "runs" => parse_runs(value, &mut progress.runs),
+                "merged" => parse_runs(value, &mut progress.merged),
+                "next-run" => parse(value, &mut progress.next_run),
+                "records" => parse(value, &mut progress.stats.records),
+                "input-runs" => parse(value, &mut progress.stats.runs),
+                "merge-passes" => {
+                    parse(value, &mut progress.stats.merge_passes)
+                },
+                "spilled-bytes" => {
+                    parse(value, &mut progress.stats.spilled_bytes)
+                },
+                "elapsed-nanos" => value.parse().ok().map(|nanos| {
+                    progress.stats.elapsed = Duration::from_nanos(nanos)
+                }),
+                _ => None,
+            };
+            if parsed.is_none() {
+                return Err(invalid(format!("invalid line {:?}", line)));
+            }
+        }
+
+        if progress.fan_in < 2 {
+            return Err(invalid("missing fan-in".into()));
+        }
+        progress.stats.input_bytes = progress.input_offset;
+        Ok(progress)
+    }
+}
+
+/// Parses the value of a line of a manifest into the given field.
+fn parse<T>(value: &str, field: &mut T) -> Option<()>
+where
+    T: FromStr,
+{
+    *field = value.parse().ok()?;
+    Some(())
+}
+
+/// Parses the run numbers of a line of a manifest into the given field.
+fn parse_runs(value: &str, field: &mut Vec<usize>) -> Option<()> {
+    let runs = value.split_whitespace().map(|run| run.parse().ok());
+    *field = runs.collect::<Option<_>>()?;
+    Some(())
+}
@@ -0,0 +1,179 @@
+//! How the records of external sorts are read from and written to files.
+
+use std::{
+    io::{self, BufRead, Write},
+    marker::PhantomData,
+    mem,
+};
+
+/// Reads and writes records of a file, one after the other.
+pub trait Codec {
+    /// The records read and written.
+    type Record;
+
+    /// Reads the next record, `None` at the end of the input. Input ending in
+    /// the middle of a record is an error.
+    fn read<R>(&self, reader: &mut R) -> io::Result<Option<Self::Record>>
+    where
+        R: BufRead;
+
+    /// Writes a record after those already written.
+    fn write<W>(&self, writer: &mut W, record: &Self::Record) -> io::Result<()>
+    where
+        W: Write;
+
+    /// About how many bytes the record takes in memory, which is counted
+    /// against the memory budget of the sort. Just the size of the type by
+    /// default, which is right for records owning no heap memory.
+    fn size(&self, record: &Self::Record) -> usize {
+        let _ = record;
+        mem::size_of::<Self::Record>()
+    }
+}
+
+impl<C> Codec for &C
+where
+    C: Codec + ?Sized,
+{
+    type Record = C::Record;
+
+    fn read<R>(&self, reader: &mut R) -> io::Result<Option<C::Record>>
+    where
+        R: BufRead,
+    {
+        (**self).read(reader)
+    }
+
+    fn write<W>(&self, writer: &mut W, record: &C::Record) -> io::Result<()>
+    where
+        W: Write,
+    {
+        (**self).write(writer, record)
+    }
+
+    fn size(&self, record: &C::Record) -> usize {
+        (**self).size(record)
+    }
+}
+
+/// Records that are lines of UTF-8 text, each ended by `\n`, except maybe for
+/// the last one. The `\n` is not part of the record, but a `\r` before it is.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Lines;
+
+impl Codec for Lines {
+    type Record = String;
+
+    fn read<R>(&self, reader: &mut R) -> io::Result<Option<String>>
+    where
+        R: BufRead,
+    {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        if line.ends_with('\n') {
+            line.pop();
+        }
+        Ok(Some(line))
+    }
+
+    fn write<W>(&self, writer: &mut W, record: &String) -> io::Result<()>
+    where
+        W: Write,
+    {
+        writer.write_all(record.as_bytes())?;
+        writer.write_all(b"\n")
+    }
+
+    fn size(&self, record: &String) -> usize {
+        mem::size_of::<String>() + record.capacity()
+    }
+}
+
+/// Records that are numbers of type `T`, each stored in its little endian
+/// bytes, with nothing between them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Binary<T> {
+    /// Here so we can have T as a type parameter.
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T> Binary<T>
+where
+    T: Number,
+{
+    /// The codec of numbers of type `T`.
+    pub fn new() -> Self {
+        Self { _marker: PhantomData }
+    }
+}
+
+impl<T> Default for Binary<T>
+where
+    T: Number,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Codec for Binary<T>
+where
+    T: Number,
+{
+    type Record = T;
+
+    fn read<R>(&self, reader: &mut R) -> io::Result<Option<T>>
+    where
+        R: BufRead,
+    {
+        // Nothing left is the end of the input, a part of a number is not.
+        if reader.fill_buf()?.is_empty() {
+            return Ok(None);
+        }
+        let mut bytes = T::Bytes::default();
+        reader.read_exact(bytes.as_mut())?;
+        Ok(Some(T::from_le_bytes(bytes)))
+    }
+
+    fn write<W>(&self, writer: &mut W, record: &T) -> io::Result<()>
+    where
+        W: Write,
+    {
+        writer.write_all(record.to_le_bytes().as_ref())
+    }
+}
+
+/// A primitive number, which [`Binary`] reads and writes.
+pub trait Number: Copy {
+    /// The bytes of the number.
+    type Bytes: Default + AsRef<[u8]> + AsMut<[u8]>;
+
+    /// The number of the given little endian bytes.
+    fn from_le_bytes(bytes: Self::Bytes) -> Self;
+
+    /// The little endian bytes of the number.
+    fn to_le_bytes(self) -> Self::Bytes;
+}
+
+/// Implements [`Number`] for primitive number types.
+macro_rules! impl_number {
+    ($($ty:ty),*) => {
+        $(
+            impl Number for $ty {
+                type Bytes = [u8; mem::size_of::<$ty>()];
+
+                fn from_le_bytes(bytes: Self::Bytes) -> Self {
+                    <$ty>::from_le_bytes(bytes)
+                }
+
+                fn to_le_bytes(self) -> Self::Bytes {
+                    <$ty>::to_le_bytes(self)
+                }
+            }
+        )*
+    };
+}
+
+impl_number!(i8, i16, i32, i64, u8, u16, u32, u64, f32, f64);
@@ -0,0 +1,147 @@
+//! How the runs of external sorts are compressed on disk.
+
+use std::{
+    fs::File,
+    io::{self, BufReader, BufWriter, Read, Write},
+    path::Path,
+};
+
+/// How the runs spilled by an external sort are compressed. Compressing trades
+/// CPU time for less disk space and bandwidth, which pays off on records that
+/// compress well, such as text.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Compression {
+    /// The runs are written as the codec writes them (default).
+    #[default]
+    None,
+    /// LZ4 frames, fast to compress and decompress. Only with the `lz4`
+    /// feature.
+    #[cfg(feature = "lz4")]
+    Lz4,
+    /// Zstandard frames at the given level, from 1 to 22, higher levels
+    /// compressing more, slower. Level 0 is the default of the library, 3.
+    /// Only with the `zstd` feature.
+    #[cfg(feature = "zstd")]
+    Zstd {
+        /// The compression level.
+        level: i32,
+    },
+}
+
+impl Compression {
+    /// The name of the compression in the manifests of checkpointed sorts.
+    pub(super) fn name(self) -> String {
+        match self {
+            Compression::None => "none".to_owned(),
+            #[cfg(feature = "lz4")]
+            Compression::Lz4 => "lz4".to_owned(),
+            #[cfg(feature = "zstd")]
+            Compression::Zstd { level } => format!("zstd:{}", level),
+        }
+    }
+
+    /// The compression of the given name, `None` if it is unknown or this
+    /// build does not have it.
+    pub(super) fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "none" => Some(Compression::None),
+            #[cfg(feature = "lz4")]
+            "lz4" => Some(Compression::Lz4),
+            #[cfg(feature = "zstd")]
+            _ if name.starts_with("zstd:") => {
+                let level = name["zstd:".len() ..].parse().ok()?;
+                Some(Compression::Zstd { level })
+            },
+            _ => None,
+        }
+    }
+}
+
+/// Writes a run, compressed as configured.
+pub(super) enum RunWriter {
+    /// Not compressed.
+    Plain(BufWriter<File>),
+    /// Compressed with LZ4.
+    #[cfg(feature = "lz4")]
+    Lz4(lz4_flex::frame::FrameEncoder<BufWriter<File>>),
+    /// Compressed with Zstandard.
+    #[cfg(feature = "zstd")]
+    Zstd(zstd::stream::write::Encoder<'static, BufWriter<File>>),
+}
+
+impl RunWriter {
+    /// Creates the file of a run at the given path.
+    pub(super) fn create(
+        path: &Path,
+        compression: Compression,
+    ) -> io::Result<Self> {
+        let file = BufWriter::new(File::create(path)?);
+        Ok(match compression {
+            Compression::None => RunWriter::Plain(file),
+            #[cfg(feature = "lz4")]
+            Compression::Lz4 => {
+                RunWriter::Lz4(lz4_flex::frame::FrameEncoder::new(file))
+            },
+            #[cfg(feature = "zstd")]
+            Compression::Zstd { level } => {
+                RunWriter::Zstd(zstd::stream::write::Encoder::new(file, level)?)
+            },
+        })
+    }
+
+    /// Ends the compressed stream, if any, and flushes the file, returning
+    /// it.
+    pub(super) fn finish(self) -> io::Result<File> {
+        let file = |file: BufWriter<File>| {
+            file.into_inner().map_err(io::IntoInnerError::into_error)
+        };
+        match self {
+            RunWriter::Plain(plain) => file(plain),
+            #[cfg(feature = "lz4")]
+            RunWriter::Lz4(encoder) => file(encoder.finish()?),
+            #[cfg(feature = "zstd")]
+            RunWriter::Zstd(encoder) => file(encoder.finish()?),
+        }
+    }
+}
+
+impl Write for RunWriter {
+    fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+        match self {
+            RunWriter::Plain(file) => file.write(bytes),
+            #[cfg(feature = "lz4")]
+            RunWriter::Lz4(encoder) => encoder.write(bytes),
+            #[cfg(feature = "zstd")]
+            RunWriter::Zstd(encoder) => encoder.write(bytes),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            RunWriter::Plain(file) => file.flush(),
+            #[cfg(feature = "lz4")]
+            RunWriter::Lz4(encoder) => encoder.flush(),
+            #[cfg(feature = "zstd")]
+            RunWriter::Zstd(encoder) => encoder.flush(),
+        }
+    }
+}
+
+/// Opens the file of a run written with the given compression, for its
+/// records to be read.
+pub(super) fn open_run(
+    path: &Path,
+    compression: Compression,
+) -> io::Result<BufReader<Box<dyn Read + Send>>> {
+    let file = File::open(path)?;
+    let reader: Box<dyn Read + Send> = match compression {
+        Compression::None => Box::new(file),
+        #[cfg(feature = "lz4")]
+        Compression::Lz4 => Box::new(lz4_flex::frame::FrameDecoder::new(file)),
+        #[cfg(feature = "zstd")]
+        Compression::Zstd { .. } => {
+            Box::new(zstd::stream::read::Decoder::new(file)?)
+        },
+    };
+    Ok(BufReader::new(reader))
+}
@@ -0,0 +1,150 @@
+//! A C interface to the parallel merge sort, for arrays of primitive numbers,
+//! so C and C++ projects can call it through the crate built as a `cdylib`.
+//! Only built with the `ffi` feature. The declarations are in
+//! `include/mergesort_cmp.h`. Cargo only builds the `rlib` by default:
+//! ```text
+//! $ cargo rustc --release --lib --crate-type cdylib --features ffi
+//! ```
+//!
+//! Every function returns an error code instead of panicking across the
+//! boundary: [`MSCMP_OK`] when the array was sorted, or one of the other
+//! `MSCMP_*` codes, which [`mscmp_error_message`] describes. The array is left
+//! as it was on errors.
+//!
+//! Floating point numbers are sorted by their total order, the one of
+//! [`f64::total_cmp`]: negative NaNs first, then negative infinity, the
+//! numbers, `-0.0` before `0.0`, positive infinity and positive NaNs last.
+//!
+//! # Examples
+//! ```rust
+//! use mergesort_cmp::ffi::{self, MSCMP_INVALID_ARGUMENT, MSCMP_OK};
+//! use std::ptr;
+//!
+//! let mut array = [-1, 5, 91293, 12, -95, 20000, 20001, -12, 7];
+//!
+//! let (ptr, len) = (array.as_mut_ptr(), array.len());
+//! let code = unsafe { ffi::mscmp_sort_i64(ptr, len, 4) };
+//! assert_eq!(code, MSCMP_OK);
+//! assert_eq!(array, [-95, -12, -1, 5, 7, 12, 20000, 20001, 91293]);
+//!
+//! let code = unsafe { ffi::mscmp_sort_i64(ptr::null_mut(), 3, 4) };
+//! assert_eq!(code, MSCMP_INVALID_ARGUMENT);
+//! ```
+
+use crate::parallel;
+use std::{
+    cmp::Ordering,
+    os::raw::{c_char, c_int},
+    panic::{self, AssertUnwindSafe},
+    slice,
+    sync::Arc,
+};
+
+/// The array was sorted.
+pub const MSCMP_OK: c_int = 0;
+/// The pointer to the array was null, and its length was not zero.
+pub const MSCMP_INVALID_ARGUMENT: c_int = 1;
+/// The copy of the array the sort works on could not be allocated.
+pub const MSCMP_OUT_OF_MEMORY: c_int = 2;
+/// The sort failed unexpectedly, a bug of this crate.
+pub const MSCMP_INTERNAL_ERROR: c_int = 3;
+
+/// Describes an error code as a static, null terminated string, which must
+/// not be freed. Unknown codes get a description too.
+#[no_mangle]
+pub extern "C" fn mscmp_error_message(code: c_int) -> *const c_char {
+    let message: &'static [u8] = match code {
+        MSCMP_OK => b"no error\0",
+        MSCMP_INVALID_ARGUMENT => b"null array with a nonzero length\0",
+        MSCMP_OUT_OF_MEMORY => b"out of memory\0",
+        MSCMP_INTERNAL_ERROR => b"internal error of the sort\0",
+        _ => b"unknown error code\0",
+    };
+    message.as_ptr().cast()
+}
+
+/// Sorts the array in place with the parallel merge sort and the given
+/// comparison function, catching panics.
+///
+/// # Safety
+/// If `len` is not zero, `ptr` must point to `len` initialized elements,
+/// valid for reads and writes, that nothing else accesses during the call.
+unsafe fn sort_raw<T, F>(
+    ptr: *mut T,
+    len: usize,
+    threads: usize,
+    compare: F,
+) -> c_int
+where
+    T: Copy + Send + Sync + 'static,
+    F: Fn(&T, &T) -> Ordering + Clone + Send + Sync + 'static,
+{
+    if len == 0 {
+        return MSCMP_OK;
+    }
+    if ptr.is_null() {
+        return MSCMP_INVALID_ARGUMENT;
+    }
+    let array = slice::from_raw_parts_mut(ptr, len);
+
+    let sorted = panic::catch_unwind(AssertUnwindSafe(|| {
+        let mut copy = Vec::new();
+        if copy.try_reserve_exact(array.len()).is_err() {
+            return Err(MSCMP_OUT_OF_MEMORY);
+        }
+        copy.extend_from_slice(array);
+        let copy: Arc<[T]> = Arc::from(copy);
+
+        let mut options = parallel::custom_order(compare);
+        match threads {
+            0 => options.thread_per_cpu(),
+            threads => options.threads(threads),
+        };
+        Ok(options.sort(&copy))
+    }));
+
+    match sorted {
+        Ok(Ok(sorted)) => {
+            array.copy_from_slice(&sorted);
+            MSCMP_OK
+        },
+        Ok(Err(code)) => code,
+        Err(_) => MSCMP_INTERNAL_ERROR,
+    }
+}
+
+/// Defines the sort function of a primitive type, with its documentation.
+macro_rules! sort_fn {
+    ($name:ident, $type:ty, $compare:expr, $order:literal) => {
+        #[doc = concat!(
+            "Sorts the `len` elements of type `", stringify!($type),
+            "` at `ptr` in place, ", $order, ", with the parallel merge sort ",
+            "on `threads` threads, or one per logical CPU if it is zero. ",
+            "Returns [`MSCMP_OK`] if they were sorted, or the code of the ",
+            "error otherwise, leaving them as they were.\n\n",
+            "# Safety\n",
+            "If `len` is not zero, `ptr` must point to `len` initialized ",
+            "elements, valid for reads and writes, that nothing else accesses ",
+            "during the call.",
+        )]
+        #[no_mangle]
+        pub unsafe extern "C" fn $name(
+            ptr: *mut $type,
+            len: usize,
+            threads: usize,
+        ) -> c_int {
+            sort_raw(ptr, len, threads, $compare)
+        }
+    };
+}
+
+sort_fn!(mscmp_sort_i8, i8, i8::cmp, "ascending");
+sort_fn!(mscmp_sort_i16, i16, i16::cmp, "ascending");
+sort_fn!(mscmp_sort_i32, i32, i32::cmp, "ascending");
+sort_fn!(mscmp_sort_i64, i64, i64::cmp, "ascending");
+sort_fn!(mscmp_sort_u8, u8, u8::cmp, "ascending");
+sort_fn!(mscmp_sort_u16, u16, u16::cmp, "ascending");
+sort_fn!(mscmp_sort_u32, u32, u32::cmp, "ascending");
+sort_fn!(mscmp_sort_u64, u64, u64::cmp, "ascending");
+sort_fn!(mscmp_sort_f32, f32, f32::total_cmp, "in their total order");
+sort_fn!(mscmp_sort_f64, f64, f64::total_cmp, "in their total order");
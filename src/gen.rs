@@ -0,0 +1,298 @@
+//! Generators of arrays with the patterns the sorts are benchmarked on, from
+//! uniformly random elements to layouts that are the worst for merge sorts.
+//! The same seed always gives the same arrays, so tests, benches and other
+//! code can share their cases.
+//!
+//! # Examples
+//! ```rust
+//! use mergesort_cmp::gen::Pattern;
+//!
+//! let pattern = "nearly-sorted(5%)".parse::<Pattern>().unwrap();
+//! let array = pattern.generate_seeded::<i64>(1000, 42);
+//!
+//! assert_eq!(array.len(), 1000);
+//! assert_eq!(array, pattern.generate_seeded::<i64>(1000, 42));
+//! assert_eq!(pattern.to_string(), "nearly-sorted(5%)");
+//! ```
+
+use rand::{
+    distributions::{Distribution, Standard},
+    rngs::StdRng,
+    Rng,
+    SeedableRng,
+};
+use std::{cmp::Ordering, error::Error, fmt, str::FromStr};
+
+/// How the elements of a generated array are laid out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pattern {
+    /// Uniformly random elements.
+    Random,
+    /// Random elements, already in ascending order.
+    Sorted,
+    /// Random elements in descending order.
+    Reverse,
+    /// Sorted random elements, but with some of them swapped out of place.
+    NearlySorted {
+        /// How many elements are out of place, in hundredths of a percent.
+        basis_points: u32,
+    },
+    /// Elements drawn from a few random values.
+    FewUnique {
+        /// How many distinct values there are at most. 0 is taken as 1.
+        values: usize,
+    },
+    /// Ascending runs of random elements, one after the other.
+    Sawtooth {
+        /// The length of each run. 0 is taken as 1.
+        period: usize,
+    },
+    /// Random elements ascending up to the middle, then descending.
+    OrganPipe,
+    /// A single random element, repeated.
+    AllEqual,
+    /// Random elements laid out so that every merge of the sort alternates
+    /// between the halves, doing the most comparisons and pivot changes.
+    MergeAdversarial,
+}
+
+impl Pattern {
+    /// The names of every pattern, as accepted by [`Pattern::from_str`], with
+    /// their parameters in capitals.
+    pub const NAMES: &'static [&'static str] = &[
+        "random",
+        "sorted",
+        "reverse",
+        "nearly-sorted(P%)",
+        "few-unique(K)",
+        "sawtooth(PERIOD)",
+        "organ-pipe",
+        "all-equal",
+        "merge-adversarial",
+    ];
+
+    /// Generates an array of the given size with this pattern, drawing
+    /// uniformly random elements from the generator and ordering them with
+    /// the comparison function, which must be a total order.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use mergesort_cmp::gen::Pattern;
+    /// use rand::{rngs::StdRng, Rng, SeedableRng};
+    ///
+    /// let mut rng = StdRng::seed_from_u64(7);
+    /// let array = Pattern::Reverse.generate_by(
+    ///     100,
+    ///     &mut rng,
+    ///     |rng: &mut StdRng| rng.gen_range(0, 1000),
+    ///     |left: &u32, right: &u32| left.cmp(right),
+    /// );
+    ///
+    /// assert!(array.windows(2).all(|pair| pair[0] >= pair[1]));
+    /// ```
+    pub fn generate_by<T, R, G, C>(
+        self,
+        size: usize,
+        rng: &mut R,
+        mut random: G,
+        mut compare: C,
+    ) -> Vec<T>
+    where
+        T: Clone,
+        R: Rng,
+        G: FnMut(&mut R) -> T,
+        C: FnMut(&T, &T) -> Ordering,
+    {
+        let mut random_array = |size: usize, rng: &mut R| {
+            (0 .. size).map(|_| random(rng)).collect::<Vec<_>>()
+        };
+        match self {
+            Pattern::Random => random_array(size, rng),
+
+            Pattern::Sorted => {
+                let mut array = random_array(size, rng);
+                array.sort_unstable_by(compare);
+                array
+            },
+
+            Pattern::Reverse => {
+                let mut array = random_array(size, rng);
+                array.sort_unstable_by(|left, right| compare(right, left));
+                array
+            },
+
+            Pattern::NearlySorted { basis_points } => {
+                let mut array = random_array(size, rng);
+                array.sort_unstable_by(compare);
+
+                // Each swap takes two elements out of place.
+                let displaced = size * basis_points as usize / 10000;
+                if size > 1 {
+                    for _ in 0 .. displaced.div_ceil(2) {
+                        let first = rng.gen_range(0, size);
+                        let second = rng.gen_range(0, size);
+                        array.swap(first, second);
+                    }
+                }
+                array
+            },
+
+            Pattern::FewUnique { values } => {
+                let values = random_array(values.max(1), rng);
+                let pick = |rng: &mut R| {
+                    values[rng.gen_range(0, values.len())].clone()
+                };
+                (0 .. size).map(|_| pick(rng)).collect()
+            },
+
+            Pattern::Sawtooth { period } => {
+                let mut array = random_array(size, rng);
+                for run in array.chunks_mut(period.max(1)) {
+                    run.sort_unstable_by(&mut compare);
+                }
+                array
+            },
+
+            Pattern::OrganPipe => {
+                // The upper half has the biggest elements, reversed it goes
+                // from the biggest one back down.
+                let mut array = random_array(size, rng);
+                array.sort_unstable_by(compare);
+                array[size / 2 ..].reverse();
+                array
+            },
+
+            Pattern::AllEqual => vec![random(rng); size],
+
+            Pattern::MergeAdversarial => {
+                let mut sorted = random_array(size, rng);
+                sorted.sort_unstable_by(compare);
+                let mut array = Vec::with_capacity(size);
+                unmerge(&sorted, &mut array);
+                array
+            },
+        }
+    }
+
+    /// Generates an array of the given size with this pattern, made of
+    /// uniformly random elements in their natural order.
+    pub fn generate<T, R>(self, size: usize, rng: &mut R) -> Vec<T>
+    where
+        T: Ord + Clone,
+        R: Rng,
+        Standard: Distribution<T>,
+    {
+        self.generate_by(size, rng, |rng| rng.gen(), T::cmp)
+    }
+
+    /// Generates an array of the given size with this pattern, like
+    /// [`Pattern::generate`], with a generator seeded by the given seed.
+    pub fn generate_seeded<T>(self, size: usize, seed: u64) -> Vec<T>
+    where
+        T: Ord + Clone,
+        Standard: Distribution<T>,
+    {
+        self.generate(size, &mut StdRng::seed_from_u64(seed))
+    }
+}
+
+/// Undoes the merges of a merge sort on the sorted elements, pushing them into
+/// the array. Halves are split like the sorts in this crate split them, the
+/// lower half with the first ceil(length/2) elements. The lower half gets the
+/// elements of even rank and the upper one those of odd rank, so merging them
+/// takes from each half in turn.
+fn unmerge<T>(sorted: &[T], array: &mut Vec<T>)
+where
+    T: Clone,
+{
+    if sorted.len() < 2 {
+        array.extend_from_slice(sorted);
+        return;
+    }
+
+    let lower = sorted.iter().step_by(2).cloned().collect::<Vec<_>>();
+    let upper = sorted.iter().skip(1).step_by(2).cloned().collect::<Vec<_>>();
+    unmerge(&lower, array);
+    unmerge(&upper, array);
+}
+
+impl fmt::Display for Pattern {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Pattern::Random => fmt.write_str("random"),
+            Pattern::Sorted => fmt.write_str("sorted"),
+            Pattern::Reverse => fmt.write_str("reverse"),
+            Pattern::NearlySorted { basis_points } => {
+                let percent = f64::from(*basis_points) / 100.0;
+                write!(fmt, "nearly-sorted({}%)", percent)
+            },
+            Pattern::FewUnique { values } => {
+                write!(fmt, "few-unique({})", values)
+            },
+            Pattern::Sawtooth { period } => write!(fmt, "sawtooth({})", period),
+            Pattern::OrganPipe => fmt.write_str("organ-pipe"),
+            Pattern::AllEqual => fmt.write_str("all-equal"),
+            Pattern::MergeAdversarial => fmt.write_str("merge-adversarial"),
+        }
+    }
+}
+
+impl FromStr for Pattern {
+    type Err = ParsePatternError;
+
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        let error = || ParsePatternError { pattern: string.to_owned() };
+
+        // Parameters go between parentheses: `name(parameter)`.
+        let trimmed = string.trim();
+        let (name, parameter) = match trimmed.find('(') {
+            Some(open) if trimmed.ends_with(')') => {
+                let parameter = &trimmed[open + 1 .. trimmed.len() - 1];
+                (&trimmed[.. open], Some(parameter.trim()))
+            },
+            _ => (trimmed, None),
+        };
+
+        match (name, parameter) {
+            ("random", None) => Ok(Pattern::Random),
+            ("sorted", None) => Ok(Pattern::Sorted),
+            ("reverse", None) => Ok(Pattern::Reverse),
+            ("nearly-sorted", Some(percent)) => {
+                let percent = percent.trim_end_matches('%').trim();
+                let percent = percent.parse::<f64>().map_err(|_| error())?;
+                if !(0.0 ..= 100.0).contains(&percent) {
+                    return Err(error());
+                }
+                let basis_points = (percent * 100.0).round() as u32;
+                Ok(Pattern::NearlySorted { basis_points })
+            },
+            ("few-unique", Some(values)) => match values.parse() {
+                Ok(0) | Err(_) => Err(error()),
+                Ok(values) => Ok(Pattern::FewUnique { values }),
+            },
+            ("sawtooth", Some(period)) => match period.parse() {
+                Ok(0) | Err(_) => Err(error()),
+                Ok(period) => Ok(Pattern::Sawtooth { period }),
+            },
+            ("organ-pipe", None) => Ok(Pattern::OrganPipe),
+            ("all-equal", None) => Ok(Pattern::AllEqual),
+            ("merge-adversarial", None) => Ok(Pattern::MergeAdversarial),
+            _ => Err(error()),
+        }
+    }
+}
+
+/// A pattern that does not exist or has invalid parameters.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParsePatternError {
+    /// The pattern as given.
+    pattern: String,
+}
+
+impl fmt::Display for ParsePatternError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "invalid pattern {:?}", self.pattern)
+    }
+}
+
+impl Error for ParsePatternError {}
@@ -0,0 +1,350 @@
+//! This module provides an experimental GPU backend, compiled only with the
+//! `gpu` feature. Arrays of primitive integers are uploaded to the GPU and
+//! sorted by a bitonic sort running in a compute shader. Any other element
+//! type, arrays too big for the device, or machines without a usable adapter
+//! fall back to the CPU, using [`parallel`](crate::parallel).
+//!
+//! # Examples
+//! ```rust
+//! use mergesort_cmp::gpu::GpuSorter;
+//! use std::sync::Arc;
+//!
+//! let count = 10000;
+//! let expected = (0 .. count).collect::<Vec<_>>();
+//! let reversed = (0 .. count).rev().collect::<Vec<_>>();
+//! let array: Arc<[i64]> = Arc::from(reversed);
+//!
+//! let sorter = GpuSorter::new();
+//! let sorted = sorter.sort(&array);
+//!
+//! assert_eq!(expected, sorted);
+//! ```
+
+use crate::parallel;
+use std::{
+    any::Any,
+    borrow::Cow,
+    convert::{TryFrom, TryInto},
+    sync::{mpsc, Arc},
+};
+use wgpu::util::DeviceExt;
+
+/// How many shader invocations there are per workgroup. Must match the
+/// shader's `@workgroup_size`.
+const WORKGROUP_SIZE: u64 = 256;
+
+/// A single compare-and-swap stage of the bitonic sort, over keys stored as
+/// pairs of 32-bit words `(low, high)`.
+const SHADER: &str = r#"
+struct Stage {
+    block: u32,
+    distance: u32,
+}
+
+@group(0) @binding(0) var<storage, read_write> keys: array<vec2<u32>>;
+@group(0) @binding(1) var<uniform> stage: Stage;
+
+fn less(left: vec2<u32>, right: vec2<u32>) -> bool {
+    return left.y < right.y || (left.y == right.y && left.x < right.x);
+}
+
+@compute @workgroup_size(256)
+fn main(
+    @builtin(global_invocation_id) id: vec3<u32>,
+    @builtin(num_workgroups) groups: vec3<u32>,
+) {
+    let index = id.x + id.y * groups.x * 256u;
+    let partner = index ^ stage.distance;
+
+    if (partner <= index || partner >= arrayLength(&keys)) {
+        return;
+    }
+
+    let left = keys[index];
+    let right = keys[partner];
+    let ascending = (index & stage.block) == 0u;
+
+    if (less(right, left) == ascending) {
+        keys[index] = right;
+        keys[partner] = left;
+    }
+}
+"#;
+
+/// A primitive type that can be sorted on the GPU. It is mapped into a `u64`
+/// such that the order of the keys is the order of the values.
+trait GpuKey: Copy + 'static {
+    /// Converts the value into an order-preserving key.
+    fn to_key(self) -> u64;
+
+    /// Converts a key back into the value.
+    fn from_key(key: u64) -> Self;
+}
+
+macro_rules! unsigned_key {
+    ($($ty:ty),*) => {
+        $(
+            impl GpuKey for $ty {
+                fn to_key(self) -> u64 {
+                    self as u64
+                }
+
+                fn from_key(key: u64) -> Self {
+                    key as Self
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! signed_key {
+    ($($ty:ty),*) => {
+        $(
+            impl GpuKey for $ty {
+                fn to_key(self) -> u64 {
+                    // Flipping the sign bit turns two's complement order into
+                    // unsigned order.
+                    (self as i64 as u64) ^ (1 << 63)
+                }
+
+                fn from_key(key: u64) -> Self {
+                    (key ^ (1 << 63)) as i64 as Self
+                }
+            }
+        )*
+    };
+}
+
+unsigned_key!(u8, u16, u32, u64, usize);
+signed_key!(i8, i16, i32, i64, isize);
+
+/// The GPU resources needed for a sort.
+struct Device {
+    /// The logical device.
+    device: wgpu::Device,
+    /// Its command queue.
+    queue: wgpu::Queue,
+    /// The compare-and-swap stage pipeline.
+    pipeline: wgpu::ComputePipeline,
+}
+
+/// A sorter that offloads arrays of primitive integers to the GPU. Creating it
+/// initializes the device, which is expensive, so it should be reused.
+pub struct GpuSorter {
+    /// The device, or `None` if no adapter is available.
+    device: Option<Device>,
+}
+
+impl GpuSorter {
+    /// Initializes the default GPU adapter. If no adapter is found, the sorter
+    /// still works, but always sorts on the CPU.
+    pub fn new() -> Self {
+        Self { device: pollster::block_on(Self::request_device()) }
+    }
+
+    /// Whether a GPU is actually being used.
+    pub fn is_available(&self) -> bool {
+        self.device.is_some()
+    }
+
+    /// Requests an adapter, a device for it, and builds the sorting pipeline.
+    async fn request_device() -> Option<Device> {
+        let instance = wgpu::Instance::default();
+        let adapter = instance
+            .request_adapter(&wgpu::RequestAdapterOptions::default())
+            .await
+            .ok()?;
+
+        // Asks for the adapter's own limits, so big buffers can be used.
+        let descriptor = wgpu::DeviceDescriptor {
+            label: Some("mergesort-cmp"),
+            required_limits: adapter.limits(),
+            ..Default::default()
+        };
+        let (device, queue) = adapter.request_device(&descriptor).await.ok()?;
+
+        let module =
+            device.create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: Some("bitonic stage"),
+                source: wgpu::ShaderSource::Wgsl(Cow::Borrowed(SHADER)),
+            });
+        let pipeline =
+            device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                label: Some("bitonic stage"),
+                layout: None,
+                module: &module,
+                entry_point: Some("main"),
+                compilation_options: Default::default(),
+                cache: None,
+            });
+
+        Some(Device { device, queue, pipeline })
+    }
+
+    /// Sorts the given array using the default order. Primitive integers are
+    /// sorted on the GPU when possible, everything else goes to
+    /// [`parallel::sort`].
+    pub fn sort<T>(&self, array: &Arc<[T]>) -> Vec<T>
+    where
+        T: Ord + Clone + Send + Sync + 'static,
+    {
+        self.try_sort_as::<u8, T>(array)
+            .or_else(|| self.try_sort_as::<u16, T>(array))
+            .or_else(|| self.try_sort_as::<u32, T>(array))
+            .or_else(|| self.try_sort_as::<u64, T>(array))
+            .or_else(|| self.try_sort_as::<usize, T>(array))
+            .or_else(|| self.try_sort_as::<i8, T>(array))
+            .or_else(|| self.try_sort_as::<i16, T>(array))
+            .or_else(|| self.try_sort_as::<i32, T>(array))
+            .or_else(|| self.try_sort_as::<i64, T>(array))
+            .or_else(|| self.try_sort_as::<isize, T>(array))
+            .unwrap_or_else(|| parallel::sort(array))
+    }
+
+    /// Sorts the array on the GPU if its elements are of type `K`. Returns
+    /// `None` if they are not, or if the GPU cannot take the array.
+    fn try_sort_as<K, T>(&self, array: &Arc<[T]>) -> Option<Vec<T>>
+    where
+        K: GpuKey,
+        T: 'static,
+    {
+        let values = (array as &dyn Any).downcast_ref::<Arc<[K]>>()?;
+        let keys = values.iter().map(|value| value.to_key()).collect();
+        let sorted = self
+            .sort_keys(keys)?
+            .into_iter()
+            .map(K::from_key)
+            .collect::<Vec<_>>();
+
+        // `K` and `T` are the same type, this just convinces the compiler.
+        let sorted: Box<dyn Any> = Box::new(sorted);
+        sorted.downcast::<Vec<T>>().ok().map(|sorted| *sorted)
+    }
+
+    /// Sorts the keys on the GPU. Returns `None` if there is no device or the
+    /// keys do not fit in its limits.
+    fn sort_keys(&self, mut keys: Vec<u64>) -> Option<Vec<u64>> {
+        let Device { device, queue, pipeline } = self.device.as_ref()?;
+
+        let count = keys.len();
+        if count < 2 {
+            return Some(keys);
+        }
+
+        // Bitonic sort needs a power of two; the padding sorts last.
+        let padded = count.checked_next_power_of_two()?;
+        let size = u64::try_from(padded).ok()?.checked_mul(8)?;
+        let limits = device.limits();
+        if padded > u32::MAX as usize
+            || size > limits.max_buffer_size
+            || size > limits.max_storage_buffer_binding_size
+        {
+            return None;
+        }
+
+        // Spreads the workgroups over two dimensions if needed.
+        let groups = (padded as u64).div_ceil(WORKGROUP_SIZE);
+        let max_groups = u64::from(limits.max_compute_workgroups_per_dimension);
+        let groups_x = groups.min(max_groups);
+        let groups_y = groups.div_ceil(groups_x);
+        if groups_y > max_groups {
+            return None;
+        }
+
+        keys.resize(padded, u64::MAX);
+        // Little endian keys are laid out as the shader's `(low, high)`.
+        let bytes = keys
+            .iter()
+            .flat_map(|key| key.to_le_bytes())
+            .collect::<Vec<_>>();
+
+        let storage =
+            device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("keys"),
+                contents: &bytes,
+                usage: wgpu::BufferUsages::STORAGE
+                    | wgpu::BufferUsages::COPY_SRC,
+            });
+        let staging = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("readback"),
+            size,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        // One bind group per stage, each with its own block and distance.
+        // They are counted in 64 bits, as doubling the last block past a
+        // padded length of 2^31 overflows 32 bits; the blocks and distances
+        // themselves never exceed the padded length, which fits the shader.
+        let layout = pipeline.get_bind_group_layout(0);
+        let mut stages = Vec::new();
+        let mut block = 2u64;
+        while block <= padded as u64 {
+            let mut distance = block / 2;
+            while distance > 0 {
+                let mut params = (block as u32).to_le_bytes().to_vec();
+                params.extend_from_slice(&(distance as u32).to_le_bytes());
+                let uniform = device.create_buffer_init(
+                    &wgpu::util::BufferInitDescriptor {
+                        label: Some("stage"),
+                        contents: &params,
+                        usage: wgpu::BufferUsages::UNIFORM,
+                    },
+                );
+                stages.push(device.create_bind_group(
+                    &wgpu::BindGroupDescriptor {
+                        label: Some("stage"),
+                        layout: &layout,
+                        entries: &[
+                            wgpu::BindGroupEntry {
+                                binding: 0,
+                                resource: storage.as_entire_binding(),
+                            },
+                            wgpu::BindGroupEntry {
+                                binding: 1,
+                                resource: uniform.as_entire_binding(),
+                            },
+                        ],
+                    },
+                ));
+                distance /= 2;
+            }
+            block *= 2;
+        }
+
+        let mut encoder = device.create_command_encoder(&Default::default());
+        {
+            let mut pass = encoder.begin_compute_pass(&Default::default());
+            pass.set_pipeline(pipeline);
+            for stage in &stages {
+                pass.set_bind_group(0, stage, &[]);
+                pass.dispatch_workgroups(groups_x as u32, groups_y as u32, 1);
+            }
+        }
+        encoder.copy_buffer_to_buffer(&storage, 0, &staging, 0, size);
+        queue.submit(Some(encoder.finish()));
+
+        // Waits for the results to be mapped back.
+        let (sender, receiver) = mpsc::channel();
+        staging.map_async(wgpu::MapMode::Read, .., move |result| {
+            let _ = sender.send(result);
+        });
+        device.poll(wgpu::PollType::wait_indefinitely()).ok()?;
+        receiver.recv().ok()?.ok()?;
+
+        let mapped = staging.get_mapped_range(..).ok()?;
+        let sorted = mapped
+            .chunks_exact(8)
+            .take(count)
+            .map(|word| u64::from_le_bytes(word.try_into().expect("8 bytes")))
+            .collect();
+
+        Some(sorted)
+    }
+}
+
+impl Default for GpuSorter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,5 +1,25 @@
 //! This crate provides a sequential and a parallel implementation of merge
-//! sort.
+//! sort, plus an experimental GPU backend behind the `gpu` feature, an external
+//! merge sort for inputs that do not fit in memory, the harness of the
+//! benchmark comparing them and the schema of its results.
 
 pub mod sequential;
 pub mod parallel;
+pub mod external;
+pub mod bench;
+pub mod comparator;
+pub mod order;
+pub mod gen;
+pub mod verify;
+pub mod report;
+pub mod alloc;
+#[cfg(feature = "trace")]
+pub mod trace;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+#[cfg(feature = "python")]
+pub mod python;
+#[cfg(feature = "gpu")]
+pub mod gpu;
@@ -0,0 +1,169 @@
+//! Comparison functions for the orders strings are commonly sorted in, which
+//! any of the sorts, of this crate or of the standard library, can be given.
+//! Each order is total: strings that are not equal never compare equal, ties
+//! being broken by comparing them as plain strings.
+
+#[cfg(feature = "collation")]
+use std::cell::RefCell;
+use std::cmp::Ordering;
+#[cfg(feature = "icu")]
+use std::{error::Error, fmt};
+
+pub use crate::comparator::{natural_cmp, natural_order};
+
+/// Compares strings ignoring the case of their letters, lowercasing each
+/// character as Unicode maps it, so `"Éclair"` and `"éclair"` compare as
+/// plain strings do, after `"apple"` and before `"zebra"`. Characters
+/// lowercasing into several are compared by all of them, but this is not full
+/// case folding: `"ß"` and `"SS"` are still different.
+///
+/// # Examples
+/// ```rust
+/// use mergesort_cmp::order;
+/// use std::cmp::Ordering;
+///
+/// assert_eq!(order::case_insensitive_cmp("apple", "Banana"), Ordering::Less);
+/// assert_eq!(order::case_insensitive_cmp("ÉTÉ", "étés"), Ordering::Less);
+/// // Only the case differs, which then decides, as with plain strings.
+/// assert_eq!(order::case_insensitive_cmp("Rust", "rust"), Ordering::Less);
+/// ```
+pub fn case_insensitive_cmp(left: &str, right: &str) -> Ordering {
+    let left_lower = left.chars().flat_map(char::to_lowercase);
+    let right_lower = right.chars().flat_map(char::to_lowercase);
+    left_lower.cmp(right_lower).then_with(|| left.cmp(right))
+}
+
+/// A comparison function putting strings in case insensitive order, as
+/// [`case_insensitive_cmp`] compares them.
+///
+/// # Examples
+/// ```rust
+/// use mergesort_cmp::{order, sequential};
+///
+/// let words = ["banana", "Cherry", "apple", "Banana"];
+/// let sorted = sequential::sort_by(&words, order::case_insensitive());
+/// assert_eq!(sorted, ["apple", "Banana", "banana", "Cherry"]);
+/// ```
+pub fn case_insensitive<T>() -> impl Fn(&T, &T) -> Ordering + Copy + Send + Sync
+where
+    T: AsRef<str> + ?Sized,
+{
+    |left: &T, right: &T| case_insensitive_cmp(left.as_ref(), right.as_ref())
+}
+
+/// Compares strings with the Unicode Collation Algorithm, in the root order of
+/// the Unicode Common Locale Data Repository, which sorts letters with accents
+/// next to those without them, and case and accents only decide between
+/// strings that are otherwise equal. Punctuation and spaces are ignored but
+/// for breaking ties. Only with the `collation` feature.
+///
+/// # Examples
+/// ```rust
+/// use mergesort_cmp::order;
+/// use std::cmp::Ordering;
+///
+/// assert_eq!(order::collate("Émile", "Ernie"), Ordering::Less);
+/// assert_eq!(order::collate("peña", "peng"), Ordering::Less);
+/// assert_eq!(order::collate("co-op", "coon"), Ordering::Greater);
+/// ```
+#[cfg(feature = "collation")]
+pub fn collate(left: &str, right: &str) -> Ordering {
+    thread_local! {
+        // The collator keeps buffers across comparisons, so each thread has
+        // one of its own.
+        static COLLATOR: RefCell<feruca::Collator> =
+            RefCell::new(feruca::Collator::default());
+    }
+    COLLATOR.with(|collator| collator.borrow_mut().collate(left, right))
+}
+
+/// A comparison function putting strings in Unicode collation order, as
+/// [`collate`] compares them. Only with the `collation` feature.
+///
+/// # Examples
+/// ```rust
+/// use mergesort_cmp::{order, parallel};
+/// use std::sync::Arc;
+///
+/// let names = ["Zoë", "Émile", "zoe", "Ernie", "Eve"];
+/// let names: Arc<[String]> = names.iter().map(|&name| name.into()).collect();
+/// let sorted = parallel::custom_order(order::unicode_collation())
+///     .threads(2)
+///     .sort(&names);
+/// assert_eq!(*sorted, ["Émile", "Ernie", "Eve", "zoe", "Zoë"]);
+/// ```
+#[cfg(feature = "collation")]
+pub fn unicode_collation<T>(
+) -> impl Fn(&T, &T) -> Ordering + Copy + Send + Sync
+where
+    T: AsRef<str> + ?Sized,
+{
+    |left: &T, right: &T| collate(left.as_ref(), right.as_ref())
+}
+
+/// A comparison function putting strings in the order of the given locale, a
+/// BCP 47 language tag such as `"de-DE"` or `"sv"`, as the collator of ICU4X
+/// for the locale compares them. Locales with no rules of their own follow
+/// the root order, as [`collate`] does. Unicode extensions of the tag are
+/// followed, so `"de-u-co-phonebk"` is the order of German phone books, and
+/// `"en-u-kn"` compares numbers by value. Only with the `icu` feature.
+///
+/// # Errors
+/// Fails if the tag is not a valid locale.
+///
+/// # Examples
+/// ```rust
+/// use mergesort_cmp::{order, parallel, sequential};
+/// use std::sync::Arc;
+///
+/// let words = ["Zucker", "Äpfel", "Ofen", "Öl", "Apfel"];
+///
+/// let german = order::locale("de-DE").unwrap();
+/// let sorted = sequential::sort_by(&words, &german);
+/// assert_eq!(sorted, ["Apfel", "Äpfel", "Ofen", "Öl", "Zucker"]);
+///
+/// // In Swedish, Ä and Ö are letters of their own, after Z.
+/// let words: Arc<[String]> = words.iter().map(|&word| word.into()).collect();
+/// let sorted = parallel::custom_order(order::locale("sv").unwrap())
+///     .threads(2)
+///     .sort(&words);
+/// assert_eq!(*sorted, ["Apfel", "Ofen", "Zucker", "Äpfel", "Öl"]);
+///
+/// assert!(order::locale::<str>("not a locale").is_err());
+/// ```
+#[cfg(feature = "icu")]
+pub fn locale<T>(
+    tag: &str,
+) -> Result<impl Fn(&T, &T) -> Ordering + Send + Sync, LocaleError>
+where
+    T: AsRef<str> + ?Sized,
+{
+    let error = || LocaleError { tag: tag.to_owned() };
+    let locale =
+        icu_locale_core::Locale::try_from_str(tag).map_err(|_| error())?;
+    let options = icu_collator::options::CollatorOptions::default();
+    let collator = icu_collator::Collator::try_new((&locale).into(), options)
+        .map_err(|_| error())?;
+    Ok(move |left: &T, right: &T| {
+        let (left, right) = (left.as_ref(), right.as_ref());
+        collator.compare(left, right).then_with(|| left.cmp(right))
+    })
+}
+
+/// Error returned when a locale is not valid, see [`locale`].
+#[cfg(feature = "icu")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LocaleError {
+    /// The tag of the locale as given.
+    tag: String,
+}
+
+#[cfg(feature = "icu")]
+impl fmt::Display for LocaleError {
+    fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmtr, "invalid locale {:?}", self.tag)
+    }
+}
+
+#[cfg(feature = "icu")]
+impl Error for LocaleError {}
@@ -55,12 +55,62 @@
 //!
 //! assert_eq!(sorted, &[12, -95, 95, 20000]);
 //! ```
+//!
+//! # Stability
+//! The sort is stable, however the threads split the array: elements that
+//! compare equal keep their order.
+//! ```rust
+//! use mergesort_cmp::parallel;
+//! use std::sync::Arc;
+//!
+//! // Every key is equal, so the positions must not move.
+//! let array = (0 .. 1000).map(|index| (7, index)).collect::<Vec<_>>();
+//! let array: Arc<[(i32, usize)]> = Arc::from(array);
+//!
+//! let sorted = parallel::custom_order(|a: &(i32, usize), b: &(i32, usize)| {
+//!     a.0.cmp(&b.0)
+//! })
+//! .threads(8)
+//! .sort(&array);
+//!
+//! assert_eq!(&sorted[..], &array[..]);
+//! ```
 
-use std::{cmp::Ordering, marker::PhantomData, ops::Range, sync::Arc, thread};
+use crate::{
+    alloc::{self, AllocStats},
+    verify::{ConsistencyCheck, InconsistentComparatorError},
+};
+use std::{
+    cmp::Ordering,
+    error::Error,
+    fmt,
+    marker::PhantomData,
+    mem,
+    ops::Range,
+    panic::{self, AssertUnwindSafe},
+    str::FromStr,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering as AtomicOrdering},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+mod pool;
+mod simulate;
+
+pub use pool::SortPool;
+pub use simulate::{SimulatedAction, SimulatedStep, Simulation};
+
+/// How many adjacent pairs [`is_sorted_by`] checks between looking whether
+/// another thread already found an unsorted pair.
+const PAIRS_PER_CHECK: usize = 1024;
 
 /// A parallel merge sort. This function uses the default order, sorts the whole
-/// array, and spawns 1 thread per logical CPU. For customization, see
-/// [`SortOptions`].
+/// array, and runs on the process-wide [`SortPool::global`], which has 1 thread
+/// per logical CPU unless the `MERGESORT_CMP_THREADS` environment variable
+/// says otherwise. For customization, see [`SortOptions`].
 ///
 /// It uses the default comparison order and sorts the whole array.
 ///
@@ -80,7 +130,7 @@ pub fn sort<T>(array: &Arc<[T]>) -> Vec<T>
 where
     T: Ord + Clone + Send + Sync + 'static,
 {
-    default_order().sort(array)
+    default_order().sort_in(SortPool::global(), array)
 }
 
 /// # Examples
@@ -98,12 +148,7 @@ pub fn default_order<T>() -> SortOptions<T, impl Fn(&T, &T) -> Ordering>
 where
     T: Ord,
 {
-    SortOptions {
-        threads: num_cpus::get(),
-        compare: Arc::new(Ord::cmp),
-        range: None,
-        _marker: PhantomData,
-    }
+    SortOptions::new(Ord::cmp)
 }
 
 /// # Examples
@@ -122,12 +167,7 @@ pub fn reverse_order<T>() -> SortOptions<T, impl Fn(&T, &T) -> Ordering>
 where
     T: Ord,
 {
-    SortOptions {
-        threads: num_cpus::get(),
-        compare: Arc::new(|left: &T, right: &T| right.cmp(left)),
-        range: None,
-        _marker: PhantomData,
-    }
+    SortOptions::new(|left: &T, right: &T| right.cmp(left))
 }
 
 /// ## Examples
@@ -150,31 +190,213 @@ pub fn custom_order<T, F>(compare: F) -> SortOptions<T, F>
 where
     F: Fn(&T, &T) -> Ordering,
 {
-    SortOptions {
-        threads: num_cpus::get(),
-        compare: Arc::new(compare),
-        range: None,
-        _marker: PhantomData,
+    SortOptions::new(compare)
+}
+
+/// Checks whether the given array is sorted according to the given comparison
+/// function, i.e. no element is greater than the next one. The adjacent pairs
+/// are split in contiguous chunks, one per thread, and every thread stops as
+/// soon as any of them finds an unsorted pair.
+///
+/// # Examples
+/// ```rust
+/// use mergesort_cmp::parallel;
+/// use std::sync::Arc;
+///
+/// let sorted: Arc<[i32]> = Arc::from((0 .. 10000).collect::<Vec<_>>());
+/// assert!(parallel::is_sorted_by(&sorted, |a, b| a.cmp(b), 8));
+///
+/// let mut unsorted = (0 .. 10000).collect::<Vec<_>>();
+/// unsorted.swap(5000, 5001);
+/// let unsorted: Arc<[i32]> = Arc::from(unsorted);
+/// assert!(!parallel::is_sorted_by(&unsorted, |a, b| a.cmp(b), 8));
+/// ```
+pub fn is_sorted_by<T, F>(array: &Arc<[T]>, compare: F, threads: usize) -> bool
+where
+    T: Send + Sync + 'static,
+    F: Fn(&T, &T) -> Ordering + Send + Sync + 'static,
+{
+    // Pair `i` is made of the elements `i` and `i + 1`.
+    let pairs = array.len().saturating_sub(1);
+    let chunk_size = pairs.div_ceil(threads.max(1)).max(1);
+    let compare = Arc::new(compare);
+    let unsorted = Arc::new(AtomicBool::new(false));
+
+    // Spawns a thread for every chunk but the first one.
+    let handles = (chunk_size .. pairs)
+        .step_by(chunk_size)
+        .map(|start| {
+            let array = array.clone();
+            let compare = compare.clone();
+            let unsorted = unsorted.clone();
+            let end = pairs.min(start + chunk_size);
+            thread::spawn(move || {
+                check_pairs(&array, start .. end, &*compare, &unsorted)
+            })
+        })
+        .collect::<Vec<_>>();
+
+    // The current thread checks the first chunk.
+    check_pairs(array, 0 .. pairs.min(chunk_size), &*compare, &unsorted);
+
+    for handle in handles {
+        if let Err(payload) = handle.join() {
+            panic::resume_unwind(payload);
+        }
+    }
+
+    !unsorted.load(AtomicOrdering::Relaxed)
+}
+
+/// Checks the adjacent pairs of the given range of pair indices, raising the
+/// `unsorted` flag if one of them is out of order. Gives up early if the flag
+/// is raised by someone else. If the comparison function panics, the flag is
+/// raised as well, so the other threads stop, and the panic resumed.
+fn check_pairs<T, F>(
+    array: &[T],
+    pairs: Range<usize>,
+    compare: &F,
+    unsorted: &AtomicBool,
+) where
+    F: Fn(&T, &T) -> Ordering,
+{
+    let mut start = pairs.start;
+
+    let checked = panic::catch_unwind(AssertUnwindSafe(|| {
+        while start < pairs.end && !unsorted.load(AtomicOrdering::Relaxed) {
+            let end = pairs.end.min(start + PAIRS_PER_CHECK);
+
+            // The block of elements covering these pairs.
+            let block = &array[start ..= end];
+            let found = block
+                .windows(2)
+                .any(|pair| compare(&pair[0], &pair[1]) == Ordering::Greater);
+
+            if found {
+                unsorted.store(true, AtomicOrdering::Relaxed);
+            }
+
+            start = end;
+        }
+    }));
+
+    if let Err(payload) = checked {
+        unsorted.store(true, AtomicOrdering::Relaxed);
+        panic::resume_unwind(payload);
     }
 }
 
+/// Merges two arrays, each already sorted according to the options, into a
+/// sorted vector. The arrays are partitioned around middle elements and the
+/// parts merged in parallel, using the thread count and comparison function of
+/// the options; their range is ignored. Elements of `lower` go before equal
+/// elements of `upper`.
+///
+/// # Examples
+/// ```rust
+/// use mergesort_cmp::parallel;
+/// use std::sync::Arc;
+///
+/// let lower: Arc<[i32]> = Arc::from(vec![-95, -1, 5, 12]);
+/// let upper: Arc<[i32]> = Arc::from(vec![-12, 7, 20000, 20001, 91293]);
+///
+/// let options = parallel::default_order();
+/// let merged = parallel::merge(&lower, &upper, &options);
+///
+/// assert_eq!(merged, &[-95, -12, -1, 5, 7, 12, 20000, 20001, 91293]);
+/// ```
+pub fn merge<T, F>(
+    lower: &Arc<[T]>,
+    upper: &Arc<[T]>,
+    options: &SortOptions<T, F>,
+) -> Vec<T>
+where
+    T: Clone + Send + Sync + 'static,
+    F: Fn(&T, &T) -> Ordering + Send + Sync + 'static,
+{
+    merge_shared(
+        lower,
+        0 .. lower.len(),
+        upper,
+        0 .. upper.len(),
+        &options.compare,
+        &options.workers(),
+        options.merge_threads,
+    )
+}
+
 /// Options to configure the parallel merge sort.
 pub struct SortOptions<T, F> {
-    /// On how many threads the sorting will be executed.
-    threads: usize,
+    /// On how many threads the halves will be sorted.
+    split_threads: usize,
+    /// On how many threads the sorted halves will be merged.
+    merge_threads: usize,
     /// Comparison function.
     compare: Arc<F>,
     /// What range of the array will be sorted. `None` automatically selects
     /// the full array.
     range: Option<Range<usize>>,
+    /// How many bytes the sort may allocate. `None` means unlimited.
+    memory_limit: Option<usize>,
+    /// Priority of the spawned threads. `None` keeps the inherited one.
+    #[cfg(feature = "thread-priority")]
+    priority: Option<Priority>,
+    /// What cancels the sorts. `None` lets them run to the end.
+    cancellation: Option<Cancellation>,
     /// Here so we can have T as a type parameter.
     _marker: PhantomData<*const T>,
 }
 
 impl<T, F> SortOptions<T, F> {
-    /// Sets the number of threads used.
+    /// Creates options with the given comparison function and everything else
+    /// set to the defaults.
+    fn new(compare: F) -> Self {
+        Self {
+            split_threads: num_cpus::get(),
+            merge_threads: num_cpus::get(),
+            compare: Arc::new(compare),
+            range: None,
+            memory_limit: None,
+            #[cfg(feature = "thread-priority")]
+            priority: None,
+            cancellation: None,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Sets the number of threads used, both to sort the halves and to merge
+    /// them.
     pub fn threads(&mut self, threads: usize) -> &mut Self {
-        self.threads = threads;
+        self.split_threads(threads).merge_threads(threads)
+    }
+
+    /// Sets the number of threads used to sort the halves, e.g. one per
+    /// physical CPU for the compute bound part.
+    pub fn split_threads(&mut self, threads: usize) -> &mut Self {
+        self.split_threads = threads;
+        self
+    }
+
+    /// Sets the number of threads used to merge sorted halves, e.g. one per
+    /// logical CPU for the memory bound part.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use mergesort_cmp::parallel;
+    /// use std::sync::Arc;
+    ///
+    /// let array = [-1, 5, 91293, 12, -95, 20000, 20001, -12, 7];
+    /// let array: Arc<[i32]> = Arc::from(&array as &[_]);
+    ///
+    /// let sorted = parallel::default_order()
+    ///     .split_threads(2)
+    ///     .merge_threads(8)
+    ///     .sort(&array);
+    ///
+    /// assert_eq!(sorted, &[-95, -12, -1, 5, 7, 12, 20000, 20001, 91293]);
+    /// ```
+    pub fn merge_threads(&mut self, threads: usize) -> &mut Self {
+        self.merge_threads = threads;
         self
     }
 
@@ -200,32 +422,1182 @@ impl<T, F> SortOptions<T, F> {
         self
     }
 
+    /// Limits how many bytes the sort may allocate, not counting the input
+    /// array. Merging into fresh buffers needs about twice the size of the
+    /// sorted range; below that, merges are done in place, which is slower
+    /// but only needs room for the output. A limit that cannot even hold the
+    /// output makes [`SortOptions::try_sort`] fail.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use mergesort_cmp::parallel;
+    /// use std::{mem, sync::Arc};
+    ///
+    /// let count = 10000;
+    /// let expected = (0 .. count).collect::<Vec<_>>();
+    /// let reversed = (0 .. count).rev().collect::<Vec<_>>();
+    /// let array: Arc<[i32]> = Arc::from(reversed);
+    ///
+    /// let sorted = parallel::default_order()
+    ///     .memory_limit(count as usize * mem::size_of::<i32>())
+    ///     .try_sort(&array);
+    /// assert_eq!(sorted, Ok(expected));
+    ///
+    /// let sorted =
+    ///     parallel::default_order().memory_limit(100).try_sort(&array);
+    /// assert!(sorted.is_err());
+    /// ```
+    pub fn memory_limit(&mut self, bytes: usize) -> &mut Self {
+        self.memory_limit = Some(bytes);
+        self
+    }
+
+    /// Lets the sort allocate as much memory as it wants (default).
+    pub fn unlimited_memory(&mut self) -> &mut Self {
+        self.memory_limit = None;
+        self
+    }
+
+    /// Sets the scheduling priority of the spawned threads, so background
+    /// sorts can yield to other work, or latency critical sorts can be
+    /// boosted. The calling thread keeps its own priority.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use mergesort_cmp::parallel::{self, Priority};
+    /// use std::sync::Arc;
+    ///
+    /// let array = [-1, 5, 91293, 12, -95, 20000, 20001, -12, 7];
+    /// let array: Arc<[i32]> = Arc::from(&array as &[_]);
+    ///
+    /// let sorted = parallel::default_order()
+    ///     .thread_priority(Priority::Lowest)
+    ///     .sort(&array);
+    ///
+    /// assert_eq!(sorted, &[-95, -12, -1, 5, 7, 12, 20000, 20001, 91293]);
+    /// ```
+    #[cfg(feature = "thread-priority")]
+    pub fn thread_priority(&mut self, priority: Priority) -> &mut Self {
+        self.priority = Some(priority);
+        self
+    }
+
+    /// Lets the spawned threads inherit the caller's priority (default).
+    #[cfg(feature = "thread-priority")]
+    pub fn inherit_priority(&mut self) -> &mut Self {
+        self.priority = None;
+        self
+    }
+
+    /// Lets the sorts be cancelled from another thread through the given
+    /// handle. Once it is cancelled, the threads of a sort give up at their
+    /// next split or merge step, and the sort panics with [`Cancelled`] as the
+    /// payload.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use mergesort_cmp::parallel::{self, Cancellation, Cancelled};
+    /// use std::{
+    ///     panic::{self, AssertUnwindSafe},
+    ///     sync::Arc,
+    /// };
+    ///
+    /// let reversed = (0 .. 10000).rev().collect::<Vec<_>>();
+    /// let array: Arc<[i32]> = Arc::from(reversed);
+    /// let cancellation = Cancellation::new();
+    /// let mut options = parallel::default_order();
+    /// options.threads(4).cancellation(&cancellation);
+    ///
+    /// cancellation.cancel();
+    /// let sorted = panic::catch_unwind(AssertUnwindSafe(|| {
+    ///     options.sort(&array)
+    /// }));
+    ///
+    /// assert!(sorted.unwrap_err().is::<Cancelled>());
+    /// ```
+    pub fn cancellation(&mut self, cancellation: &Cancellation) -> &mut Self {
+        self.cancellation = Some(cancellation.clone());
+        self
+    }
+
+    /// Lets the sorts run to the end (default).
+    pub fn uncancellable(&mut self) -> &mut Self {
+        self.cancellation = None;
+        self
+    }
+
     /// Sorts the given array using the given options.
+    ///
+    /// # Panics
+    /// Panics if the memory limit is too low, see [`SortOptions::try_sort`].
+    /// If the comparison function panics, the panic is resumed on the calling
+    /// thread once the other threads gave up on their halves and merges.
     pub fn sort(&self, array: &Arc<[T]>) -> Vec<T>
+    where
+        F: Fn(&T, &T) -> Ordering + Send + Sync + 'static,
+        T: Clone + Send + Sync + 'static,
+    {
+        self.try_sort(array).unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    /// Sorts the given array like [`SortOptions::sort`], also counting what
+    /// the sort allocates on all of its threads, as [`alloc::measure`] does.
+    /// The stats are `None` if the global allocator is not an
+    /// [`alloc::CountingAllocator`].
+    ///
+    /// # Examples
+    /// ```rust
+    /// use mergesort_cmp::{alloc::CountingAllocator, parallel};
+    /// use std::sync::Arc;
+    ///
+    /// #[global_allocator]
+    /// static ALLOCATOR: CountingAllocator = CountingAllocator::system();
+    ///
+    /// fn main() {
+    ///     let array: Arc<[u32]> = (0 .. 1000).rev().collect();
+    ///
+    ///     let mut options = parallel::default_order();
+    ///     options.threads(4);
+    ///     let (sorted, stats) = options.sort_with_stats(&array);
+    ///     let stats = stats.unwrap();
+    ///
+    ///     assert_eq!(sorted, (0 .. 1000).collect::<Vec<u32>>());
+    ///     // At least the sorted output is live at the end.
+    ///     assert!(stats.peak_bytes >= 4 * 1000);
+    /// }
+    /// ```
+    pub fn sort_with_stats(
+        &self,
+        array: &Arc<[T]>,
+    ) -> (Vec<T>, Option<AllocStats>)
+    where
+        F: Fn(&T, &T) -> Ordering + Send + Sync + 'static,
+        T: Clone + Send + Sync + 'static,
+    {
+        alloc::measure(|| self.sort(array))
+    }
+
+    /// Sorts the given array using the given options, failing if the memory
+    /// limit cannot hold the sorted output.
+    pub fn try_sort(&self, array: &Arc<[T]>) -> Result<Vec<T>, MemoryLimitError>
     where
         F: Fn(&T, &T) -> Ordering + Send + Sync + 'static,
         T: Clone + Send + Sync + 'static,
     {
         let range = self.range.clone().unwrap_or(0 .. array.len());
-        split(array, range, &self.compare, self.threads)
+
+        // Leaves are copied straight from the shared array.
+        let leaf = {
+            let array = array.clone();
+            move |range: Range<usize>| array[range].to_vec()
+        };
+
+        self.execute(range, leaf, self.compare.clone())
+    }
+
+    /// Sorts the given array like [`SortOptions::sort`], also checking the
+    /// comparison function while sorting, to debug comparison functions: one
+    /// of every `every` comparisons, or every one with 1, is done the other
+    /// way around too, which must give the opposite ordering, as
+    /// [`sequential::sort_checked_by`](crate::sequential::sort_checked_by)
+    /// does. Comparisons are counted over all the threads.
+    ///
+    /// # Errors
+    /// The first pair of elements the comparison function was found
+    /// inconsistent on, once the sort is done.
+    ///
+    /// # Panics
+    /// Panics if the memory limit is too low, see [`SortOptions::try_sort`].
+    ///
+    /// # Examples
+    /// ```rust
+    /// use mergesort_cmp::parallel;
+    /// use std::{cmp::Ordering, sync::Arc};
+    ///
+    /// let array: Arc<[i32]> = (0 .. 1000).rev().map(|n| n % 10).collect();
+    ///
+    /// // Equal elements are greater than each other.
+    /// let greater_or_equal = |left: &i32, right: &i32| match left >= right {
+    ///     true => Ordering::Greater,
+    ///     false => Ordering::Less,
+    /// };
+    /// let error = parallel::custom_order(greater_or_equal)
+    ///     .threads(4)
+    ///     .sort_checked(&array, 10)
+    ///     .unwrap_err();
+    ///
+    /// assert_eq!(error.left, error.right);
+    /// assert_eq!(error.forward, Ordering::Greater);
+    /// ```
+    pub fn sort_checked(
+        &self,
+        array: &Arc<[T]>,
+        every: usize,
+    ) -> Result<Vec<T>, InconsistentComparatorError<T>>
+    where
+        F: Fn(&T, &T) -> Ordering + Send + Sync + 'static,
+        T: Clone + Send + Sync + 'static,
+    {
+        let range = self.range.clone().unwrap_or(0 .. array.len());
+        let leaf = {
+            let array = array.clone();
+            move |range: Range<usize>| array[range].to_vec()
+        };
+
+        let check = Arc::new(ConsistencyCheck::new(every));
+        let compare = {
+            let check = check.clone();
+            let compare = self.compare.clone();
+            Arc::new(move |left: &T, right: &T| {
+                check.compare(&*compare, left, right)
+            })
+        };
+        let sorted = self
+            .execute(range, leaf, compare)
+            .unwrap_or_else(|error| panic!("{}", error));
+
+        check.finish()?;
+        Ok(sorted)
+    }
+
+    /// Merges the two halves of the given array split at `middle`, each
+    /// already sorted according to the options, into a sorted vector, as
+    /// [`merge`] does with two arrays. The range of the options is ignored.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use mergesort_cmp::parallel;
+    /// use std::sync::Arc;
+    ///
+    /// let array = [-95, -1, 5, 12, -12, 7, 20000, 20001, 91293];
+    /// let array: Arc<[i32]> = Arc::from(&array as &[_]);
+    ///
+    /// let merged = parallel::default_order()
+    ///     .threads(4)
+    ///     .merge_halves(&array, 4);
+    ///
+    /// assert_eq!(merged, &[-95, -12, -1, 5, 7, 12, 20000, 20001, 91293]);
+    /// ```
+    pub fn merge_halves(&self, array: &Arc<[T]>, middle: usize) -> Vec<T>
+    where
+        F: Fn(&T, &T) -> Ordering + Send + Sync + 'static,
+        T: Clone + Send + Sync + 'static,
+    {
+        merge_shared(
+            array,
+            0 .. middle,
+            array,
+            middle .. array.len(),
+            &self.compare,
+            &self.workers(),
+            self.merge_threads,
+        )
+    }
+
+    /// Sorts several ranges of the given array in a single pass, ignoring the
+    /// range of the options. The ranges are split in groups of about the same
+    /// number of elements, sharing the threads like the halves of a single
+    /// sort do. Returns the sorted ranges, in the given order. If the memory
+    /// limit cannot hold the merge buffers, the ranges are sorted in place
+    /// instead, one after the other.
+    ///
+    /// # Panics
+    /// Panics before sorting anything if a range is out of bounds of the
+    /// array, or if two ranges overlap. Empty ranges overlap nothing. Panics
+    /// too if the memory limit cannot hold the sorted ranges.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use mergesort_cmp::parallel;
+    /// use std::sync::Arc;
+    ///
+    /// let array = [-1, 5, 91293, 12, -95, 20000, 20001, -12, 7];
+    /// let array: Arc<[i32]> = Arc::from(&array as &[_]);
+    ///
+    /// let sorted = parallel::default_order()
+    ///     .threads(4)
+    ///     .sort_ranges(&array, &[0 .. 3, 3 .. 6, 7 .. 9]);
+    ///
+    /// assert_eq!(sorted, vec![
+    ///     vec![-1, 5, 91293],
+    ///     vec![-95, 12, 20000],
+    ///     vec![-12, 7],
+    /// ]);
+    /// ```
+    ///
+    /// Ranges must not overlap:
+    /// ```rust,should_panic
+    /// use mergesort_cmp::parallel;
+    /// use std::sync::Arc;
+    ///
+    /// let array: Arc<[i32]> = Arc::from(vec![3, 2, 1, 0]);
+    ///
+    /// parallel::default_order().sort_ranges(&array, &[0 .. 3, 2 .. 4]);
+    /// ```
+    pub fn sort_ranges(
+        &self,
+        array: &Arc<[T]>,
+        ranges: &[Range<usize>],
+    ) -> Vec<Vec<T>>
+    where
+        F: Fn(&T, &T) -> Ordering + Send + Sync + 'static,
+        T: Clone + Send + Sync + 'static,
+    {
+        check_ranges(ranges, array.len());
+
+        let leaf = {
+            let array = array.clone();
+            move |range: Range<usize>| array[range].to_vec()
+        };
+        let context = self.context(leaf, self.compare.clone());
+
+        // The outputs alone; merging in place needs nothing more.
+        let total = ranges.iter().map(|range| range.len()).sum::<usize>();
+        let required = total.saturating_mul(mem::size_of::<T>());
+        match self.memory_limit {
+            Some(limit) if limit < required => {
+                panic!("{}", MemoryLimitError { required, limit })
+            },
+
+            Some(limit) if limit / 2 < required => {
+                // Only the outputs fit: copies each range once, sorts it in
+                // place with all the threads.
+                let sort = |range: &Range<usize>| {
+                    let mut sorted = (context.leaf)(range.clone());
+                    split_in_place(&mut sorted, &context, self.split_threads);
+                    sorted
+                };
+                ranges.iter().map(sort).collect()
+            },
+
+            _ => split_ranges(ranges.to_vec(), &context, self.budget()),
+        }
+    }
+
+    /// Sorts the indices of the given array using the given options, without
+    /// cloning any element. The returned vector holds the indices of the
+    /// sorted range, ordered as their elements would be.
+    ///
+    /// # Panics
+    /// Panics if the memory limit cannot hold the sorted indices.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use mergesort_cmp::parallel;
+    /// use std::sync::Arc;
+    ///
+    /// let array = [-1, 5, 91293, 12, -95, 20000, 20001, -12, 7];
+    /// let array: Arc<[i32]> = Arc::from(&array as &[_]);
+    ///
+    /// let indices = parallel::default_order().range(2 .. 7).argsort(&array);
+    ///
+    /// assert_eq!(indices, &[4, 3, 5, 6, 2]);
+    /// ```
+    pub fn argsort(&self, array: &Arc<[T]>) -> Vec<usize>
+    where
+        F: Fn(&T, &T) -> Ordering + Send + Sync + 'static,
+        T: Send + Sync + 'static,
+    {
+        let range = self.range.clone().unwrap_or(0 .. array.len());
+
+        // Leaves are just the indices themselves.
+        let leaf = |range: Range<usize>| range.collect();
+
+        // Compares indices by the elements they reference.
+        let compare = {
+            let array = array.clone();
+            let compare = self.compare.clone();
+            Arc::new(move |left: &usize, right: &usize| {
+                compare(&array[*left], &array[*right])
+            })
+        };
+
+        self.execute(range, leaf, compare)
+            .unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    /// Sorts the given array by the keys the given function extracts,
+    /// ignoring the comparison function of the options. The keys are first
+    /// extracted in parallel into a shared vector, calling the function
+    /// exactly once per element, and then `(key, index)` pairs are sorted.
+    /// This pays off when extracting keys is expensive.
+    ///
+    /// # Panics
+    /// Panics if the memory limit cannot hold the sorted indices.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use mergesort_cmp::parallel;
+    /// use std::sync::Arc;
+    ///
+    /// let array = ["banana", "Cherry", "apple", "Date"];
+    /// let array: Arc<[&str]> = Arc::from(&array as &[_]);
+    ///
+    /// let sorted = parallel::default_order()
+    ///     .sort_by_cached_key(&array, |word| word.to_lowercase());
+    ///
+    /// assert_eq!(sorted, &["apple", "banana", "Cherry", "Date"]);
+    /// ```
+    pub fn sort_by_cached_key<K, G>(&self, array: &Arc<[T]>, key: G) -> Vec<T>
+    where
+        T: Clone + Send + Sync + 'static,
+        K: Ord + Send + Sync + 'static,
+        G: Fn(&T) -> K + Send + Sync + 'static,
+    {
+        let range = self.range.clone().unwrap_or(0 .. array.len());
+        let start = range.start;
+
+        // Every key is extracted only once.
+        let key = Arc::new(key);
+        let workers = self.workers();
+        let threads = self.split_threads;
+        let keys = extract_keys(array, range.clone(), &key, &workers, threads);
+        let keys: Arc<[K]> = Arc::from(keys);
+
+        // Sorts the indices by their keys, and then by themselves.
+        let leaf = |range: Range<usize>| range.collect();
+        let compare = Arc::new(move |left: &usize, right: &usize| {
+            let left_key = &keys[*left - start];
+            let right_key = &keys[*right - start];
+            left_key.cmp(right_key).then(left.cmp(right))
+        });
+        let indices = self
+            .execute(range, leaf, compare)
+            .unwrap_or_else(|error| panic!("{}", error));
+
+        indices.into_iter().map(|index| array[index].clone()).collect()
+    }
+
+    /// Chooses a merge strategy that fits in the memory limit, if any, and
+    /// sorts the given range with it.
+    fn execute<E, L, G>(
+        &self,
+        range: Range<usize>,
+        leaf: L,
+        compare: Arc<G>,
+    ) -> Result<Vec<E>, MemoryLimitError>
+    where
+        E: Send + 'static,
+        L: Fn(Range<usize>) -> Vec<E> + Send + Sync + 'static,
+        G: Fn(&E, &E) -> Ordering + Send + Sync + 'static,
+    {
+        let context = self.context(leaf, compare);
+
+        let limit = match self.memory_limit {
+            Some(limit) => limit,
+            None => {
+                #[cfg(feature = "log")]
+                log::debug!(
+                    "sorting {:?} with {} split and {} merge threads",
+                    range,
+                    self.split_threads,
+                    self.merge_threads
+                );
+                return Ok(split(range, &context, self.budget()));
+            },
+        };
+
+        // The output alone; merging in place needs nothing more.
+        let required = range.len().saturating_mul(mem::size_of::<E>());
+        if limit < required {
+            return Err(MemoryLimitError { required, limit });
+        }
+
+        if limit / 2 >= required {
+            // The sorted halves plus the merge buffer fit.
+            #[cfg(feature = "log")]
+            log::debug!(
+                "sorting {:?} with {} split and {} merge threads, {} bytes \
+                 fitting in the limit of {}",
+                range,
+                self.split_threads,
+                self.merge_threads,
+                required.saturating_mul(2),
+                limit
+            );
+            Ok(split(range, &context, self.budget()))
+        } else {
+            // Only the output fits: copy the whole range once, sort it in
+            // place.
+            #[cfg(feature = "log")]
+            log::debug!(
+                "sorting {:?} in place with {} threads, the {} bytes of the \
+                 merge buffers exceeding the limit of {}",
+                range,
+                self.split_threads,
+                required.saturating_mul(2),
+                limit
+            );
+            let mut sorted = (context.leaf)(range);
+            split_in_place(&mut sorted, &context, self.split_threads);
+            Ok(sorted)
+        }
+    }
+
+    /// Sorts the given array like [`SortOptions::sort`], also recording the
+    /// tree of tasks the sort was split into: which ranges were sorted, by
+    /// which threads, and when. Merges are always done into fresh buffers,
+    /// regardless of the memory limit.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use mergesort_cmp::parallel;
+    /// use std::sync::Arc;
+    ///
+    /// let array = [-1, 5, 91293, 12, -95, 20000, 20001, -12, 7];
+    /// let array: Arc<[i32]> = Arc::from(&array as &[_]);
+    ///
+    /// let (sorted, trace) =
+    ///     parallel::default_order().threads(4).sort_traced(&array);
+    ///
+    /// assert_eq!(sorted, &[-95, -12, -1, 5, 7, 12, 20000, 20001, 91293]);
+    /// assert_eq!(trace.root.range, 0 .. 9);
+    /// assert_eq!(trace.threads(), 4);
+    /// ```
+    pub fn sort_traced(&self, array: &Arc<[T]>) -> (Vec<T>, ExecutionTrace)
+    where
+        F: Fn(&T, &T) -> Ordering + Send + Sync + 'static,
+        T: Clone + Send + Sync + 'static,
+    {
+        let range = self.range.clone().unwrap_or(0 .. array.len());
+        #[cfg(feature = "log")]
+        log::debug!(
+            "sorting {:?} with {} split and {} merge threads, traced",
+            range,
+            self.split_threads,
+            self.merge_threads
+        );
+        self.run_plan(array, range, Plan::Threads(self.budget()))
+    }
+
+    /// Sorts the given array again, following the task tree of a recorded
+    /// trace instead of the thread count of the options, and records a fresh
+    /// trace of this run. This allows comparing the timings of two runs of
+    /// exactly the same decomposition.
+    ///
+    /// # Panics
+    /// Panics if the trace does not describe a valid task tree for the array,
+    /// i.e. its ranges are out of bounds or its halves do not split their
+    /// parent tasks.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use mergesort_cmp::parallel::{self, ExecutionTrace};
+    /// use std::sync::Arc;
+    ///
+    /// let array = [-1, 5, 91293, 12, -95, 20000, 20001, -12, 7];
+    /// let array: Arc<[i32]> = Arc::from(&array as &[_]);
+    ///
+    /// let (_, trace) =
+    ///     parallel::default_order().threads(4).sort_traced(&array);
+    /// let saved = trace.to_string();
+    ///
+    /// let loaded = saved.parse::<ExecutionTrace>().unwrap();
+    /// let (sorted, replayed) =
+    ///     parallel::default_order().replay(&array, &loaded);
+    ///
+    /// assert_eq!(sorted, &[-95, -12, -1, 5, 7, 12, 20000, 20001, 91293]);
+    /// assert_eq!(replayed.threads(), trace.threads());
+    /// ```
+    pub fn replay(
+        &self,
+        array: &Arc<[T]>,
+        trace: &ExecutionTrace,
+    ) -> (Vec<T>, ExecutionTrace)
+    where
+        F: Fn(&T, &T) -> Ordering + Send + Sync + 'static,
+        T: Clone + Send + Sync + 'static,
+    {
+        let range = trace.root.range.clone();
+        if range.end > array.len() || !trace.root.is_valid() {
+            panic!("trace does not describe a valid task tree for the array");
+        }
+        self.run_plan(array, range, Plan::Trace(trace.root.clone()))
+    }
+
+    /// Sorts the range of the array following the given plan, and traces it.
+    fn run_plan(
+        &self,
+        array: &Arc<[T]>,
+        range: Range<usize>,
+        plan: Plan,
+    ) -> (Vec<T>, ExecutionTrace)
+    where
+        F: Fn(&T, &T) -> Ordering + Send + Sync + 'static,
+        T: Clone + Send + Sync + 'static,
+    {
+        let leaf = {
+            let array = array.clone();
+            move |range: Range<usize>| array[range].to_vec()
+        };
+        let context = self.context(leaf, self.compare.clone());
+        let recorder = Arc::new(Recorder::new());
+
+        let (sorted, root) = split_task(range, &context, plan, 0, &recorder);
+        (sorted, ExecutionTrace { root })
+    }
+
+    /// Builds the context shared by the tasks of a sort.
+    fn context<E, L, G>(&self, leaf: L, compare: Arc<G>) -> Arc<Context<L, G>>
+    where
+        L: Fn(Range<usize>) -> Vec<E>,
+        G: Fn(&E, &E) -> Ordering,
+    {
+        Arc::new(Context { leaf, compare, workers: self.workers() })
+    }
+
+    /// The threads a sort with these options starts with.
+    fn budget(&self) -> Budget {
+        Budget { split: self.split_threads, merge: self.merge_threads }
+    }
+
+    /// How the threads spawned by a sort with these options are set up.
+    fn workers(&self) -> Workers {
+        Workers {
+            failed: Arc::new(AtomicBool::new(false)),
+            #[cfg(feature = "thread-priority")]
+            priority: self.priority,
+            counter: alloc::current(),
+            cancellation: self.cancellation.clone(),
+        }
+    }
+}
+
+/// A handle to cancel sorts from another thread, given to their options with
+/// [`SortOptions::cancellation`]. Clones of a handle cancel the same sorts.
+#[derive(Debug, Clone, Default)]
+pub struct Cancellation {
+    /// Whether the sorts were cancelled.
+    cancelled: Arc<AtomicBool>,
+}
+
+impl Cancellation {
+    /// Creates a handle that did not cancel anything yet.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancels the sorts running with this handle, and those started with it
+    /// from now on.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, AtomicOrdering::Relaxed);
+    }
+
+    /// Whether the sorts were cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(AtomicOrdering::Relaxed)
+    }
+}
+
+/// The panic payload of a sort given up because its [`Cancellation`] was
+/// cancelled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cancelled;
+
+/// Error returned when the memory limit of a sort is too low to even hold the
+/// sorted output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MemoryLimitError {
+    /// The minimum number of bytes the sort needs.
+    pub required: usize,
+    /// The configured limit, in bytes.
+    pub limit: usize,
+}
+
+impl fmt::Display for MemoryLimitError {
+    fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            fmtr,
+            "memory limit of {} bytes is below the {} bytes required to hold \
+             the sorted output",
+            self.limit, self.required
+        )
+    }
+}
+
+impl Error for MemoryLimitError {}
+
+/// The recorded tree of tasks of a sort, see [`SortOptions::sort_traced`].
+///
+/// A trace can be saved with its [`Display`](fmt::Display) implementation and
+/// loaded back with its [`FromStr`] implementation. The format has one line
+/// per task, in pre-order, indented by depth:
+/// ```text
+/// 0..9 thread=0 start=1523 end=80210 order=2 split
+///   0..5 thread=0 start=1610 end=40030 order=0
+///   5..9 thread=1 start=35371 end=61132 order=1
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExecutionTrace {
+    /// The task that sorted the whole range.
+    pub root: TraceTask,
+}
+
+/// A task of a recorded sort. A task either sorts its range sequentially, or
+/// splits it in two halves: the lower half is sorted in the same thread, the
+/// upper half in a new thread, and then both are joined and merged.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceTask {
+    /// The range of the array sorted by this task.
+    pub range: Range<usize>,
+    /// The thread that ran this task: `0` is the calling thread, the others
+    /// are numbered as they are spawned.
+    pub thread: usize,
+    /// When this task started, since the start of the sort.
+    pub start: Duration,
+    /// When this task finished, since the start of the sort.
+    pub end: Duration,
+    /// The position in which this task finished among all tasks, i.e. the
+    /// order in which tasks were joined.
+    pub order: usize,
+    /// The lower and upper halves, if this task was split.
+    pub halves: Option<Box<(TraceTask, TraceTask)>>,
+}
+
+impl ExecutionTrace {
+    /// How many threads took part in the sort, including the calling thread.
+    pub fn threads(&self) -> usize {
+        self.root.threads()
+    }
+
+    /// The share of the time the threads of the task tree were busy over the
+    /// whole sort, from 0 to 1. A thread is idle before it is spawned, once
+    /// it finished, and while it waits for the thread sorting the upper half
+    /// of its task. The threads spawned by the merges are not traced, and
+    /// are not counted.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use mergesort_cmp::parallel;
+    /// use std::sync::Arc;
+    ///
+    /// let array = (0 .. 10000).rev().collect::<Vec<i32>>();
+    /// let array: Arc<[i32]> = Arc::from(array);
+    ///
+    /// let (_, trace) =
+    ///     parallel::default_order().threads(4).sort_traced(&array);
+    /// let utilization = trace.utilization();
+    ///
+    /// assert!(utilization > 0.0 && utilization <= 1.0);
+    /// ```
+    pub fn utilization(&self) -> f64 {
+        let root = &self.root;
+        let span = root.end.as_secs_f64() - root.start.as_secs_f64();
+        let capacity = span * self.threads() as f64;
+        match capacity > 0.0 {
+            true => (root.busy() / capacity).clamp(0.0, 1.0),
+            false => 1.0,
+        }
+    }
+}
+
+impl TraceTask {
+    /// How many distinct threads ran this task and its halves.
+    fn threads(&self) -> usize {
+        match &self.halves {
+            Some(halves) => halves.0.threads() + halves.1.threads(),
+            None => 1,
+        }
+    }
+
+    /// How many seconds the threads that ran this task and its halves were
+    /// busy with it, summed over the threads.
+    fn busy(&self) -> f64 {
+        let span = |task: &TraceTask| {
+            task.end.as_secs_f64() - task.start.as_secs_f64()
+        };
+        match &self.halves {
+            Some(halves) => {
+                let (lower, upper) = &**halves;
+                // Past the lower half, this thread waits for the upper one
+                // before merging them.
+                let waiting = upper.end.as_secs_f64() - lower.end.as_secs_f64();
+                let waiting = waiting.max(0.0);
+                let own = (span(self) - span(lower) - waiting).max(0.0);
+                own + lower.busy() + upper.busy()
+            },
+            None => span(self),
+        }
+    }
+
+    /// Whether the halves of this task, and of the halves, split their parent
+    /// ranges.
+    fn is_valid(&self) -> bool {
+        if self.range.start > self.range.end {
+            return false;
+        }
+        match &self.halves {
+            Some(halves) => {
+                let (lower, upper) = &**halves;
+                lower.range.start == self.range.start
+                    && lower.range.end == upper.range.start
+                    && upper.range.end == self.range.end
+                    && lower.is_valid()
+                    && upper.is_valid()
+            },
+            None => true,
+        }
+    }
+
+    /// Writes this task and its halves, indented by the given depth.
+    fn write(&self, fmtr: &mut fmt::Formatter, depth: usize) -> fmt::Result {
+        write!(
+            fmtr,
+            "{:indent$}{}..{} thread={} start={} end={} order={}",
+            "",
+            self.range.start,
+            self.range.end,
+            self.thread,
+            self.start.as_nanos(),
+            self.end.as_nanos(),
+            self.order,
+            indent = depth * 2,
+        )?;
+        match &self.halves {
+            Some(halves) => {
+                writeln!(fmtr, " split")?;
+                halves.0.write(fmtr, depth + 1)?;
+                halves.1.write(fmtr, depth + 1)
+            },
+            None => writeln!(fmtr),
+        }
+    }
+
+    /// Parses a task and its halves from the given lines.
+    fn parse<'input, I>(lines: &mut I) -> Result<Self, ParseTraceError>
+    where
+        I: Iterator<Item = (usize, &'input str)>,
+    {
+        let (number, line) = lines.next().ok_or(ParseTraceError {
+            line: None,
+            message: "missing task",
+        })?;
+        let error =
+            |message| ParseTraceError { line: Some(number + 1), message };
+
+        let mut fields = line.split_whitespace();
+
+        let range = fields.next().ok_or_else(|| error("missing range"))?;
+        let (start, end) =
+            range.split_once("..").ok_or_else(|| error("invalid range"))?;
+        let range = start.parse().map_err(|_| error("invalid range start"))?
+            .. end.parse().map_err(|_| error("invalid range end"))?;
+
+        let mut field = |key: &str| {
+            fields
+                .next()
+                .and_then(|field| field.strip_prefix(key))
+                .and_then(|field| field.strip_prefix('='))
+                .and_then(|value| value.parse::<u64>().ok())
+                .ok_or_else(|| error("invalid field"))
+        };
+        let thread = field("thread")? as usize;
+        let start = Duration::from_nanos(field("start")?);
+        let end = Duration::from_nanos(field("end")?);
+        let order = field("order")? as usize;
+
+        let halves = match fields.next() {
+            Some("split") => {
+                let lower = Self::parse(lines)?;
+                let upper = Self::parse(lines)?;
+                Some(Box::new((lower, upper)))
+            },
+            Some(_) => return Err(error("unexpected field")),
+            None => None,
+        };
+
+        Ok(Self { range, thread, start, end, order, halves })
+    }
+}
+
+impl fmt::Display for ExecutionTrace {
+    fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
+        self.root.write(fmtr, 0)
+    }
+}
+
+impl FromStr for ExecutionTrace {
+    type Err = ParseTraceError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let mut lines = input
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty());
+
+        let root = TraceTask::parse(&mut lines)?;
+
+        match lines.next() {
+            Some((number, _)) => Err(ParseTraceError {
+                line: Some(number + 1),
+                message: "unexpected task after the root task",
+            }),
+            None => Ok(Self { root }),
+        }
+    }
+}
+
+/// Error returned when parsing an [`ExecutionTrace`] fails.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseTraceError {
+    /// The line of the error, starting at 1. `None` means end of input.
+    pub line: Option<usize>,
+    /// What is wrong.
+    pub message: &'static str,
+}
+
+impl fmt::Display for ParseTraceError {
+    fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(fmtr, "{} at line {}", self.message, line),
+            None => write!(fmtr, "{} at end of input", self.message),
+        }
+    }
+}
+
+impl Error for ParseTraceError {}
+
+/// How a traced sort decides to split its tasks.
+#[derive(Debug, Clone)]
+enum Plan {
+    /// Split while there are threads, like the regular sort.
+    Threads(Budget),
+    /// Split exactly like the recorded task.
+    Trace(TraceTask),
+}
+
+/// Assigns thread numbers and completion order to the tasks of a traced sort.
+struct Recorder {
+    /// When the sort started.
+    start: Instant,
+    /// The number of the next spawned thread.
+    next_thread: AtomicUsize,
+    /// The completion order of the next finished task.
+    next_order: AtomicUsize,
+}
+
+impl Recorder {
+    /// Starts recording now.
+    fn new() -> Self {
+        Self {
+            start: Instant::now(),
+            next_thread: AtomicUsize::new(1),
+            next_order: AtomicUsize::new(0),
+        }
+    }
+
+    /// Time elapsed since the start of the sort.
+    fn now(&self) -> Duration {
+        self.start.elapsed()
+    }
+
+    /// Numbers a newly spawned thread.
+    fn spawn_thread(&self) -> usize {
+        self.next_thread.fetch_add(1, AtomicOrdering::Relaxed)
+    }
+
+    /// Numbers a newly finished task.
+    fn finish_task(&self) -> usize {
+        self.next_order.fetch_add(1, AtomicOrdering::Relaxed)
+    }
+}
+
+/// A scheduling priority for the threads spawned by a sort. Only has effect
+/// on Linux, where each thread has its own niceness. Raising the priority
+/// above normal usually requires privileges; if it is denied, the threads keep
+/// running at their inherited priority.
+#[cfg(feature = "thread-priority")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Priority {
+    /// Runs only when nothing else wants the CPU (niceness 19).
+    Lowest,
+    /// Below normal (niceness 10).
+    Low,
+    /// The default priority (niceness 0).
+    Normal,
+    /// Above normal (niceness -10).
+    High,
+    /// Runs before everything else (niceness -20).
+    Highest,
+}
+
+#[cfg(feature = "thread-priority")]
+impl Priority {
+    /// The niceness value of this priority.
+    #[cfg(target_os = "linux")]
+    fn niceness(self) -> libc::c_int {
+        match self {
+            Priority::Lowest => 19,
+            Priority::Low => 10,
+            Priority::Normal => 0,
+            Priority::High => -10,
+            Priority::Highest => -20,
+        }
+    }
+
+    /// Applies this priority to the current thread, best effort.
+    fn apply(self) {
+        #[cfg(target_os = "linux")]
+        unsafe {
+            // On Linux, `PRIO_PROCESS` with id `0` targets the calling thread
+            // only. Failure just keeps the inherited priority.
+            libc::setpriority(libc::PRIO_PROCESS, 0, self.niceness());
+        }
+    }
+}
+
+/// How the threads spawned by a sort or a merge are set up, and how they
+/// give up together.
+#[derive(Debug, Clone, Default)]
+struct Workers {
+    /// Whether some thread failed, so the others should give up.
+    failed: Arc<AtomicBool>,
+    /// Priority of the spawned threads. `None` keeps the inherited one.
+    #[cfg(feature = "thread-priority")]
+    priority: Option<Priority>,
+    /// The allocation counter of the measurement the sort runs in, which
+    /// the spawned threads count to as well.
+    counter: Option<Arc<alloc::Counter>>,
+    /// What cancels the sort, if anything.
+    cancellation: Option<Cancellation>,
+}
+
+impl Workers {
+    /// Prepares a freshly spawned thread to work, until the returned guard
+    /// is dropped.
+    fn enter(&self) -> Entered {
+        #[cfg(feature = "thread-priority")]
+        if let Some(priority) = self.priority {
+            priority.apply();
+        }
+        Entered {
+            _counting: alloc::Entered::new(self.counter.clone()),
+        }
+    }
+
+    /// Prepares a thread of a pool to work on a job, until the returned guard
+    /// is dropped. Unlike [`Workers::enter`], the priority is left alone: the
+    /// thread outlives the job, and a lowered priority cannot always be
+    /// raised back.
+    fn enter_job(&self) -> Entered {
+        Entered {
+            _counting: alloc::Entered::new(self.counter.clone()),
+        }
+    }
+
+    /// Gives up the current task if the sort was cancelled, or if some other
+    /// task already failed.
+    fn check(&self) {
+        if self.cancellation.as_ref().is_some_and(Cancellation::is_cancelled) {
+            self.failed.store(true, AtomicOrdering::Relaxed);
+            panic::resume_unwind(Box::new(Cancelled));
+        }
+        if self.failed.load(AtomicOrdering::Relaxed) {
+            panic::resume_unwind(Box::new(Aborted));
+        }
+    }
+
+    /// Runs a task, telling the other tasks to give up if it panics.
+    fn guard<R, G>(&self, task: G) -> thread::Result<R>
+    where
+        G: FnOnce() -> R,
+    {
+        let result = panic::catch_unwind(AssertUnwindSafe(task));
+        if result.is_err() {
+            self.failed.store(true, AtomicOrdering::Relaxed);
+        }
+        result
+    }
+}
+
+/// Keeps a spawned thread set up while it works.
+struct Entered {
+    /// Counts the allocations of the thread to the measurement.
+    _counting: alloc::Entered,
+}
+
+/// How many threads a task may use in each phase of the sort.
+#[derive(Debug, Clone, Copy)]
+struct Budget {
+    /// Threads to sort the halves.
+    split: usize,
+    /// Threads to merge the sorted halves.
+    merge: usize,
+}
+
+impl Budget {
+    /// The budget of each half of a task with this budget.
+    fn halve(self) -> Self {
+        Self { split: self.split / 2, merge: self.merge / 2 }
+    }
+}
+
+/// What every task of a sort shares.
+struct Context<L, F> {
+    /// Converts ranges of length up to 1 into vectors.
+    leaf: L,
+    /// Comparison function.
+    compare: Arc<F>,
+    /// How spawned threads are set up, and whether some task failed.
+    workers: Workers,
+}
+
+impl<L, F> Context<L, F> {
+    /// Gives up the current task if some other task already failed.
+    fn check(&self) {
+        self.workers.check();
+    }
+
+    /// Runs a task, telling the other tasks to give up if it panics.
+    fn guard<R, G>(&self, task: G) -> thread::Result<R>
+    where
+        G: FnOnce() -> R,
+    {
+        self.workers.guard(task)
+    }
+}
+
+/// The panic payload of tasks that gave up because another task failed.
+struct Aborted;
+
+/// Unwraps the results of two guarded halves. If any of them failed, resumes
+/// the panic that caused the failure, rather than the one of a task that just
+/// gave up.
+fn join_halves<A, B>(
+    lower: thread::Result<A>,
+    upper: thread::Result<B>,
+) -> (A, B) {
+    match (lower, upper) {
+        (Ok(lower), Ok(upper)) => (lower, upper),
+        (Err(lower), Err(upper)) => {
+            let payload = if lower.is::<Aborted>() { upper } else { lower };
+            panic::resume_unwind(payload)
+        },
+        (Err(payload), Ok(_)) | (Ok(_), Err(payload)) => {
+            panic::resume_unwind(payload)
+        },
     }
 }
 
 /// Performs the "split" step of the merge sort algorithm, and then merges the
-/// sorted halves.
-fn split<T, F>(
-    array: &Arc<[T]>,
+/// sorted halves. Ranges of length up to 1 are turned into vectors by the
+/// context's leaf function.
+fn split<E, L, F>(
     range: Range<usize>,
-    compare: &Arc<F>,
-    threads: usize,
-) -> Vec<T>
+    context: &Arc<Context<L, F>>,
+    budget: Budget,
+) -> Vec<E>
 where
-    T: Clone + Send + Sync + 'static,
-    F: Fn(&T, &T) -> Ordering + Send + Sync + 'static,
+    E: Send + 'static,
+    L: Fn(Range<usize>) -> Vec<E> + Send + Sync + 'static,
+    F: Fn(&E, &E) -> Ordering + Send + Sync + 'static,
 {
+    // Does not bother if the sort already failed.
+    context.check();
+
     if range.len() > 1 {
-        // The middle index: start + (end - start + 1)/2
-        let half = range.start + (range.len() + 1) / 2;
+        // The middle index: start + ceil((end - start)/2)
+        let half = range.start + range.len().div_ceil(2);
 
         // The lower half range.
         let lower_range = range.start .. half;
@@ -233,64 +1605,511 @@ where
         // The upper half range.
         let upper_range = half .. range.end;
 
+        // Each half gets half of the threads.
+        let halves_budget = budget.halve();
+
         // If there are threads, do the split in separated threads.
-        let (lower, upper) = if threads > 1 {
+        let (lower, upper) = if budget.split > 1 {
+            #[cfg(feature = "log")]
+            log::trace!(
+                "splitting {:?} at {}, {:?} on a new thread, {} split and {} \
+                 merge threads per half",
+                range,
+                half,
+                upper_range,
+                halves_budget.split,
+                halves_budget.merge
+            );
+
             // Spawns the thread that sorts the lower half.
             let upper_handle = {
-                // Clones the array's ARC (Atomic Reference Counter).
-                let array = array.clone();
-                // Clones the comparison function's ARC.
-                let compare = compare.clone();
+                // Clones the context's ARC (Atomic Reference Counter).
+                let context = context.clone();
 
                 // Executes the split on the upper half.
                 thread::spawn(move || {
-                    split(&array, upper_range, &compare, threads / 2)
+                    let _entered = context.workers.enter();
+                    context.guard(|| {
+                        split(upper_range, &context, halves_budget)
+                    })
                 })
             };
 
             // Executes the split on the lower half.
-            let lower = split(array, lower_range, compare, threads / 2);
+            let lower =
+                context.guard(|| split(lower_range, context, halves_budget));
             // Joins the lower thread.
-            let upper = upper_handle.join().expect("thread failed");
+            let upper = upper_handle.join().and_then(|upper| upper);
 
-            (lower, upper)
+            join_halves(lower, upper)
         } else {
             // Executes the split on the lower half.
-            let lower = split(array, lower_range, compare, 1);
+            let lower = split(lower_range, context, halves_budget);
             // Executes the split on the upper half.
-            let upper = split(array, upper_range, compare, 1);
+            let upper = split(upper_range, context, halves_budget);
 
             (lower, upper)
         };
 
-        // Merges the two halves.
-        merge(lower, upper, compare)
+        // Merges the two halves, with every merge thread of this split.
+        let workers = &context.workers;
+        merge_owned(lower, upper, &context.compare, workers, budget.merge)
     } else {
-        // Converts the range of a reference counted, immutable array into a
-        // mutable, owned vector. Returns it.
-        array[range].to_vec()
+        // Converts the range into a mutable, owned vector. Returns it.
+        (context.leaf)(range)
     }
 }
 
-/// Merges two halves of a sorting target.
-fn merge<T, F>(lower: Vec<T>, upper: Vec<T>, compare: &Arc<F>) -> Vec<T>
+/// Extracts the keys of the given range of the array, in order. If there are
+/// threads, the range is split in two halves, whose keys are then extracted in
+/// separated threads.
+fn extract_keys<T, K, G>(
+    array: &Arc<[T]>,
+    range: Range<usize>,
+    key: &Arc<G>,
+    workers: &Workers,
+    threads: usize,
+) -> Vec<K>
 where
-    F: Fn(&T, &T) -> Ordering,
+    T: Send + Sync + 'static,
+    K: Send + 'static,
+    G: Fn(&T) -> K + Send + Sync + 'static,
+{
+    // Does not bother if the extraction already failed.
+    workers.check();
+
+    if threads < 2 || range.len() < 2 {
+        return array[range].iter().map(|elem| key(elem)).collect();
+    }
+
+    // The middle index: start + ceil((end - start)/2)
+    let half = range.start + range.len().div_ceil(2);
+    let upper_range = half .. range.end;
+
+    // Spawns the thread that extracts the upper half.
+    let upper_handle = {
+        let array = array.clone();
+        let key = key.clone();
+        let workers = workers.clone();
+        thread::spawn(move || {
+            let _entered = workers.enter();
+            workers.guard(|| {
+                extract_keys(&array, upper_range, &key, &workers, threads / 2)
+            })
+        })
+    };
+
+    // Extracts the lower half on this thread, then joins the upper half.
+    let lower = workers.guard(|| {
+        extract_keys(array, range.start .. half, key, workers, threads / 2)
+    });
+    let upper = upper_handle.join().and_then(|upper| upper);
+    let (mut keys, upper) = join_halves(lower, upper);
+    keys.extend(upper);
+    keys
+}
+
+/// Panics if a range is out of bounds of an array of the given length, or if
+/// two non-empty ranges overlap, so that no thread of the sort panics on them.
+fn check_ranges(ranges: &[Range<usize>], len: usize) {
+    for range in ranges {
+        if range.start > range.end || range.end > len {
+            panic!(
+                "range {:?} is out of bounds of an array of {} elements",
+                range, len
+            );
+        }
+    }
+
+    let mut sorted = ranges
+        .iter()
+        .filter(|range| !range.is_empty())
+        .collect::<Vec<_>>();
+    sorted.sort_by_key(|range| range.start);
+    for pair in sorted.windows(2) {
+        if pair[0].end > pair[1].start {
+            panic!("ranges {:?} and {:?} overlap", pair[0], pair[1]);
+        }
+    }
+}
+
+/// Sorts each of the given ranges. If there are threads, the ranges are split
+/// in two groups of about the same number of elements, which are then sorted
+/// in separated threads.
+fn split_ranges<E, L, F>(
+    mut ranges: Vec<Range<usize>>,
+    context: &Arc<Context<L, F>>,
+    budget: Budget,
+) -> Vec<Vec<E>>
+where
+    E: Send + 'static,
+    L: Fn(Range<usize>) -> Vec<E> + Send + Sync + 'static,
+    F: Fn(&E, &E) -> Ordering + Send + Sync + 'static,
+{
+    if ranges.len() < 2 {
+        // A single range gets all the threads.
+        let sort = |range| split(range, context, budget);
+        return ranges.into_iter().map(sort).collect();
+    }
+
+    if budget.split < 2 {
+        // No threads? Sorts one range after the other.
+        let sort = |range| split(range, context, budget);
+        return ranges.into_iter().map(sort).collect();
+    }
+
+    // The lower group goes up to the first range that reaches half of the
+    // elements, but leaves at least one range to the upper group.
+    let total = ranges.iter().map(|range| range.len()).sum::<usize>();
+    let mut accumulated = 0;
+    let cut = ranges
+        .iter()
+        .position(|range| {
+            accumulated += range.len();
+            accumulated * 2 >= total
+        })
+        .map_or(1, |index| index + 1)
+        .min(ranges.len() - 1);
+
+    // Splits the ranges in two groups.
+    let upper_ranges = ranges.split_off(cut);
+
+    // Spawns the thread that sorts the upper group.
+    let upper_handle = {
+        let context = context.clone();
+        thread::spawn(move || {
+            let _entered = context.workers.enter();
+            context.guard(|| {
+                split_ranges(upper_ranges, &context, budget.halve())
+            })
+        })
+    };
+
+    // Sorts the lower group on this thread, then joins the upper group.
+    let lower =
+        context.guard(|| split_ranges(ranges, context, budget.halve()));
+    let upper = upper_handle.join().and_then(|upper| upper);
+    let (mut sorted, upper) = join_halves(lower, upper);
+    sorted.extend(upper);
+    sorted
+}
+
+/// Runs a task of a traced sort on the current thread, numbered `thread`. The
+/// task is split according to the plan; tasks that are not split are sorted
+/// sequentially.
+fn split_task<E, L, F>(
+    range: Range<usize>,
+    context: &Arc<Context<L, F>>,
+    plan: Plan,
+    thread: usize,
+    recorder: &Arc<Recorder>,
+) -> (Vec<E>, TraceTask)
+where
+    E: Send + 'static,
+    L: Fn(Range<usize>) -> Vec<E> + Send + Sync + 'static,
+    F: Fn(&E, &E) -> Ordering + Send + Sync + 'static,
+{
+    // Does not bother if the sort already failed.
+    context.check();
+
+    let start = recorder.now();
+
+    // The threads of this task. A recorded task gets one split thread and
+    // one merge thread per thread that ran it.
+    let budget = match &plan {
+        Plan::Threads(budget) => *budget,
+        Plan::Trace(task) => Budget { split: 1, merge: task.threads() },
+    };
+
+    // Finds out the plans for the halves, if the task is split at all.
+    let halves = match plan {
+        Plan::Threads(budget) if budget.split > 1 && range.len() > 1 => {
+            // The middle index: start + ceil((end - start)/2)
+            let half = range.start + range.len().div_ceil(2);
+            let lower = (range.start .. half, Plan::Threads(budget.halve()));
+            let upper = (half .. range.end, Plan::Threads(budget.halve()));
+            Some((lower, upper))
+        },
+        Plan::Trace(task) => task.halves.map(|halves| {
+            let (lower, upper) = *halves;
+            let lower = (lower.range.clone(), Plan::Trace(lower));
+            let upper = (upper.range.clone(), Plan::Trace(upper));
+            (lower, upper)
+        }),
+        Plan::Threads(_) => None,
+    };
+
+    let (sorted, halves) = match halves {
+        Some(((lower_range, lower_plan), (upper_range, upper_plan))) => {
+            #[cfg(feature = "log")]
+            log::trace!(
+                "splitting {:?} at {}, {:?} on a new thread",
+                range,
+                upper_range.start,
+                upper_range
+            );
+
+            // Spawns the thread that sorts the upper half.
+            let upper_handle = {
+                let context = context.clone();
+                let recorder = recorder.clone();
+                let upper_thread = recorder.spawn_thread();
+
+                thread::spawn(move || {
+                    let _entered = context.workers.enter();
+                    context.guard(|| {
+                        split_task(
+                            upper_range,
+                            &context,
+                            upper_plan,
+                            upper_thread,
+                            &recorder,
+                        )
+                    })
+                })
+            };
+
+            // Executes the lower half on this thread.
+            let lower = context.guard(|| {
+                split_task(lower_range, context, lower_plan, thread, recorder)
+            });
+            // Joins the upper thread.
+            let upper = upper_handle.join().and_then(|upper| upper);
+            let ((lower, lower_task), (upper, upper_task)) =
+                join_halves(lower, upper);
+
+            let merged = merge_owned(
+                lower,
+                upper,
+                &context.compare,
+                &context.workers,
+                budget.merge,
+            );
+            (merged, Some(Box::new((lower_task, upper_task))))
+        },
+
+        // Not split? Sorts the whole range on this thread.
+        None => (split(range.clone(), context, budget), None),
+    };
+
+    let task = TraceTask {
+        range,
+        thread,
+        start,
+        end: recorder.now(),
+        order: recorder.finish_task(),
+        halves,
+    };
+
+    (sorted, task)
+}
+
+/// Merges two sorted halves of a sorting target, taking them away. If there
+/// are threads, the halves are partitioned and the parts merged in separated
+/// threads.
+fn merge_owned<E, F>(
+    mut lower: Vec<E>,
+    mut upper: Vec<E>,
+    compare: &Arc<F>,
+    workers: &Workers,
+    threads: usize,
+) -> Vec<E>
+where
+    E: Send + 'static,
+    F: Fn(&E, &E) -> Ordering + Send + Sync + 'static,
+{
+    // Does not bother if the sort already failed.
+    workers.check();
+
+    if threads < 2 || lower.is_empty() || upper.is_empty() {
+        let (lower, upper) = (lower.into_iter(), upper.into_iter());
+        return merge_iters(lower, upper, &**compare, workers);
+    }
+
+    // Cuts both halves so the heads go before the tails.
+    let (lower_cut, upper_cut) = partition(&lower, &upper, &**compare);
+    #[cfg(feature = "log")]
+    log::trace!(
+        "merging {} and {} elements on {} threads, the tails from {} and {} \
+         on a new thread",
+        lower.len(),
+        upper.len(),
+        threads,
+        lower_cut,
+        upper_cut
+    );
+    let lower_tail = lower.split_off(lower_cut);
+    let upper_tail = upper.split_off(upper_cut);
+
+    // Spawns the thread that merges the tails.
+    let tail_handle = {
+        let compare = compare.clone();
+        let workers = workers.clone();
+        thread::spawn(move || {
+            let _entered = workers.enter();
+            workers.guard(|| {
+                let threads = threads / 2;
+                merge_owned(lower_tail, upper_tail, &compare, &workers, threads)
+            })
+        })
+    };
+
+    // Merges the heads on this thread, then appends the merged tails.
+    let heads = workers.guard(|| {
+        merge_owned(lower, upper, compare, workers, threads / 2)
+    });
+    let tails = tail_handle.join().and_then(|tails| tails);
+    let (mut merged, tails) = join_halves(heads, tails);
+    merged.extend(tails);
+    merged
+}
+
+/// Merges the given ranges of two sorted shared arrays, cloning the elements.
+/// If there are threads, the ranges are partitioned and the parts merged in
+/// separated threads.
+fn merge_shared<T, F>(
+    lower: &Arc<[T]>,
+    lower_range: Range<usize>,
+    upper: &Arc<[T]>,
+    upper_range: Range<usize>,
+    compare: &Arc<F>,
+    workers: &Workers,
+    threads: usize,
+) -> Vec<T>
+where
+    T: Clone + Send + Sync + 'static,
+    F: Fn(&T, &T) -> Ordering + Send + Sync + 'static,
+{
+    // Does not bother if the merge already failed.
+    workers.check();
+
+    let lower_slice = &lower[lower_range.clone()];
+    let upper_slice = &upper[upper_range.clone()];
+
+    if threads < 2 || lower_slice.is_empty() || upper_slice.is_empty() {
+        let lower_iter = lower_slice.iter().cloned();
+        let upper_iter = upper_slice.iter().cloned();
+        return merge_iters(lower_iter, upper_iter, &**compare, workers);
+    }
+
+    // Cuts both ranges so the heads go before the tails.
+    let (lower_cut, upper_cut) =
+        partition(lower_slice, upper_slice, &**compare);
+    let lower_cut = lower_range.start + lower_cut;
+    let upper_cut = upper_range.start + upper_cut;
+    #[cfg(feature = "log")]
+    log::trace!(
+        "merging {:?} and {:?} on {} threads, the tails from {} and {} on a \
+         new thread",
+        lower_range,
+        upper_range,
+        threads,
+        lower_cut,
+        upper_cut
+    );
+
+    // The heads and tails of each range.
+    let lower_head = lower_range.start .. lower_cut;
+    let lower_tail = lower_cut .. lower_range.end;
+    let upper_head = upper_range.start .. upper_cut;
+    let upper_tail = upper_cut .. upper_range.end;
+
+    // Spawns the thread that merges the tails.
+    let tail_handle = {
+        let lower = lower.clone();
+        let upper = upper.clone();
+        let compare = compare.clone();
+        let workers = workers.clone();
+        thread::spawn(move || {
+            let _entered = workers.enter();
+            workers.guard(|| {
+                merge_shared(
+                    &lower, lower_tail, &upper, upper_tail, &compare,
+                    &workers, threads / 2,
+                )
+            })
+        })
+    };
+
+    // Merges the heads on this thread, then appends the merged tails.
+    let heads = workers.guard(|| {
+        merge_shared(
+            lower, lower_head, upper, upper_head, compare, workers,
+            threads / 2,
+        )
+    });
+    let tails = tail_handle.join().and_then(|tails| tails);
+    let (mut merged, tails) = join_halves(heads, tails);
+    merged.extend(tails);
+    merged
+}
+
+/// Finds where to cut two sorted sequences so that merging the heads and then
+/// the tails is the same as merging everything. The longer sequence is cut in
+/// the middle, the other where the middle element belongs. Elements of the
+/// lower sequence still go before equal elements of the upper sequence.
+fn partition<E, F>(lower: &[E], upper: &[E], compare: &F) -> (usize, usize)
+where
+    F: Fn(&E, &E) -> Ordering,
+{
+    if lower.len() >= upper.len() {
+        let cut = lower.len() / 2;
+        // Upper elements less than the middle go in the heads.
+        let other = upper.partition_point(|elem| {
+            compare(elem, &lower[cut]) == Ordering::Less
+        });
+        (cut, other)
+    } else {
+        let cut = upper.len() / 2;
+        // Lower elements not greater than the middle go in the heads.
+        let other = lower.partition_point(|elem| {
+            compare(elem, &upper[cut]) != Ordering::Greater
+        });
+        (other, cut)
+    }
+}
+
+/// Merges two halves of a sorting target, sequentially. Gives up if some
+/// other task of the workers fails meanwhile.
+fn merge_iters<I, F>(
+    lower: I,
+    upper: I,
+    compare: &F,
+    workers: &Workers,
+) -> Vec<I::Item>
+where
+    I: ExactSizeIterator,
+    F: Fn(&I::Item, &I::Item) -> Ordering,
 {
     let mut merged = Vec::with_capacity(lower.len() + upper.len());
-    // Iterator over the lower half. Takes the vector away.
-    let mut lower_iter = lower.into_iter();
-    // Iterator over the upper half. Takes the vector away.
-    let mut upper_iter = upper.into_iter();
+    // Iterator over the lower half.
+    let mut lower_iter = lower;
+    // Iterator over the upper half.
+    let mut upper_iter = upper;
 
     // Initializes the "pivot".
     let mut pivot = lower_iter.next();
 
     // Intercalates the merge of the upper half with the merge lower half,
     // according to the pivot element.
-    while merge_while_less(&mut upper_iter, &mut pivot, &mut merged, &compare)
-        && merge_while_less(&mut lower_iter, &mut pivot, &mut merged, &compare)
-    {
+    // On ties, elements of the lower half go first, which keeps the merge
+    // stable: upper elements pass a lower pivot only if they are less, and
+    // lower elements pass an upper pivot unless they are greater.
+    while merge_while_less(
+        &mut upper_iter,
+        &mut pivot,
+        &mut merged,
+        compare,
+        Ordering::Equal,
+    ) && merge_while_less(
+        &mut lower_iter,
+        &mut pivot,
+        &mut merged,
+        compare,
+        Ordering::Greater,
+    ) {
+        // Does not bother if the sort already failed.
+        workers.check();
     }
 
     // Returns the merged vector.
@@ -298,13 +2117,14 @@ where
 }
 
 /// Merges the given half into the merged elements vector while the yielded
-/// elements are less than the pivot. When a greater than or equal element is
-/// found, it becomes the new pivot. Returns whether there is a pivot.
+/// elements compare less than `stop` to the pivot. When an element that does
+/// not is found, it becomes the new pivot. Returns whether there is a pivot.
 fn merge_while_less<I, F>(
     mut half: I,
     pivot: &mut Option<I::Item>,
     merged: &mut Vec<I::Item>,
-    compare: &Arc<F>,
+    compare: &F,
+    stop: Ordering,
 ) -> bool
 where
     I: Iterator,
@@ -334,15 +2154,131 @@ where
             },
         };
 
-        // Is greater than or equal? Change pivot and return.
-        if compare(&elem, &pivot_elem) >= Ordering::Equal {
+        // Reached the stop? Change pivot and return.
+        if compare(&elem, &pivot_elem) >= stop {
             *pivot = Some(elem);
             // Don't forget to save the previous pivot.
             merged.push(pivot_elem);
             return true;
         }
 
-        // Less? Ok, add it ot the merged vector.
+        // Below the stop? Ok, add it to the merged vector.
         merged.push(elem);
     }
 }
+
+/// Performs the "split" step of the merge sort algorithm directly on the given
+/// slice, and then merges the sorted halves in place.
+fn split_in_place<E, L, F>(
+    slice: &mut [E],
+    context: &Context<L, F>,
+    threads: usize,
+) where
+    E: Send,
+    L: Sync,
+    F: Fn(&E, &E) -> Ordering + Send + Sync,
+{
+    // Does not bother if the sort already failed.
+    context.check();
+
+    if slice.len() > 1 {
+        // The middle index: ceil(length/2)
+        let half = slice.len().div_ceil(2);
+
+        // Splits the slice in two.
+        let (lower, upper) = slice.split_at_mut(half);
+
+        // If there are threads, do the split in separated threads.
+        if threads > 1 {
+            #[cfg(feature = "log")]
+            log::trace!(
+                "splitting {} elements in place at {}, the upper half on a \
+                 new thread",
+                lower.len() + upper.len(),
+                half
+            );
+            let (lower, upper) = thread::scope(|scope| {
+                // Executes the split on the upper half in another thread.
+                let upper_handle = scope.spawn(|| {
+                    let _entered = context.workers.enter();
+                    context.guard(|| {
+                        split_in_place(upper, context, threads / 2)
+                    })
+                });
+                // Executes the split on the lower half.
+                let lower = context.guard(|| {
+                    split_in_place(lower, context, threads / 2)
+                });
+                // Joins the upper thread, so the scope does not panic on its
+                // own.
+                (lower, upper_handle.join().and_then(|upper| upper))
+            });
+            join_halves(lower, upper);
+        } else {
+            // Executes the split on the lower half.
+            split_in_place(lower, context, 1);
+            // Executes the split on the upper half.
+            split_in_place(upper, context, 1);
+        }
+
+        // Merges the two halves.
+        merge_in_place(slice, half, &*context.compare, &context.workers);
+    }
+}
+
+/// Merges the sorted halves `slice[.. mid]` and `slice[mid ..]` without any
+/// buffer, by rotating blocks around a pivot taken from the longer half.
+/// Gives up if some other task of the workers fails meanwhile.
+fn merge_in_place<E, F>(
+    slice: &mut [E],
+    mid: usize,
+    compare: &F,
+    workers: &Workers,
+) where
+    F: Fn(&E, &E) -> Ordering,
+{
+    // Does not bother if the sort already failed.
+    workers.check();
+
+    // An empty half? Nothing to merge.
+    if mid == 0 || mid == slice.len() {
+        return;
+    }
+
+    if mid >= slice.len() - mid {
+        // The pivot is the middle of the lower half.
+        let pivot = mid / 2;
+        // Upper elements less than the pivot go before it.
+        let end = mid
+            + slice[mid ..].partition_point(|elem| {
+                compare(elem, &slice[pivot]) == Ordering::Less
+            });
+
+        // Moves the pivot and what follows it in the lower half after those
+        // upper elements. The pivot then lands at its final position.
+        slice[pivot .. end].rotate_left(mid - pivot);
+        let pivot_pos = pivot + (end - mid);
+
+        // Merges what is left on each side of the pivot.
+        let (before, after) = slice.split_at_mut(pivot_pos);
+        merge_in_place(before, pivot, compare, workers);
+        merge_in_place(&mut after[1 ..], mid - pivot - 1, compare, workers);
+    } else {
+        // The pivot is the middle of the upper half.
+        let pivot = mid + (slice.len() - mid) / 2;
+        // Lower elements greater than the pivot go after it.
+        let start = slice[.. mid].partition_point(|elem| {
+            compare(elem, &slice[pivot]) != Ordering::Greater
+        });
+
+        // Moves the upper elements up to the pivot, inclusive, before those
+        // lower elements. The pivot then lands at its final position.
+        slice[start ..= pivot].rotate_left(mid - start);
+        let pivot_pos = start + (pivot - mid);
+
+        // Merges what is left on each side of the pivot.
+        let (before, after) = slice.split_at_mut(pivot_pos);
+        merge_in_place(before, start, compare, workers);
+        merge_in_place(&mut after[1 ..], mid - start, compare, workers);
+    }
+}
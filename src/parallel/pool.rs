@@ -0,0 +1,259 @@
+//! This module provides a pool of threads, so sorts do not have to spawn
+//! threads every time.
+
+use super::{merge_iters, split, Aborted, Budget, Context, SortOptions};
+use std::{
+    any::Any,
+    cmp::Ordering,
+    env,
+    ops::Range,
+    panic,
+    sync::{mpsc, Arc, Mutex, OnceLock},
+    thread,
+};
+
+/// The environment variable that sets the size of the global pool.
+const THREADS_VAR: &str = "MERGESORT_CMP_THREADS";
+
+/// Something to be done by a thread of the pool.
+type Job = Box<dyn FnOnce() + Send>;
+
+/// What a job reports back: the node it computed and the sorted elements, or
+/// the payload of its panic.
+type JobResult<E> = (usize, Result<Vec<E>, Box<dyn Any + Send>>);
+
+/// A fixed set of threads that run the chunk sorts and the merges of parallel
+/// sorts. The calling thread only hands out work and waits on the results.
+///
+/// # Examples
+/// ```rust
+/// use mergesort_cmp::parallel::{self, SortPool};
+/// use std::sync::Arc;
+///
+/// let pool = SortPool::new(4);
+///
+/// let array = [-1, 5, 91293, 12, -95, 20000, 20001, -12, 7];
+/// let array: Arc<[i32]> = Arc::from(&array as &[_]);
+///
+/// let sorted = parallel::reverse_order().sort_in(&pool, &array);
+///
+/// assert_eq!(sorted, &[91293, 20001, 20000, 12, 7, 5, -1, -12, -95]);
+/// ```
+pub struct SortPool {
+    /// Where jobs are sent to. `None` only while dropping.
+    sender: Option<mpsc::Sender<Job>>,
+    /// The threads of the pool.
+    handles: Vec<thread::JoinHandle<()>>,
+}
+
+impl SortPool {
+    /// Spawns a pool with the given number of threads, at least 1.
+    pub fn new(threads: usize) -> Self {
+        let (sender, receiver) = mpsc::channel::<Job>();
+        let receiver = Arc::new(Mutex::new(receiver));
+
+        let handles = (0 .. threads.max(1))
+            .map(|_| {
+                let receiver = receiver.clone();
+                thread::spawn(move || loop {
+                    // The lock is released as soon as a job is received.
+                    let job = receiver.lock().expect("pool lock").recv();
+                    match job {
+                        Ok(job) => job(),
+                        // The pool was dropped.
+                        Err(_) => break,
+                    }
+                })
+            })
+            .collect();
+
+        Self { sender: Some(sender), handles }
+    }
+
+    /// The process-wide pool used by [`parallel::sort`](super::sort). It is
+    /// spawned on first use, with as many threads as the
+    /// `MERGESORT_CMP_THREADS` environment variable says, or one per logical
+    /// CPU if it is not a positive number.
+    pub fn global() -> &'static Self {
+        static GLOBAL: OnceLock<SortPool> = OnceLock::new();
+
+        GLOBAL.get_or_init(|| {
+            let threads = env::var(THREADS_VAR)
+                .ok()
+                .and_then(|value| value.trim().parse::<usize>().ok())
+                .filter(|&threads| threads > 0)
+                .unwrap_or_else(num_cpus::get);
+            Self::new(threads)
+        })
+    }
+
+    /// How many threads the pool has.
+    pub fn threads(&self) -> usize {
+        self.handles.len()
+    }
+
+    /// Hands a job out to the pool.
+    fn execute<J>(&self, job: J)
+    where
+        J: FnOnce() + Send + 'static,
+    {
+        self.sender
+            .as_ref()
+            .expect("pool sender")
+            .send(Box::new(job))
+            .expect("pool threads are alive");
+    }
+}
+
+impl Drop for SortPool {
+    fn drop(&mut self) {
+        // Closing the channel makes every thread stop once it is idle.
+        self.sender = None;
+        for handle in self.handles.drain(..) {
+            let _ = handle.join();
+        }
+    }
+}
+
+impl<T, F> SortOptions<T, F> {
+    /// Sorts the given array on the threads of the given pool instead of
+    /// spawning new ones. The range is split in one chunk per thread of the
+    /// pool, ignoring the thread counts and the memory limit of the options,
+    /// as well as their thread priority, since the threads of the pool are
+    /// shared by every sort. Chunks are merged as soon as both of them are
+    /// sorted. What the jobs allocate counts to the measurement of
+    /// [`alloc::measure`](crate::alloc::measure) the sort runs in, if any.
+    ///
+    /// # Panics
+    /// If the comparison function panics, the panic is resumed on the calling
+    /// thread, and the jobs of this sort still waiting in the pool are skipped.
+    pub fn sort_in(&self, pool: &SortPool, array: &Arc<[T]>) -> Vec<T>
+    where
+        F: Fn(&T, &T) -> Ordering + Send + Sync + 'static,
+        T: Clone + Send + Sync + 'static,
+    {
+        let range = self.range.clone().unwrap_or(0 .. array.len());
+
+        // Leaves are copied straight from the shared array.
+        let leaf = {
+            let array = array.clone();
+            move |range: Range<usize>| array[range].to_vec()
+        };
+        let context = self.context(leaf, self.compare.clone());
+
+        sort_on(pool, range, &context)
+    }
+}
+
+/// A node of the tree of chunks of a pooled sort.
+struct Node {
+    /// The range of the array covered by the node.
+    range: Range<usize>,
+    /// The parent node, `None` for the root.
+    parent: Option<usize>,
+    /// The lower and upper halves, `None` for chunks.
+    halves: Option<(usize, usize)>,
+}
+
+/// Splits the range like [`split`] would with the given threads, pushing the
+/// nodes into the tree. Returns the index of the node of the range.
+fn build_tree(
+    range: Range<usize>,
+    threads: usize,
+    parent: Option<usize>,
+    tree: &mut Vec<Node>,
+) -> usize {
+    let index = tree.len();
+    tree.push(Node { range: range.clone(), parent, halves: None });
+
+    if threads > 1 && range.len() > 1 {
+        // The middle index: start + ceil((end - start)/2)
+        let half = range.start + range.len().div_ceil(2);
+        let threads = threads / 2;
+        let lower = build_tree(range.start .. half, threads, Some(index), tree);
+        let upper = build_tree(half .. range.end, threads, Some(index), tree);
+        tree[index].halves = Some((lower, upper));
+    }
+
+    index
+}
+
+/// Sorts the range on the pool: every chunk is a job, and so is every merge
+/// of two sorted halves.
+fn sort_on<E, L, F>(
+    pool: &SortPool,
+    range: Range<usize>,
+    context: &Arc<Context<L, F>>,
+) -> Vec<E>
+where
+    E: Send + 'static,
+    L: Fn(Range<usize>) -> Vec<E> + Send + Sync + 'static,
+    F: Fn(&E, &E) -> Ordering + Send + Sync + 'static,
+{
+    let mut tree = Vec::new();
+    build_tree(range.clone(), pool.threads(), None, &mut tree);
+    #[cfg(feature = "log")]
+    log::debug!(
+        "sorting {:?} on a pool of {} threads, in {} chunks",
+        range,
+        pool.threads(),
+        tree.iter().filter(|node| node.halves.is_none()).count()
+    );
+
+    let (sender, receiver) = mpsc::channel::<JobResult<E>>();
+
+    // Hands out the chunks.
+    for (index, node) in tree.iter().enumerate() {
+        if node.halves.is_none() {
+            let context = context.clone();
+            let sender = sender.clone();
+            let range = node.range.clone();
+            pool.execute(move || {
+                let _entered = context.workers.enter_job();
+                let sorted = context.guard(|| {
+                    split(range, &context, Budget { split: 1, merge: 1 })
+                });
+                let _ = sender.send((index, sorted));
+            });
+        }
+    }
+
+    // Sorted nodes waiting for their sibling.
+    let mut done = tree.iter().map(|_| None).collect::<Vec<Option<Vec<E>>>>();
+
+    loop {
+        let (index, sorted) = receiver.recv().expect("pool job result");
+        let sorted = match sorted {
+            Ok(sorted) => sorted,
+            // The job that actually failed will report too.
+            Err(payload) if payload.is::<Aborted>() => continue,
+            Err(payload) => panic::resume_unwind(payload),
+        };
+
+        // The root? Then everything is sorted.
+        let parent = match tree[index].parent {
+            Some(parent) => parent,
+            None => break sorted,
+        };
+        done[index] = Some(sorted);
+
+        // Both halves are sorted? Merges them.
+        let (lower, upper) = tree[parent].halves.expect("parent has halves");
+        if done[lower].is_some() && done[upper].is_some() {
+            let lower = done[lower].take().expect("lower half");
+            let upper = done[upper].take().expect("upper half");
+            let context = context.clone();
+            let sender = sender.clone();
+            pool.execute(move || {
+                let _entered = context.workers.enter_job();
+                let merged = context.guard(|| {
+                    context.check();
+                    let compare = &*context.compare;
+                    let (lower, upper) = (lower.into_iter(), upper.into_iter());
+                    merge_iters(lower, upper, compare, &context.workers)
+                });
+                let _ = sender.send((parent, merged));
+            });
+        }
+    }
+}
@@ -0,0 +1,433 @@
+//! This module provides a simulation of the parallel sort on a single thread,
+//! so the tasks of a sort can be reproduced and stepped through in a
+//! debugger.
+
+use super::{merge_iters, partition, Budget, SortOptions, Workers};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::{cmp::Ordering, fmt, ops::Range};
+
+/// What a simulated sort did, step by step, see
+/// [`SortOptions::sort_simulated`].
+///
+/// A simulation can be printed with its [`Display`](fmt::Display)
+/// implementation, one step per line, here for 9 elements on 2 threads:
+/// ```text
+/// thread 0: split 0..9 at 5, upper half to thread 1
+/// thread 1: sequential 5..9
+/// thread 0: sequential 0..5
+/// thread 0: partition 0..9, head of 3, tails to thread 2
+/// thread 2: merge 3..9, 3 from the lower half
+/// thread 0: merge 0..3, 2 from the lower half
+/// thread 0: concat 0..9, head of 3
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Simulation {
+    /// The steps, in the order the scheduler ran them.
+    pub steps: Vec<SimulatedStep>,
+}
+
+/// A step of a simulated sort: something a thread of the parallel sort does
+/// between waiting on other threads.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SimulatedStep {
+    /// The virtual thread that ran the step: `0` is the calling thread, the
+    /// others are numbered as they are spawned.
+    pub thread: usize,
+    /// What the thread did.
+    pub action: SimulatedAction,
+}
+
+/// What a thread of a simulated sort did in a step. Ranges are of positions
+/// of the sorted output, which for the halves of a split are the positions of
+/// the elements in the array too.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SimulatedAction {
+    /// Split the range in halves at the middle, to sort each of them. The
+    /// upper half is sorted by the spawned thread, if any, and otherwise by
+    /// this thread after the lower half.
+    Split {
+        /// The range split.
+        range: Range<usize>,
+        /// Where the upper half starts.
+        middle: usize,
+        /// The thread spawned for the upper half, if any.
+        spawned: Option<usize>,
+    },
+    /// Sorted the range with no more threads, as the parallel sort does once
+    /// it ran out of them.
+    Sequential {
+        /// The range sorted.
+        range: Range<usize>,
+    },
+    /// Merged two sorted sequences into the range, with no more threads.
+    Merge {
+        /// The range merged.
+        range: Range<usize>,
+        /// How many elements came from the lower sequence.
+        lower: usize,
+    },
+    /// Cut two sorted sequences so the heads go before the tails, to merge
+    /// the heads on this thread and the tails on the spawned one.
+    Partition {
+        /// The range the sequences are merged into.
+        range: Range<usize>,
+        /// How many elements the heads have.
+        head: usize,
+        /// The thread spawned for the tails.
+        spawned: usize,
+    },
+    /// Appended the merged tails to the merged heads, once the thread
+    /// merging the tails was joined.
+    Concat {
+        /// The range of the heads and the tails.
+        range: Range<usize>,
+        /// How many elements the heads have.
+        head: usize,
+    },
+}
+
+impl Simulation {
+    /// How many virtual threads took part in the sort, including the calling
+    /// thread.
+    pub fn threads(&self) -> usize {
+        let spawned = self.steps.iter().filter(|step| match step.action {
+            SimulatedAction::Split { spawned, .. } => spawned.is_some(),
+            SimulatedAction::Partition { .. } => true,
+            _ => false,
+        });
+        1 + spawned.count()
+    }
+}
+
+impl fmt::Display for Simulation {
+    fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
+        for step in &self.steps {
+            writeln!(fmtr, "{}", step)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for SimulatedStep {
+    fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmtr, "thread {}: ", self.thread)?;
+        match &self.action {
+            SimulatedAction::Split { range, middle, spawned } => {
+                write!(fmtr, "split {:?} at {}", range, middle)?;
+                match spawned {
+                    Some(spawned) => {
+                        write!(fmtr, ", upper half to thread {}", spawned)
+                    },
+                    None => Ok(()),
+                }
+            },
+            SimulatedAction::Sequential { range } => {
+                write!(fmtr, "sequential {:?}", range)
+            },
+            SimulatedAction::Merge { range, lower } => {
+                write!(fmtr, "merge {:?}, {} from the lower half", range, lower)
+            },
+            SimulatedAction::Partition { range, head, spawned } => write!(
+                fmtr,
+                "partition {:?}, head of {}, tails to thread {}",
+                range, head, spawned
+            ),
+            SimulatedAction::Concat { range, head } => {
+                write!(fmtr, "concat {:?}, head of {}", range, head)
+            },
+        }
+    }
+}
+
+impl<T, F> SortOptions<T, F> {
+    /// Sorts the given array like [`SortOptions::sort`], but on the calling
+    /// thread alone, with a virtual scheduler running the tasks the threads
+    /// of the sort would run. The tasks are exactly those of the parallel
+    /// sort: the same ranges split at the same places, and the same merges
+    /// partitioned at the same cuts. Which of the virtual threads that can go
+    /// on runs next is drawn from the seed, so a seed always gives the same
+    /// steps, while different seeds interleave the threads differently.
+    /// Merges are always done into fresh buffers, regardless of the memory
+    /// limit.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use mergesort_cmp::parallel::{self, SimulatedAction};
+    ///
+    /// let array = [-1, 5, 91293, 12, -95, 20000, 20001, -12, 7];
+    ///
+    /// let mut options = parallel::default_order();
+    /// options.threads(4);
+    /// let (sorted, simulation) = options.sort_simulated(&array, 42);
+    ///
+    /// assert_eq!(sorted, &[-95, -12, -1, 5, 7, 12, 20000, 20001, 91293]);
+    /// assert!(simulation.threads() >= 4);
+    /// assert_eq!(simulation.steps[0].action, SimulatedAction::Split {
+    ///     range: 0 .. 9,
+    ///     middle: 5,
+    ///     spawned: Some(1),
+    /// });
+    /// assert_eq!(simulation, options.sort_simulated(&array, 42).1);
+    /// ```
+    pub fn sort_simulated(&self, array: &[T], seed: u64) -> (Vec<T>, Simulation)
+    where
+        F: Fn(&T, &T) -> Ordering,
+        T: Clone,
+    {
+        let range = self.range.clone().unwrap_or(0 .. array.len());
+        let mut scheduler = Scheduler {
+            array,
+            compare: &*self.compare,
+            workers: Workers::default(),
+            threads: vec![Vec::new()],
+            slots: vec![None],
+            steps: Vec::new(),
+        };
+        let budget = self.budget();
+        scheduler.threads[0].push(Job::Sort { range, budget, output: 0 });
+
+        let mut rng = StdRng::seed_from_u64(seed);
+        while let Some(thread) = scheduler.pick(&mut rng) {
+            scheduler.step(thread);
+        }
+
+        let sorted = scheduler.slots[0].take().expect("sort did not finish");
+        (sorted, Simulation { steps: scheduler.steps })
+    }
+}
+
+/// Something a virtual thread has to do. Results are passed along in slots,
+/// filled by the job computing them and emptied by the job taking them.
+#[derive(Debug)]
+enum Job {
+    /// Sorts a range into the output slot.
+    Sort {
+        /// The range sorted.
+        range: Range<usize>,
+        /// The threads the range may use.
+        budget: Budget,
+        /// The slot of the sorted range.
+        output: usize,
+    },
+    /// Merges two sorted sequences into the output slot.
+    Merge {
+        /// The range merged into.
+        range: Range<usize>,
+        /// The slot of the lower sequence.
+        lower: usize,
+        /// The slot of the upper sequence.
+        upper: usize,
+        /// The threads the merge may use.
+        threads: usize,
+        /// The slot of the merged sequence.
+        output: usize,
+    },
+    /// Appends the tails to the heads, into the output slot.
+    Concat {
+        /// The range of the heads and the tails.
+        range: Range<usize>,
+        /// The slot of the merged heads.
+        head: usize,
+        /// The slot of the merged tails.
+        tail: usize,
+        /// The slot of everything.
+        output: usize,
+    },
+}
+
+/// Runs the jobs of the virtual threads, one step at a time.
+struct Scheduler<'array, T, F> {
+    /// The array sorted.
+    array: &'array [T],
+    /// The comparison function.
+    compare: &'array F,
+    /// The workers of the merges, which never fail on one thread.
+    workers: Workers,
+    /// The jobs left of each virtual thread, the next one last, as a stack
+    /// of the calls the thread is in.
+    threads: Vec<Vec<Job>>,
+    /// The results passed between jobs.
+    slots: Vec<Option<Vec<T>>>,
+    /// The steps run so far.
+    steps: Vec<SimulatedStep>,
+}
+
+impl<'array, T, F> Scheduler<'array, T, F>
+where
+    T: Clone,
+    F: Fn(&T, &T) -> Ordering,
+{
+    /// Picks a virtual thread whose next job can run, `None` if every thread
+    /// finished.
+    fn pick(&self, rng: &mut StdRng) -> Option<usize> {
+        let runnable = (0 .. self.threads.len())
+            .filter(|&thread| match self.threads[thread].last() {
+                Some(job) => self.is_ready(job),
+                None => false,
+            })
+            .collect::<Vec<_>>();
+        match runnable.len() {
+            0 => None,
+            count => Some(runnable[rng.gen_range(0, count)]),
+        }
+    }
+
+    /// Whether the inputs of the job were computed, so it can run.
+    fn is_ready(&self, job: &Job) -> bool {
+        match *job {
+            Job::Sort { .. } => true,
+            Job::Merge { lower, upper, .. } => {
+                self.slots[lower].is_some() && self.slots[upper].is_some()
+            },
+            Job::Concat { head, tail, .. } => {
+                self.slots[head].is_some() && self.slots[tail].is_some()
+            },
+        }
+    }
+
+    /// Runs the next job of the virtual thread, like [`super::split`] and
+    /// [`super::merge_owned`] would up to their next spawn or join.
+    fn step(&mut self, thread: usize) {
+        let job = self.threads[thread].pop().expect("thread has no job");
+        let action = match job {
+            Job::Sort { range, budget, output }
+                if range.len() > 1 && (budget.split > 1 || budget.merge > 1) =>
+            {
+                let middle = range.start + range.len().div_ceil(2);
+                let lower = self.slot();
+                let upper = self.slot();
+                let halves = budget.halve();
+                let merge = Job::Merge {
+                    range: range.clone(),
+                    lower,
+                    upper,
+                    threads: budget.merge,
+                    output,
+                };
+                let lower_sort = Job::Sort {
+                    range: range.start .. middle,
+                    budget: halves,
+                    output: lower,
+                };
+                let upper_sort = Job::Sort {
+                    range: middle .. range.end,
+                    budget: halves,
+                    output: upper,
+                };
+
+                // Jobs run last pushed first: the lower half, then the
+                // upper one if no thread was spawned for it, then the merge.
+                self.threads[thread].push(merge);
+                let spawned = match budget.split > 1 {
+                    true => Some(self.spawn(upper_sort)),
+                    false => {
+                        self.threads[thread].push(upper_sort);
+                        None
+                    },
+                };
+                self.threads[thread].push(lower_sort);
+                SimulatedAction::Split { range, middle, spawned }
+            },
+
+            Job::Sort { range, output, .. } => {
+                let sorted = self.sort_sequentially(range.clone());
+                self.slots[output] = Some(sorted);
+                SimulatedAction::Sequential { range }
+            },
+
+            Job::Merge { range, lower, upper, threads, output } => {
+                let mut lower = self.take(lower);
+                let mut upper = self.take(upper);
+                if threads < 2 || lower.is_empty() || upper.is_empty() {
+                    let lower_len = lower.len();
+                    let merged = merge_iters(
+                        lower.into_iter(),
+                        upper.into_iter(),
+                        self.compare,
+                        &self.workers,
+                    );
+                    self.slots[output] = Some(merged);
+                    SimulatedAction::Merge { range, lower: lower_len }
+                } else {
+                    let (lower_cut, upper_cut) =
+                        partition(&lower, &upper, self.compare);
+                    let head = lower_cut + upper_cut;
+                    let lower_tail = self.fill(lower.split_off(lower_cut));
+                    let upper_tail = self.fill(upper.split_off(upper_cut));
+                    let lower_head = self.fill(lower);
+                    let upper_head = self.fill(upper);
+                    let head_output = self.slot();
+                    let tail_output = self.slot();
+                    let middle = range.start + head;
+
+                    self.threads[thread].push(Job::Concat {
+                        range: range.clone(),
+                        head: head_output,
+                        tail: tail_output,
+                        output,
+                    });
+                    self.threads[thread].push(Job::Merge {
+                        range: range.start .. middle,
+                        lower: lower_head,
+                        upper: upper_head,
+                        threads: threads / 2,
+                        output: head_output,
+                    });
+                    let spawned = self.spawn(Job::Merge {
+                        range: middle .. range.end,
+                        lower: lower_tail,
+                        upper: upper_tail,
+                        threads: threads / 2,
+                        output: tail_output,
+                    });
+                    SimulatedAction::Partition { range, head, spawned }
+                }
+            },
+
+            Job::Concat { range, head, tail, output } => {
+                let mut merged = self.take(head);
+                let head = merged.len();
+                merged.extend(self.take(tail));
+                self.slots[output] = Some(merged);
+                SimulatedAction::Concat { range, head }
+            },
+        };
+        self.steps.push(SimulatedStep { thread, action });
+    }
+
+    /// Sorts the range on this thread alone, splitting and merging it like
+    /// [`super::split`] does with no threads.
+    fn sort_sequentially(&self, range: Range<usize>) -> Vec<T> {
+        if range.len() < 2 {
+            return self.array[range].to_vec();
+        }
+        let middle = range.start + range.len().div_ceil(2);
+        let lower = self.sort_sequentially(range.start .. middle);
+        let upper = self.sort_sequentially(middle .. range.end);
+        let (lower, upper) = (lower.into_iter(), upper.into_iter());
+        merge_iters(lower, upper, self.compare, &self.workers)
+    }
+
+    /// Spawns a virtual thread with the given job, returning its number.
+    fn spawn(&mut self, job: Job) -> usize {
+        self.threads.push(vec![job]);
+        self.threads.len() - 1
+    }
+
+    /// Makes a new empty slot.
+    fn slot(&mut self) -> usize {
+        self.slots.push(None);
+        self.slots.len() - 1
+    }
+
+    /// Makes a new slot with the given elements.
+    fn fill(&mut self, elements: Vec<T>) -> usize {
+        self.slots.push(Some(elements));
+        self.slots.len() - 1
+    }
+
+    /// Takes the elements out of a slot.
+    fn take(&mut self, slot: usize) -> Vec<T> {
+        self.slots[slot].take().expect("slot was not filled")
+    }
+}
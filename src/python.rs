@@ -0,0 +1,440 @@
+//! Bindings of the parallel merge sort and of the harness of the benchmark for
+//! Python, through PyO3, so the sorts can be compared to `numpy.sort` and
+//! friends on the same data. Only built with the `python` feature. The
+//! extension module is the crate built as a shared library, named as Python
+//! expects it:
+//! ```text
+//! $ PYO3_BUILD_EXTENSION_MODULE=1 cargo rustc --release --lib \
+//!     --crate-type cdylib --features python
+//! $ cp target/release/libmergesort_cmp.so mergesort_cmp.so
+//! ```
+//!
+//! Numbers are taken from any object with the buffer protocol, such as numpy
+//! arrays, `array.array`s and `memoryview`s, without going through Python
+//! objects, and sorted in place, like `ndarray.sort` does. Other sequences
+//! are sorted into a new list, like `sorted` does. Floating point numbers are
+//! in their total order: negative NaNs first, `-0.0` before `0.0`, and
+//! positive NaNs last. The sorts release the GIL while they run.
+//!
+//! # Examples
+//! ```python
+//! import array, mergesort_cmp, numpy
+//!
+//! values = numpy.random.default_rng(7).integers(0, 1000, 100_000)
+//! mergesort_cmp.sort(values, threads=4)
+//! assert (values[:-1] <= values[1:]).all()
+//!
+//! assert mergesort_cmp.sort(["pear", "fig"]) == ["fig", "pear"]
+//! assert mergesort_cmp.argsort(array.array("d", [2.5, -1.0])) == [1, 0]
+//! assert mergesort_cmp.sort_by_key(["pear", "fig"], len) == ["fig", "pear"]
+//!
+//! results = mergesort_cmp.bench(
+//!     {"small": [list(range(1000, 0, -1))] * 10},
+//!     targets=["sequential", ("numpy", numpy.sort)],
+//!     repeat=3,
+//! )
+//! for result in results:
+//!     print(result["target"], result["mean_seconds"])
+//! ```
+
+use crate::{
+    bench::{CaseSet, Measurement, Runner, Target},
+    parallel::{self, SortOptions},
+};
+use pyo3::{
+    buffer::{Element, ElementType, PyBuffer, PyUntypedBuffer},
+    exceptions::{PyTypeError, PyValueError},
+    prelude::*,
+    types::{PyDict, PyList, PyString, PyTuple},
+};
+use std::{cell::RefCell, cmp::Ordering, rc::Rc, sync::Arc};
+
+/// Sorts `data`: in place if it is a buffer of numbers, returning `None`, or
+/// into a new list if it is another sequence of integers, floats or strings.
+/// Runs on `threads` threads, one per logical CPU by default.
+#[pyfunction]
+#[pyo3(signature = (data, threads = None))]
+fn sort<'py>(
+    data: &Bound<'py, PyAny>,
+    threads: Option<usize>,
+) -> PyResult<Option<Bound<'py, PyList>>> {
+    let py = data.py();
+    if let Ok(buffer) = PyUntypedBuffer::get(data) {
+        if buffer.readonly() {
+            return Err(PyTypeError::new_err("cannot sort a read-only buffer"));
+        }
+        by_element_type!(sort_buffer, py, &buffer, threads)?;
+        return Ok(None);
+    }
+    let sorted = match Keys::extract(data)? {
+        Keys::Integers(values) => {
+            let sorted = sort_values(py, values, i64::cmp, threads)?;
+            PyList::new(py, sorted)?
+        },
+        Keys::Floats(values) => {
+            let sorted = sort_values(py, values, f64::total_cmp, threads)?;
+            PyList::new(py, sorted)?
+        },
+        Keys::Strings(values) => {
+            let sorted = sort_values(py, values, String::cmp, threads)?;
+            PyList::new(py, sorted)?
+        },
+    };
+    Ok(Some(sorted))
+}
+
+/// The indices of the elements of `data`, a buffer of numbers or another
+/// sequence of integers, floats or strings, in their sorted order, equal
+/// elements keeping their order.
+#[pyfunction]
+#[pyo3(signature = (data, threads = None))]
+fn argsort(
+    data: &Bound<'_, PyAny>,
+    threads: Option<usize>,
+) -> PyResult<Vec<usize>> {
+    let py = data.py();
+    match PyUntypedBuffer::get(data) {
+        Ok(buffer) => by_element_type!(argsort_buffer, py, &buffer, threads),
+        Err(_) => Keys::extract(data)?.argsort(py, threads),
+    }
+}
+
+/// Sorts the items into a new list by the keys `key` gives for them, which
+/// must all be integers, all floats or all strings. `key` is called once per
+/// item, before sorting, and equal keys keep the order of their items.
+#[pyfunction]
+#[pyo3(signature = (items, key, threads = None))]
+fn sort_by_key<'py>(
+    items: &Bound<'py, PyAny>,
+    key: &Bound<'py, PyAny>,
+    threads: Option<usize>,
+) -> PyResult<Bound<'py, PyList>> {
+    let py = items.py();
+    let items = items.try_iter()?.collect::<PyResult<Vec<_>>>()?;
+    let keys = items.iter().map(|item| key.call1((item,)));
+    let keys = PyList::new(py, keys.collect::<PyResult<Vec<_>>>()?)?;
+    let indices = Keys::extract(keys.as_any())?.argsort(py, threads)?;
+    PyList::new(py, indices.into_iter().map(|index| &items[index]))
+}
+
+/// Times targets on sets of cases with the benchmark's runner, returning a
+/// dict per target and set: `set`, `target`, `elements`, the `runs` with the
+/// seconds of each case, `mean_seconds` and `elements_per_second`, the last
+/// two `None` if they could not be computed.
+///
+/// `cases` maps the names of the sets to their cases, sequences or buffers of
+/// integers. A target is either the name of one of the library's,
+/// `sequential`, `parallel_logical` or `std_sort`, all three by default, or a
+/// `(name, function)` pair, the function returning the list of integers it is
+/// given, sorted. Converting the case to a list and the result back is timed
+/// along with such a function.
+#[pyfunction]
+#[pyo3(signature = (
+    cases,
+    targets = None,
+    repeat = 1,
+    warmup = 1,
+    verify = false,
+))]
+#[pyo3(name = "bench")]
+fn run_bench<'py>(
+    cases: &Bound<'py, PyDict>,
+    targets: Option<&Bound<'py, PyList>>,
+    repeat: usize,
+    warmup: usize,
+    verify: bool,
+) -> PyResult<Vec<Bound<'py, PyDict>>> {
+    let py = cases.py();
+    if repeat == 0 {
+        return Err(PyValueError::new_err("at least a run must be timed"));
+    }
+    let mut sets = Vec::with_capacity(cases.len());
+    for (name, set) in cases {
+        let name = name.extract::<String>()?;
+        let set = set.try_iter()?.map(|case| case?.extract::<Vec<i64>>());
+        sets.push(CaseSet::new(name, set.collect::<PyResult<_>>()?));
+    }
+
+    // The first exception of a Python target, raised once the run is done.
+    let raised = Rc::new(RefCell::new(None));
+    let mut built = Vec::new();
+    match targets {
+        Some(targets) => {
+            for target in targets {
+                built.push(build_target(&target, &raised)?);
+            }
+        },
+        None => {
+            built.push(Target::sequential());
+            built.push(Target::parallel());
+            built.push(Target::std_sort());
+        },
+    }
+
+    let measured = Runner::new()
+        .warmup(warmup)
+        .repeat(repeat)
+        .verify(verify)
+        .run(&sets, &mut built);
+    if let Some(error) = raised.borrow_mut().take() {
+        return Err(error);
+    }
+    let measured = measured
+        .map_err(|error| PyValueError::new_err(error.to_string()))?;
+    measured.iter().map(|measurement| result(py, measurement)).collect()
+}
+
+/// The module itself.
+#[pymodule]
+pub fn mergesort_cmp(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_function(wrap_pyfunction!(sort, module)?)?;
+    module.add_function(wrap_pyfunction!(argsort, module)?)?;
+    module.add_function(wrap_pyfunction!(sort_by_key, module)?)?;
+    module.add_function(wrap_pyfunction!(run_bench, module)?)?;
+    Ok(())
+}
+
+/// Calls the generic function with the element type of the buffer, along
+/// with its order, failing for element types that are not numbers.
+macro_rules! by_element_type {
+    ($function:ident, $py:expr, $buffer:expr, $threads:expr) => {{
+        let buffer: &PyUntypedBuffer = $buffer;
+        match ElementType::from_format(buffer.format()) {
+            ElementType::SignedInteger { bytes: 1 } => {
+                $function::<i8, _>($py, buffer, i8::cmp, $threads)
+            },
+            ElementType::SignedInteger { bytes: 2 } => {
+                $function::<i16, _>($py, buffer, i16::cmp, $threads)
+            },
+            ElementType::SignedInteger { bytes: 4 } => {
+                $function::<i32, _>($py, buffer, i32::cmp, $threads)
+            },
+            ElementType::SignedInteger { bytes: 8 } => {
+                $function::<i64, _>($py, buffer, i64::cmp, $threads)
+            },
+            ElementType::UnsignedInteger { bytes: 1 } => {
+                $function::<u8, _>($py, buffer, u8::cmp, $threads)
+            },
+            ElementType::UnsignedInteger { bytes: 2 } => {
+                $function::<u16, _>($py, buffer, u16::cmp, $threads)
+            },
+            ElementType::UnsignedInteger { bytes: 4 } => {
+                $function::<u32, _>($py, buffer, u32::cmp, $threads)
+            },
+            ElementType::UnsignedInteger { bytes: 8 } => {
+                $function::<u64, _>($py, buffer, u64::cmp, $threads)
+            },
+            ElementType::Float { bytes: 4 } => {
+                $function::<f32, _>($py, buffer, f32::total_cmp, $threads)
+            },
+            ElementType::Float { bytes: 8 } => {
+                $function::<f64, _>($py, buffer, f64::total_cmp, $threads)
+            },
+            _ => Err(PyTypeError::new_err(format!(
+                "cannot sort buffers of format {:?}",
+                buffer.format()
+            ))),
+        }
+    }};
+}
+use by_element_type;
+
+/// Sorts the buffer in place.
+fn sort_buffer<T, F>(
+    py: Python<'_>,
+    buffer: &PyUntypedBuffer,
+    compare: F,
+    threads: Option<usize>,
+) -> PyResult<()>
+where
+    T: Element + Send + Sync + 'static,
+    F: Fn(&T, &T) -> Ordering + Send + Sync + 'static,
+{
+    let buffer: &PyBuffer<T> = buffer.as_typed()?;
+    let sorted = sort_values(py, buffer.to_vec(py)?, compare, threads)?;
+    buffer.copy_from_slice(py, &sorted)
+}
+
+/// The indices of the elements of the buffer in their sorted order.
+fn argsort_buffer<T, F>(
+    py: Python<'_>,
+    buffer: &PyUntypedBuffer,
+    compare: F,
+    threads: Option<usize>,
+) -> PyResult<Vec<usize>>
+where
+    T: Element + Send + Sync + 'static,
+    F: Fn(&T, &T) -> Ordering + Send + Sync + 'static,
+{
+    let buffer: &PyBuffer<T> = buffer.as_typed()?;
+    argsort_values(py, buffer.to_vec(py)?, compare, threads)
+}
+
+/// Fails if the sort was asked to run on no threads.
+fn check_threads(threads: Option<usize>) -> PyResult<()> {
+    match threads {
+        Some(0) => Err(PyValueError::new_err("threads must be at least 1")),
+        _ => Ok(()),
+    }
+}
+
+/// The options of a sort on the given threads, one per logical CPU if none.
+fn options<T, F>(compare: F, threads: Option<usize>) -> SortOptions<T, F>
+where
+    F: Fn(&T, &T) -> Ordering,
+{
+    let mut options = parallel::custom_order(compare);
+    match threads {
+        Some(threads) => options.threads(threads),
+        None => options.thread_per_cpu(),
+    };
+    options
+}
+
+/// Sorts the values without the GIL.
+fn sort_values<T, F>(
+    py: Python<'_>,
+    values: Vec<T>,
+    compare: F,
+    threads: Option<usize>,
+) -> PyResult<Vec<T>>
+where
+    T: Clone + Send + Sync + 'static,
+    F: Fn(&T, &T) -> Ordering + Send + Sync + 'static,
+{
+    check_threads(threads)?;
+    let values: Arc<[T]> = Arc::from(values);
+    Ok(py.detach(|| options(compare, threads).sort(&values)))
+}
+
+/// Sorts the indices of the values without the GIL.
+fn argsort_values<T, F>(
+    py: Python<'_>,
+    values: Vec<T>,
+    compare: F,
+    threads: Option<usize>,
+) -> PyResult<Vec<usize>>
+where
+    T: Send + Sync + 'static,
+    F: Fn(&T, &T) -> Ordering + Send + Sync + 'static,
+{
+    check_threads(threads)?;
+    let values: Arc<[T]> = Arc::from(values);
+    Ok(py.detach(|| options(compare, threads).argsort(&values)))
+}
+
+/// The elements of a sequence that is not a buffer, all of the same kind.
+enum Keys {
+    /// Integers that fit in 64 bits.
+    Integers(Vec<i64>),
+    /// Floating point numbers, or integers mixed with them.
+    Floats(Vec<f64>),
+    /// Strings.
+    Strings(Vec<String>),
+}
+
+impl Keys {
+    /// Extracts the elements of the sequence.
+    fn extract(sequence: &Bound<'_, PyAny>) -> PyResult<Self> {
+        // Strings are sequences of strings themselves.
+        if sequence.is_instance_of::<PyString>() {
+            return Err(PyTypeError::new_err("cannot sort a string"));
+        }
+        if let Ok(values) = sequence.extract() {
+            return Ok(Keys::Integers(values));
+        }
+        if let Ok(values) = sequence.extract() {
+            return Ok(Keys::Floats(values));
+        }
+        if let Ok(values) = sequence.extract() {
+            return Ok(Keys::Strings(values));
+        }
+        Err(PyTypeError::new_err(
+            "can only sort buffers of numbers, or sequences of integers, \
+             floats or strings",
+        ))
+    }
+
+    /// The indices of the elements in their sorted order.
+    fn argsort(
+        self,
+        py: Python<'_>,
+        threads: Option<usize>,
+    ) -> PyResult<Vec<usize>> {
+        match self {
+            Keys::Integers(values) => {
+                argsort_values(py, values, i64::cmp, threads)
+            },
+            Keys::Floats(values) => {
+                argsort_values(py, values, f64::total_cmp, threads)
+            },
+            Keys::Strings(values) => {
+                argsort_values(py, values, String::cmp, threads)
+            },
+        }
+    }
+}
+
+/// Builds a target of [`run_bench`], keeping the first exception of a Python
+/// function in `raised`.
+fn build_target(
+    target: &Bound<'_, PyAny>,
+    raised: &Rc<RefCell<Option<PyErr>>>,
+) -> PyResult<Target<i64>> {
+    if let Ok(name) = target.extract::<String>() {
+        return match name.as_str() {
+            "sequential" => Ok(Target::sequential()),
+            "parallel_logical" => Ok(Target::parallel()),
+            "std_sort" => Ok(Target::std_sort()),
+            _ => Err(PyValueError::new_err(format!(
+                "unknown target {:?}, expected sequential, parallel_logical \
+                 or std_sort",
+                name
+            ))),
+        };
+    }
+
+    let (name, function) = target
+        .cast::<PyTuple>()
+        .ok()
+        .and_then(|pair| pair.extract::<(String, Py<PyAny>)>().ok())
+        .ok_or_else(|| {
+            PyTypeError::new_err(
+                "a target is a name or a (name, function) pair",
+            )
+        })?;
+    let raised = raised.clone();
+    Ok(Target::new(name, move |case: &Arc<[i64]>| {
+        Python::attach(|py| {
+            let sorted = function
+                .call1(py, (PyList::new(py, case.iter())?,))?
+                .extract::<Vec<i64>>(py)?;
+            Ok(sorted)
+        })
+        .unwrap_or_else(|error: PyErr| {
+            // The first one is raised, the order no longer matters.
+            raised.borrow_mut().get_or_insert(error);
+            case.to_vec()
+        })
+    }))
+}
+
+/// The results of a target on a set, as a dict.
+fn result<'py>(
+    py: Python<'py>,
+    measurement: &Measurement,
+) -> PyResult<Bound<'py, PyDict>> {
+    let runs = measurement.runs.iter().map(|run| {
+        run.iter().map(|time| time.as_secs_f64()).collect::<Vec<_>>()
+    });
+    let mean = measurement.mean().map(|mean| mean.as_secs_f64());
+
+    let dict = PyDict::new(py);
+    dict.set_item("set", &measurement.set)?;
+    dict.set_item("target", &measurement.target)?;
+    dict.set_item("elements", measurement.elements)?;
+    dict.set_item("runs", runs.collect::<Vec<_>>())?;
+    dict.set_item("mean_seconds", mean)?;
+    dict.set_item("elements_per_second", measurement.throughput())?;
+    Ok(dict)
+}
@@ -0,0 +1,259 @@
+//! The results of a run of the benchmark, as its JSON output has them, so
+//! tools reading the output can share its schema instead of picking the
+//! members out by hand. With the `serde` feature, every type here can be
+//! serialized and deserialized, a serialized [`BenchReport`] being the same
+//! document as `--format json` writes.
+//!
+//! Times are in seconds. Members that the run did not measure are `None`,
+//! `null` in the JSON.
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// The results of a whole run.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct BenchReport {
+    /// The first seed of the cases.
+    pub seed: u64,
+    /// Every seed the cases were generated with.
+    pub seeds: Vec<u64>,
+    /// The order the targets ran in.
+    pub order: String,
+    /// Whether the caches were flushed before every case.
+    pub cold_cache: bool,
+    /// What of the targets was timed: `sort` or `merge`.
+    pub phase: String,
+    /// The CPUs the run was pinned to, if it was.
+    pub cpus: Option<Vec<usize>>,
+    /// The iterations of dummy work every comparison did.
+    pub cmp_cost: u64,
+    /// The confidence level of the significance of differences, if there
+    /// were sets.
+    pub confidence: Option<f64>,
+    /// The type of the elements sorted.
+    pub element_type: String,
+    /// The shape of the strings, if the elements were strings.
+    pub strings: Option<StringShape>,
+    /// The size of the payloads, if the elements had payloads.
+    pub payload_bytes: Option<usize>,
+    /// The machine the run was on.
+    pub machine: Machine,
+    /// How the run was produced. Only missing from the reports of runs that
+    /// did not record it.
+    pub manifest: Option<Manifest>,
+    /// The results of every case set, in the order they ran.
+    pub case_sets: Vec<CaseSetResult>,
+    /// How the time of every target grows with the size of the cases.
+    pub complexity: Vec<Fit>,
+}
+
+/// The shape of the strings sorted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct StringShape {
+    /// The least length of a string.
+    pub min_length: usize,
+    /// The greatest length of a string.
+    pub max_length: usize,
+    /// The characters of the strings.
+    pub alphabet: String,
+}
+
+/// The machine a run was on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Machine {
+    /// The operating system.
+    pub os: String,
+    /// The CPU architecture.
+    pub arch: String,
+    /// The model of the CPU, if it could be found.
+    pub cpu_model: Option<String>,
+    /// The base frequency of the CPU in MHz, if it could be found.
+    pub base_mhz: Option<u64>,
+    /// The number of logical CPUs.
+    pub logical_cpus: usize,
+    /// The number of physical CPUs.
+    pub physical_cpus: usize,
+    /// The total memory, if it could be found.
+    pub memory_bytes: Option<usize>,
+    /// The version of the compiler the benchmark was built with.
+    pub rustc: String,
+}
+
+/// How a run was produced.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Manifest {
+    /// The command line, as it could be typed again.
+    pub command_line: String,
+    /// The arguments of the command line, the binary left out.
+    pub args: Vec<String>,
+    /// The hash of the file the cases were read from, if they were.
+    pub cases_hash: Option<String>,
+    /// The version of the crate.
+    pub version: String,
+    /// The commit the benchmark was built from, if it was built from a
+    /// checkout.
+    pub commit: Option<String>,
+    /// The version of the compiler.
+    pub rustc: String,
+    /// The Cargo profile of the build.
+    pub profile: String,
+    /// The optimization level of the build.
+    pub opt_level: String,
+    /// The target triple of the build.
+    pub target: String,
+    /// The extra flags the compiler was given, separated by spaces.
+    pub rustflags: String,
+    /// Whether the build had debug assertions.
+    pub debug_assertions: bool,
+    /// The features of the build.
+    pub features: Vec<String>,
+    /// The environment variables of the run that can change it, by name.
+    pub env: BTreeMap<String, String>,
+}
+
+/// The results of a case set.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct CaseSetResult {
+    /// The name of the set.
+    pub name: String,
+    /// The pattern of the arrays.
+    pub pattern: String,
+    /// The number of cases, over every seed.
+    pub cases: usize,
+    /// The least size of a case.
+    pub min_size: usize,
+    /// The greatest size of a case.
+    pub max_size: usize,
+    /// The results of every target, in the order they were reported.
+    pub targets: Vec<TargetResult>,
+}
+
+/// The results of a target on a case set.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TargetResult {
+    /// The name of the target.
+    pub name: String,
+    /// The logical CPUs the target assumed, if it ran as part of a sweep.
+    pub threads: Option<usize>,
+    /// The time of every run over the whole set.
+    pub seconds: Vec<f64>,
+    /// Statistics of the times of the runs over the whole set, if it ran.
+    pub total_seconds: Option<Stats>,
+    /// Statistics of the times of the cases, if it ran.
+    pub case_seconds: Option<Stats>,
+    /// The mean time of a run over the cases of each seed.
+    pub seed_seconds: Vec<f64>,
+    /// How many elements were sorted per second, if it ran.
+    pub elements_per_second: Option<f64>,
+    /// The p-value of the difference from the baseline, if there is one.
+    pub p_value: Option<f64>,
+    /// Whether the difference from the baseline is significant, if there is
+    /// a baseline.
+    pub significant: Option<bool>,
+    /// What the target allocated, if it was counted.
+    pub memory: Option<MemoryStats>,
+    /// The comparisons of a run over the set, if they were counted.
+    pub comparisons: Option<u64>,
+    /// The hardware events of a run over the set, if they were counted.
+    pub counters: Option<CounterStats>,
+    /// The CPU time of a run over the set, summed over the threads, if it was
+    /// measured.
+    pub cpu_seconds: Option<f64>,
+    /// The energy of a run over the set, if it was measured.
+    pub energy_joules: Option<f64>,
+    /// Whether the target was stopped for taking too long.
+    pub timed_out: bool,
+    /// How many times of cases were outliers, replaced by the median of their
+    /// case, if outliers were rejected.
+    pub outliers: Option<usize>,
+    /// Whether the runs settled before the limit, if they ran until then.
+    pub converged: Option<bool>,
+    /// The share of the time the threads of the target were busy, if it was
+    /// measured.
+    pub utilization: Option<f64>,
+    /// The confidence interval of the mean, as a percentage of it, if there
+    /// were enough runs.
+    pub interval_percent: Option<f64>,
+}
+
+/// Summary statistics of times.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Stats {
+    /// The mean.
+    pub mean: f64,
+    /// The median.
+    pub median: f64,
+    /// The standard deviation.
+    pub stddev: f64,
+    /// The least.
+    pub min: f64,
+    /// The greatest.
+    pub max: f64,
+    /// The 95th percentile.
+    pub p95: f64,
+    /// The mean without the least and the greatest tenth of the values.
+    pub trimmed_mean: f64,
+    /// The standard deviation relative to the mean, as a percentage.
+    pub relative_stddev_percent: f64,
+}
+
+/// What a target allocated in a run over a set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct MemoryStats {
+    /// The allocations of a run over the set, reallocations included.
+    pub allocations: u64,
+    /// The most bytes allocated at once by a single sort, if they were
+    /// counted.
+    pub peak_bytes: Option<usize>,
+}
+
+/// The hardware events of a target in a run over a set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct CounterStats {
+    /// The instructions that ran.
+    pub instructions: u64,
+    /// The references to memory that missed the last level cache.
+    pub cache_misses: u64,
+    /// The mispredicted branches.
+    pub branch_misses: u64,
+}
+
+/// How the time of a target grows with the size of the cases.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Fit {
+    /// The label of the target, with its thread count in a sweep.
+    pub target: String,
+    /// How many cases were fitted.
+    pub points: usize,
+    /// The fit of `time = c * n * log2(n)`.
+    pub n_log_n: ModelFit,
+    /// The fit of `time = c * n`.
+    pub linear: ModelFit,
+    /// The `k` of `time ~ n^k`.
+    pub exponent: f64,
+    /// The exponent an exact `n log n` would have over the same sizes.
+    pub expected_exponent: f64,
+    /// Whether the exponent is too far from the expected one.
+    pub deviates: bool,
+}
+
+/// The fit of a model of the time.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ModelFit {
+    /// The constant of the model, in seconds.
+    pub constant_seconds: f64,
+    /// The coefficient of determination.
+    pub r_squared: f64,
+}
@@ -13,7 +13,12 @@
 //! assert_eq!(expected, sorted);
 //! ```
 
-use std::cmp::Ordering;
+use crate::verify::{ConsistencyCheck, InconsistentComparatorError};
+use std::{cmp::Ordering, slice, vec};
+
+/// How many times in a row a half of a galloping merge must win before the
+/// merge starts searching for how far it wins.
+const MIN_GALLOP: usize = 7;
 
 /// Sorts the given array using the default order. Uses a merge sort.
 ///
@@ -37,6 +42,7 @@ where
 /// A sorter parameterized by a comparison function. Uses a merge sort.
 ///
 /// It uses the given comparison function to compare and sorts the whole array.
+/// The sort is stable: elements that compare equal keep their order.
 ///
 /// # Examples
 /// ```rust
@@ -48,32 +54,177 @@ where
 ///
 /// assert_eq!(sorted, &[91293, 20001, 20000, 12, 7, 5, -1, -12, -95]);
 /// ```
+///
+/// ## Stability
+/// ```rust
+/// use mergesort_cmp::sequential;
+///
+/// // Every key is equal, so the positions must not move.
+/// let array = [(7, 0), (7, 1), (7, 2), (7, 3), (7, 4)];
+///
+/// let sorted = sequential::sort_by(&array, |a, b| a.0.cmp(&b.0));
+///
+/// assert_eq!(sorted, &array);
+/// ```
 pub fn sort_by<T, F>(array: &[T], mut compare: F) -> Vec<T>
 where
-    T: Ord + Clone,
+    T: Clone,
+    F: FnMut(&T, &T) -> Ordering,
+{
+    split(array, &mut compare, merge_owned)
+}
+
+/// Like [`sort_by`], but merging the halves with galloping merges, as
+/// [`merge_galloping_by`] does. Partly sorted arrays take fewer comparisons.
+///
+/// # Examples
+/// ```rust
+/// use mergesort_cmp::sequential;
+///
+/// let array = [-1, 5, 91293, 12, -95, 20000, 20001, -12, 7];
+///
+/// let sorted = sequential::sort_galloping_by(&array, |a, b| a.cmp(&b));
+///
+/// assert_eq!(sorted, &[-95, -12, -1, 5, 7, 12, 20000, 20001, 91293]);
+/// ```
+pub fn sort_galloping_by<T, F>(array: &[T], mut compare: F) -> Vec<T>
+where
+    T: Clone,
+    F: FnMut(&T, &T) -> Ordering,
+{
+    split(array, &mut compare, gallop_owned)
+}
+
+/// Like [`sort_by`], but also checking the comparison function while sorting,
+/// to debug comparison functions: one of every `every` comparisons, or every
+/// one with 1, is done the other way around too, which must give the opposite
+/// ordering. An inconsistent comparison function makes the sort give wrong
+/// output, see [`verify::check_total_order`](crate::verify::check_total_order)
+/// to check one before sorting.
+///
+/// # Errors
+/// The first pair of elements the comparison function was found inconsistent
+/// on, once the sort is done.
+///
+/// # Examples
+/// ```rust
+/// use mergesort_cmp::sequential;
+/// use std::cmp::Ordering;
+///
+/// let array = [3, 1, 4, 1, 5];
+///
+/// let sorted = sequential::sort_checked_by(&array, i32::cmp, 1);
+/// assert_eq!(sorted, Ok(vec![1, 1, 3, 4, 5]));
+///
+/// // Equal elements are less than each other.
+/// let less_or_equal = |left: &i32, right: &i32| match left <= right {
+///     true => Ordering::Less,
+///     false => Ordering::Greater,
+/// };
+/// let error = sequential::sort_checked_by(&array, less_or_equal, 1)
+///     .unwrap_err();
+/// assert_eq!((error.left, error.right), (1, 1));
+/// assert_eq!(
+///     error.to_string(),
+///     "inconsistent comparison function: 1 is less than 1, but 1 is less \
+///      than 1"
+/// );
+/// ```
+pub fn sort_checked_by<T, F>(
+    array: &[T],
+    mut compare: F,
+    every: usize,
+) -> Result<Vec<T>, InconsistentComparatorError<T>>
+where
+    T: Clone,
     F: FnMut(&T, &T) -> Ordering,
 {
-    split(array, &mut compare)
+    let check = ConsistencyCheck::new(every);
+    let sorted = sort_by(array, |left, right| {
+        check.compare(&mut compare, left, right)
+    });
+    check.finish()?;
+    Ok(sorted)
+}
+
+/// Merges two arrays, each already sorted by the comparison function, into a
+/// sorted vector, cloning the elements. It is the merge the sort does, alone.
+/// Elements of `lower` go before equal elements of `upper`.
+///
+/// # Examples
+/// ```rust
+/// use mergesort_cmp::sequential;
+///
+/// let lower = [-95, -1, 5, 12];
+/// let upper = [-12, 7, 20000, 20001, 91293];
+///
+/// let merged = sequential::merge_by(&lower, &upper, |a, b| a.cmp(&b));
+///
+/// assert_eq!(merged, &[-95, -12, -1, 5, 7, 12, 20000, 20001, 91293]);
+/// ```
+pub fn merge_by<T, F>(lower: &[T], upper: &[T], mut compare: F) -> Vec<T>
+where
+    T: Clone,
+    F: FnMut(&T, &T) -> Ordering,
+{
+    merge(lower.iter().cloned(), upper.iter().cloned(), &mut compare)
+}
+
+/// Merges two arrays, each already sorted by the comparison function, into a
+/// sorted vector, cloning the elements. Once one of them went first a few
+/// times in a row, the merge searches how many more of its elements go
+/// first, exponentially and then by bisection, taking them all at once, so
+/// long runs from one side take logarithmic comparisons. Elements of `lower`
+/// go before equal elements of `upper`.
+///
+/// # Examples
+/// ```rust
+/// use mergesort_cmp::sequential;
+///
+/// let lower = (0 .. 100).collect::<Vec<_>>();
+/// let upper = (50 .. 60).collect::<Vec<_>>();
+///
+/// let merged =
+///     sequential::merge_galloping_by(&lower, &upper, |a, b| a.cmp(&b));
+///
+/// let mut expected = lower.iter().chain(&upper).copied().collect::<Vec<_>>();
+/// expected.sort();
+/// assert_eq!(merged, expected);
+/// ```
+pub fn merge_galloping_by<T, F>(
+    lower: &[T],
+    upper: &[T],
+    mut compare: F,
+) -> Vec<T>
+where
+    T: Clone,
+    F: FnMut(&T, &T) -> Ordering,
+{
+    gallop(Cloned(lower.iter()), Cloned(upper.iter()), &mut compare)
 }
 
 /// Performs the "split" step of the merge sort algorithm, and then merges the
-/// sorted halves.
-fn split<T, F>(array: &[T], compare: &mut F) -> Vec<T>
+/// sorted halves with the given merge.
+fn split<T, F>(
+    array: &[T],
+    compare: &mut F,
+    merge: fn(Vec<T>, Vec<T>, &mut F) -> Vec<T>,
+) -> Vec<T>
 where
     T: Clone,
     F: FnMut(&T, &T) -> Ordering,
 {
     if array.len() > 1 {
-        // The middle index: (length + 1)/2
-        let half = (array.len() + 1) / 2;
+        // The middle index: ceil(length/2)
+        let half = array.len().div_ceil(2);
 
         // Splits the slice in two.
         let (lower_slice, upper_slice) = array.split_at(half);
 
         // Executes the split on the lower half.
-        let lower = split(lower_slice, compare);
+        let lower = split(lower_slice, compare, merge);
         // Executes the split on the upper half.
-        let upper = split(upper_slice, compare);
+        let upper = split(upper_slice, compare, merge);
 
         // Merges the two halves.
         merge(lower, upper, compare)
@@ -84,25 +235,60 @@ where
     }
 }
 
-/// Merges two halves of a sorting target.
-fn merge<T, F>(lower: Vec<T>, upper: Vec<T>, compare: &mut F) -> Vec<T>
+/// Merges two halves of a sorting target, taking the vectors away.
+pub(crate) fn merge_owned<T, F>(
+    lower: Vec<T>,
+    upper: Vec<T>,
+    compare: &mut F,
+) -> Vec<T>
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    merge(lower.into_iter(), upper.into_iter(), compare)
+}
+
+/// Merges two halves of a sorting target with a galloping merge, taking the
+/// vectors away.
+fn gallop_owned<T, F>(lower: Vec<T>, upper: Vec<T>, compare: &mut F) -> Vec<T>
 where
     F: FnMut(&T, &T) -> Ordering,
+{
+    gallop(lower.into_iter(), upper.into_iter(), compare)
+}
+
+/// Merges two halves of a sorting target.
+fn merge<I, F>(lower: I, upper: I, compare: &mut F) -> Vec<I::Item>
+where
+    I: ExactSizeIterator,
+    F: FnMut(&I::Item, &I::Item) -> Ordering,
 {
     let mut merged = Vec::with_capacity(lower.len() + upper.len());
-    // Iterator over the lower half. Takes the vector away.
-    let mut lower_iter = lower.into_iter();
-    // Iterator over the upper half. Takes the vector away.
-    let mut upper_iter = upper.into_iter();
+    // Iterator over the lower half.
+    let mut lower_iter = lower;
+    // Iterator over the upper half.
+    let mut upper_iter = upper;
 
     // Initializes the "pivot".
     let mut pivot = lower_iter.next();
 
     // Intercalates the merge of the upper half with the merge lower half,
     // according to the pivot element.
-    while merge_while_less(&mut upper_iter, &mut pivot, &mut merged, compare)
-        && merge_while_less(&mut lower_iter, &mut pivot, &mut merged, compare)
-    {
+    // On ties, elements of the lower half go first, which keeps the merge
+    // stable: upper elements pass a lower pivot only if they are less, and
+    // lower elements pass an upper pivot unless they are greater.
+    while merge_while_less(
+        &mut upper_iter,
+        &mut pivot,
+        &mut merged,
+        compare,
+        Ordering::Equal,
+    ) && merge_while_less(
+        &mut lower_iter,
+        &mut pivot,
+        &mut merged,
+        compare,
+        Ordering::Greater,
+    ) {
     }
 
     // Returns the merged vector.
@@ -110,13 +296,14 @@ where
 }
 
 /// Merges the given half into the merged elements vector while the yielded
-/// elements are less than the pivot. When a greater than or equal element is
-/// found, it becomes the new pivot. Returns whether there is a pivot.
+/// elements compare less than `stop` to the pivot. When an element that does
+/// not is found, it becomes the new pivot. Returns whether there is a pivot.
 fn merge_while_less<I, F>(
     mut half: I,
     pivot: &mut Option<I::Item>,
     merged: &mut Vec<I::Item>,
     compare: &mut F,
+    stop: Ordering,
 ) -> bool
 where
     I: Iterator,
@@ -146,15 +333,120 @@ where
             },
         };
 
-        // Is greater than or equal? Change pivot and return.
-        if compare(&elem, &pivot_elem) >= Ordering::Equal {
+        // Reached the stop? Change pivot and return.
+        if compare(&elem, &pivot_elem) >= stop {
             *pivot = Some(elem);
             // Don't forget to save the previous pivot.
             merged.push(pivot_elem);
             return true;
         }
 
-        // Less? Ok, add it ot the merged vector.
+        // Below the stop? Ok, add it to the merged vector.
         merged.push(elem);
     }
 }
+
+/// A half of a galloping merge, whose elements can be searched before they
+/// are taken.
+trait Half: ExactSizeIterator {
+    /// The elements not taken yet.
+    fn rest(&self) -> &[Self::Item];
+}
+
+impl<T> Half for vec::IntoIter<T> {
+    fn rest(&self) -> &[T] {
+        self.as_slice()
+    }
+}
+
+/// The elements of a slice, cloned as they are taken.
+struct Cloned<'slice, T>(slice::Iter<'slice, T>);
+
+impl<'slice, T> Iterator for Cloned<'slice, T>
+where
+    T: Clone,
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.0.next().cloned()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<'slice, T> ExactSizeIterator for Cloned<'slice, T> where T: Clone {}
+
+impl<'slice, T> Half for Cloned<'slice, T>
+where
+    T: Clone,
+{
+    fn rest(&self) -> &[T] {
+        self.0.as_slice()
+    }
+}
+
+/// Merges two halves of a sorting target, galloping through the runs of
+/// either half once it won [`MIN_GALLOP`] times in a row.
+fn gallop<I, F>(mut lower: I, mut upper: I, compare: &mut F) -> Vec<I::Item>
+where
+    I: Half,
+    F: FnMut(&I::Item, &I::Item) -> Ordering,
+{
+    let mut merged = Vec::with_capacity(lower.len() + upper.len());
+    // How many times in a row each half went first.
+    let mut lower_wins = 0;
+    let mut upper_wins = 0;
+
+    while let (Some(lower_head), Some(upper_head)) =
+        (lower.rest().first(), upper.rest().first())
+    {
+        // As in the plain merge, elements of the lower half go first on ties,
+        // which keeps the merge stable.
+        if lower_wins >= MIN_GALLOP {
+            let count = run_length(lower.rest(), |elem| {
+                compare(elem, upper_head) != Ordering::Greater
+            });
+            merged.extend(lower.by_ref().take(count));
+            lower_wins = 0;
+        } else if upper_wins >= MIN_GALLOP {
+            let count = run_length(upper.rest(), |elem| {
+                compare(elem, lower_head) == Ordering::Less
+            });
+            merged.extend(upper.by_ref().take(count));
+            upper_wins = 0;
+        } else if compare(upper_head, lower_head) == Ordering::Less {
+            merged.extend(upper.next());
+            upper_wins += 1;
+            lower_wins = 0;
+        } else {
+            merged.extend(lower.next());
+            lower_wins += 1;
+            upper_wins = 0;
+        }
+    }
+
+    // One of the halves is over, the rest of the other goes last.
+    merged.extend(lower);
+    merged.extend(upper);
+    merged
+}
+
+/// How many elements at the start of the slice satisfy the predicate, which
+/// holds for a prefix of the slice and not after it. Probes at exponentially
+/// growing distances, then bisects between the last two probes.
+fn run_length<T, P>(slice: &[T], mut predicate: P) -> usize
+where
+    P: FnMut(&T) -> bool,
+{
+    let mut bound = 1;
+    while bound <= slice.len() && predicate(&slice[bound - 1]) {
+        bound *= 2;
+    }
+    // Everything before the previous probe holds, the last probe does not.
+    let start = bound / 2;
+    let end = (bound - 1).min(slice.len());
+    start + slice[start .. end].partition_point(predicate)
+}
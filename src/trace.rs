@@ -0,0 +1,370 @@
+//! Recording of the tree of splits and merges of a sort: which range each
+//! node of the recursion sorted, at which depth, on which thread and when,
+//! to see how a sort went, and how the parallel one scheduled its threads.
+//! Only built with the `trace` feature.
+//!
+//! A tree can be saved with its [`Display`](fmt::Display) implementation and
+//! loaded back with its [`FromStr`] implementation, or, with the `serde`
+//! feature, serialized. The text format has one line per node, in pre-order,
+//! indented by depth, with times in nanoseconds since the start of the sort,
+//! and `merge` at the time the halves started being merged, if it is known:
+//! ```text
+//! 0..5 thread=0 start=120 merge=2210 end=2630 split
+//!   0..3 thread=0 start=160 merge=1050 end=1400 split
+//!     0..2 thread=0 start=180 merge=700 end=900 split
+//!       0..1 thread=0 start=200 end=350
+//!       1..2 thread=0 start=380 end=560
+//!     2..3 thread=0 start=1000 end=1020
+//!   3..5 thread=0 start=1430 merge=1900 end=2190 split
+//!     3..4 thread=0 start=1450 end=1600
+//!     4..5 thread=0 start=1650 end=1800
+//! ```
+//!
+//! # Examples
+//! ```rust
+//! use mergesort_cmp::{parallel, trace::{self, MergeTree}};
+//! use std::sync::Arc;
+//!
+//! let array = (0 .. 1000).rev().collect::<Vec<i32>>();
+//!
+//! let (sorted, tree) = trace::sort_by(&array, i32::cmp, 3);
+//! assert_eq!(sorted, (0 .. 1000).collect::<Vec<_>>());
+//! assert_eq!(tree.depth(), 3);
+//! assert_eq!(tree.nodes().count(), 15);
+//!
+//! let array: Arc<[i32]> = Arc::from(array);
+//! let (_, trace) = parallel::default_order().threads(4).sort_traced(&array);
+//! let tree = MergeTree::from(&trace);
+//! assert_eq!(tree.threads(), 4);
+//! assert_eq!(tree.to_string().parse::<MergeTree>(), Ok(tree));
+//! ```
+
+use crate::{
+    parallel::{ExecutionTrace, TraceTask},
+    sequential,
+};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::{
+    cmp::Ordering,
+    error::Error,
+    fmt,
+    ops::Range,
+    str::FromStr,
+    time::Instant,
+};
+
+/// The recorded tree of splits and merges of a sort.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct MergeTree {
+    /// The node that sorted the whole range.
+    pub root: MergeNode,
+}
+
+/// A node of the recursion of a sort. A node either sorts its range without
+/// recording how, or splits it in two halves, sorts both, and merges them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct MergeNode {
+    /// The range of the array sorted by this node.
+    pub range: Range<usize>,
+    /// How deep in the recursion this node is, `0` for the root.
+    pub depth: usize,
+    /// The thread that ran this node: `0` is the calling thread, the others
+    /// are numbered as they are spawned.
+    pub thread: usize,
+    /// When this node started, in nanoseconds since the start of the sort.
+    pub start: u64,
+    /// When the halves started being merged, in nanoseconds since the start
+    /// of the sort, if the node was split and it is known.
+    pub merge: Option<u64>,
+    /// When this node finished, in nanoseconds since the start of the sort.
+    pub end: u64,
+    /// The lower and the upper half, if this node was split, or none.
+    pub halves: Vec<MergeNode>,
+}
+
+impl MergeTree {
+    /// Every node of the tree, in pre-order.
+    pub fn nodes(&self) -> impl Iterator<Item = &MergeNode> {
+        let mut stack = vec![&self.root];
+        std::iter::from_fn(move || {
+            let node = stack.pop()?;
+            stack.extend(node.halves.iter().rev());
+            Some(node)
+        })
+    }
+
+    /// The depth of the deepest node.
+    pub fn depth(&self) -> usize {
+        self.nodes().map(|node| node.depth).max().unwrap_or(0)
+    }
+
+    /// How many threads ran the nodes of the tree.
+    pub fn threads(&self) -> usize {
+        self.nodes().map(|node| node.thread + 1).max().unwrap_or(1)
+    }
+
+    /// How long the sort took, in nanoseconds.
+    pub fn span(&self) -> u64 {
+        self.root.end.saturating_sub(self.root.start)
+    }
+}
+
+impl MergeNode {
+    /// How long this node took, in nanoseconds.
+    pub fn duration(&self) -> u64 {
+        self.end.saturating_sub(self.start)
+    }
+
+    /// How long merging the halves took, in nanoseconds, if it is known.
+    pub fn merge_duration(&self) -> Option<u64> {
+        Some(self.end.saturating_sub(self.merge?))
+    }
+
+    /// Converts a task of a parallel sort and its halves.
+    fn from_task(task: &TraceTask, depth: usize) -> Self {
+        let halves = task.halves.iter().flat_map(|halves| {
+            let (lower, upper) = &**halves;
+            vec![
+                Self::from_task(lower, depth + 1),
+                Self::from_task(upper, depth + 1),
+            ]
+        });
+        Self {
+            range: task.range.clone(),
+            depth,
+            thread: task.thread,
+            start: task.start.as_nanos() as u64,
+            merge: None,
+            end: task.end.as_nanos() as u64,
+            halves: halves.collect(),
+        }
+    }
+
+    /// Writes this node and its halves, indented by its depth.
+    fn write(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            fmtr,
+            "{:indent$}{}..{} thread={} start={}",
+            "",
+            self.range.start,
+            self.range.end,
+            self.thread,
+            self.start,
+            indent = self.depth * 2,
+        )?;
+        if let Some(merge) = self.merge {
+            write!(fmtr, " merge={}", merge)?;
+        }
+        write!(fmtr, " end={}", self.end)?;
+        match self.halves.is_empty() {
+            true => writeln!(fmtr),
+            false => {
+                writeln!(fmtr, " split")?;
+                self.halves.iter().try_for_each(|half| half.write(fmtr))
+            },
+        }
+    }
+
+    /// Parses a node at the given depth and its halves from the given lines.
+    fn parse<'input, I>(
+        lines: &mut I,
+        depth: usize,
+    ) -> Result<Self, ParseTreeError>
+    where
+        I: Iterator<Item = (usize, &'input str)>,
+    {
+        let (number, line) = lines.next().ok_or(ParseTreeError {
+            line: None,
+            message: "missing node",
+        })?;
+        let error =
+            |message| ParseTreeError { line: Some(number + 1), message };
+
+        let mut fields = line.split_whitespace().peekable();
+
+        let range = fields.next().ok_or_else(|| error("missing range"))?;
+        let (start, end) =
+            range.split_once("..").ok_or_else(|| error("invalid range"))?;
+        let range = start.parse().map_err(|_| error("invalid range start"))?
+            .. end.parse().map_err(|_| error("invalid range end"))?;
+
+        let mut field = |key: &str, optional: bool| {
+            let value = fields
+                .peek()
+                .and_then(|field| field.strip_prefix(key))
+                .and_then(|field| field.strip_prefix('='))
+                .map(|value| value.parse::<u64>());
+            match value {
+                Some(Ok(value)) => {
+                    fields.next();
+                    Ok(Some(value))
+                },
+                None if optional => Ok(None),
+                _ => Err(error("invalid field")),
+            }
+        };
+        let thread = field("thread", false)?.unwrap_or_default() as usize;
+        let start = field("start", false)?.unwrap_or_default();
+        let merge = field("merge", true)?;
+        let end = field("end", false)?.unwrap_or_default();
+
+        let halves = match fields.next() {
+            Some("split") => vec![
+                Self::parse(lines, depth + 1)?,
+                Self::parse(lines, depth + 1)?,
+            ],
+            Some(_) => return Err(error("unexpected field")),
+            None => Vec::new(),
+        };
+
+        Ok(Self { range, depth, thread, start, merge, end, halves })
+    }
+}
+
+impl From<&ExecutionTrace> for MergeTree {
+    /// Converts the recorded tasks of a parallel sort. The tasks it did not
+    /// split are leaves, and the times of the merges are not known.
+    fn from(trace: &ExecutionTrace) -> Self {
+        Self { root: MergeNode::from_task(&trace.root, 0) }
+    }
+}
+
+impl fmt::Display for MergeTree {
+    fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
+        self.root.write(fmtr)
+    }
+}
+
+impl FromStr for MergeTree {
+    type Err = ParseTreeError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let mut lines = input
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty());
+
+        let root = MergeNode::parse(&mut lines, 0)?;
+
+        match lines.next() {
+            Some((number, _)) => Err(ParseTreeError {
+                line: Some(number + 1),
+                message: "unexpected node after the root node",
+            }),
+            None => Ok(Self { root }),
+        }
+    }
+}
+
+/// Error returned when parsing a [`MergeTree`] fails.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseTreeError {
+    /// The line of the error, starting at 1. `None` means end of input.
+    pub line: Option<usize>,
+    /// What is wrong.
+    pub message: &'static str,
+}
+
+impl fmt::Display for ParseTreeError {
+    fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(fmtr, "{} at line {}", self.message, line),
+            None => write!(fmtr, "{} at end of input", self.message),
+        }
+    }
+}
+
+impl Error for ParseTreeError {}
+
+/// Sorts the given array with the sequential merge sort, recording its
+/// recursion down to the given depth. Deeper nodes are sorted without being
+/// recorded, so a depth of 10 records at most 2047 nodes, whatever the size
+/// of the array. Recording slows the sort down.
+///
+/// # Examples
+/// ```rust
+/// use mergesort_cmp::trace;
+///
+/// let array = [-1, 5, 91293, 12, -95];
+///
+/// let (sorted, tree) = trace::sort_by(&array, i32::cmp, usize::MAX);
+///
+/// assert_eq!(sorted, &[-95, -1, 5, 12, 91293]);
+/// assert_eq!(tree.root.halves[0].range, 0 .. 3);
+/// assert_eq!(tree.root.halves[1].range, 3 .. 5);
+/// assert!(tree.root.merge.is_some());
+/// ```
+pub fn sort_by<T, F>(
+    array: &[T],
+    mut compare: F,
+    depth: usize,
+) -> (Vec<T>, MergeTree)
+where
+    T: Clone,
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let clock = Instant::now();
+    let mut recording = Recording { compare: &mut compare, clock, depth };
+    let (sorted, root) = recording.split(array, 0, 0);
+    (sorted, MergeTree { root })
+}
+
+/// The state of a recorded sequential sort.
+struct Recording<'compare, F> {
+    /// The comparison function of the sort.
+    compare: &'compare mut F,
+    /// When the sort started.
+    clock: Instant,
+    /// The depth of the deepest node recorded.
+    depth: usize,
+}
+
+impl<'compare, F> Recording<'compare, F> {
+    /// Nanoseconds since the start of the sort.
+    fn now(&self) -> u64 {
+        self.clock.elapsed().as_nanos() as u64
+    }
+
+    /// Sorts the slice, which starts at the given offset of the array, at the
+    /// given depth, recording it.
+    fn split<T>(
+        &mut self,
+        array: &[T],
+        offset: usize,
+        depth: usize,
+    ) -> (Vec<T>, MergeNode)
+    where
+        T: Clone,
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        let start = self.now();
+        let range = offset .. offset + array.len();
+        let split = array.len() > 1 && depth < self.depth;
+        let (sorted, merge, halves) = match split {
+            true => {
+                // The middle index: ceil(length/2), like the sort splits.
+                let half = array.len().div_ceil(2);
+                let (lower_slice, upper_slice) = array.split_at(half);
+                let (lower, lower_node) =
+                    self.split(lower_slice, offset, depth + 1);
+                let (upper, upper_node) =
+                    self.split(upper_slice, offset + half, depth + 1);
+                let merge = self.now();
+                let merged =
+                    sequential::merge_owned(lower, upper, self.compare);
+                (merged, Some(merge), vec![lower_node, upper_node])
+            },
+            false => {
+                let sorted = sequential::sort_by(array, &mut *self.compare);
+                (sorted, None, Vec::new())
+            },
+        };
+        let end = self.now();
+        let node =
+            MergeNode { range, depth, thread: 0, start, merge, end, halves };
+        (sorted, node)
+    }
+}
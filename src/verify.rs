@@ -0,0 +1,416 @@
+//! Checks that a sort actually sorted: that its output is in order, and that
+//! it has the elements of its input. They take the comparison function the
+//! sort was given, so custom comparators can be checked too. Comparison
+//! functions themselves can be checked to be total orders, since the sorts
+//! give wrong output with any other.
+//!
+//! # Examples
+//! ```rust
+//! use mergesort_cmp::{sequential, verify};
+//!
+//! let array = [5, -3, 12, 0, 5, 7];
+//! let sorted = sequential::sort(&array);
+//!
+//! assert!(verify::is_sorted_by(&sorted, i32::cmp));
+//! assert!(verify::is_permutation_of(&sorted, &array, i32::cmp));
+//! assert_eq!(verify::find_first_unsorted_pair(&array, i32::cmp), Some(0));
+//! ```
+
+use rand::Rng;
+use std::{
+    cmp::Ordering,
+    error::Error,
+    fmt,
+    sync::{
+        atomic::{self, AtomicBool, AtomicUsize},
+        Mutex,
+    },
+};
+
+/// How many triples of the sample [`check_total_order`] draws, unless it has
+/// few enough elements to check every triple.
+const TRIPLES: usize = 10000;
+
+/// Finds the first pair of consecutive elements out of order with the given
+/// comparison function, returning the index of the first element of the
+/// pair, the one that is greater than the element after it. `None` if the
+/// array is sorted.
+///
+/// # Examples
+/// ```rust
+/// use mergesort_cmp::verify;
+///
+/// let array = [1, 2, 2, 5, 4, 9];
+///
+/// assert_eq!(verify::find_first_unsorted_pair(&array, i32::cmp), Some(3));
+/// assert_eq!(verify::find_first_unsorted_pair(&array[.. 4], i32::cmp), None);
+/// ```
+pub fn find_first_unsorted_pair<T, F>(
+    array: &[T],
+    mut compare: F,
+) -> Option<usize>
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    array.windows(2).position(|pair| compare(&pair[0], &pair[1]).is_gt())
+}
+
+/// Tests whether the array is sorted with the given comparison function,
+/// equal elements being in any order.
+///
+/// # Examples
+/// ```rust
+/// use mergesort_cmp::verify;
+///
+/// let descending = |left: &i32, right: &i32| right.cmp(left);
+///
+/// assert!(verify::is_sorted_by(&[9, 4, 4, 1], descending));
+/// assert!(!verify::is_sorted_by(&[9, 4, 5, 1], descending));
+/// ```
+pub fn is_sorted_by<T, F>(array: &[T], compare: F) -> bool
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    find_first_unsorted_pair(array, compare).is_none()
+}
+
+/// Tests whether the array has exactly the elements of the other one, as
+/// many times each, sorting copies of both with the given comparison
+/// function, which must be a total order. Elements that compare equal are
+/// taken for the same one, so elements sorted by a key are only told apart
+/// by a comparison function looking past the key.
+///
+/// # Examples
+/// ```rust
+/// use mergesort_cmp::verify;
+///
+/// assert!(verify::is_permutation_of(&[1, 2, 2, 3], &[2, 3, 1, 2], i32::cmp));
+/// assert!(!verify::is_permutation_of(&[1, 2, 3, 3], &[2, 3, 1, 2], i32::cmp));
+/// ```
+pub fn is_permutation_of<T, F>(array: &[T], other: &[T], mut compare: F) -> bool
+where
+    T: Clone,
+    F: FnMut(&T, &T) -> Ordering,
+{
+    if array.len() != other.len() {
+        return false;
+    }
+    // Sorted permutations of the same multiset are equal.
+    let mut left = array.to_vec();
+    left.sort_unstable_by(&mut compare);
+    let mut right = other.to_vec();
+    right.sort_unstable_by(&mut compare);
+    left.iter().zip(&right).all(|(left, right)| compare(left, right).is_eq())
+}
+
+/// Checks that the comparison function is a total order on the sample, the
+/// one thing the sorts assume of it: that every element compares equal to
+/// itself, that swapping two elements reverses how they compare, and that
+/// comparing is transitive. Every triple of elements is checked in small
+/// samples, and random triples in bigger ones, so a comparison function
+/// passing is only likely to be a total order, and more so with samples
+/// holding the elements it gets wrong.
+///
+/// # Errors
+/// The first violation found, with the indices in the sample of the elements
+/// involved.
+///
+/// # Examples
+/// ```rust
+/// use mergesort_cmp::verify::{self, TotalOrderError};
+/// use std::cmp::Ordering;
+///
+/// let sample = [3.5, -1.0, 0.0, 7.25];
+/// assert_eq!(verify::check_total_order(f64::total_cmp, &sample), Ok(()));
+///
+/// // Only partial: NaN is neither less, greater nor equal to anything.
+/// let partial = |left: &f64, right: &f64| {
+///     left.partial_cmp(right).unwrap_or(Ordering::Less)
+/// };
+/// let sample = [1.0, f64::NAN, 2.0];
+/// let error = verify::check_total_order(partial, &sample).unwrap_err();
+/// assert_eq!(
+///     error,
+///     TotalOrderError::NotReflexive { index: 1, ordering: Ordering::Less }
+/// );
+/// ```
+pub fn check_total_order<T, F>(
+    mut compare: F,
+    sample: &[T],
+) -> Result<(), TotalOrderError>
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let len = sample.len();
+    let mut check = |indices: [usize; 3]| {
+        check_triple(&mut compare, sample, indices)
+    };
+
+    if len.saturating_pow(3) <= TRIPLES {
+        for first in 0 .. len {
+            for second in first .. len {
+                for third in second .. len {
+                    check([first, second, third])?;
+                }
+            }
+        }
+    } else {
+        let mut rng = rand::thread_rng();
+        for _ in 0 .. TRIPLES {
+            let mut index = || rng.gen_range(0, len);
+            check([index(), index(), index()])?;
+        }
+    }
+    Ok(())
+}
+
+/// Checks the comparison function on a triple of elements of the sample, in
+/// every order they can be compared in.
+fn check_triple<T, F>(
+    compare: &mut F,
+    sample: &[T],
+    indices: [usize; 3],
+) -> Result<(), TotalOrderError>
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let mut orderings = [[Ordering::Equal; 3]; 3];
+    for left in 0 .. 3 {
+        for right in 0 .. 3 {
+            orderings[left][right] =
+                compare(&sample[indices[left]], &sample[indices[right]]);
+        }
+    }
+
+    for position in 0 .. 3 {
+        let ordering = orderings[position][position];
+        if ordering != Ordering::Equal {
+            let index = indices[position];
+            return Err(TotalOrderError::NotReflexive { index, ordering });
+        }
+    }
+    for (left, right) in [(0, 1), (1, 2), (0, 2)] {
+        let forward = orderings[left][right];
+        let backward = orderings[right][left];
+        if forward != backward.reverse() {
+            return Err(TotalOrderError::NotAntisymmetric {
+                left: indices[left],
+                right: indices[right],
+                forward,
+                backward,
+            });
+        }
+    }
+    let orders =
+        [[0, 1, 2], [0, 2, 1], [1, 0, 2], [1, 2, 0], [2, 0, 1], [2, 1, 0]];
+    for [first, second, third] in orders {
+        let first_second = orderings[first][second];
+        let second_third = orderings[second][third];
+        let first_third = orderings[first][third];
+        // Then `first <= second <= third`, the other way around being
+        // checked in the reverse order.
+        if first_second.is_gt() || second_third.is_gt() {
+            continue;
+        }
+        let expected = first_second.then(second_third);
+        if first_third != expected {
+            return Err(TotalOrderError::NotTransitive {
+                indices: [indices[first], indices[second], indices[third]],
+                orderings: [first_second, second_third, first_third],
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Error returned when a comparison function is not a total order, holding
+/// the indices in the sample of the elements it gets wrong.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TotalOrderError {
+    /// An element does not compare equal to itself.
+    NotReflexive {
+        /// The index of the element.
+        index: usize,
+        /// How it compares to itself.
+        ordering: Ordering,
+    },
+    /// Two elements do not compare the opposite way when swapped.
+    NotAntisymmetric {
+        /// The index of the element compared first.
+        left: usize,
+        /// The index of the element compared second.
+        right: usize,
+        /// How the left element compares to the right one.
+        forward: Ordering,
+        /// How the right element compares to the left one.
+        backward: Ordering,
+    },
+    /// Three elements, the first not greater than the second and the second
+    /// not greater than the third, where the first does not compare to the
+    /// third as it should.
+    NotTransitive {
+        /// The indices of the elements, in that order.
+        indices: [usize; 3],
+        /// How the first compares to the second, the second to the third and
+        /// the first to the third.
+        orderings: [Ordering; 3],
+    },
+}
+
+impl fmt::Display for TotalOrderError {
+    fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            TotalOrderError::NotReflexive { index, ordering } => write!(
+                fmtr,
+                "element {} is {} itself",
+                index,
+                relation(ordering)
+            ),
+            TotalOrderError::NotAntisymmetric {
+                left,
+                right,
+                forward,
+                backward,
+            } => write!(
+                fmtr,
+                "element {} is {} element {}, but element {} is {} element {}",
+                left,
+                relation(forward),
+                right,
+                right,
+                relation(backward),
+                left
+            ),
+            TotalOrderError::NotTransitive { indices, orderings } => write!(
+                fmtr,
+                "element {} is {} element {}, which is {} element {}, but \
+                 element {} is {} element {}",
+                indices[0],
+                relation(orderings[0]),
+                indices[1],
+                relation(orderings[1]),
+                indices[2],
+                indices[0],
+                relation(orderings[2]),
+                indices[2]
+            ),
+        }
+    }
+}
+
+impl Error for TotalOrderError {}
+
+/// Error returned by the checked sorts, such as
+/// [`sequential::sort_checked_by`](crate::sequential::sort_checked_by), when
+/// the comparison function did not give opposite orderings for a pair of
+/// elements compared both ways. Holds the first such pair found.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InconsistentComparatorError<T> {
+    /// The element compared first.
+    pub left: T,
+    /// The element compared second.
+    pub right: T,
+    /// How the left element compared to the right one.
+    pub forward: Ordering,
+    /// How the right element compared to the left one.
+    pub backward: Ordering,
+}
+
+impl<T> fmt::Display for InconsistentComparatorError<T>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            fmtr,
+            "inconsistent comparison function: {:?} is {} {:?}, but {:?} is \
+             {} {:?}",
+            self.left,
+            relation(self.forward),
+            self.right,
+            self.right,
+            relation(self.backward),
+            self.left
+        )
+    }
+}
+
+impl<T> Error for InconsistentComparatorError<T> where T: fmt::Debug {}
+
+/// Checks a sample of the comparisons of a sort, comparing their elements the
+/// other way around too, and keeps the first pair found inconsistent.
+pub(crate) struct ConsistencyCheck<T> {
+    /// Every how many comparisons one is checked.
+    every: usize,
+    /// How many comparisons were done.
+    calls: AtomicUsize,
+    /// Whether an inconsistent pair was found, so no more are checked.
+    failed: AtomicBool,
+    /// The first inconsistent pair found.
+    found: Mutex<Option<InconsistentComparatorError<T>>>,
+}
+
+impl<T> ConsistencyCheck<T>
+where
+    T: Clone,
+{
+    /// Checks one of every `every` comparisons, every one with 0.
+    pub(crate) fn new(every: usize) -> Self {
+        Self {
+            every: every.max(1),
+            calls: AtomicUsize::new(0),
+            failed: AtomicBool::new(false),
+            found: Mutex::new(None),
+        }
+    }
+
+    /// Compares the elements with the comparison function, checking it on
+    /// them if it is the turn of this comparison.
+    pub(crate) fn compare<F>(
+        &self,
+        mut compare: F,
+        left: &T,
+        right: &T,
+    ) -> Ordering
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        let forward = compare(left, right);
+        let call = self.calls.fetch_add(1, atomic::Ordering::Relaxed);
+        let failed = self.failed.load(atomic::Ordering::Relaxed);
+        if !call.is_multiple_of(self.every) || failed {
+            return forward;
+        }
+        let backward = compare(right, left);
+        if forward != backward.reverse() {
+            let mut found = self.found.lock().unwrap();
+            if found.is_none() {
+                *found = Some(InconsistentComparatorError {
+                    left: left.clone(),
+                    right: right.clone(),
+                    forward,
+                    backward,
+                });
+            }
+            self.failed.store(true, atomic::Ordering::Relaxed);
+        }
+        forward
+    }
+
+    /// Fails with the first inconsistent pair found, if any.
+    pub(crate) fn finish(&self) -> Result<(), InconsistentComparatorError<T>> {
+        match self.found.lock().unwrap().take() {
+            Some(error) => Err(error),
+            None => Ok(()),
+        }
+    }
+}
+
+/// How an element compares to another, as words.
+fn relation(ordering: Ordering) -> &'static str {
+    match ordering {
+        Ordering::Less => "less than",
+        Ordering::Equal => "equal to",
+        Ordering::Greater => "greater than",
+    }
+}
@@ -0,0 +1,99 @@
+//! Bindings of the sequential merge sort for JavaScript, through
+//! `wasm-bindgen`, so the sorts can run in the browser. Only built with the
+//! `wasm` feature, for the `wasm32-unknown-unknown` target:
+//! ```text
+//! $ cargo rustc --release --lib --crate-type cdylib --features wasm \
+//!     --target wasm32-unknown-unknown
+//! $ wasm-bindgen --target web --out-dir pkg \
+//!     target/wasm32-unknown-unknown/release/mergesort_cmp.wasm
+//! ```
+//!
+//! The parallel sort is not bound, since the browser has no threads the
+//! standard library can spawn. Every function sorts a copy of the array it is
+//! given, which is left as it was, like the sorts of this crate do.
+//!
+//! # Examples
+//! ```js
+//! import init, { sort_i32_array, sort_array_by } from "./mergesort_cmp.js";
+//!
+//! await init();
+//! const sorted = sort_i32_array(new Int32Array([5, -3, 12, 0]));
+//! const words = ["pear", "fig", "apple"];
+//! const byLength = sort_array_by(words, (a, b) => a.length - b.length);
+//! ```
+
+use crate::sequential;
+use js_sys::{
+    Array,
+    Float32Array,
+    Float64Array,
+    Function,
+    Int32Array,
+    Uint32Array,
+};
+use std::cmp::Ordering;
+use wasm_bindgen::prelude::*;
+
+/// Sorts the array with the sequential merge sort, returning a sorted copy.
+#[wasm_bindgen]
+pub fn sort_i32_array(array: &Int32Array) -> Int32Array {
+    Int32Array::from(&sequential::sort(&array.to_vec())[..])
+}
+
+/// Sorts the array with the sequential merge sort, returning a sorted copy.
+#[wasm_bindgen]
+pub fn sort_u32_array(array: &Uint32Array) -> Uint32Array {
+    Uint32Array::from(&sequential::sort(&array.to_vec())[..])
+}
+
+/// Sorts the array with the sequential merge sort, returning a sorted copy.
+/// The numbers are in their total order, the one of [`f32::total_cmp`]:
+/// negative NaNs first, `-0` before `0`, and positive NaNs last.
+#[wasm_bindgen]
+pub fn sort_f32_array(array: &Float32Array) -> Float32Array {
+    let sorted = sequential::sort_by(&array.to_vec(), f32::total_cmp);
+    Float32Array::from(&sorted[..])
+}
+
+/// Sorts the array with the sequential merge sort, returning a sorted copy.
+/// The numbers are in their total order, the one of [`f64::total_cmp`]:
+/// negative NaNs first, `-0` before `0`, and positive NaNs last.
+#[wasm_bindgen]
+pub fn sort_f64_array(array: &Float64Array) -> Float64Array {
+    let sorted = sequential::sort_by(&array.to_vec(), f64::total_cmp);
+    Float64Array::from(&sorted[..])
+}
+
+/// Sorts the array with the sequential merge sort and the given comparison
+/// function, returning a sorted copy. The function is called like the one of
+/// `Array.prototype.sort`: with two elements, returning a negative number if
+/// the first goes before the second, a positive number if it goes after, and
+/// zero or `NaN` if they are equal. The first exception it throws is thrown
+/// again once the sort is done, instead of returning.
+#[wasm_bindgen]
+pub fn sort_array_by(
+    array: &Array,
+    compare: &Function,
+) -> Result<Array, JsValue> {
+    let mut thrown = None;
+    let sorted = sequential::sort_by(&array.to_vec(), |left, right| {
+        // After an exception, the order does not matter anymore.
+        if thrown.is_some() {
+            return Ordering::Equal;
+        }
+        match compare.call2(&JsValue::UNDEFINED, left, right) {
+            Ok(order) => {
+                let order = order.as_f64().unwrap_or(f64::NAN);
+                order.partial_cmp(&0.0).unwrap_or(Ordering::Equal)
+            },
+            Err(exception) => {
+                thrown = Some(exception);
+                Ordering::Equal
+            },
+        }
+    });
+    match thrown {
+        Some(exception) => Err(exception),
+        None => Ok(sorted.into_iter().collect()),
+    }
+}
@@ -0,0 +1,117 @@
+//! Properties every sort must have, checked on random arrays of random sizes
+//! and ranges, many of them full of duplicates, sorted on random numbers of
+//! threads. Only with the `proptest-harness` feature:
+//!
+//! ```text
+//! cargo test --features proptest-harness
+//! ```
+
+use mergesort_cmp::{parallel, sequential, verify};
+use proptest::prelude::*;
+use std::{cmp::Ordering, sync::Arc};
+
+/// An element sorted by its key, along with its position in the unsorted
+/// array, which tells elements with equal keys apart.
+type Element = (i32, usize);
+
+/// A random array, with the threads the parallel sort splits and merges it
+/// with.
+#[derive(Debug, Clone)]
+struct Case {
+    /// The elements, their positions in order.
+    array: Vec<Element>,
+    /// The threads the parallel sort splits the array with.
+    split_threads: usize,
+    /// The threads the parallel sort merges the halves with.
+    merge_threads: usize,
+}
+
+/// Compares the keys of elements only.
+fn by_key(left: &Element, right: &Element) -> Ordering {
+    left.0.cmp(&right.0)
+}
+
+/// Compares whole elements, telling all of them apart.
+fn by_element(left: &Element, right: &Element) -> Ordering {
+    left.cmp(right)
+}
+
+/// Random cases, with keys from narrow ranges as often as from wide ones, so
+/// that many keys repeat.
+fn cases() -> impl Strategy<Value = Case> {
+    let range = prop_oneof![Just(1), Just(4), Just(100), Just(i32::MAX)];
+    let keys = range.prop_flat_map(|range| {
+        prop::collection::vec(-range .. range, 0 .. 2000)
+    });
+    (keys, 1 ..= 8usize, 1 ..= 8usize).prop_map(
+        |(keys, split_threads, merge_threads)| Case {
+            array: keys.into_iter().zip(0 ..).collect(),
+            split_threads,
+            merge_threads,
+        },
+    )
+}
+
+/// The elements of the case sorted by key by each of the sorts, with their
+/// names.
+fn sort_all(case: &Case) -> Vec<(&'static str, Vec<Element>)> {
+    let shared: Arc<[Element]> = Arc::from(&case.array[..]);
+    let parallel = parallel::custom_order(by_key)
+        .split_threads(case.split_threads)
+        .merge_threads(case.merge_threads)
+        .sort(&shared);
+    vec![
+        ("sequential::sort_by", sequential::sort_by(&case.array, by_key)),
+        (
+            "sequential::sort_galloping_by",
+            sequential::sort_galloping_by(&case.array, by_key),
+        ),
+        ("parallel::custom_order", parallel),
+    ]
+}
+
+proptest! {
+    #[test]
+    fn sorts_are_sorted(case in cases()) {
+        for (name, sorted) in sort_all(&case) {
+            let unsorted = verify::find_first_unsorted_pair(&sorted, by_key);
+            prop_assert_eq!(unsorted, None, "{} is not sorted", name);
+        }
+    }
+
+    #[test]
+    fn sorts_are_permutations(case in cases()) {
+        for (name, sorted) in sort_all(&case) {
+            prop_assert!(
+                verify::is_permutation_of(&sorted, &case.array, by_element),
+                "{} lost or duplicated elements",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn sorts_are_stable(case in cases()) {
+        for (name, sorted) in sort_all(&case) {
+            // Equal keys must keep the order of their positions.
+            let unstable = sorted.windows(2).position(|pair| {
+                pair[0].0 == pair[1].0 && pair[0].1 > pair[1].1
+            });
+            prop_assert_eq!(unstable, None, "{} is not stable", name);
+        }
+    }
+
+    #[test]
+    fn sorts_agree(case in cases()) {
+        let mut expected = case.array.clone();
+        expected.sort_by(by_key);
+        for (name, sorted) in sort_all(&case) {
+            prop_assert_eq!(
+                &sorted,
+                &expected,
+                "{} disagrees with slice::sort_by",
+                name
+            );
+        }
+    }
+}
@@ -0,0 +1,212 @@
+//! The Python module, driven from an embedded interpreter. Only with the
+//! `python` feature, and a Python with its shared library to link against:
+//!
+//! ```text
+//! cargo test --features python --test python
+//! ```
+//!
+//! The checks of numpy arrays are skipped if numpy cannot be imported.
+
+use mergesort_cmp::python::mergesort_cmp;
+use pyo3::prelude::*;
+use std::{ffi::CString, sync::Once};
+
+/// Runs the Python code with the module imported, failing on any exception.
+fn run(code: &str) {
+    static INIT: Once = Once::new();
+    INIT.call_once(|| {
+        pyo3::append_to_inittab!(mergesort_cmp);
+        Python::initialize();
+    });
+    let code = format!("import mergesort_cmp\n{}", code);
+    let code = CString::new(code).unwrap();
+    Python::attach(|py| {
+        if let Err(error) = py.run(&code, None, None) {
+            error.display(py);
+            panic!("{}", error);
+        }
+    })
+}
+
+#[test]
+fn sorts_buffers_in_place() {
+    run(r#"
+import array, random
+rng = random.Random(1)
+for code in "bBhHiIlLqQ":
+    size = array.array(code).itemsize * 8
+    low = -(1 << (size - 1)) if code.islower() else 0
+    high = (1 << (size - 1)) - 1 if code.islower() else (1 << size) - 1
+    values = array.array(code, (rng.randint(low, high) for _ in range(2000)))
+    expected = sorted(values)
+    assert mergesort_cmp.sort(values, threads=3) is None
+    assert list(values) == expected, code
+for code in "fd":
+    nan = float("nan")
+    values = array.array(code, [2.5, nan, -0.0, 0.0, -1.0, float("inf")])
+    mergesort_cmp.sort(values)
+    assert list(values)[:5] == [-1.0, -0.0, 0.0, 2.5, float("inf")], code
+    assert str(list(values)[0]) == "-1.0" and str(values[1]) == "-0.0"
+    assert values[5] != values[5]
+"#);
+}
+
+#[test]
+fn sorts_memoryviews() {
+    run(r#"
+import array
+values = array.array("i", [5, 4, 3, 2, 1, 0])
+mergesort_cmp.sort(memoryview(values)[1:5])
+assert list(values) == [5, 1, 2, 3, 4, 0]
+mergesort_cmp.sort(memoryview(values)[::2])
+assert list(values) == [2, 1, 4, 3, 5, 0]
+raw = bytearray(array.array("h", [300, -2, 7]).tobytes())
+mergesort_cmp.sort(memoryview(raw).cast("h"))
+assert list(memoryview(raw).cast("h")) == [-2, 7, 300]
+"#);
+}
+
+#[test]
+fn rejects_invalid_buffers() {
+    run(r#"
+import array
+for data, error in [
+    (b"cba", TypeError),
+    (array.array("u", "cba"), TypeError),
+    ("cba", TypeError),
+    ([1, "a"], TypeError),
+]:
+    try:
+        mergesort_cmp.sort(data)
+    except error:
+        pass
+    else:
+        raise AssertionError(data)
+try:
+    mergesort_cmp.sort(array.array("i", [2, 1]), threads=0)
+except ValueError:
+    pass
+else:
+    raise AssertionError("no threads")
+"#);
+}
+
+#[test]
+fn sorts_sequences() {
+    run(r#"
+import random
+rng = random.Random(2)
+integers = [rng.randint(-100, 100) for _ in range(3000)]
+assert mergesort_cmp.sort(integers) == sorted(integers)
+assert mergesort_cmp.sort(tuple(integers), threads=1) == sorted(integers)
+floats = [rng.random() for _ in range(3000)] + [3]
+assert mergesort_cmp.sort(floats) == sorted(floats)
+words = ["pear", "fig", "apple", "fig"]
+assert mergesort_cmp.sort(words) == sorted(words)
+assert mergesort_cmp.sort([]) == []
+"#);
+}
+
+#[test]
+fn argsorts_stably() {
+    run(r#"
+import array
+assert mergesort_cmp.argsort([3, 1, 3, 0]) == [3, 1, 0, 2]
+assert mergesort_cmp.argsort(array.array("d", [2.5, -1.0])) == [1, 0]
+assert mergesort_cmp.argsort(["b", "a", "b"], threads=2) == [1, 0, 2]
+"#);
+}
+
+#[test]
+fn sorts_by_key() {
+    run(r#"
+calls = []
+def key(word):
+    calls.append(word)
+    return len(word)
+words = ["pear", "fig", "kiwi", "apple", "nut"]
+assert mergesort_cmp.sort_by_key(words, key) == [
+    "fig", "nut", "pear", "kiwi", "apple",
+]
+assert calls == words
+pairs = [(1, "b"), (0, "a"), (1, "a")]
+assert mergesort_cmp.sort_by_key(pairs, lambda pair: pair[1]) == [
+    (0, "a"), (1, "a"), (1, "b"),
+]
+try:
+    mergesort_cmp.sort_by_key(words, lambda word: 1 / 0)
+except ZeroDivisionError:
+    pass
+else:
+    raise AssertionError("the key raised")
+"#);
+}
+
+#[test]
+fn benches() {
+    run(r#"
+cases = {"reversed": [list(range(500, 0, -1))] * 4, "empty": [[]]}
+results = mergesort_cmp.bench(cases, repeat=2, verify=True)
+assert [(result["set"], result["target"]) for result in results] == [
+    ("reversed", "sequential"),
+    ("reversed", "parallel_logical"),
+    ("reversed", "std_sort"),
+    ("empty", "sequential"),
+    ("empty", "parallel_logical"),
+    ("empty", "std_sort"),
+]
+for result in results:
+    assert len(result["runs"]) == 2
+    assert all(len(run) == len(cases[result["set"]]) for run in result["runs"])
+    assert result["mean_seconds"] >= 0
+assert results[0]["elements"] == 2000
+
+results = mergesort_cmp.bench(
+    cases, targets=[("sorted", sorted), "std_sort"], warmup=0, verify=True,
+)
+assert [result["target"] for result in results[:2]] == ["sorted", "std_sort"]
+
+for targets, error in [
+    ([("wrong", lambda case: case)], ValueError),
+    ([("raises", lambda case: 1 / 0)], ZeroDivisionError),
+    (["unknown"], ValueError),
+    ([1], TypeError),
+]:
+    try:
+        mergesort_cmp.bench(cases, targets=targets, verify=True)
+    except error:
+        pass
+    else:
+        raise AssertionError(targets)
+"#);
+}
+
+#[test]
+fn sorts_numpy_arrays() {
+    run(r#"
+try:
+    import numpy
+except ImportError:
+    numpy = None
+if numpy is not None:
+    rng = numpy.random.default_rng(3)
+    for dtype in ["int8", "uint16", "int32", "uint64", "float32", "float64"]:
+        values = (rng.random(5000) * 100).astype(dtype)
+        expected = numpy.sort(values)
+        mergesort_cmp.sort(values, threads=4)
+        assert (values == expected).all(), dtype
+        assert mergesort_cmp.argsort(values) == list(range(5000))
+    grid = rng.integers(0, 100, (50, 40))
+    column = grid[:, 7].copy()
+    mergesort_cmp.sort(grid[:, 7])
+    assert (grid[:, 7] == numpy.sort(column)).all()
+    values = numpy.arange(10)
+    values.flags.writeable = False
+    try:
+        mergesort_cmp.sort(values)
+    except TypeError:
+        pass
+    else:
+        raise AssertionError("read-only array")
+"#);
+}